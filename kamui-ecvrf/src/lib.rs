@@ -0,0 +1,198 @@
+//! On-chain verification of the `sol_vrf` proofs produced by the Kamui oracles
+//! (`mangekyou::kamui_vrf::ecvrf`, ristretto255 with SHA-512).
+//!
+//! Group operations go through the curve25519 syscalls, so verification fits in
+//! a transaction. The native coordinator, `kamui-vrf` and the LayerZero OApps
+//! all verify with this crate, so a proof accepted by one is accepted by all.
+//!
+//! A proof is 80 bytes:
+//!
+//! ```text
+//! 0          32        48                   80
+//! |----------|---------|--------------------|
+//! |  Gamma   |    c    |         s          |
+//! |----------|---------|--------------------|
+//! ```
+//!
+//! [`pop`] verifies the proofs of possession oracles register their keys with.
+
+pub mod pop;
+
+use sha2::{Digest, Sha512};
+
+pub use solana_zk_token_sdk::curve25519::{ristretto::PodRistrettoPoint, scalar::PodScalar};
+use solana_zk_token_sdk::curve25519::ristretto::{
+    multiply_ristretto, subtract_ristretto, validate_ristretto,
+};
+
+pub const PUBLIC_KEY_LENGTH: usize = 32;
+pub const PROOF_LENGTH: usize = 80;
+pub const OUTPUT_LENGTH: usize = 64;
+
+const C_LEN: usize = 16;
+const SUITE_STRING: &[u8] = b"sol_vrf";
+const DST: &[u8] = b"ECVRF_ristretto255_XMD:SHA-512_R255MAP_RO_sol_vrf";
+
+/// Compressed ristretto255 basepoint
+pub const BASEPOINT: PodRistrettoPoint = PodRistrettoPoint([
+    0xe2, 0xf2, 0xae, 0x0a, 0x6a, 0xbc, 0x4e, 0x71,
+    0xa8, 0x84, 0xa9, 0x61, 0xc5, 0x00, 0x51, 0x5f,
+    0x58, 0xe3, 0x0b, 0x6a, 0xa5, 0x82, 0xdd, 0x8d,
+    0xb6, 0xa6, 0x59, 0x45, 0xe0, 0x8d, 0x2d, 0x76,
+]);
+
+/// Why a proof was rejected. Programs map this onto their own error codes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EcvrfError {
+    /// The public key is the identity or not a valid ristretto255 point
+    InvalidPublicKey,
+    /// The proof is malformed or does not verify
+    InvalidProof,
+}
+
+impl std::fmt::Display for EcvrfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EcvrfError::InvalidPublicKey => write!(f, "invalid VRF public key"),
+            EcvrfError::InvalidProof => write!(f, "invalid VRF proof"),
+        }
+    }
+}
+
+impl std::error::Error for EcvrfError {}
+
+/// Verify `proof` for `alpha` under `public_key` and return the VRF output.
+pub fn verify(
+    public_key: &[u8; PUBLIC_KEY_LENGTH],
+    alpha: &[u8],
+    proof: &[u8],
+) -> Result<[u8; OUTPUT_LENGTH], EcvrfError> {
+    let y = PodRistrettoPoint(*public_key);
+    if *public_key == [0u8; PUBLIC_KEY_LENGTH] || !validate_ristretto(&y) {
+        return Err(EcvrfError::InvalidPublicKey);
+    }
+    if proof.len() != PROOF_LENGTH {
+        return Err(EcvrfError::InvalidProof);
+    }
+    let gamma = PodRistrettoPoint(proof[..32].try_into().unwrap());
+    let c: [u8; C_LEN] = proof[32..32 + C_LEN].try_into().unwrap();
+    let s = PodScalar(proof[32 + C_LEN..].try_into().unwrap());
+
+    let mut c_scalar = [0u8; 32];
+    c_scalar[..C_LEN].copy_from_slice(&c);
+    let c_scalar = PodScalar(c_scalar);
+
+    let h = encode_to_curve(alpha);
+
+    // U = s*B - c*Y and V = s*H - c*Gamma. The syscalls reject non-canonical
+    // scalars and invalid point encodings.
+    let u = subtract(&multiply(&s, &BASEPOINT)?, &multiply(&c_scalar, &y)?)?;
+    let v = subtract(&multiply(&s, &h)?, &multiply(&c_scalar, &gamma)?)?;
+
+    if challenge([&y, &h, &gamma, &u, &v]) != c {
+        return Err(EcvrfError::InvalidProof);
+    }
+
+    Ok(proof_to_hash(&gamma))
+}
+
+/// The VRF output for a proof with the given Gamma.
+pub fn proof_to_hash(gamma: &PodRistrettoPoint) -> [u8; OUTPUT_LENGTH] {
+    let mut hasher = Sha512::new();
+    hasher.update(SUITE_STRING);
+    hasher.update([0x03]);
+    hasher.update(gamma.0);
+    hasher.update([0x00]);
+    hasher.finalize().into()
+}
+
+/// The truncated challenge over Y, H, Gamma, U and V, in that order. Public so
+/// test provers can build proofs this crate accepts.
+pub fn challenge(points: [&PodRistrettoPoint; 5]) -> [u8; C_LEN] {
+    let mut hasher = Sha512::new();
+    hasher.update(SUITE_STRING);
+    hasher.update([0x02]);
+    for point in points {
+        hasher.update(point.0);
+    }
+    hasher.update([0x00]);
+    hasher.finalize()[..C_LEN].try_into().unwrap()
+}
+
+/// Hash `alpha` to a point the same way the prover does.
+pub fn encode_to_curve(alpha: &[u8]) -> PodRistrettoPoint {
+    const LEN_IN_BYTES: usize = 64;
+
+    let b_0 = Sha512::new()
+        .chain_update([0u8; 128])
+        .chain_update(alpha)
+        .chain_update([(LEN_IN_BYTES >> 8) as u8, LEN_IN_BYTES as u8])
+        .chain_update(DST)
+        .chain_update([DST.len() as u8])
+        .finalize();
+    let b_1 = Sha512::new()
+        .chain_update(b_0)
+        .chain_update([1u8])
+        .chain_update(DST)
+        .chain_update([DST.len() as u8])
+        .finalize();
+
+    let mut point = PodRistrettoPoint(b_1[..32].try_into().unwrap());
+    point.0[31] &= 0b0111_1111;
+
+    // Step through candidates until one decodes to a valid point
+    for _ in 0..256 {
+        if validate_ristretto(&point) {
+            return point;
+        }
+        point.0[0] = point.0[0].wrapping_add(1);
+    }
+    BASEPOINT
+}
+
+fn multiply(scalar: &PodScalar, point: &PodRistrettoPoint) -> Result<PodRistrettoPoint, EcvrfError> {
+    multiply_ristretto(scalar, point).ok_or(EcvrfError::InvalidProof)
+}
+
+fn subtract(left: &PodRistrettoPoint, right: &PodRistrettoPoint) -> Result<PodRistrettoPoint, EcvrfError> {
+    subtract_ristretto(left, right).ok_or(EcvrfError::InvalidProof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_hex<const N: usize>(hex_str: &str) -> [u8; N] {
+        hex::decode(hex_str).unwrap().try_into().unwrap()
+    }
+
+    // Round 1 of a `mangekyou::kamui_vrf::beacon` with a zero genesis value
+    const VRF_KEY: &str = "46dc35b41237542a57d48cb0cf48509ccac0d05f04bacd526fe76d4825d45740";
+    const PROOF: &str = "f48871e04e05306ef6dc802237f03e9af41452512527fc2d5e3dee0c3b272d3d6700e4b7c5fdef832385d06aa674ce61ba56f08c6fd0f59a1905e41f7b5ed06253e471c56611f7500a75bcf5e64a9901";
+    const OUTPUT: &str = "971fe5574ff96483facb8d7e33ab1394ce3d8078b7a5dee6790c2883732a05326154281f7f49ce8ca44756e716d21afdaeba562207e6259bb8f0a63a33a61163";
+
+    fn alpha() -> Vec<u8> {
+        let mut alpha = 1u64.to_be_bytes().to_vec();
+        alpha.extend_from_slice(&[0u8; 64]);
+        alpha
+    }
+
+    #[test]
+    fn test_verify_known_proof() {
+        let output = verify(&from_hex(VRF_KEY), &alpha(), &from_hex::<80>(PROOF)).unwrap();
+        assert_eq!(output, from_hex::<64>(OUTPUT));
+    }
+
+    #[test]
+    fn test_verify_rejects_invalid_input() {
+        let vrf_key = from_hex(VRF_KEY);
+        let proof = from_hex::<80>(PROOF);
+        assert_eq!(verify(&vrf_key, b"other", &proof), Err(EcvrfError::InvalidProof));
+        assert_eq!(verify(&vrf_key, &alpha(), &proof[..79]), Err(EcvrfError::InvalidProof));
+        assert_eq!(verify(&[0u8; 32], &alpha(), &proof), Err(EcvrfError::InvalidPublicKey));
+
+        let mut tampered = proof;
+        tampered[40] ^= 1;
+        assert_eq!(verify(&vrf_key, &alpha(), &tampered), Err(EcvrfError::InvalidProof));
+    }
+}
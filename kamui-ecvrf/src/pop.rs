@@ -0,0 +1,119 @@
+//! Proof of possession of an oracle VRF key, produced by `ecvrf-cli pop`
+//! (`mangekyou::kamui_vrf::pop`). It is a Schnorr proof of knowledge of the
+//! secret key on a merlin transcript bound to the oracle authority, so a key
+//! can only be registered by an authority that holds it.
+//!
+//! A proof is 64 bytes:
+//!
+//! ```text
+//! 0                    32                   64
+//! |--------------------|--------------------|
+//! |   commitment (A)   |    response (z)    |
+//! |--------------------|--------------------|
+//! ```
+//!
+//! and is valid for the key Y if z*B == A + c*Y.
+
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use sha2::{Digest, Sha512};
+use solana_zk_token_sdk::curve25519::ristretto::{add_ristretto, validate_ristretto};
+
+use crate::{multiply, EcvrfError, PodRistrettoPoint, PodScalar, BASEPOINT, PUBLIC_KEY_LENGTH};
+
+pub const POP_LENGTH: usize = 64;
+
+const TRANSCRIPT_LABEL: &[u8] = b"kamui-oracle-pop";
+const SCHNORR_DOMAIN: &[u8] = b"schnorr-pok";
+
+/// Check that `authority` holds the secret key of `vrf_key`. The key must be a
+/// valid ristretto255 point other than the identity.
+pub fn verify_proof_of_possession(
+    vrf_key: &[u8; PUBLIC_KEY_LENGTH],
+    authority: &[u8; 32],
+    proof: &[u8],
+) -> Result<(), EcvrfError> {
+    let y = PodRistrettoPoint(*vrf_key);
+    if *vrf_key == [0u8; PUBLIC_KEY_LENGTH] || !validate_ristretto(&y) {
+        return Err(EcvrfError::InvalidPublicKey);
+    }
+    if proof.len() != POP_LENGTH {
+        return Err(EcvrfError::InvalidProof);
+    }
+    let a = PodRistrettoPoint(proof[..32].try_into().unwrap());
+    let z = PodScalar(proof[32..].try_into().unwrap());
+    let c = challenge(authority, &y, &a);
+
+    // The syscalls reject a non-canonical response and an invalid commitment.
+    let lhs = multiply(&z, &BASEPOINT)?;
+    let rhs = add_ristretto(&a, &multiply(&c, &y)?).ok_or(EcvrfError::InvalidProof)?;
+    if lhs != rhs {
+        return Err(EcvrfError::InvalidProof);
+    }
+
+    Ok(())
+}
+
+/// The Fiat-Shamir challenge, replaying the prover's transcript.
+fn challenge(authority: &[u8; 32], y: &PodRistrettoPoint, a: &PodRistrettoPoint) -> PodScalar {
+    let mut transcript = Transcript::new(TRANSCRIPT_LABEL);
+    transcript.append_message(b"authority", authority);
+    transcript.append_message(b"dom-sep", SCHNORR_DOMAIN);
+    transcript.append_message(b"base", &BASEPOINT.0);
+    transcript.append_message(b"public", &y.0);
+    transcript.append_message(b"commitment", &a.0);
+    let mut buffer = [0u8; 64];
+    transcript.challenge_bytes(b"challenge", &mut buffer);
+
+    let wide: [u8; 64] = Sha512::digest(buffer).into();
+    PodScalar(Scalar::from_bytes_mod_order_wide(&wide).to_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_hex<const N: usize>(hex_str: &str) -> [u8; N] {
+        hex::decode(hex_str).unwrap().try_into().unwrap()
+    }
+
+    // Produced by `ecvrf-cli pop` for the authority
+    // AKnL4NNf3DGWZJS6cPknBuEGnVsV4A4m5tgebLHaRSZ9
+    const VRF_KEY: &str = "7a66a0fe0f2bcdcea5bfb97e3e9f6b298d25899052721bc2b4f3cb570a921b23";
+    const AUTHORITY: &str = "8a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c";
+    const PROOF: &str = "92acfef779459c86015f7a4946800e97b63fb9b8980d6e04ab40537939cedf1539b0a055d697f83bf07491434ed557bb4a4453aef58bf33ecd892901866dec0f";
+
+    #[test]
+    fn test_verify_known_proof() {
+        let proof = from_hex::<64>(PROOF);
+        assert_eq!(
+            verify_proof_of_possession(&from_hex(VRF_KEY), &from_hex(AUTHORITY), &proof),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_verify_rejects_invalid_input() {
+        let vrf_key = from_hex(VRF_KEY);
+        let authority = from_hex(AUTHORITY);
+        let proof = from_hex::<64>(PROOF);
+        assert_eq!(
+            verify_proof_of_possession(&vrf_key, &[1u8; 32], &proof),
+            Err(EcvrfError::InvalidProof)
+        );
+        assert_eq!(
+            verify_proof_of_possession(&vrf_key, &authority, &proof[..63]),
+            Err(EcvrfError::InvalidProof)
+        );
+        assert_eq!(
+            verify_proof_of_possession(&[0u8; 32], &authority, &proof),
+            Err(EcvrfError::InvalidPublicKey)
+        );
+
+        for i in [0, 31, 32, 63] {
+            let mut tampered = proof;
+            tampered[i] ^= 1;
+            assert!(verify_proof_of_possession(&vrf_key, &authority, &tampered).is_err());
+        }
+    }
+}
@@ -0,0 +1,603 @@
+use anchor_lang::prelude::error_code;
+use std::str;
+use crate::constants::{VRF_PROOF_SIZE, VRF_RANDOMNESS_SIZE};
+use crate::types::{
+    MessageType, VrfFailurePayload, VrfFailureReason, VrfFulfillmentPayload, VrfRequestPayload,
+};
+
+// -----------------------------------------------------------------------------
+// This file defines how the OApps encode and decode their messages. The
+// sending and receiving chains must agree on the layout, so every program uses
+// this codec. Plain strings are prefixed with a 32 byte length header; the
+// EVM-side equivalent is in `my-lz-oapp/contracts/libs/StringMsgCodec.sol`
+// -----------------------------------------------------------------------------
+
+
+// The message is a UTF-8 encoded string prefixed with a 32 byte header.
+// The following is the layout of the message:
+// Offset →
+// 0                     28     32                     32+N
+// |---------------------|------|---------------------------->
+// |     28 bytes        | 4B   |     N bytes                |
+// |    zero padding     | len  | UTF-8 encoded string       |
+// |---------------------|------|----------------------------|
+
+
+// We prefix the encoded string with a 32 byte length header.
+pub const LENGTH_OFFSET: usize = 0;
+pub const STRING_OFFSET: usize = 32;
+
+#[error_code]
+pub enum MsgCodecError {
+    /// Buffer too short to even contain the 32‐byte length header
+    InvalidLength,
+    /// Header says "string is N bytes" but buffer < 32+N
+    BodyTooShort,
+    /// Payload bytes aren't valid UTF-8
+    InvalidUtf8,
+    /// Version byte of a VRF message is not `CODEC_VERSION`
+    UnsupportedVersion,
+    /// Message type byte is not a known VRF message
+    UnknownMessageType,
+    /// Payload is not the canonical `abi.encode` of the expected struct
+    InvalidAbiEncoding,
+}
+
+/// Extract the string length
+fn decode_string_len(buf: &[u8]) -> Result<usize, MsgCodecError> {
+    // Header not long enough
+    if buf.len() < STRING_OFFSET {
+        return Err(MsgCodecError::InvalidLength);
+    }
+    let mut string_len_bytes = [0u8;32];
+    string_len_bytes.copy_from_slice(&buf[LENGTH_OFFSET..LENGTH_OFFSET+32]);
+    // The length is stored in the last 4 bytes (big endian)
+    Ok(u32::from_be_bytes(string_len_bytes[28..32].try_into().unwrap()) as usize)
+}
+
+// Encode a UTF-8 string into a message format with a 32 byte header
+pub fn encode(string: &str) -> Vec<u8> {
+    let string_bytes = string.as_bytes();
+    let mut msg = Vec::with_capacity(
+        STRING_OFFSET +               // header length
+        string_bytes.len()            // string bytes
+    );
+
+    // 4 byte length stored at the end of the 32 byte header
+    msg.extend_from_slice(&[0; 28]); // padding
+    msg.extend_from_slice(&(string_bytes.len() as u32).to_be_bytes());
+
+    // string
+    msg.extend_from_slice(string_bytes);
+
+    msg
+}
+
+// Decode a message format with a 32 byte header into a UTF-8 string
+// Returns an error if the message is malformed or not valid UTF-8
+pub fn decode(message: &[u8]) -> Result<String, MsgCodecError> {
+    // Read the declared payload length from the header
+    let string_len = decode_string_len(message)?;
+
+    let start = STRING_OFFSET;
+    // Safely compute end index and check for overflow
+    let end = start
+        .checked_add(string_len)
+        .ok_or(MsgCodecError::InvalidLength)?;
+
+    // Ensure the buffer actually contains the full payload
+    if end > message.len() {
+        return Err(MsgCodecError::BodyTooShort);
+    }
+
+    // Slice out the payload bytes
+    let payload = &message[start..end];
+    // Attempt to convert the bytes into a Rust string
+    match str::from_utf8(payload) {
+        Ok(s) => Ok(s.to_string()),
+        Err(_) => Err(MsgCodecError::InvalidUtf8),
+    }
+}
+
+// -----------------------------------------------------------------------------
+// VRF messages use a versioned layout that EVM peers can produce and consume
+// with plain `abi.encode` / `abi.decode`:
+//
+// Offset →
+// 0         1          2
+// |---------|----------|------------------------------------------------------>
+// | version | msg type | abi.encode(VrfRequest | VrfFulfillment | VrfFailure) |
+// |---------|----------|------------------------------------------------------|
+//
+// where the payload structs are
+//
+//     struct VrfRequest {
+//         bytes32 requester;
+//         bytes32 seed;
+//         uint32 numWords;
+//         bytes callbackData;
+//     }
+//
+//     struct VrfFulfillment {
+//         bytes32 requestId;
+//         bytes alpha;        // VRF input
+//         bytes proof;        // 80 bytes: Gamma || c || s
+//         bytes32 publicKey;  // oracle ECVRF public key
+//         bytes beta;         // 64 byte VRF output
+//     }
+//
+//     struct VrfFailure {
+//         bytes32 requestId;
+//         uint8 reason;       // 0: expired
+//     }
+//
+// On the EVM side a message is built with
+// `abi.encodePacked(uint8(1), uint8(msgType), abi.encode(payload))` and read
+// with `abi.decode(message[2:], (VrfRequest))`. Requests and fulfillments
+// contain dynamic members, so their `abi.encode` starts with a 32 byte offset
+// to the tuple. A failure is static and encodes to its two words.
+//
+// The EVM-side equivalent is `my-lz-oapp/contracts/libs/VrfMsgCodec.sol`.
+//
+// String messages start with the zero padding of their length header, so a
+// leading 0 byte identifies them and never collides with a version.
+// -----------------------------------------------------------------------------
+
+/// Version byte of the VRF message layout
+pub const CODEC_VERSION: u8 = 1;
+
+const VERSION_OFFSET: usize = 0;
+const TYPE_OFFSET: usize = 1;
+const PAYLOAD_OFFSET: usize = 2;
+const WORD: usize = 32;
+
+const VRF_PROOF_LEN: usize = VRF_PROOF_SIZE;
+const VRF_OUTPUT_LEN: usize = VRF_RANDOMNESS_SIZE;
+
+const MSG_TYPE_VRF_REQUEST: u8 = 0;
+const MSG_TYPE_VRF_FULFILLMENT: u8 = 1;
+const MSG_TYPE_VRF_FAILURE: u8 = 2;
+
+const FAILURE_REASON_EXPIRED: u8 = 0;
+
+/// Message codec for VRF and other LayerZero messages
+pub struct MessageCodec;
+
+impl MessageCodec {
+    /// Encode a VRF request payload
+    pub fn encode_vrf_request(payload: &VrfRequestPayload) -> Result<Vec<u8>, MsgCodecError> {
+        // Static head: requester, seed, numWords, offset of callbackData
+        let mut tuple = Vec::with_capacity(4 * WORD + padded_len(payload.callback_data.len()) + WORD);
+        tuple.extend_from_slice(&payload.requester);
+        tuple.extend_from_slice(&payload.seed);
+        tuple.extend_from_slice(&uint_word(payload.num_words as u64));
+        tuple.extend_from_slice(&uint_word((4 * WORD) as u64));
+        put_bytes(&mut tuple, &payload.callback_data);
+
+        Ok(Self::envelope(MSG_TYPE_VRF_REQUEST, tuple))
+    }
+    
+    /// Encode a VRF fulfillment payload
+    pub fn encode_vrf_fulfillment(payload: &VrfFulfillmentPayload) -> Result<Vec<u8>, MsgCodecError> {
+        if payload.proof.len() != VRF_PROOF_LEN {
+            return Err(MsgCodecError::InvalidLength);
+        }
+        let alpha_offset = 5 * WORD;
+        let proof_offset = alpha_offset + WORD + padded_len(payload.alpha.len());
+        let beta_offset = proof_offset + WORD + padded_len(VRF_PROOF_LEN);
+
+        // Static head: requestId, offset of alpha, offset of proof, publicKey,
+        // offset of beta
+        let mut tuple = Vec::with_capacity(beta_offset + WORD + VRF_OUTPUT_LEN);
+        tuple.extend_from_slice(&payload.request_id);
+        tuple.extend_from_slice(&uint_word(alpha_offset as u64));
+        tuple.extend_from_slice(&uint_word(proof_offset as u64));
+        tuple.extend_from_slice(&payload.public_key);
+        tuple.extend_from_slice(&uint_word(beta_offset as u64));
+        put_bytes(&mut tuple, &payload.alpha);
+        put_bytes(&mut tuple, &payload.proof);
+        put_bytes(&mut tuple, &payload.beta);
+
+        Ok(Self::envelope(MSG_TYPE_VRF_FULFILLMENT, tuple))
+    }
+
+    /// Encode a VRF failure payload
+    pub fn encode_vrf_failure(payload: &VrfFailurePayload) -> Result<Vec<u8>, MsgCodecError> {
+        let reason = match payload.reason {
+            VrfFailureReason::Expired => FAILURE_REASON_EXPIRED,
+        };
+
+        // Static tuple: no offset word in front
+        let mut message = Vec::with_capacity(PAYLOAD_OFFSET + 2 * WORD);
+        message.push(CODEC_VERSION);
+        message.push(MSG_TYPE_VRF_FAILURE);
+        message.extend_from_slice(&payload.request_id);
+        message.extend_from_slice(&uint_word(reason as u64));
+        Ok(message)
+    }
+    
+    /// Decode a LayerZero message and determine its type
+    pub fn decode_message_type(message: &[u8]) -> Result<MessageType, MsgCodecError> {
+        if message.is_empty() {
+            return Err(MsgCodecError::InvalidLength);
+        }
+        
+        match message[VERSION_OFFSET] {
+            0 => Ok(MessageType::Generic),
+            CODEC_VERSION => match message.get(TYPE_OFFSET) {
+                Some(&MSG_TYPE_VRF_REQUEST) => Ok(MessageType::VrfRequest),
+                Some(&MSG_TYPE_VRF_FULFILLMENT) => Ok(MessageType::VrfFulfillment),
+                Some(&MSG_TYPE_VRF_FAILURE) => Ok(MessageType::VrfFailure),
+                Some(_) => Err(MsgCodecError::UnknownMessageType),
+                None => Err(MsgCodecError::InvalidLength),
+            },
+            _ => Err(MsgCodecError::UnsupportedVersion),
+        }
+    }
+    
+    /// Decode a VRF request message
+    pub fn decode_vrf_request(message: &[u8]) -> Result<VrfRequestPayload, MsgCodecError> {
+        let tuple = Self::open(message, MessageType::VrfRequest)?;
+
+        let requester = read_bytes32(tuple, 0)?;
+        let seed = read_bytes32(tuple, WORD)?;
+        let num_words = u32::try_from(read_uint(tuple, 2 * WORD)?)
+            .map_err(|_| MsgCodecError::InvalidAbiEncoding)?;
+        let (callback_data, end) = read_bytes(tuple, 3 * WORD, 4 * WORD)?;
+        if end != tuple.len() {
+            return Err(MsgCodecError::InvalidAbiEncoding);
+        }
+
+        Ok(VrfRequestPayload {
+            msg_type: MessageType::VrfRequest,
+            requester,
+            seed,
+            num_words,
+            callback_data,
+        })
+    }
+
+    /// Decode a VRF fulfillment message
+    pub fn decode_vrf_fulfillment(message: &[u8]) -> Result<VrfFulfillmentPayload, MsgCodecError> {
+        let tuple = Self::open(message, MessageType::VrfFulfillment)?;
+
+        let request_id = read_bytes32(tuple, 0)?;
+        let (alpha, proof_offset) = read_bytes(tuple, WORD, 5 * WORD)?;
+        let (proof, beta_offset) = read_bytes(tuple, 2 * WORD, proof_offset)?;
+        let public_key = read_bytes32(tuple, 3 * WORD)?;
+        let (beta, end) = read_bytes(tuple, 4 * WORD, beta_offset)?;
+        if end != tuple.len() || proof.len() != VRF_PROOF_LEN {
+            return Err(MsgCodecError::InvalidAbiEncoding);
+        }
+        let beta: [u8; VRF_OUTPUT_LEN] = beta
+            .try_into()
+            .map_err(|_| MsgCodecError::InvalidAbiEncoding)?;
+
+        Ok(VrfFulfillmentPayload {
+            msg_type: MessageType::VrfFulfillment,
+            request_id,
+            alpha,
+            proof,
+            public_key,
+            beta,
+        })
+    }
+    
+    /// Decode a VRF failure message
+    pub fn decode_vrf_failure(message: &[u8]) -> Result<VrfFailurePayload, MsgCodecError> {
+        let tuple = Self::payload(message, MessageType::VrfFailure)?;
+        if tuple.len() != 2 * WORD {
+            return Err(MsgCodecError::InvalidAbiEncoding);
+        }
+
+        let request_id = read_bytes32(tuple, 0)?;
+        let reason = match read_uint(tuple, WORD)? {
+            0 => VrfFailureReason::Expired,
+            _ => return Err(MsgCodecError::InvalidAbiEncoding),
+        };
+
+        Ok(VrfFailurePayload {
+            msg_type: MessageType::VrfFailure,
+            request_id,
+            reason,
+        })
+    }
+
+    /// Decode `message` if it is a VRF request.
+    pub fn try_decode_vrf_request(message: &[u8]) -> Option<VrfRequestPayload> {
+        match Self::decode_message_type(message) {
+            Ok(MessageType::VrfRequest) => Self::decode_vrf_request(message).ok(),
+            _ => None,
+        }
+    }
+
+    /// Decode `message` if it is a VRF fulfillment.
+    pub fn try_decode_vrf_fulfillment(message: &[u8]) -> Option<VrfFulfillmentPayload> {
+        match Self::decode_message_type(message) {
+            Ok(MessageType::VrfFulfillment) => Self::decode_vrf_fulfillment(message).ok(),
+            _ => None,
+        }
+    }
+
+    /// Decode `message` if it is a VRF failure.
+    pub fn try_decode_vrf_failure(message: &[u8]) -> Option<VrfFailurePayload> {
+        match Self::decode_message_type(message) {
+            Ok(MessageType::VrfFailure) => Self::decode_vrf_failure(message).ok(),
+            _ => None,
+        }
+    }
+
+    /// Prefix `abi.encode(tuple)` with the version and message type.
+    fn envelope(msg_type: u8, tuple: Vec<u8>) -> Vec<u8> {
+        let mut message = Vec::with_capacity(PAYLOAD_OFFSET + WORD + tuple.len());
+        message.push(CODEC_VERSION);
+        message.push(msg_type);
+        message.extend_from_slice(&uint_word(WORD as u64));
+        message.extend_from_slice(&tuple);
+        message
+    }
+
+    /// Check the version and type of `message` and return what follows them.
+    fn payload(message: &[u8], expected: MessageType) -> Result<&[u8], MsgCodecError> {
+        if Self::decode_message_type(message)? != expected {
+            return Err(MsgCodecError::UnknownMessageType);
+        }
+        Ok(&message[PAYLOAD_OFFSET..])
+    }
+
+    /// Check the version and type of `message` and return the encoded tuple
+    /// of a payload with dynamic members.
+    fn open(message: &[u8], expected: MessageType) -> Result<&[u8], MsgCodecError> {
+        let payload = Self::payload(message, expected)?;
+        if read_uint(payload, 0)? != WORD as u64 {
+            return Err(MsgCodecError::InvalidAbiEncoding);
+        }
+        Ok(&payload[WORD..])
+    }
+}
+
+/// Length of `len` bytes padded to a whole number of words
+fn padded_len(len: usize) -> usize {
+    len.div_ceil(WORD) * WORD
+}
+
+/// A uint left-padded to 32 bytes
+fn uint_word(value: u64) -> [u8; WORD] {
+    let mut word = [0u8; WORD];
+    word[WORD - 8..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// Append `bytes` as an ABI `bytes` tail: its length followed by the data
+/// right-padded to a whole number of words.
+fn put_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.extend_from_slice(&uint_word(bytes.len() as u64));
+    buf.extend_from_slice(bytes);
+    buf.resize(buf.len() + padded_len(bytes.len()) - bytes.len(), 0);
+}
+
+fn read_word(buf: &[u8], offset: usize) -> Result<&[u8], MsgCodecError> {
+    buf.get(offset..offset + WORD).ok_or(MsgCodecError::BodyTooShort)
+}
+
+fn read_bytes32(buf: &[u8], offset: usize) -> Result<[u8; 32], MsgCodecError> {
+    let mut out = [0u8; 32];
+    out.copy_from_slice(read_word(buf, offset)?);
+    Ok(out)
+}
+
+/// Read a uint word, rejecting values that do not fit in 64 bits.
+fn read_uint(buf: &[u8], offset: usize) -> Result<u64, MsgCodecError> {
+    let word = read_word(buf, offset)?;
+    if word[..WORD - 8].iter().any(|&b| b != 0) {
+        return Err(MsgCodecError::InvalidAbiEncoding);
+    }
+    Ok(u64::from_be_bytes(word[WORD - 8..].try_into().unwrap()))
+}
+
+/// Read the `bytes` whose offset is stored at `head`. Only the canonical
+/// encoding produced by `abi.encode` is accepted: the data must start at
+/// `expected_offset` and be zero-padded. Returns the data and the end of its
+/// padding.
+fn read_bytes(buf: &[u8], head: usize, expected_offset: usize) -> Result<(Vec<u8>, usize), MsgCodecError> {
+    if read_uint(buf, head)? != expected_offset as u64 {
+        return Err(MsgCodecError::InvalidAbiEncoding);
+    }
+    let len = read_uint(buf, expected_offset)? as usize;
+    let start = expected_offset + WORD;
+    let end = len
+        .checked_add(WORD - 1)
+        .map(|len| start + len / WORD * WORD)
+        .ok_or(MsgCodecError::InvalidLength)?;
+    let padded = buf.get(start..end).ok_or(MsgCodecError::BodyTooShort)?;
+    if padded[len..].iter().any(|&b| b != 0) {
+        return Err(MsgCodecError::InvalidAbiEncoding);
+    }
+    Ok((padded[..len].to_vec(), end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(hex_word: &str) -> String {
+        format!("{:0>64}", hex_word)
+    }
+
+    fn from_hex(hex_str: &str) -> Vec<u8> {
+        (0..hex_str.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex_str[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn request() -> VrfRequestPayload {
+        VrfRequestPayload {
+            msg_type: MessageType::VrfRequest,
+            requester: [0x11; 32],
+            seed: [0x22; 32],
+            num_words: 3,
+            callback_data: vec![0xde, 0xad, 0xbe, 0xef],
+        }
+    }
+
+    // 0x01 0x00 ++ abi.encode(VrfRequest(0x11..11, 0x22..22, 3, hex"deadbeef"))
+    fn request_vector() -> Vec<u8> {
+        from_hex(&[
+            "0100".to_string(),
+            word("20"),             // offset of the tuple
+            "11".repeat(32),        // requester
+            "22".repeat(32),        // seed
+            word("3"),              // numWords
+            word("80"),             // offset of callbackData
+            word("4"),              // callbackData length
+            format!("{:0<64}", "deadbeef"),
+        ].concat())
+    }
+
+    // A proof from mangekyou's ECVRF prover
+    const ALPHA: &str = "4869204b616d756921";
+    const PROOF: &str = "54b58f527e999ceedb24485a7629e3caa9f7deb152852a0f483a6646495fa253c4131e87ff0b48fefacf4b5be04211a77390ca85553aa2c06f0023db34e7b36194eadf11539c0ef1c8dcae09aa35580a";
+    const PUBLIC_KEY: &str = "7a66a0fe0f2bcdcea5bfb97e3e9f6b298d25899052721bc2b4f3cb570a921b23";
+    const BETA: &str = "8d9c5b901c05a4edf4dff80bbe970db6ca782fe785ef1375989a3fdb3a93b521f4165ea3a6d1c90ae5641bb528beb98c1eed13d36fb32951ecf163b7900e3da6";
+
+    fn fulfillment() -> VrfFulfillmentPayload {
+        VrfFulfillmentPayload {
+            msg_type: MessageType::VrfFulfillment,
+            request_id: [0x33; 32],
+            alpha: from_hex(ALPHA),
+            proof: from_hex(PROOF),
+            public_key: from_hex(PUBLIC_KEY).try_into().unwrap(),
+            beta: from_hex(BETA).try_into().unwrap(),
+        }
+    }
+
+    // 0x01 0x01 ++ abi.encode(VrfFulfillment(0x33..33, ALPHA, PROOF, PUBLIC_KEY, BETA))
+    fn fulfillment_vector() -> Vec<u8> {
+        from_hex(&[
+            "0101".to_string(),
+            word("20"),             // offset of the tuple
+            "33".repeat(32),        // requestId
+            word("a0"),             // offset of alpha
+            word("e0"),             // offset of proof
+            PUBLIC_KEY.to_string(), // publicKey
+            word("160"),            // offset of beta
+            word("9"),              // alpha length
+            format!("{:0<64}", ALPHA),
+            word("50"),             // proof length
+            format!("{:0<192}", PROOF),
+            word("40"),             // beta length
+            BETA.to_string(),
+        ].concat())
+    }
+
+    #[test]
+    fn test_vrf_request_matches_abi_encoding() {
+        let encoded = MessageCodec::encode_vrf_request(&request()).unwrap();
+        assert_eq!(encoded, request_vector());
+
+        let decoded = MessageCodec::decode_vrf_request(&encoded).unwrap();
+        assert_eq!(decoded.requester, [0x11; 32]);
+        assert_eq!(decoded.seed, [0x22; 32]);
+        assert_eq!(decoded.num_words, 3);
+        assert_eq!(decoded.callback_data, vec![0xde, 0xad, 0xbe, 0xef]);
+    }
+
+    #[test]
+    fn test_vrf_request_with_empty_and_word_sized_callback() {
+        for len in [0usize, 32, 33] {
+            let mut payload = request();
+            payload.callback_data = vec![0x5a; len];
+            let encoded = MessageCodec::encode_vrf_request(&payload).unwrap();
+            assert_eq!(encoded.len(), PAYLOAD_OFFSET + 6 * WORD + padded_len(len));
+            let decoded = MessageCodec::decode_vrf_request(&encoded).unwrap();
+            assert_eq!(decoded.callback_data, payload.callback_data);
+        }
+    }
+
+    #[test]
+    fn test_vrf_fulfillment_matches_abi_encoding() {
+        let encoded = MessageCodec::encode_vrf_fulfillment(&fulfillment()).unwrap();
+        assert_eq!(encoded, fulfillment_vector());
+
+        let decoded = MessageCodec::decode_vrf_fulfillment(&encoded).unwrap();
+        assert_eq!(decoded.request_id, [0x33; 32]);
+        assert_eq!(decoded.alpha, from_hex(ALPHA));
+        assert_eq!(decoded.proof, from_hex(PROOF));
+        assert_eq!(decoded.public_key.to_vec(), from_hex(PUBLIC_KEY));
+        assert_eq!(decoded.beta.to_vec(), from_hex(BETA));
+    }
+
+    // 0x01 0x02 ++ abi.encode(VrfFailure(0x44..44, 0))
+    fn failure_vector() -> Vec<u8> {
+        from_hex(&["0102".to_string(), "44".repeat(32), word("0")].concat())
+    }
+
+    #[test]
+    fn test_vrf_failure_matches_abi_encoding() {
+        let payload = VrfFailurePayload {
+            msg_type: MessageType::VrfFailure,
+            request_id: [0x44; 32],
+            reason: VrfFailureReason::Expired,
+        };
+        let encoded = MessageCodec::encode_vrf_failure(&payload).unwrap();
+        assert_eq!(encoded, failure_vector());
+
+        let decoded = MessageCodec::decode_vrf_failure(&encoded).unwrap();
+        assert_eq!(decoded.request_id, [0x44; 32]);
+        assert_eq!(decoded.reason, VrfFailureReason::Expired);
+
+        // Unknown reason
+        let mut message = failure_vector();
+        *message.last_mut().unwrap() = 1;
+        assert!(MessageCodec::decode_vrf_failure(&message).is_err());
+
+        // Trailing bytes
+        let mut message = failure_vector();
+        message.push(0);
+        assert!(MessageCodec::decode_vrf_failure(&message).is_err());
+    }
+
+    #[test]
+    fn test_message_type() {
+        assert_eq!(MessageCodec::decode_message_type(&request_vector()).unwrap(), MessageType::VrfRequest);
+        assert_eq!(MessageCodec::decode_message_type(&fulfillment_vector()).unwrap(), MessageType::VrfFulfillment);
+        assert_eq!(MessageCodec::decode_message_type(&failure_vector()).unwrap(), MessageType::VrfFailure);
+        assert_eq!(MessageCodec::decode_message_type(&encode("hello")).unwrap(), MessageType::Generic);
+        assert!(MessageCodec::try_decode_vrf_request(&encode("hello")).is_none());
+        assert!(MessageCodec::try_decode_vrf_request(&fulfillment_vector()).is_none());
+        assert!(MessageCodec::try_decode_vrf_failure(&request_vector()).is_none());
+    }
+
+    #[test]
+    fn test_rejects_non_canonical_messages() {
+        // Unsupported version
+        let mut message = request_vector();
+        message[VERSION_OFFSET] = 2;
+        assert!(MessageCodec::decode_vrf_request(&message).is_err());
+
+        // numWords does not fit in a uint32
+        let mut message = request_vector();
+        message[PAYLOAD_OFFSET + 3 * WORD + 27] = 1;
+        assert!(MessageCodec::decode_vrf_request(&message).is_err());
+
+        // Non-zero padding after callbackData
+        let mut message = request_vector();
+        *message.last_mut().unwrap() = 1;
+        assert!(MessageCodec::decode_vrf_request(&message).is_err());
+
+        // Trailing bytes
+        let mut message = request_vector();
+        message.extend_from_slice(&[0; WORD]);
+        assert!(MessageCodec::decode_vrf_request(&message).is_err());
+
+        // Truncated
+        let message = fulfillment_vector();
+        assert!(MessageCodec::decode_vrf_fulfillment(&message[..message.len() - WORD]).is_err());
+
+        // Proof of the wrong length
+        let mut payload = fulfillment();
+        payload.proof.pop();
+        assert!(MessageCodec::encode_vrf_fulfillment(&payload).is_err());
+    }
+}
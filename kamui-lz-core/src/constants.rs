@@ -0,0 +1,74 @@
+/// LayerZero OApp standard seeds
+pub const STORE_SEED: &[u8] = b"Store"; // You are free to edit this seed.
+pub const PEER_SEED: &[u8] = b"Peer"; // The Executor relies on this exact seed to derive the Peer PDA. Keep it the same.
+pub const LZ_RECEIVE_TYPES_SEED: &[u8] = b"LzReceiveTypes"; // The Executor relies on this exact seed to derive the LzReceiveTypes PDA. Keep it the same.
+pub const LZ_COMPOSE_TYPES_SEED: &[u8] = b"LzComposeTypes"; // The Executor relies on this exact seed to derive the LzComposeTypes PDA. Keep it the same.
+
+/// Seed of the per-eid `EnforcedOptionsConfig` PDA
+pub const ENFORCED_OPTIONS_SEED: &[u8] = b"EnforcedOptions";
+
+/// Seed of the per-request `VrfRequest` PDA
+pub const VRF_REQUEST_SEED: &[u8] = b"VrfRequest";
+
+/// Seed of the per-request `OutboundVrfRequest` PDA
+pub const OUTBOUND_VRF_REQUEST_SEED: &[u8] = b"OutboundVrfRequest";
+
+/// Seed of the per-message `ProcessedGuid` PDA
+pub const PROCESSED_GUID_SEED: &[u8] = b"ProcessedGuid";
+
+/// LayerZero Endpoint Program ID for Solana Devnet
+pub const LAYERZERO_ENDPOINT_PROGRAM_ID: &str = "76y77prsiCMvXMjuoZ5VRrhG5qYBrUMYTE5WgHqgjEn6";
+
+/// LayerZero Endpoint ID for operations (Solana Devnet)
+pub const ENDPOINT_ID: u32 = SOLANA_DEVNET_EID;
+
+/// LayerZero Endpoint IDs for Mainnet
+pub const ETHEREUM_EID: u32 = 30101;
+pub const BINANCE_EID: u32 = 30102;
+pub const AVALANCHE_EID: u32 = 30106;
+pub const POLYGON_EID: u32 = 30109;
+pub const ARBITRUM_EID: u32 = 30110;
+pub const OPTIMISM_EID: u32 = 30111;
+pub const FANTOM_EID: u32 = 30112;
+pub const BASE_EID: u32 = 30184;
+pub const SOLANA_EID: u32 = 30168;
+
+/// LayerZero Endpoint IDs for Testnets/Devnets
+pub const ETHEREUM_SEPOLIA_EID: u32 = 40161;
+pub const OPTIMISM_SEPOLIA_EID: u32 = 40232;
+pub const ARBITRUM_SEPOLIA_EID: u32 = 40231;
+pub const BASE_SEPOLIA_EID: u32 = 40245;
+pub const POLYGON_AMOY_EID: u32 = 40267;
+pub const AVALANCHE_FUJI_EID: u32 = 40106;
+pub const BINANCE_TESTNET_EID: u32 = 40102;
+pub const SOLANA_DEVNET_EID: u32 = 40168;
+
+/// Endpoints the OApps are deployed against
+pub const MAINNET_EIDS: &[u32] = &[
+    ETHEREUM_EID, BINANCE_EID, AVALANCHE_EID, POLYGON_EID, ARBITRUM_EID,
+    OPTIMISM_EID, FANTOM_EID, BASE_EID, SOLANA_EID,
+];
+pub const TESTNET_EIDS: &[u32] = &[
+    ETHEREUM_SEPOLIA_EID, OPTIMISM_SEPOLIA_EID, ARBITRUM_SEPOLIA_EID, BASE_SEPOLIA_EID,
+    POLYGON_AMOY_EID, AVALANCHE_FUJI_EID, BINANCE_TESTNET_EID, SOLANA_DEVNET_EID,
+];
+
+/// Message size limits
+pub const MAX_MESSAGE_SIZE: usize = 65535;
+pub const MAX_COMPOSE_MESSAGE_SIZE: usize = 65535;
+
+/// VRF specific constants
+pub const VRF_SEED_SIZE: usize = 32;
+pub const VRF_RANDOMNESS_SIZE: usize = 64;
+/// ECVRF proof: Gamma || c || s
+pub const VRF_PROOF_SIZE: usize = 80;
+pub const VRF_PUBLIC_KEY_SIZE: usize = 32;
+/// Most random words one request can ask for
+pub const MAX_VRF_WORDS: u32 = 10;
+
+// Additional LayerZero seeds used by the instruction files
+pub const OAPP_SEED: &[u8] = b"OApp";
+pub const ENDPOINT_AUTHORITY_SEED: &[u8] = b"EndpointAuthority";
+pub const EVENT_AUTHORITY_SEED: &[u8] = b"EventAuthority";
+pub const NONCE_ACCOUNT_SEED: &[u8] = b"NonceAccount";
+pub const SOLANA_CHAIN_ID: u16 = 40168; // Solana Devnet for testing
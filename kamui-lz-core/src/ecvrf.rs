@@ -0,0 +1,91 @@
+use crate::constants::{VRF_PROOF_SIZE, VRF_PUBLIC_KEY_SIZE, VRF_RANDOMNESS_SIZE};
+use crate::validation::ValidationError;
+
+// -----------------------------------------------------------------------------
+// On-chain verification of the ECVRF proofs carried by fulfillments. The
+// verifier itself is `kamui-ecvrf`, shared with the native coordinator and
+// `kamui-vrf`, so a proof accepted by one program is accepted by all of them.
+// -----------------------------------------------------------------------------
+
+pub use kamui_ecvrf::{challenge, encode_to_curve, proof_to_hash, BASEPOINT};
+
+pub const PUBLIC_KEY_LENGTH: usize = VRF_PUBLIC_KEY_SIZE;
+pub const PROOF_LENGTH: usize = VRF_PROOF_SIZE;
+pub const OUTPUT_LENGTH: usize = VRF_RANDOMNESS_SIZE;
+
+/// Verify `proof` for `alpha` under `public_key` and return the VRF output.
+pub fn verify(
+    public_key: &[u8; PUBLIC_KEY_LENGTH],
+    alpha: &[u8],
+    proof: &[u8],
+) -> Result<[u8; OUTPUT_LENGTH], ValidationError> {
+    kamui_ecvrf::verify(public_key, alpha, proof).map_err(|_| ValidationError::InvalidVrfProof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::MessageCodec;
+    use crate::types::{MessageType, VrfFulfillmentPayload};
+
+    fn from_hex(hex_str: &str) -> Vec<u8> {
+        (0..hex_str.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex_str[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    // Produced by mangekyou's ECVRF prover
+    const PUBLIC_KEY: &str = "7a66a0fe0f2bcdcea5bfb97e3e9f6b298d25899052721bc2b4f3cb570a921b23";
+    const ALPHA: &str = "4869204b616d756921";
+    const PROOF: &str = "54b58f527e999ceedb24485a7629e3caa9f7deb152852a0f483a6646495fa253c4131e87ff0b48fefacf4b5be04211a77390ca85553aa2c06f0023db34e7b36194eadf11539c0ef1c8dcae09aa35580a";
+    const OUTPUT: &str = "8d9c5b901c05a4edf4dff80bbe970db6ca782fe785ef1375989a3fdb3a93b521f4165ea3a6d1c90ae5641bb528beb98c1eed13d36fb32951ecf163b7900e3da6";
+
+    fn public_key() -> [u8; 32] {
+        from_hex(PUBLIC_KEY).try_into().unwrap()
+    }
+
+    #[test]
+    fn test_verify_known_proof() {
+        let output = verify(&public_key(), &from_hex(ALPHA), &from_hex(PROOF)).unwrap();
+        assert_eq!(output.to_vec(), from_hex(OUTPUT));
+    }
+
+    #[test]
+    fn test_verify_proof_carried_by_fulfillment() {
+        let payload = VrfFulfillmentPayload {
+            msg_type: MessageType::VrfFulfillment,
+            request_id: [0x33; 32],
+            alpha: from_hex(ALPHA),
+            proof: from_hex(PROOF),
+            public_key: public_key(),
+            beta: from_hex(OUTPUT).try_into().unwrap(),
+        };
+        let message = MessageCodec::encode_vrf_fulfillment(&payload).unwrap();
+
+        let decoded = MessageCodec::decode_vrf_fulfillment(&message).unwrap();
+        let beta = verify(&decoded.public_key, &decoded.alpha, &decoded.proof).unwrap();
+        assert_eq!(beta, decoded.beta);
+    }
+
+    #[test]
+    fn test_verify_rejects_tampering() {
+        let proof = from_hex(PROOF);
+        assert!(verify(&public_key(), b"another input", &proof).is_err());
+
+        for i in [0, 40, 79] {
+            let mut tampered = proof.clone();
+            tampered[i] ^= 1;
+            assert!(verify(&public_key(), &from_hex(ALPHA), &tampered).is_err());
+        }
+
+        let mut other_key = public_key();
+        other_key[0] ^= 1;
+        assert!(verify(&other_key, &from_hex(ALPHA), &proof).is_err());
+        assert_eq!(
+            verify(&[0; 32], &from_hex(ALPHA), &proof),
+            Err(ValidationError::InvalidVrfProof)
+        );
+        assert!(verify(&public_key(), &from_hex(ALPHA), &proof[..79]).is_err());
+    }
+}
@@ -0,0 +1,197 @@
+use crate::codec::{self, MessageCodec, MsgCodecError};
+use crate::constants::{VRF_PUBLIC_KEY_SIZE, VRF_RANDOMNESS_SIZE};
+use crate::ecvrf;
+use crate::types::{
+    vrf_alpha, MessageType, VrfFailurePayload, VrfFulfillmentPayload, VrfRequestPayload,
+};
+use crate::validation::{self, ValidationError};
+
+// -----------------------------------------------------------------------------
+// The steps of the OApp instructions that don't depend on where a program keeps
+// its requests: reading inbound messages, building VRF messages and verifying
+// the proofs they carry. The programs own the accounts and call into these, so
+// `request_vrf`, `fulfill_vrf` and `lz_receive` check the same things in every
+// OApp.
+// -----------------------------------------------------------------------------
+
+/// A message received from a peer
+#[derive(Clone)]
+pub enum InboundMessage {
+    VrfRequest(VrfRequestPayload),
+    VrfFulfillment(VrfFulfillmentPayload),
+    VrfFailure(VrfFailurePayload),
+    /// A plain string, see [`codec::decode`]
+    Generic(String),
+}
+
+impl InboundMessage {
+    /// Decode `message` by the type in its header.
+    pub fn decode(message: &[u8]) -> Result<Self, MsgCodecError> {
+        Ok(match MessageCodec::decode_message_type(message)? {
+            MessageType::VrfRequest => Self::VrfRequest(MessageCodec::decode_vrf_request(message)?),
+            MessageType::VrfFulfillment => {
+                Self::VrfFulfillment(MessageCodec::decode_vrf_fulfillment(message)?)
+            }
+            MessageType::VrfFailure => Self::VrfFailure(MessageCodec::decode_vrf_failure(message)?),
+            MessageType::Generic => Self::Generic(codec::decode(message)?),
+        })
+    }
+}
+
+/// The message `request_vrf` sends for `requester`.
+pub fn vrf_request(
+    requester: [u8; 32],
+    seed: [u8; 32],
+    num_words: u32,
+    callback_data: Vec<u8>,
+    max_callback_data_size: usize,
+) -> Result<VrfRequestPayload, ValidationError> {
+    validation::validate_vrf_request(num_words, &callback_data, max_callback_data_size)?;
+    Ok(VrfRequestPayload {
+        msg_type: MessageType::VrfRequest,
+        requester,
+        seed,
+        num_words,
+        callback_data,
+    })
+}
+
+/// The message `fulfill_vrf` sends back for the request `request_id`. `proof`
+/// must verify under the oracle VRF key registered with the OApp, and the
+/// message carries the output computed from it.
+pub fn vrf_fulfillment(
+    oracle_vrf_key: Option<[u8; VRF_PUBLIC_KEY_SIZE]>,
+    request_id: [u8; 32],
+    seed: &[u8; 32],
+    proof: Vec<u8>,
+) -> Result<VrfFulfillmentPayload, ValidationError> {
+    let public_key = oracle_vrf_key.ok_or(ValidationError::UnknownOracle)?;
+    let alpha = vrf_alpha(&request_id, seed);
+    let beta = ecvrf::verify(&public_key, &alpha, &proof)?;
+
+    Ok(VrfFulfillmentPayload {
+        msg_type: MessageType::VrfFulfillment,
+        request_id,
+        alpha,
+        proof,
+        public_key,
+        beta,
+    })
+}
+
+/// Verify a fulfillment received for a request sent with `seed` and return its
+/// randomness. The proof must be for the request's input and under the oracle
+/// VRF key registered with the OApp.
+pub fn verify_vrf_fulfillment(
+    oracle_vrf_key: Option<[u8; VRF_PUBLIC_KEY_SIZE]>,
+    seed: &[u8; 32],
+    fulfillment: &VrfFulfillmentPayload,
+) -> Result<[u8; VRF_RANDOMNESS_SIZE], ValidationError> {
+    let public_key = oracle_vrf_key.ok_or(ValidationError::UnknownOracle)?;
+    if fulfillment.public_key != public_key {
+        return Err(ValidationError::UnknownOracle);
+    }
+    if fulfillment.alpha != vrf_alpha(&fulfillment.request_id, seed) {
+        return Err(ValidationError::InvalidVrfProof);
+    }
+    let beta = ecvrf::verify(&public_key, &fulfillment.alpha, &fulfillment.proof)?;
+    if beta != fulfillment.beta {
+        return Err(ValidationError::InvalidVrfProof);
+    }
+    Ok(beta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::VRF_PROOF_SIZE;
+    use crate::types::VrfFailureReason;
+
+    const SEED: [u8; 32] = [5; 32];
+    const REQUEST_ID: [u8; 32] = [6; 32];
+
+    fn fulfillment(public_key: [u8; 32]) -> VrfFulfillmentPayload {
+        VrfFulfillmentPayload {
+            msg_type: MessageType::VrfFulfillment,
+            request_id: REQUEST_ID,
+            alpha: vrf_alpha(&REQUEST_ID, &SEED),
+            proof: vec![1; VRF_PROOF_SIZE],
+            public_key,
+            beta: [2; VRF_RANDOMNESS_SIZE],
+        }
+    }
+
+    #[test]
+    fn test_decode_inbound_messages() {
+        let request = vrf_request([1; 32], SEED, 2, vec![3; 4], 32).unwrap();
+        let message = MessageCodec::encode_vrf_request(&request).unwrap();
+        match InboundMessage::decode(&message).unwrap() {
+            InboundMessage::VrfRequest(decoded) => assert_eq!(decoded.callback_data, vec![3; 4]),
+            _ => panic!("expected a VRF request"),
+        }
+
+        let message = MessageCodec::encode_vrf_fulfillment(&fulfillment([7; 32])).unwrap();
+        assert!(matches!(InboundMessage::decode(&message), Ok(InboundMessage::VrfFulfillment(_))));
+
+        let failure = VrfFailurePayload {
+            msg_type: MessageType::VrfFailure,
+            request_id: REQUEST_ID,
+            reason: VrfFailureReason::Expired,
+        };
+        let message = MessageCodec::encode_vrf_failure(&failure).unwrap();
+        assert!(matches!(InboundMessage::decode(&message), Ok(InboundMessage::VrfFailure(_))));
+
+        match InboundMessage::decode(&codec::encode("hello")).unwrap() {
+            InboundMessage::Generic(string) => assert_eq!(string, "hello"),
+            _ => panic!("expected a string"),
+        }
+        assert!(InboundMessage::decode(&[]).is_err());
+    }
+
+    #[test]
+    fn test_vrf_request_is_validated() {
+        assert!(vrf_request([1; 32], SEED, 1, vec![0; 32], 32).is_ok());
+        assert_eq!(
+            vrf_request([1; 32], SEED, 0, vec![], 32).err(),
+            Some(ValidationError::InvalidVrfParams)
+        );
+        assert_eq!(
+            vrf_request([1; 32], SEED, 1, vec![0; 33], 32).err(),
+            Some(ValidationError::InvalidCallbackDataSize)
+        );
+    }
+
+    #[test]
+    fn test_fulfillment_requires_oracle_key() {
+        assert_eq!(
+            vrf_fulfillment(None, REQUEST_ID, &SEED, vec![1; VRF_PROOF_SIZE]).err(),
+            Some(ValidationError::UnknownOracle)
+        );
+        assert_eq!(
+            verify_vrf_fulfillment(None, &SEED, &fulfillment([7; 32])),
+            Err(ValidationError::UnknownOracle)
+        );
+        // A proof under another key is refused before it is verified
+        assert_eq!(
+            verify_vrf_fulfillment(Some([8; 32]), &SEED, &fulfillment([7; 32])),
+            Err(ValidationError::UnknownOracle)
+        );
+    }
+
+    #[test]
+    fn test_fulfillment_must_match_request() {
+        // The proof is checked against the seed of the request that was sent
+        assert_eq!(
+            verify_vrf_fulfillment(Some([7; 32]), &[0; 32], &fulfillment([7; 32])),
+            Err(ValidationError::InvalidVrfProof)
+        );
+        assert_eq!(
+            verify_vrf_fulfillment(Some([7; 32]), &SEED, &fulfillment([7; 32])),
+            Err(ValidationError::InvalidVrfProof)
+        );
+        assert_eq!(
+            vrf_fulfillment(Some([7; 32]), REQUEST_ID, &SEED, vec![1; VRF_PROOF_SIZE]).err(),
+            Some(ValidationError::InvalidVrfProof)
+        );
+    }
+}
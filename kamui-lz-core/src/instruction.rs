@@ -0,0 +1,177 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{hash::hash, instruction::Instruction, system_program};
+use crate::pda;
+
+// -----------------------------------------------------------------------------
+// Off-chain construction of OApp instructions, for clients and Executors that
+// don't link against a program crate. Instruction data is the Anchor
+// discriminator of the instruction followed by its Borsh-encoded arguments.
+// -----------------------------------------------------------------------------
+
+/// Anchor discriminator of the instruction `name`: the first 8 bytes of
+/// `sha256("global:<name>")`.
+pub fn sighash(name: &str) -> [u8; 8] {
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hash(format!("global:{}", name).as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+/// Builds one instruction for an OApp program.
+///
+/// ```ignore
+/// let instruction = InstructionBuilder::new(program_id, "set_delegate")
+///     .signer(admin, true)
+///     .account(store, false)
+///     .args(&delegate)
+///     .build();
+/// ```
+pub struct InstructionBuilder {
+    program_id: Pubkey,
+    accounts: Vec<AccountMeta>,
+    data: Vec<u8>,
+}
+
+impl InstructionBuilder {
+    pub fn new(program_id: Pubkey, name: &str) -> Self {
+        Self { program_id, accounts: vec![], data: sighash(name).to_vec() }
+    }
+
+    pub fn account(mut self, pubkey: Pubkey, is_writable: bool) -> Self {
+        self.accounts.push(AccountMeta { pubkey, is_signer: false, is_writable });
+        self
+    }
+
+    pub fn signer(mut self, pubkey: Pubkey, is_writable: bool) -> Self {
+        self.accounts.push(AccountMeta { pubkey, is_signer: true, is_writable });
+        self
+    }
+
+    /// Append `accounts`, e.g. the remaining accounts for an Endpoint CPI
+    pub fn accounts(mut self, accounts: impl IntoIterator<Item = AccountMeta>) -> Self {
+        self.accounts.extend(accounts);
+        self
+    }
+
+    /// Append the next argument of the instruction
+    pub fn args(mut self, args: &impl AnchorSerialize) -> Self {
+        args.serialize(&mut self.data).expect("writing to a Vec does not fail");
+        self
+    }
+
+    pub fn build(self) -> Instruction {
+        Instruction { program_id: self.program_id, accounts: self.accounts, data: self.data }
+    }
+}
+
+/// `set_peer_config` for `remote_eid`. `config` is the program's
+/// `PeerConfigParam`.
+pub fn set_peer_config(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    remote_eid: u32,
+    config: &impl AnchorSerialize,
+) -> Instruction {
+    let (store, _) = pda::store(program_id);
+    InstructionBuilder::new(*program_id, "set_peer_config")
+        .signer(*admin, true)
+        .account(pda::peer(program_id, &store, remote_eid).0, true)
+        .account(store, false)
+        .account(system_program::ID, false)
+        .args(&remote_eid)
+        .args(config)
+        .build()
+}
+
+/// `lz_receive_types`, simulated by the Executor to learn the accounts of
+/// `lz_receive`. `params` are the `LzReceiveParams` of the message.
+pub fn lz_receive_types(program_id: &Pubkey, params: &impl AnchorSerialize) -> Instruction {
+    InstructionBuilder::new(*program_id, "lz_receive_types")
+        .account(pda::store(program_id).0, false)
+        .args(params)
+        .build()
+}
+
+/// `lz_receive` with the accounts returned by `lz_receive_types`. Signer
+/// accounts left as the default pubkey are placeholders for `payer`.
+pub fn lz_receive(
+    program_id: &Pubkey,
+    accounts: impl IntoIterator<Item = AccountMeta>,
+    payer: &Pubkey,
+    params: &impl AnchorSerialize,
+) -> Instruction {
+    InstructionBuilder::new(*program_id, "lz_receive")
+        .accounts(fill_payer(accounts, payer))
+        .args(params)
+        .build()
+}
+
+/// `lz_compose_types`, simulated by the Executor to learn the accounts of
+/// `lz_compose`. `params` are the `LzComposeParams` of the compose message.
+pub fn lz_compose_types(program_id: &Pubkey, params: &impl AnchorSerialize) -> Instruction {
+    InstructionBuilder::new(*program_id, "lz_compose_types")
+        .account(pda::store(program_id).0, false)
+        .args(params)
+        .build()
+}
+
+/// `lz_compose` with the accounts returned by `lz_compose_types`
+pub fn lz_compose(
+    program_id: &Pubkey,
+    accounts: impl IntoIterator<Item = AccountMeta>,
+    payer: &Pubkey,
+    params: &impl AnchorSerialize,
+) -> Instruction {
+    InstructionBuilder::new(*program_id, "lz_compose")
+        .accounts(fill_payer(accounts, payer))
+        .args(params)
+        .build()
+}
+
+/// Replace the payer placeholders in `accounts` with `payer`
+pub fn fill_payer(accounts: impl IntoIterator<Item = AccountMeta>, payer: &Pubkey) -> Vec<AccountMeta> {
+    accounts
+        .into_iter()
+        .map(|mut account| {
+            if account.is_signer && account.pubkey == Pubkey::default() {
+                account.pubkey = *payer;
+            }
+            account
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instruction_data() {
+        // Anchor's discriminator for `global:lz_receive`
+        assert_eq!(sighash("lz_receive"), [8, 179, 120, 109, 33, 118, 189, 80]);
+
+        let program_id = Pubkey::new_unique();
+        let instruction = InstructionBuilder::new(program_id, "set_delegate")
+            .args(&7u32)
+            .args(&vec![1u8, 2])
+            .build();
+        assert_eq!(instruction.data[..8], sighash("set_delegate"));
+        assert_eq!(instruction.data[8..], [7, 0, 0, 0, 2, 0, 0, 0, 1, 2]);
+    }
+
+    #[test]
+    fn test_lz_receive_fills_payer() {
+        let program_id = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let system = AccountMeta::new_readonly(system_program::ID, false);
+        let accounts = vec![
+            AccountMeta::new(pda::store(&program_id).0, false),
+            AccountMeta::new(Pubkey::default(), true),
+            system.clone(),
+        ];
+
+        let instruction = lz_receive(&program_id, accounts, &payer, &0u8);
+        assert_eq!(instruction.accounts[1], AccountMeta::new(payer, true));
+        // The system program is also the default pubkey, but not a signer
+        assert_eq!(instruction.accounts[2], system);
+    }
+}
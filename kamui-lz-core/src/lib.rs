@@ -0,0 +1,23 @@
+//! Shared core of the Kamui LayerZero OApps.
+//!
+//! `kamui-layerzero`, `standalone-layerzero` and `my_oapp` all speak the same
+//! wire format and derive the same PDAs. Everything that has to agree between
+//! them lives here, so a fix to the message layout lands in every program at
+//! once:
+//!
+//! - [`types`] and [`codec`]: the messages and their EVM-compatible encoding
+//! - [`constants`] and [`pda`]: seeds and account derivations
+//! - [`validation`]: checks on endpoints, peers and messages
+//! - [`ecvrf`]: on-chain verification of the oracles' ECVRF proofs
+//! - [`handler`]: the steps of `request_vrf`, `fulfill_vrf` and `lz_receive`
+//!   that every OApp shares
+//! - [`instruction`]: building OApp instructions off-chain, e.g. for an Executor
+
+pub mod codec;
+pub mod constants;
+pub mod ecvrf;
+pub mod handler;
+pub mod instruction;
+pub mod pda;
+pub mod types;
+pub mod validation;
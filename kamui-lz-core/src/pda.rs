@@ -0,0 +1,57 @@
+use anchor_lang::prelude::Pubkey;
+use crate::constants::{
+    ENFORCED_OPTIONS_SEED, LZ_COMPOSE_TYPES_SEED, LZ_RECEIVE_TYPES_SEED, OUTBOUND_VRF_REQUEST_SEED,
+    PEER_SEED, PROCESSED_GUID_SEED, STORE_SEED, VRF_REQUEST_SEED,
+};
+
+// -----------------------------------------------------------------------------
+// PDA derivations of the OApps. `program_id` is the OApp program; every other
+// account is seeded by its Store. Endpoint IDs are big endian in seeds, as the
+// Executor expects for the peer.
+// -----------------------------------------------------------------------------
+
+/// The Store PDA, the OApp's address towards the Endpoint
+pub fn store(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STORE_SEED], program_id)
+}
+
+/// The peer config of `eid`, which the Executor derives to check the sender
+pub fn peer(program_id: &Pubkey, store: &Pubkey, eid: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PEER_SEED, store.as_ref(), &eid.to_be_bytes()], program_id)
+}
+
+/// The account the Executor reads before calling `lz_receive_types`
+pub fn lz_receive_types(program_id: &Pubkey, store: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LZ_RECEIVE_TYPES_SEED, store.as_ref()], program_id)
+}
+
+/// The account the Executor reads before calling `lz_compose_types`
+pub fn lz_compose_types(program_id: &Pubkey, store: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LZ_COMPOSE_TYPES_SEED, store.as_ref()], program_id)
+}
+
+/// Options enforced on VRF messages to `eid`
+pub fn enforced_options(program_id: &Pubkey, store: &Pubkey, eid: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[ENFORCED_OPTIONS_SEED, store.as_ref(), &eid.to_be_bytes()],
+        program_id,
+    )
+}
+
+/// An inbound VRF request, by the GUID of the request message
+pub fn vrf_request(program_id: &Pubkey, store: &Pubkey, guid: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VRF_REQUEST_SEED, store.as_ref(), guid], program_id)
+}
+
+/// An outbound VRF request, by the GUID of the request message
+pub fn outbound_vrf_request(program_id: &Pubkey, store: &Pubkey, guid: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[OUTBOUND_VRF_REQUEST_SEED, store.as_ref(), guid], program_id)
+}
+
+/// The replay record of a message received from `src_eid`
+pub fn processed_guid(program_id: &Pubkey, store: &Pubkey, src_eid: u32, guid: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[PROCESSED_GUID_SEED, store.as_ref(), &src_eid.to_be_bytes(), guid],
+        program_id,
+    )
+}
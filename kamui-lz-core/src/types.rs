@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+use crate::constants::{VRF_PUBLIC_KEY_SIZE, VRF_RANDOMNESS_SIZE};
+
+/// Message types for LayerZero messages
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum MessageType {
+    VrfRequest,
+    VrfFulfillment,
+    /// Sent back when a request could not be fulfilled
+    VrfFailure,
+    Generic,
+}
+
+/// VRF request payload structure
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct VrfRequestPayload {
+    pub msg_type: MessageType,
+    pub requester: [u8; 32],
+    pub seed: [u8; 32],
+    pub num_words: u32,
+    pub callback_data: Vec<u8>,
+}
+
+/// VRF fulfillment payload structure. Carries everything needed to verify the
+/// proof again on the destination chain.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct VrfFulfillmentPayload {
+    pub msg_type: MessageType,
+    pub request_id: [u8; 32],
+    /// VRF input
+    pub alpha: Vec<u8>,
+    /// 80-byte ECVRF proof
+    pub proof: Vec<u8>,
+    /// Oracle VRF public key the proof verifies under
+    pub public_key: [u8; VRF_PUBLIC_KEY_SIZE],
+    /// VRF output
+    pub beta: [u8; VRF_RANDOMNESS_SIZE],
+}
+
+/// Why a VRF request was answered with a failure
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VrfFailureReason {
+    /// The oracle did not fulfill the request before its deadline
+    Expired,
+}
+
+/// VRF failure payload structure. Tells the source chain the request will not
+/// be fulfilled so it can release whatever was waiting on it.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct VrfFailurePayload {
+    pub msg_type: MessageType,
+    pub request_id: [u8; 32],
+    pub reason: VrfFailureReason,
+}
+
+/// The VRF input for a request: its request ID followed by the seed. Including
+/// the GUID keeps outputs distinct when a seed is reused.
+pub fn vrf_alpha(request_id: &[u8; 32], seed: &[u8; 32]) -> Vec<u8> {
+    [&request_id[..], &seed[..]].concat()
+}
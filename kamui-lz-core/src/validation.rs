@@ -0,0 +1,137 @@
+use crate::constants::{MAINNET_EIDS, MAX_MESSAGE_SIZE, MAX_VRF_WORDS, TESTNET_EIDS};
+
+// -----------------------------------------------------------------------------
+// Checks shared by the OApps. They return a `ValidationError` rather than an
+// Anchor error so each program can map it onto its own error codes.
+// -----------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    /// Not an endpoint the OApps are deployed against
+    UnknownEndpoint,
+    /// Message larger than `MAX_MESSAGE_SIZE`
+    MessageTooLarge,
+    /// The zero address, i.e. a peer that was never set
+    InvalidPeerAddress,
+    /// Message not sent by the configured peer
+    InvalidSender,
+    /// No random words, or more than `MAX_VRF_WORDS`
+    InvalidVrfParams,
+    /// Callback data larger than the OApp accepts
+    InvalidCallbackDataSize,
+    /// No oracle VRF key is registered, or the proof is under another key
+    UnknownOracle,
+    /// The proof does not verify for the request's input
+    InvalidVrfProof,
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            Self::UnknownEndpoint => "unknown endpoint ID",
+            Self::MessageTooLarge => "message payload too large",
+            Self::InvalidPeerAddress => "invalid peer address",
+            Self::InvalidSender => "sender is not the configured peer",
+            Self::InvalidVrfParams => "invalid number of random words",
+            Self::InvalidCallbackDataSize => "callback data too large",
+            Self::UnknownOracle => "not the registered oracle VRF key",
+            Self::InvalidVrfProof => "invalid VRF proof",
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Check that `eid` is one of the known mainnet or testnet endpoints.
+pub fn validate_endpoint_id(eid: u32) -> Result<(), ValidationError> {
+    if MAINNET_EIDS.contains(&eid) || TESTNET_EIDS.contains(&eid) {
+        Ok(())
+    } else {
+        Err(ValidationError::UnknownEndpoint)
+    }
+}
+
+/// Check that `message` fits in a LayerZero message.
+pub fn validate_message_size(message: &[u8]) -> Result<(), ValidationError> {
+    if message.len() > MAX_MESSAGE_SIZE {
+        return Err(ValidationError::MessageTooLarge);
+    }
+    Ok(())
+}
+
+/// Check that a peer address is set.
+pub fn validate_peer_address(address: &[u8; 32]) -> Result<(), ValidationError> {
+    if address.iter().all(|&b| b == 0) {
+        return Err(ValidationError::InvalidPeerAddress);
+    }
+    Ok(())
+}
+
+/// Check the words and callback data of a VRF request. Each OApp sets its own
+/// limit on the callback data.
+pub fn validate_vrf_request(
+    num_words: u32,
+    callback_data: &[u8],
+    max_callback_data_size: usize,
+) -> Result<(), ValidationError> {
+    if num_words == 0 || num_words > MAX_VRF_WORDS {
+        return Err(ValidationError::InvalidVrfParams);
+    }
+    if callback_data.len() > max_callback_data_size {
+        return Err(ValidationError::InvalidCallbackDataSize);
+    }
+    Ok(())
+}
+
+/// Check that a received message comes from the configured peer.
+pub fn validate_sender(sender: &[u8; 32], peer_address: &[u8; 32]) -> Result<(), ValidationError> {
+    validate_peer_address(peer_address)?;
+    if sender != peer_address {
+        return Err(ValidationError::InvalidSender);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{ETHEREUM_EID, SOLANA_DEVNET_EID};
+
+    #[test]
+    fn test_endpoint_ids() {
+        assert!(validate_endpoint_id(ETHEREUM_EID).is_ok());
+        assert!(validate_endpoint_id(SOLANA_DEVNET_EID).is_ok());
+        assert_eq!(validate_endpoint_id(1), Err(ValidationError::UnknownEndpoint));
+    }
+
+    #[test]
+    fn test_message_and_peer_checks() {
+        assert!(validate_message_size(&vec![0; MAX_MESSAGE_SIZE]).is_ok());
+        assert_eq!(
+            validate_message_size(&vec![0; MAX_MESSAGE_SIZE + 1]),
+            Err(ValidationError::MessageTooLarge)
+        );
+
+        let peer = [7; 32];
+        assert!(validate_sender(&peer, &peer).is_ok());
+        assert_eq!(validate_sender(&[8; 32], &peer), Err(ValidationError::InvalidSender));
+        // An unset peer matches nobody, not even a zero sender
+        assert_eq!(validate_sender(&[0; 32], &[0; 32]), Err(ValidationError::InvalidPeerAddress));
+    }
+
+    #[test]
+    fn test_vrf_request_checks() {
+        assert!(validate_vrf_request(1, &[0; 32], 32).is_ok());
+        assert!(validate_vrf_request(MAX_VRF_WORDS, &[], 0).is_ok());
+        assert_eq!(validate_vrf_request(0, &[], 32), Err(ValidationError::InvalidVrfParams));
+        assert_eq!(
+            validate_vrf_request(MAX_VRF_WORDS + 1, &[], 32),
+            Err(ValidationError::InvalidVrfParams)
+        );
+        assert_eq!(
+            validate_vrf_request(1, &[0; 33], 32),
+            Err(ValidationError::InvalidCallbackDataSize)
+        );
+    }
+}
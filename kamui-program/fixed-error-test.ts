@@ -20,6 +20,11 @@ import { KamuiVrf } from '../target/types/kamui_vrf';
 import { KamuiVrfConsumer } from '../target/types/kamui_vrf_consumer';
 import { KamuiLayerzero } from '../target/types/kamui_layerzero';
 
+// VRF key and proof of possession for the oracle authority, from
+// `ecvrf-cli pop --authority <oracle authority>`
+const ORACLE_VRF_KEY = '7a66a0fe0f2bcdcea5bfb97e3e9f6b298d25899052721bc2b4f3cb570a921b23';
+const ORACLE_PROOF_OF_POSSESSION = '92acfef779459c86015f7a4946800e97b63fb9b8980d6e04ab40537939cedf1539b0a055d697f83bf07491434ed557bb4a4453aef58bf33ecd892901866dec0f';
+
 describe('Kamui VRF Fixed Error Test', () => {
     // Configure the client to use the local cluster
     const provider = anchor.AnchorProvider.env();
@@ -41,7 +46,8 @@ describe('Kamui VRF Fixed Error Test', () => {
     // Test accounts
     const admin = Keypair.generate();
     const owner = Keypair.generate();
-    const oracle = Keypair.generate();
+    // Fixed so that the proof of possession above is bound to it
+    const oracle = Keypair.fromSeed(new Uint8Array(32).fill(1));
 
     // PDAs
     let registryPDA: PublicKey;
@@ -184,8 +190,9 @@ describe('Kamui VRF Fixed Error Test', () => {
             try {
                 const tx = await vrfProgram.methods
                     .registerOracle(
-                        Array.from(vrfKeypair.publicKey),
-                        new BN(0.01 * LAMPORTS_PER_SOL) // Reduced stake amount
+                        Array.from(Buffer.from(ORACLE_VRF_KEY, 'hex')),
+                        new BN(0.01 * LAMPORTS_PER_SOL), // Reduced stake amount
+                        Array.from(Buffer.from(ORACLE_PROOF_OF_POSSESSION, 'hex'))
                     )
                     .accounts({
                         oracleAuthority: oracle.publicKey,
@@ -0,0 +1,217 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    hash::hash,
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+};
+use oapp::endpoint_cpi::LzAccount;
+use crate::constants::{MAX_CONSUMER_CALLBACK_ACCOUNTS, VRF_CALLBACK_SEED};
+use crate::errors::LayerZeroError;
+use crate::state::{CallbackAccount, ConsumerCallback};
+
+// -----------------------------------------------------------------------------
+// Delivery of inbound VRF fulfillments to Solana consumer programs.
+//
+// `lz_receive` records the verified randomness and composes a message to this
+// OApp; `lz_compose` then calls the consumer. A failing consumer only reverts
+// `lz_compose`, which the Executor can retry, while the fulfillment itself has
+// already been cleared.
+//
+// Compose message:
+// 0            32                96             128   129
+// |------------|-----------------|--------------|-----|------------------------|
+// | request id |   randomness    |   consumer   |  n  | (pubkey, writable) * n |
+// |------------|-----------------|--------------|-----|------------------------|
+//
+// The consumer is invoked with the instruction `vrf_callback(request_id,
+// randomness)` (Anchor discriminator of `global:vrf_callback`) and the accounts
+// [callback signer (signer), outbound request, ...callback accounts]. The
+// callback signer is the PDA `[VRF_CALLBACK_SEED]` of this OApp and proves the
+// call came from it. The Store never signs into a consumer: it is the
+// Endpoint's OApp signer and the coordinator bridge authority, and signer
+// privilege carries over to every CPI the consumer makes.
+// -----------------------------------------------------------------------------
+
+const HEADER_LEN: usize = 32 + 64 + 32 + 1;
+const CALLBACK_ACCOUNT_LEN: usize = 32 + 1;
+
+/// A decoded compose message
+#[derive(Clone, Debug, PartialEq)]
+pub struct VrfComposeMessage {
+    pub request_id: [u8; 32],
+    pub randomness: [u8; 64],
+    pub consumer: ConsumerCallback,
+}
+
+#[derive(AnchorSerialize)]
+struct VrfCallbackArgs {
+    request_id: [u8; 32],
+    randomness: [u8; 64],
+}
+
+pub fn encode_compose_message(message: &VrfComposeMessage) -> Vec<u8> {
+    let accounts = &message.consumer.accounts;
+    let mut encoded = Vec::with_capacity(HEADER_LEN + accounts.len() * CALLBACK_ACCOUNT_LEN);
+    encoded.extend_from_slice(&message.request_id);
+    encoded.extend_from_slice(&message.randomness);
+    encoded.extend_from_slice(message.consumer.program_id.as_ref());
+    encoded.push(accounts.len() as u8);
+    for account in accounts {
+        encoded.extend_from_slice(account.pubkey.as_ref());
+        encoded.push(account.is_writable as u8);
+    }
+    encoded
+}
+
+pub fn decode_compose_message(message: &[u8]) -> Result<VrfComposeMessage> {
+    if message.len() < HEADER_LEN {
+        return Err(LayerZeroError::InvalidComposeMessage.into());
+    }
+    let count = message[HEADER_LEN - 1] as usize;
+    if count > MAX_CONSUMER_CALLBACK_ACCOUNTS
+        || message.len() != HEADER_LEN + count * CALLBACK_ACCOUNT_LEN
+    {
+        return Err(LayerZeroError::InvalidComposeMessage.into());
+    }
+
+    let accounts = message[HEADER_LEN..]
+        .chunks_exact(CALLBACK_ACCOUNT_LEN)
+        .map(|chunk| match chunk[32] {
+            0 | 1 => Ok(CallbackAccount {
+                pubkey: Pubkey::new_from_array(chunk[..32].try_into().unwrap()),
+                is_writable: chunk[32] == 1,
+            }),
+            _ => Err(LayerZeroError::InvalidComposeMessage.into()),
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(VrfComposeMessage {
+        request_id: message[..32].try_into().unwrap(),
+        randomness: message[32..96].try_into().unwrap(),
+        consumer: ConsumerCallback {
+            program_id: Pubkey::new_from_array(message[96..128].try_into().unwrap()),
+            accounts,
+        },
+    })
+}
+
+/// The PDA that signs consumer callbacks for the OApp `program_id`. Consumers
+/// check the first account of `vrf_callback` against it.
+pub fn callback_signer(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VRF_CALLBACK_SEED], program_id)
+}
+
+/// Returns the accounts used by [`deliver_randomness`], in the order
+/// `lz_compose` reads them.
+pub fn get_accounts_for_callback(consumer: &ConsumerCallback) -> Vec<LzAccount> {
+    let mut accounts = vec![LzAccount {
+        pubkey: consumer.program_id,
+        is_signer: false,
+        is_writable: false,
+    }];
+    accounts.extend(consumer.accounts.iter().map(|account| LzAccount {
+        pubkey: account.pubkey,
+        is_signer: false,
+        is_writable: account.is_writable,
+    }));
+    accounts
+}
+
+/// Call the consumer of `message`. `callback_signer` signs with
+/// `signer_seeds`; `accounts` are the ones returned by
+/// [`get_accounts_for_callback`].
+pub fn deliver_randomness<'info>(
+    callback_signer: &AccountInfo<'info>,
+    request: &AccountInfo<'info>,
+    accounts: &[AccountInfo<'info>],
+    signer_seeds: &[&[u8]],
+    message: &VrfComposeMessage,
+) -> Result<()> {
+    let consumer = &message.consumer;
+    let (program, callback_accounts) = accounts
+        .split_first()
+        .ok_or(LayerZeroError::AccountConstraintViolation)?;
+    if program.key() != consumer.program_id || callback_accounts.len() < consumer.accounts.len() {
+        return Err(LayerZeroError::AccountConstraintViolation.into());
+    }
+    let callback_accounts = &callback_accounts[..consumer.accounts.len()];
+
+    let mut metas = vec![
+        AccountMeta::new_readonly(callback_signer.key(), true),
+        AccountMeta::new_readonly(request.key(), false),
+    ];
+    for (info, expected) in callback_accounts.iter().zip(&consumer.accounts) {
+        if info.key() != expected.pubkey {
+            return Err(LayerZeroError::AccountConstraintViolation.into());
+        }
+        metas.push(if expected.is_writable {
+            AccountMeta::new(expected.pubkey, false)
+        } else {
+            AccountMeta::new_readonly(expected.pubkey, false)
+        });
+    }
+
+    let args = VrfCallbackArgs {
+        request_id: message.request_id,
+        randomness: message.randomness,
+    };
+    let mut data = hash(b"global:vrf_callback").to_bytes()[..8].to_vec();
+    args.serialize(&mut data)?;
+
+    let mut infos = vec![callback_signer.clone(), request.clone()];
+    infos.extend(callback_accounts.iter().cloned());
+    infos.push(program.clone());
+
+    invoke_signed(
+        &Instruction { program_id: consumer.program_id, accounts: metas, data },
+        &infos,
+        &[signer_seeds],
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message() -> VrfComposeMessage {
+        VrfComposeMessage {
+            request_id: [1; 32],
+            randomness: [2; 64],
+            consumer: ConsumerCallback {
+                program_id: Pubkey::new_from_array([3; 32]),
+                accounts: vec![
+                    CallbackAccount { pubkey: Pubkey::new_from_array([4; 32]), is_writable: true },
+                    CallbackAccount { pubkey: Pubkey::new_from_array([5; 32]), is_writable: false },
+                ],
+            },
+        }
+    }
+
+    #[test]
+    fn test_compose_message_round_trip() {
+        let encoded = encode_compose_message(&message());
+        assert_eq!(encoded.len(), HEADER_LEN + 2 * CALLBACK_ACCOUNT_LEN);
+        assert_eq!(encoded[HEADER_LEN - 1], 2);
+        assert_eq!(decode_compose_message(&encoded).unwrap(), message());
+    }
+
+    #[test]
+    fn test_compose_message_rejects_malformed() {
+        let encoded = encode_compose_message(&message());
+        assert!(decode_compose_message(&encoded[..HEADER_LEN - 1]).is_err());
+        assert!(decode_compose_message(&encoded[..encoded.len() - 1]).is_err());
+
+        // Writable flag must be 0 or 1
+        let mut bad_flag = encoded.clone();
+        bad_flag[HEADER_LEN + 32] = 2;
+        assert!(decode_compose_message(&bad_flag).is_err());
+
+        // Account count above the limit
+        let mut too_many = encoded[..HEADER_LEN].to_vec();
+        too_many[HEADER_LEN - 1] = (MAX_CONSUMER_CALLBACK_ACCOUNTS + 1) as u8;
+        too_many.extend(vec![0; (MAX_CONSUMER_CALLBACK_ACCOUNTS + 1) * CALLBACK_ACCOUNT_LEN]);
+        assert!(decode_compose_message(&too_many).is_err());
+    }
+}
@@ -0,0 +1,135 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+    system_program,
+};
+use kamui_vrf_anchor::InstructionData;
+use oapp::endpoint_cpi::LzAccount;
+use crate::constants::{
+    COORDINATOR_MAX_CALLBACK_DATA_SIZE, CROSS_CHAIN_BRIDGE_SEED, CROSS_CHAIN_REQUEST_SEED,
+    REQUEST_POOL_SEED,
+};
+use crate::errors::LayerZeroError;
+use crate::state::{CrossChainOrigin, VrfCoordinatorConfig, VrfRequestPayload};
+
+// -----------------------------------------------------------------------------
+// CPI into the kamui-vrf coordinator. kamui-vrf is built against a newer Anchor
+// release than this program, so its generated `cpi` module and account types
+// can't be used here. The instruction data comes from its generated
+// `instruction` types; the account list below must match kamui-vrf.
+// -----------------------------------------------------------------------------
+
+/// Number of accounts `lz_receive` expects after the `Endpoint::clear` accounts
+/// when the message is a VRF request.
+pub const REQUEST_ACCOUNTS_LEN: usize = 7;
+
+/// Positions within the accounts returned by [`get_accounts_for_request`]
+pub const PAYER_INDEX: usize = 0;
+pub const REQUEST_INDEX: usize = 3;
+pub const SYSTEM_PROGRAM_INDEX: usize = 6;
+
+/// Returns the accounts used by `request_randomness` for the message `guid`, in
+/// the order `lz_receive` reads them. The payer is left as the default pubkey
+/// and is filled in by the Executor.
+pub fn get_accounts_for_request(config: &VrfCoordinatorConfig, guid: &[u8; 32]) -> Vec<LzAccount> {
+    let (bridge, _) = Pubkey::find_program_address(
+        &[CROSS_CHAIN_BRIDGE_SEED, config.subscription.as_ref()],
+        &config.vrf_program,
+    );
+    let (request, _) = Pubkey::find_program_address(
+        &[CROSS_CHAIN_REQUEST_SEED, config.subscription.as_ref(), guid.as_ref()],
+        &config.vrf_program,
+    );
+    let (request_pool, _) = Pubkey::find_program_address(
+        &[REQUEST_POOL_SEED, config.subscription.as_ref(), &[config.pool_id]],
+        &config.vrf_program,
+    );
+
+    vec![
+        // payer (rent for the request account)
+        LzAccount { pubkey: Pubkey::default(), is_signer: true, is_writable: true },
+        LzAccount { pubkey: config.vrf_program, is_signer: false, is_writable: false },
+        LzAccount { pubkey: bridge, is_signer: false, is_writable: false },
+        LzAccount { pubkey: request, is_signer: false, is_writable: true },
+        LzAccount { pubkey: config.subscription, is_signer: false, is_writable: true },
+        LzAccount { pubkey: request_pool, is_signer: false, is_writable: true },
+        LzAccount { pubkey: system_program::ID, is_signer: false, is_writable: false },
+    ]
+}
+
+/// Submit `payload` to the coordinator as a request charged to the configured
+/// subscription. `authority` is the Store PDA and signs with `signer_seeds`;
+/// `accounts` are the ones returned by [`get_accounts_for_request`].
+pub fn request_randomness<'info>(
+    config: &VrfCoordinatorConfig,
+    authority: &AccountInfo<'info>,
+    accounts: &[AccountInfo<'info>],
+    signer_seeds: &[&[u8]],
+    payload: VrfRequestPayload,
+    origin: CrossChainOrigin,
+) -> Result<()> {
+    if accounts.len() < REQUEST_ACCOUNTS_LEN {
+        return Err(LayerZeroError::InvalidCoordinatorAccount.into());
+    }
+    if payload.callback_data.len() > COORDINATOR_MAX_CALLBACK_DATA_SIZE {
+        return Err(LayerZeroError::InvalidCallbackDataSize.into());
+    }
+
+    let payer = &accounts[PAYER_INDEX];
+    let vrf_program = &accounts[1];
+    let bridge = &accounts[2];
+    let request = &accounts[REQUEST_INDEX];
+    let subscription = &accounts[4];
+    let request_pool = &accounts[5];
+    let system_program = &accounts[SYSTEM_PROGRAM_INDEX];
+
+    // The bridge, request and pool PDAs are checked by kamui-vrf. The program
+    // and subscription are checked here so the Executor cannot redirect the
+    // request.
+    if vrf_program.key() != config.vrf_program || subscription.key() != config.subscription {
+        return Err(LayerZeroError::InvalidCoordinatorAccount.into());
+    }
+
+    let data = kamui_vrf::instruction::RequestCrossChainRandomness {
+        seed: payload.seed,
+        callback_data: payload.callback_data,
+        num_words: payload.num_words,
+        minimum_confirmations: config.minimum_confirmations,
+        callback_gas_limit: config.callback_gas_limit,
+        pool_id: config.pool_id,
+        origin,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: config.vrf_program,
+        accounts: vec![
+            AccountMeta::new(payer.key(), true),
+            AccountMeta::new_readonly(authority.key(), true),
+            AccountMeta::new_readonly(bridge.key(), false),
+            AccountMeta::new(request.key(), false),
+            AccountMeta::new(subscription.key(), false),
+            AccountMeta::new(request_pool.key(), false),
+            AccountMeta::new_readonly(system_program.key(), false),
+        ],
+        data,
+    };
+
+    invoke_signed(
+        &instruction,
+        &[
+            payer.clone(),
+            authority.clone(),
+            bridge.clone(),
+            request.clone(),
+            subscription.clone(),
+            request_pool.clone(),
+            system_program.clone(),
+            vrf_program.clone(),
+        ],
+        &[signer_seeds],
+    )?;
+
+    Ok(())
+}
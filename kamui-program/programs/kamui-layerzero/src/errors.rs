@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use kamui_lz_core::validation::ValidationError;
 
 #[error_code]
 pub enum LayerZeroError {
@@ -104,4 +105,49 @@ pub enum LayerZeroError {
 
     #[msg("Request already fulfilled")]
     RequestAlreadyFulfilled,
-} 
\ No newline at end of file
+
+    #[msg("VRF coordinator is not configured")]
+    VrfCoordinatorNotConfigured,
+
+    #[msg("Invalid VRF coordinator account")]
+    InvalidCoordinatorAccount,
+
+    #[msg("VRF request has expired")]
+    RequestExpired,
+
+    #[msg("VRF request cannot be closed yet")]
+    RequestNotClosable,
+
+    #[msg("Invalid VRF proof")]
+    InvalidVrfProof,
+
+    #[msg("Traffic with this peer is paused")]
+    PeerPaused,
+
+    #[msg("Peer rate limit exceeded")]
+    RateLimitExceeded,
+
+    #[msg("Invalid rate limit")]
+    InvalidRateLimit,
+
+    #[msg("Message already processed")]
+    MessageAlreadyProcessed,
+
+    #[msg("VRF request has not reached its deadline")]
+    RequestNotExpired,
+}
+
+impl From<ValidationError> for LayerZeroError {
+    fn from(error: ValidationError) -> Self {
+        match error {
+            ValidationError::UnknownEndpoint => LayerZeroError::InvalidEndpointId,
+            ValidationError::MessageTooLarge => LayerZeroError::MessageTooLarge,
+            ValidationError::InvalidPeerAddress => LayerZeroError::InvalidPeerAddress,
+            ValidationError::InvalidSender => LayerZeroError::InvalidSender,
+            ValidationError::InvalidVrfParams => LayerZeroError::InvalidVrfParams,
+            ValidationError::InvalidCallbackDataSize => LayerZeroError::InvalidCallbackDataSize,
+            ValidationError::UnknownOracle => LayerZeroError::UnauthorizedOracle,
+            ValidationError::InvalidVrfProof => LayerZeroError::InvalidVrfProof,
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::{STORE_SEED, VRF_REQUEST_SEED};
+use crate::errors::LayerZeroError;
+
+/// Closes a delivered or expired request and returns its rent to the payer.
+/// Anyone can call it; the lamports always go to `request.payer`. A request
+/// past its deadline is expired first with `expire_cross_chain_request`, which
+/// tells the source chain.
+#[derive(Accounts)]
+pub struct CloseVrfRequest<'info> {
+    #[account(seeds = [STORE_SEED], bump = store.bump)]
+    pub store: Account<'info, Store>,
+    
+    #[account(
+        mut,
+        seeds = [VRF_REQUEST_SEED, store.key().as_ref(), &request.request_id],
+        bump = request.bump,
+        has_one = payer,
+        close = payer
+    )]
+    pub request: Account<'info, VrfRequest>,
+    
+    /// CHECK: Receives the rent, checked against `request.payer`
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+}
+
+pub fn handler(ctx: Context<CloseVrfRequest>) -> Result<()> {
+    let request = &ctx.accounts.request;
+    if !request.is_closable() {
+        return Err(LayerZeroError::RequestNotClosable.into());
+    }
+    
+    msg!("Closing VRF request {:?} ({:?})", request.request_id, request.status);
+    
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::{STORE_SEED, PEER_SEED, ENFORCED_OPTIONS_SEED, VRF_REQUEST_SEED};
+use crate::errors::LayerZeroError;
+use crate::msg_codec::MessageCodec;
+use oapp::{endpoint_cpi::{self}, endpoint::{ID as ENDPOINT_ID, instructions::SendParams}};
+
+/// Expires an inbound request the oracle missed and sends a `VrfFailure` back
+/// to the chain it came from. Anyone can call it once the deadline has passed;
+/// the caller pays the LayerZero fee.
+#[derive(Accounts)]
+#[instruction(params: ExpireCrossChainRequestParams)]
+pub struct ExpireCrossChainRequest<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [STORE_SEED],
+        bump = store.bump
+    )]
+    pub store: Account<'info, Store>,
+
+    #[account(
+        mut,
+        seeds = [PEER_SEED, store.key().as_ref(), &request.src_eid.to_be_bytes()],
+        bump = peer.bump
+    )]
+    pub peer: Account<'info, PeerConfig>,
+
+    #[account(
+        seeds = [ENFORCED_OPTIONS_SEED, store.key().as_ref(), &request.src_eid.to_be_bytes()],
+        bump = enforced_options.bump
+    )]
+    pub enforced_options: Account<'info, EnforcedOptionsConfig>,
+
+    #[account(
+        mut,
+        seeds = [VRF_REQUEST_SEED, store.key().as_ref(), &params.request_id],
+        bump = request.bump
+    )]
+    pub request: Account<'info, VrfRequest>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<ExpireCrossChainRequest>, params: ExpireCrossChainRequestParams) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let request = &mut ctx.accounts.request;
+    if request.status != VrfRequestStatus::Pending {
+        return Err(LayerZeroError::RequestAlreadyFulfilled.into());
+    }
+    if !request.is_expired(now) {
+        return Err(LayerZeroError::RequestNotExpired.into());
+    }
+
+    ctx.accounts.peer.consume_outbound(now, 0)?;
+
+    request.status = VrfRequestStatus::Expired;
+    request.updated_at = now;
+    let dst_eid = request.src_eid;
+
+    let message = MessageCodec::encode_vrf_failure(&VrfFailurePayload {
+        msg_type: MessageType::VrfFailure,
+        request_id: params.request_id,
+        reason: VrfFailureReason::Expired,
+    })?;
+
+    let seeds: &[&[u8]] = &[STORE_SEED, &[ctx.accounts.store.bump]];
+
+    let send_params = SendParams {
+        dst_eid,
+        receiver: ctx.accounts.peer.peer_address,
+        message,
+        options: ctx
+            .accounts
+            .enforced_options
+            .combine_options(MessageType::VrfFailure, &params.options)?,
+        native_fee: params.fee,
+        lz_token_fee: 0,
+    };
+
+    endpoint_cpi::send(
+        ENDPOINT_ID,
+        ctx.accounts.store.key(),
+        ctx.remaining_accounts,
+        seeds,
+        send_params,
+    )?;
+
+    msg!("VRF request expired, failure sent to chain {}", dst_eid);
+    msg!("Request ID: {:?}", params.request_id);
+
+    Ok(())
+}
@@ -1,8 +1,9 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
-use crate::constants::{STORE_SEED, PEER_SEED};
+use crate::constants::{STORE_SEED, PEER_SEED, ENFORCED_OPTIONS_SEED, VRF_REQUEST_SEED};
 use crate::errors::LayerZeroError;
 use crate::msg_codec::MessageCodec;
+use kamui_lz_core::handler;
 use oapp::{endpoint_cpi::{self}, endpoint::{ID as ENDPOINT_ID, instructions::SendParams}};
 
 #[derive(Accounts)]
@@ -19,11 +20,28 @@ pub struct FulfillVrf<'info> {
     pub store: Account<'info, Store>,
     
     #[account(
+        mut,
         seeds = [PEER_SEED, store.key().as_ref(), &params.dst_eid.to_be_bytes()],
         bump = peer.bump
     )]
     pub peer: Account<'info, PeerConfig>,
     
+    #[account(
+        seeds = [ENFORCED_OPTIONS_SEED, store.key().as_ref(), &params.dst_eid.to_be_bytes()],
+        bump = enforced_options.bump
+    )]
+    pub enforced_options: Account<'info, EnforcedOptionsConfig>,
+    
+    /// The inbound request being fulfilled. The fulfillment is sent back to the
+    /// chain the request came from.
+    #[account(
+        mut,
+        seeds = [VRF_REQUEST_SEED, store.key().as_ref(), &params.request_id],
+        bump = request.bump,
+        constraint = request.src_eid == params.dst_eid @ LayerZeroError::InvalidDestinationChain
+    )]
+    pub request: Account<'info, VrfRequest>,
+    
     pub system_program: Program<'info, System>,
 }
 
@@ -35,27 +53,30 @@ pub fn handler(ctx: Context<FulfillVrf>, params: FulfillVrfParams) -> Result<()>
             }
         }
 
-        // Find the request to fulfill
-        let mut request_found = false;
-        for request in &mut ctx.accounts.store.vrf_data.pending_requests {
-            if request.request_id == params.request_id && !request.fulfilled {
-                request.fulfilled = true;
-                request_found = true;
-                break;
-            }
+        let now = Clock::get()?.unix_timestamp;
+        let request = &mut ctx.accounts.request;
+        if request.status != VrfRequestStatus::Pending {
+            return Err(LayerZeroError::RequestAlreadyFulfilled.into());
         }
-
-        if !request_found {
-            return Err(LayerZeroError::RequestNotFound.into());
+        if request.is_expired(now) {
+            return Err(LayerZeroError::RequestExpired.into());
         }
 
-        // Create VRF fulfillment payload
-        let fulfillment_payload = VrfFulfillmentPayload {
-            msg_type: MessageType::VrfFulfillment,
-            request_id: params.request_id,
-            randomness: params.randomness.clone(),
-            proof: params.proof.clone(),
-        };
+        // Fulfillments answer requests counted on the way in, so only the
+        // message counts here
+        ctx.accounts.peer.consume_outbound(now, 0)?;
+
+        // Only a valid proof for this request's input is relayed
+        let fulfillment_payload = handler::vrf_fulfillment(
+            ctx.accounts.store.vrf_data.oracle_vrf_key,
+            request.request_id,
+            &request.seed,
+            params.proof,
+        )
+        .map_err(LayerZeroError::from)?;
+
+        request.status = VrfRequestStatus::Fulfilled;
+        request.updated_at = now;
 
         // Encode the fulfillment message
         let message = MessageCodec::encode_vrf_fulfillment(&fulfillment_payload)?;
@@ -67,7 +88,10 @@ pub fn handler(ctx: Context<FulfillVrf>, params: FulfillVrfParams) -> Result<()>
             dst_eid: params.dst_eid,
             receiver: ctx.accounts.peer.peer_address,
             message,
-            options: vec![], // Default options
+            options: ctx
+                .accounts
+                .enforced_options
+                .combine_options(MessageType::VrfFulfillment, &params.options)?,
             native_fee: params.fee,
             lz_token_fee: 0,
         };
@@ -80,6 +104,8 @@ pub fn handler(ctx: Context<FulfillVrf>, params: FulfillVrfParams) -> Result<()>
             send_params,
         )?;
 
+        ctx.accounts.request.status = VrfRequestStatus::Delivered;
+
         msg!("VRF fulfillment sent to chain {}", params.dst_eid);
         msg!("Request ID: {:?}", params.request_id);
 
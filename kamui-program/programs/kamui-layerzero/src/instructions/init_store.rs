@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
-use crate::{LZ_RECEIVE_TYPES_SEED, STORE_SEED};
+use crate::{LZ_COMPOSE_TYPES_SEED, LZ_RECEIVE_TYPES_SEED, STORE_SEED};
 use oapp::endpoint::{instructions::RegisterOAppParams, ID as ENDPOINT_ID};
 
 #[derive(Accounts)]
@@ -26,7 +26,14 @@ pub struct InitStore<'info> {
     )]
     pub lz_receive_types_accounts: Account<'info, LzReceiveTypesAccounts>,
     
-
+    #[account(
+        init,
+        payer = admin,
+        space = LzComposeTypesAccounts::SIZE,
+        seeds = [LZ_COMPOSE_TYPES_SEED, store.key().as_ref()],
+        bump
+    )]
+    pub lz_compose_types_accounts: Account<'info, LzComposeTypesAccounts>,
     
     pub system_program: Program<'info, System>,
 }
@@ -36,6 +43,7 @@ pub fn handler(ctx: Context<InitStore>, params: InitStoreParams) -> Result<()> {
     ctx.accounts.store.bump = ctx.bumps.store;
     ctx.accounts.store.endpoint_program = params.endpoint;
     ctx.accounts.lz_receive_types_accounts.store = ctx.accounts.store.key();
+    ctx.accounts.lz_compose_types_accounts.store = ctx.accounts.store.key();
     // the above lines are required for all OApp implementations
 
     // the line below is specific to this string-passing example
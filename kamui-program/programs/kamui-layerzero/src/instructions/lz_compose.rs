@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LayerZeroError;
+use crate::consumer;
+use crate::constants::{OUTBOUND_VRF_REQUEST_SEED, VRF_CALLBACK_SEED};
+use crate::STORE_SEED;
+use oapp::{
+    endpoint::{instructions::ClearComposeParams, ID as ENDPOINT_ID},
+    LzComposeParams,
+};
+
+#[derive(Accounts)]
+pub struct LzCompose<'info> {
+    /// OApp Store PDA, both the sender and the receiver of the compose message
+    #[account(seeds = [STORE_SEED], bump = store.bump)]
+    pub store: Account<'info, Store>,
+    /// The fulfilled outbound request being delivered
+    #[account(
+        mut,
+        seeds = [OUTBOUND_VRF_REQUEST_SEED, store.key().as_ref(), &request.request_id],
+        bump = request.bump
+    )]
+    pub request: Account<'info, OutboundVrfRequest>,
+    /// CHECK: PDA that signs the consumer callback, it holds no data
+    #[account(seeds = [VRF_CALLBACK_SEED], bump)]
+    pub callback_signer: UncheckedAccount<'info>,
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, LzCompose<'info>>,
+    params: LzComposeParams,
+) -> Result<()> {
+    let store = ctx.accounts.store.key();
+    // Only compose messages this OApp sent to itself from `lz_receive`
+    if params.from != store || params.to != store {
+        return Err(LayerZeroError::InvalidComposeMessage.into());
+    }
+
+    let message = consumer::decode_compose_message(&params.message)?;
+    let request = &ctx.accounts.request;
+    if message.request_id != request.request_id
+        || request.randomness != Some(message.randomness)
+        || request.consumer.as_ref() != Some(&message.consumer)
+    {
+        return Err(LayerZeroError::InvalidComposeMessage.into());
+    }
+    if request.status != VrfRequestStatus::Fulfilled {
+        return Err(LayerZeroError::RequestAlreadyFulfilled.into());
+    }
+
+    // The consumer program and its callback accounts come first, followed by
+    // the accounts for `Endpoint::clear_compose`, see `lz_compose_types`.
+    let callback_len = 1 + message.consumer.accounts.len();
+    if ctx.remaining_accounts.len() < callback_len {
+        return Err(LayerZeroError::AccountConstraintViolation.into());
+    }
+    let (callback_accounts, accounts_for_clear) = ctx.remaining_accounts.split_at(callback_len);
+
+    let seeds: &[&[u8]] = &[STORE_SEED, &[ctx.accounts.store.bump]];
+
+    // Clear first so a message can't be delivered twice
+    oapp::endpoint_cpi::clear_compose(
+        ENDPOINT_ID,
+        store,
+        accounts_for_clear,
+        seeds,
+        ClearComposeParams {
+            from: params.from,
+            guid: params.guid,
+            index: params.index,
+            message: params.message.clone(),
+        },
+    )?;
+
+    consumer::deliver_randomness(
+        &ctx.accounts.callback_signer.to_account_info(),
+        &ctx.accounts.request.to_account_info(),
+        callback_accounts,
+        &[VRF_CALLBACK_SEED, &[ctx.bumps.callback_signer]],
+        &message,
+    )?;
+
+    let request = &mut ctx.accounts.request;
+    request.status = VrfRequestStatus::Delivered;
+    request.updated_at = Clock::get()?.unix_timestamp;
+
+    msg!("VRF randomness delivered to {}", message.consumer.program_id);
+    msg!("Request ID: {:?}", message.request_id);
+
+    Ok(())
+}
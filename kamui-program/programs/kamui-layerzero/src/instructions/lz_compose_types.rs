@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::consumer;
+use crate::STORE_SEED;
+use kamui_lz_core::pda;
+use oapp::endpoint_cpi::{get_accounts_for_clear_compose, LzAccount};
+use oapp::{endpoint::ID as ENDPOINT_ID, LzComposeParams};
+
+/// `lz_compose_types` is queried off-chain by the Executor before calling
+/// `lz_compose`. Like `lz_receive_types`, it returns every account `lz_compose`
+/// touches, in the order it expects them, followed by the accounts required by
+/// `Endpoint::clear_compose`.
+#[derive(Accounts)]
+pub struct LzComposeTypes<'info> {
+    #[account(seeds = [STORE_SEED], bump = store.bump)]
+    pub store: Account<'info, Store>,
+}
+
+pub fn handler(ctx: Context<LzComposeTypes>, params: LzComposeParams) -> Result<Vec<LzAccount>> {
+    let store = ctx.accounts.store.key();
+    let message = consumer::decode_compose_message(&params.message)?;
+
+    let (request, _) = pda::outbound_vrf_request(ctx.program_id, &store, &message.request_id);
+
+    let mut accounts = vec![
+        // store (read-only)
+        LzAccount { pubkey: store, is_signer: false, is_writable: false },
+        // outbound request
+        LzAccount { pubkey: request, is_signer: false, is_writable: true },
+        // callback signer (read-only, signs the callback)
+        LzAccount {
+            pubkey: consumer::callback_signer(ctx.program_id).0,
+            is_signer: false,
+            is_writable: false,
+        },
+    ];
+
+    // The consumer program and its callback accounts
+    accounts.extend(consumer::get_accounts_for_callback(&message.consumer));
+
+    // Append the additional accounts required for `Endpoint::clear_compose`
+    accounts.extend(get_accounts_for_clear_compose(
+        ENDPOINT_ID,
+        &params.from,
+        &params.to,
+        &params.guid,
+        params.index,
+        &params.message,
+    ));
+
+    msg!("LzComposeTypes called for GUID: {:?}", params.guid);
+    msg!("Returning {} accounts", accounts.len());
+
+    Ok(accounts)
+}
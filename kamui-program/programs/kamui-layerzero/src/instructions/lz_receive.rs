@@ -1,12 +1,16 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::errors::LayerZeroError;
-use crate::msg_codec;
+use crate::{consumer, coordinator, utils};
+use crate::constants::{
+    PROCESSED_GUID_SEED, VRF_COMPOSE_INDEX, VRF_REQUEST_EXPIRY_SECONDS, VRF_REQUEST_SEED,
+};
 use crate::{PEER_SEED, STORE_SEED};
+use kamui_lz_core::handler::{self, InboundMessage};
 use oapp::{
     endpoint::{
         cpi::accounts::Clear,
-        instructions::ClearParams,
+        instructions::{ClearParams, SendComposeParams},
         ID as ENDPOINT_ID,
         ConstructCPIContext,
     },
@@ -22,15 +26,38 @@ pub struct LzReceive<'info> {
     #[account(mut, seeds = [STORE_SEED], bump = store.bump)]
     pub store: Account<'info, Store>,
     /// Peer config PDA for the sending chain. Ensures `params.sender` can only be the allowed peer from that remote chain.
+    /// Mutable for its inbound rate limit.
     #[account(
+        mut,
         seeds = [PEER_SEED, &store.key().to_bytes(), &params.src_eid.to_be_bytes()],
         bump = peer.bump,
         constraint = params.sender == peer.peer_address @ LayerZeroError::InvalidSender
     )]
-    pub peer: Account<'info, PeerConfig>
+    pub peer: Account<'info, PeerConfig>,
+    /// Replay record of this message, created on first delivery
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + ProcessedGuid::INIT_SPACE,
+        seeds = [PROCESSED_GUID_SEED, &store.key().to_bytes(), &params.src_eid.to_be_bytes(), &params.guid],
+        bump
+    )]
+    pub processed_guid: Account<'info, ProcessedGuid>,
+    /// Pays for the replay record. Filled in by the Executor.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<LzReceive>, params: LzReceiveParams) -> Result<()> {
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, LzReceive<'info>>,
+    params: LzReceiveParams,
+) -> Result<()> {
+    // Refuse messages already handled here, whatever the Endpoint says
+    if ctx.accounts.processed_guid.guid == params.guid {
+        return Err(LayerZeroError::MessageAlreadyProcessed.into());
+    }
+
     // The OApp Store PDA is used to sign the CPI to the Endpoint program.
     let seeds: &[&[u8]] = &[STORE_SEED, &[ctx.accounts.store.bump]];
 
@@ -41,7 +68,7 @@ pub fn handler(ctx: Context<LzReceive>, params: LzReceiveParams) -> Result<()> {
     // Call the Endpoint::clear CPI to clear the message from the Endpoint program.
     // This is necessary to ensure the message is processed only once and to
     // prevent replays.
-    let _ = oapp::endpoint_cpi::clear(
+    let guid = oapp::endpoint_cpi::clear(
         ENDPOINT_ID,
         ctx.accounts.store.key(),
         accounts_for_clear,
@@ -55,17 +82,187 @@ pub fn handler(ctx: Context<LzReceive>, params: LzReceiveParams) -> Result<()> {
             message: params.message.clone(),
         },
     )?;
+    if guid != params.guid {
+        return Err(LayerZeroError::InvalidGuid.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    let processed_guid = &mut ctx.accounts.processed_guid;
+    processed_guid.guid = params.guid;
+    processed_guid.src_eid = params.src_eid;
+    processed_guid.nonce = params.nonce;
+    processed_guid.processed_at = now;
+    processed_guid.bump = ctx.bumps.processed_guid;
+
+    let message = InboundMessage::decode(&params.message)?;
+
+    // Only VRF requests spend the peer's word budget
+    let words = match &message {
+        InboundMessage::VrfRequest(request) => request.num_words,
+        _ => 0,
+    };
+    ctx.accounts.peer.consume_inbound(now, words)?;
+
+    match message {
+        // VRF requests are recorded in their own PDA and forwarded to the
+        // kamui-vrf coordinator. The accounts for both follow the clear
+        // accounts, see `lz_receive_types`.
+        InboundMessage::VrfRequest(request) => {
+            let config = ctx
+                .accounts
+                .store
+                .vrf_data
+                .coordinator
+                .clone()
+                .ok_or(LayerZeroError::VrfCoordinatorNotConfigured)?;
+            let (record, coordinator_accounts) = ctx.remaining_accounts[Clear::MIN_ACCOUNTS_LEN..]
+                .split_first()
+                .ok_or(LayerZeroError::InvalidCoordinatorAccount)?;
+            if coordinator_accounts.len() < coordinator::REQUEST_ACCOUNTS_LEN {
+                return Err(LayerZeroError::InvalidCoordinatorAccount.into());
+            }
+            let requester = request.requester;
+
+            let store = ctx.accounts.store.key();
+            utils::init_pda_account(
+                record,
+                &coordinator_accounts[coordinator::PAYER_INDEX],
+                &coordinator_accounts[coordinator::SYSTEM_PROGRAM_INDEX],
+                &[VRF_REQUEST_SEED, store.as_ref(), &params.guid],
+                8 + VrfRequest::INIT_SPACE,
+                |bump| VrfRequest {
+                    request_id: params.guid,
+                    src_eid: params.src_eid,
+                    requester,
+                    seed: request.seed,
+                    num_words: request.num_words,
+                    coordinator_request: coordinator_accounts[coordinator::REQUEST_INDEX].key(),
+                    status: VrfRequestStatus::Pending,
+                    payer: coordinator_accounts[coordinator::PAYER_INDEX].key(),
+                    created_at: now,
+                    updated_at: now,
+                    deadline: now.saturating_add(VRF_REQUEST_EXPIRY_SECONDS),
+                    bump,
+                },
+            )?;
+
+            coordinator::request_randomness(
+                &config,
+                &ctx.accounts.store.to_account_info(),
+                coordinator_accounts,
+                seeds,
+                request,
+                CrossChainOrigin {
+                    src_eid: params.src_eid,
+                    requester,
+                    guid: params.guid,
+                },
+            )?;
+
+            msg!("VRF request forwarded to coordinator {}", config.vrf_program);
+            msg!("Source endpoint: {}", params.src_eid);
+            msg!("Requester: {:?}", requester);
+            msg!("GUID: {:?}", params.guid);
+        }
 
-    // From here on, you can process the message as needed by your use case.
-    // For this implementation, we'll decode the string message
-    let string_value = msg_codec::decode(&params.message)?;
-    let store = &mut ctx.accounts.store;
-    store.string = string_value;
+        // Fulfillments of requests sent from here are verified and recorded in
+        // the outbound request PDA. When the request names a consumer the
+        // randomness is handed over by composing a message to this OApp, see
+        // `lz_compose`.
+        InboundMessage::VrfFulfillment(fulfillment) => {
+            let (record, compose_accounts) = ctx.remaining_accounts[Clear::MIN_ACCOUNTS_LEN..]
+                .split_first()
+                .ok_or(LayerZeroError::RequestNotFound)?;
+            let mut request = outbound_request(record, fulfillment.request_id, params.src_eid)?;
 
-    msg!("Successfully processed LayerZero message");
-    msg!("Source endpoint: {}", params.src_eid);
-    msg!("Message content: {}", store.string);
-    msg!("Nonce: {}", params.nonce);
+            let beta = handler::verify_vrf_fulfillment(
+                ctx.accounts.store.vrf_data.oracle_vrf_key,
+                &request.seed,
+                &fulfillment,
+            )
+            .map_err(LayerZeroError::from)?;
+
+            request.randomness = Some(beta);
+            request.status = VrfRequestStatus::Fulfilled;
+            request.updated_at = now;
+
+            if let Some(consumer) = request.consumer.clone() {
+                let message = consumer::encode_compose_message(&consumer::VrfComposeMessage {
+                    request_id: request.request_id,
+                    randomness: beta,
+                    consumer,
+                });
+                oapp::endpoint_cpi::send_compose(
+                    ENDPOINT_ID,
+                    ctx.accounts.store.key(),
+                    compose_accounts,
+                    seeds,
+                    SendComposeParams {
+                        to: ctx.accounts.store.key(),
+                        guid: params.guid,
+                        index: VRF_COMPOSE_INDEX,
+                        message,
+                    },
+                )?;
+            }
+            request.try_serialize(&mut &mut record.try_borrow_mut_data()?[..])?;
+
+            msg!("VRF fulfillment received from endpoint: {}", params.src_eid);
+            msg!("Request ID: {:?}", request.request_id);
+        }
+
+        // Failures release requests sent from here that the remote oracle
+        // missed. The requester reads the status from the outbound request PDA.
+        InboundMessage::VrfFailure(failure) => {
+            let record = ctx
+                .remaining_accounts
+                .get(Clear::MIN_ACCOUNTS_LEN)
+                .ok_or(LayerZeroError::RequestNotFound)?;
+            let mut request = outbound_request(record, failure.request_id, params.src_eid)?;
+
+            request.status = match failure.reason {
+                VrfFailureReason::Expired => VrfRequestStatus::Expired,
+            };
+            request.updated_at = now;
+            request.try_serialize(&mut &mut record.try_borrow_mut_data()?[..])?;
+
+            msg!("VRF failure received from endpoint: {}", params.src_eid);
+            msg!("Request ID: {:?} ({:?})", request.request_id, failure.reason);
+        }
+
+        InboundMessage::Generic(string_value) => {
+            if string_value.len() > Store::MAX_STRING_LENGTH {
+                return Err(LayerZeroError::MessageTooLarge.into());
+            }
+            let store = &mut ctx.accounts.store;
+            store.string = string_value;
+
+            msg!("Successfully processed LayerZero message");
+            msg!("Source endpoint: {}", params.src_eid);
+            msg!("Message content: {}", store.string);
+            msg!("Nonce: {}", params.nonce);
+        }
+    }
     
     Ok(())
-} 
\ No newline at end of file
+}
+
+/// The pending outbound request `request_id`, sent to `src_eid`, from its
+/// record passed after the clear accounts.
+fn outbound_request(record: &AccountInfo, request_id: [u8; 32], src_eid: u32) -> Result<OutboundVrfRequest> {
+    // Only this program creates these accounts, each under the GUID it stores
+    if record.owner != &crate::ID {
+        return Err(LayerZeroError::RequestNotFound.into());
+    }
+    let request = OutboundVrfRequest::try_deserialize(&mut &record.try_borrow_data()?[..])?;
+    if request.request_id != request_id {
+        return Err(LayerZeroError::RequestNotFound.into());
+    }
+    if request.dst_eid != src_eid {
+        return Err(LayerZeroError::InvalidSourceChain.into());
+    }
+    if request.status != VrfRequestStatus::Pending {
+        return Err(LayerZeroError::RequestAlreadyFulfilled.into());
+    }
+    Ok(request)
+}
@@ -1,7 +1,11 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::system_program;
 use crate::state::*;
-use crate::{PEER_SEED, STORE_SEED};
-use oapp::endpoint_cpi::{get_accounts_for_clear, LzAccount};
+use crate::coordinator;
+use crate::constants::VRF_COMPOSE_INDEX;
+use crate::STORE_SEED;
+use kamui_lz_core::{handler::InboundMessage, pda};
+use oapp::endpoint_cpi::{get_accounts_for_clear, get_accounts_for_send_compose, LzAccount};
 use oapp::{endpoint::ID as ENDPOINT_ID, LzReceiveParams};
 
 /// `lz_receive_types` is queried off-chain by the Executor before calling
@@ -24,15 +28,23 @@ pub fn handler(ctx: Context<LzReceiveTypes>, params: LzReceiveParams) -> Result<
     let store = ctx.accounts.store.key();
 
     // 2. The peer PDA for the remote chain needs to be retrieved, for later verification of the `params.sender`.
-    let peer_seeds = [PEER_SEED, &store.to_bytes(), &params.src_eid.to_be_bytes()];
-    let (peer, _) = Pubkey::find_program_address(&peer_seeds, ctx.program_id);
+    let (peer, _) = pda::peer(ctx.program_id, &store, params.src_eid);
+
+    // 3. The replay record of this message, created by `lz_receive`.
+    let (processed_guid, _) = pda::processed_guid(ctx.program_id, &store, params.src_eid, &params.guid);
 
     // Accounts used directly by `lz_receive`
     let mut accounts = vec![
         // store (mutable)
         LzAccount { pubkey: store, is_signer: false, is_writable: true },
-        // peer (read-only)
-        LzAccount { pubkey: peer, is_signer: false, is_writable: false }
+        // peer (mutable, holds the inbound rate limit)
+        LzAccount { pubkey: peer, is_signer: false, is_writable: true },
+        // processed guid record (mutable)
+        LzAccount { pubkey: processed_guid, is_signer: false, is_writable: true },
+        // payer of the record, filled in by the Executor
+        LzAccount { pubkey: Pubkey::default(), is_signer: true, is_writable: true },
+        // system program
+        LzAccount { pubkey: system_program::ID, is_signer: false, is_writable: false },
     ];
 
     // Append the additional accounts required for `Endpoint::clear`
@@ -45,6 +57,41 @@ pub fn handler(ctx: Context<LzReceiveTypes>, params: LzReceiveParams) -> Result<
     );
     accounts.extend(accounts_for_clear);
 
+    match InboundMessage::decode(&params.message) {
+        // VRF requests need their request PDA followed by the accounts of the
+        // kamui-vrf coordinator, after the clear accounts.
+        Ok(InboundMessage::VrfRequest(_)) => {
+            if let Some(config) = &ctx.accounts.store.vrf_data.coordinator {
+                let (record, _) = pda::vrf_request(ctx.program_id, &store, &params.guid);
+                accounts.push(LzAccount { pubkey: record, is_signer: false, is_writable: true });
+                accounts.extend(coordinator::get_accounts_for_request(config, &params.guid));
+            }
+        }
+        // VRF fulfillments need the outbound request PDA, followed by the
+        // accounts for `Endpoint::send_compose`, used when the request names a
+        // consumer. The compose message account is seeded by the GUID and index
+        // only, so the message itself is not needed here.
+        Ok(InboundMessage::VrfFulfillment(fulfillment)) => {
+            let (record, _) = pda::outbound_vrf_request(ctx.program_id, &store, &fulfillment.request_id);
+            accounts.push(LzAccount { pubkey: record, is_signer: false, is_writable: true });
+            accounts.extend(get_accounts_for_send_compose(
+                ENDPOINT_ID,
+                &store,
+                &store,
+                &params.guid,
+                VRF_COMPOSE_INDEX,
+                &[],
+            ));
+        }
+        // VRF failures only need the outbound request PDA
+        Ok(InboundMessage::VrfFailure(failure)) => {
+            let (record, _) = pda::outbound_vrf_request(ctx.program_id, &store, &failure.request_id);
+            accounts.push(LzAccount { pubkey: record, is_signer: false, is_writable: true });
+        }
+        // Strings and undecodable messages need no more accounts
+        Ok(InboundMessage::Generic(_)) | Err(_) => {}
+    }
+
     msg!("LzReceiveTypes called for src_eid: {}", params.src_eid);
     msg!("Sender: {:?}", params.sender);
     msg!("Nonce: {}", params.nonce);
@@ -2,6 +2,7 @@ use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::constants::{STORE_SEED, PEER_SEED};
 use crate::errors::LayerZeroError;
+use kamui_lz_core::validation;
 use oapp::{endpoint_cpi::{self}, endpoint::{ID as ENDPOINT_ID, instructions::SendParams}};
 
 #[derive(Accounts)]
@@ -19,6 +20,7 @@ pub struct LzSend<'info> {
     pub store: Account<'info, Store>,
     
     #[account(
+        mut,
         seeds = [PEER_SEED, store.key().as_ref(), &params.dst_eid.to_be_bytes()],
         bump = peer.bump
     )]
@@ -28,11 +30,10 @@ pub struct LzSend<'info> {
 }
 
 pub fn handler(ctx: Context<LzSend>, params: LzSendParams) -> Result<()> {
-    // TODO: Add endpoint validation if needed
-    // utils::validate_endpoint_id(params.dst_eid)?;
+    validation::validate_endpoint_id(params.dst_eid).map_err(LayerZeroError::from)?;
+    validation::validate_message_size(&params.message).map_err(LayerZeroError::from)?;
     
-    // TODO: Add message size validation if needed
-    // utils::validate_message_size(&params.message)?;
+    ctx.accounts.peer.consume_outbound(Clock::get()?.unix_timestamp, 0)?;
     
     // Get message size before moving
     let message_size = params.message.len();
@@ -42,7 +43,11 @@ pub fn handler(ctx: Context<LzSend>, params: LzSendParams) -> Result<()> {
         dst_eid: params.dst_eid,
         receiver: ctx.accounts.peer.peer_address,
         message: params.message,
-        options: params.options,
+        options: ctx
+            .accounts
+            .peer
+            .enforced_options
+            .combine_options(&None::<Vec<u8>>, &params.options)?,
         native_fee: params.fee,
         lz_token_fee: 0,
     };
@@ -1,23 +1,39 @@
 // LayerZero OApp standard instructions
 pub mod init_store;
 pub mod set_peer_config;
+pub mod resize_peer_config;
 pub mod lz_receive_types;
 pub mod lz_receive;
+pub mod lz_compose_types;
+pub mod lz_compose;
 pub mod set_delegate;
 pub mod send;
 pub mod quote_send;
 pub mod lz_send;
 pub mod request_vrf;
 pub mod fulfill_vrf;
+pub mod set_vrf_coordinator;
+pub mod close_vrf_request;
+pub mod expire_cross_chain_request;
+pub mod set_oracle;
+pub mod set_enforced_options;
 
 // Re-export all instruction types and handlers
 pub use init_store::*;
 pub use set_peer_config::*;
+pub use resize_peer_config::*;
 pub use lz_receive_types::*;
 pub use lz_receive::*;
+pub use lz_compose_types::*;
+pub use lz_compose::*;
 pub use set_delegate::*;
 pub use send::*;
 pub use quote_send::*;
 pub use lz_send::*;
 pub use request_vrf::*;
-pub use fulfill_vrf::*; 
\ No newline at end of file
+pub use fulfill_vrf::*;
+pub use set_vrf_coordinator::*;
+pub use close_vrf_request::*;
+pub use expire_cross_chain_request::*;
+pub use set_oracle::*;
+pub use set_enforced_options::*;
\ No newline at end of file
@@ -1,8 +1,13 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
-use crate::constants::{STORE_SEED, PEER_SEED, MAX_CALLBACK_DATA_SIZE};
+use crate::constants::{
+    STORE_SEED, PEER_SEED, ENFORCED_OPTIONS_SEED, OUTBOUND_VRF_REQUEST_SEED, MAX_CALLBACK_DATA_SIZE,
+    MAX_CONSUMER_CALLBACK_ACCOUNTS, VRF_REQUEST_EXPIRY_SECONDS,
+};
 use crate::errors::LayerZeroError;
 use crate::msg_codec::MessageCodec;
+use crate::utils;
+use kamui_lz_core::{handler, validation};
 use oapp::{endpoint_cpi::{self}, endpoint::{ID as ENDPOINT_ID, instructions::SendParams}};
 
 #[derive(Accounts)]
@@ -19,35 +24,48 @@ pub struct RequestVrf<'info> {
     pub store: Account<'info, Store>,
     
     #[account(
+        mut,
         seeds = [PEER_SEED, store.key().as_ref(), &params.dst_eid.to_be_bytes()],
         bump = peer.bump
     )]
     pub peer: Account<'info, PeerConfig>,
     
+    #[account(
+        seeds = [ENFORCED_OPTIONS_SEED, store.key().as_ref(), &params.dst_eid.to_be_bytes()],
+        bump = enforced_options.bump
+    )]
+    pub enforced_options: Account<'info, EnforcedOptionsConfig>,
+    
+    /// CHECK: The `OutboundVrfRequest` PDA for the GUID of the request message.
+    /// Created by the handler once the Endpoint has assigned the GUID.
+    #[account(mut)]
+    pub request: UncheckedAccount<'info>,
+    
     pub system_program: Program<'info, System>,
 }
 
 pub fn handler(ctx: Context<RequestVrf>, params: RequestVrfParams) -> Result<()> {
         // Validate parameters
-        // TODO: Add endpoint validation if needed
-        // utils::validate_endpoint_id(params.dst_eid)?;
+        validation::validate_endpoint_id(params.dst_eid).map_err(LayerZeroError::from)?;
         
-        if params.callback_data.len() > MAX_CALLBACK_DATA_SIZE {
-            return Err(LayerZeroError::InvalidCallbackDataSize.into());
-        }
+        // Create VRF request payload
+        let vrf_request = handler::vrf_request(
+            ctx.accounts.requester.key().to_bytes(),
+            params.seed,
+            params.num_words.into(),
+            params.callback_data.clone(),
+            MAX_CALLBACK_DATA_SIZE,
+        )
+        .map_err(LayerZeroError::from)?;
         
-        if params.num_words == 0 || params.num_words > 10 {
-            return Err(LayerZeroError::InvalidVrfParams.into());
+        if let Some(consumer) = &params.consumer {
+            if consumer.accounts.len() > MAX_CONSUMER_CALLBACK_ACCOUNTS {
+                return Err(LayerZeroError::InvalidVrfParams.into());
+            }
         }
         
-        // Create VRF request payload
-        let vrf_request = VrfRequestPayload {
-            msg_type: MessageType::VrfRequest,
-            requester: ctx.accounts.requester.key().to_bytes(),
-            seed: params.seed,
-            num_words: params.num_words,
-            callback_data: params.callback_data.clone(),
-        };
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.peer.consume_outbound(now, params.num_words.into())?;
         
         // Encode the message
         let message = MessageCodec::encode_vrf_request(&vrf_request)?;
@@ -57,7 +75,10 @@ pub fn handler(ctx: Context<RequestVrf>, params: RequestVrfParams) -> Result<()>
             dst_eid: params.dst_eid,
             receiver: ctx.accounts.peer.peer_address,
             message,
-            options: vec![], // Default options
+            options: ctx
+                .accounts
+                .enforced_options
+                .combine_options(MessageType::VrfRequest, &params.options)?,
             native_fee: params.fee,
             lz_token_fee: 0,
         };
@@ -65,7 +86,7 @@ pub fn handler(ctx: Context<RequestVrf>, params: RequestVrfParams) -> Result<()>
         // Send the VRF request via LayerZero
         let seeds = &[STORE_SEED, &[ctx.accounts.store.bump]];
         
-        endpoint_cpi::send(
+        let receipt = endpoint_cpi::send(
             ENDPOINT_ID,
             ctx.accounts.store.key(),
             ctx.remaining_accounts,
@@ -73,7 +94,32 @@ pub fn handler(ctx: Context<RequestVrf>, params: RequestVrfParams) -> Result<()>
             send_params,
         )?;
         
+        // Track the request under its GUID so the fulfillment can be matched
+        let store = ctx.accounts.store.key();
+        utils::init_pda_account(
+            &ctx.accounts.request.to_account_info(),
+            &ctx.accounts.requester.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &[OUTBOUND_VRF_REQUEST_SEED, store.as_ref(), &receipt.guid],
+            8 + OutboundVrfRequest::INIT_SPACE,
+            |bump| OutboundVrfRequest {
+                request_id: receipt.guid,
+                dst_eid: params.dst_eid,
+                requester: ctx.accounts.requester.key(),
+                seed: params.seed,
+                num_words: params.num_words.into(),
+                consumer: params.consumer,
+                status: VrfRequestStatus::Pending,
+                randomness: None,
+                created_at: now,
+                updated_at: now,
+                deadline: now.saturating_add(VRF_REQUEST_EXPIRY_SECONDS),
+                bump,
+            },
+        )?;
+        
         msg!("VRF request sent to endpoint: {}", params.dst_eid);
+        msg!("GUID: {:?}", receipt.guid);
         msg!("Seed: {:?}", params.seed);
         msg!("Num words: {}", params.num_words);
         msg!("Fee: {}", params.fee);
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::{PEER_SEED, STORE_SEED};
+
+#[derive(Accounts)]
+#[instruction(remote_eid: u32)]
+pub struct ResizePeerConfig<'info> {
+    #[account(mut, address = store.admin)]
+    /// Admin of the OApp store
+    pub admin: Signer<'info>,
+    // Peers created before the rate limits and the pause flag existed are too
+    // small for them, and `set_peer_config` refuses accounts of another size
+    #[account(
+        mut,
+        seeds = [PEER_SEED, &store.key().to_bytes(), &remote_eid.to_be_bytes()],
+        bump = peer.bump,
+        realloc = PeerConfig::SIZE,
+        realloc::payer = admin,
+        realloc::zero = false
+    )]
+    /// Peer configuration PDA for a specific remote chain
+    pub peer: Account<'info, PeerConfig>,
+    #[account(seeds = [STORE_SEED], bump = store.bump)]
+    /// Store PDA of this OApp
+    pub store: Account<'info, Store>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(_ctx: Context<ResizePeerConfig>, remote_eid: u32) -> Result<()> {
+    msg!("Peer config resized to {} bytes", PeerConfig::SIZE);
+    msg!("Remote EID: {}", remote_eid);
+
+    Ok(())
+}
@@ -10,6 +10,7 @@ use oapp::endpoint::{
 #[instruction(params: SendMessageParams)]
 pub struct SendMessage<'info> {
     #[account(
+        mut,
         seeds = [
             PEER_SEED,
             &store.key().to_bytes(),
@@ -28,6 +29,8 @@ pub struct SendMessage<'info> {
 }
 
 pub fn handler(ctx: Context<SendMessage>, params: SendMessageParams) -> Result<()> {
+    ctx.accounts.peer.consume_outbound(Clock::get()?.unix_timestamp, 0)?;
+
     // Serialize the message according to our codec
     let message = msg_codec::encode(&params.message);
     // Prepare the seeds for the OApp Store PDA, which is used to sign the CPI call to the Endpoint program.
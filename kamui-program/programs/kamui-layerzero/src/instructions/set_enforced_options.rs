@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::{ENFORCED_OPTIONS_SEED, STORE_SEED};
+
+#[derive(Accounts)]
+#[instruction(params: SetEnforcedOptionsParams)]
+pub struct SetEnforcedOptions<'info> {
+    #[account(mut, address = store.admin)]
+    /// Admin of the OApp store
+    pub admin: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + EnforcedOptionsConfig::INIT_SPACE,
+        seeds = [ENFORCED_OPTIONS_SEED, &store.key().to_bytes(), &params.remote_eid.to_be_bytes()],
+        bump
+    )]
+    /// Enforced options PDA for a specific remote chain
+    pub enforced_options: Account<'info, EnforcedOptionsConfig>,
+    #[account(seeds = [STORE_SEED], bump = store.bump)]
+    /// Store PDA of this OApp
+    pub store: Account<'info, Store>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<SetEnforcedOptions>, params: SetEnforcedOptionsParams) -> Result<()> {
+    oapp::options::assert_type_3(&params.vrf_request)?;
+    oapp::options::assert_type_3(&params.vrf_fulfillment)?;
+
+    let enforced_options = &mut ctx.accounts.enforced_options;
+    enforced_options.vrf_request = params.vrf_request;
+    enforced_options.vrf_fulfillment = params.vrf_fulfillment;
+    enforced_options.bump = ctx.bumps.enforced_options;
+
+    msg!("Enforced options set successfully");
+    msg!("Remote EID: {}", params.remote_eid);
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::LayerZeroError;
+
+#[derive(Accounts)]
+pub struct SetOracle<'info> {
+    pub admin: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [STORE_SEED],
+        bump = store.bump,
+        has_one = admin @ LayerZeroError::UnauthorizedAccess
+    )]
+    pub store: Account<'info, Store>,
+}
+
+pub fn handler(ctx: Context<SetOracle>, params: SetOracleParams) -> Result<()> {
+    msg!("Oracle set to: {:?}", params.oracle);
+    msg!("Oracle VRF key: {:?}", params.vrf_public_key);
+    
+    let vrf_data = &mut ctx.accounts.store.vrf_data;
+    vrf_data.oracle_pubkey = params.oracle;
+    vrf_data.oracle_vrf_key = Some(params.vrf_public_key);
+    
+    Ok(())
+}
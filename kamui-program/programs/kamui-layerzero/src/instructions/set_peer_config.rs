@@ -1,6 +1,8 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
+use crate::errors::LayerZeroError;
 use crate::{PEER_SEED, STORE_SEED};
+use kamui_lz_core::validation;
 
 #[derive(Accounts)]
 #[instruction(params: SetPeerConfigParams)]
@@ -28,6 +30,7 @@ impl<'info> SetPeerConfig<'info> {
         // Update or create the peer config PDA
         match params.config.clone() {
             PeerConfigParam::PeerAddress(peer_address) => {
+                validation::validate_peer_address(&peer_address).map_err(LayerZeroError::from)?;
                 ctx.accounts.peer.peer_address = peer_address;
             },
             PeerConfigParam::EnforcedOptions { send, send_and_call } => {
@@ -36,6 +39,15 @@ impl<'info> SetPeerConfig<'info> {
                 oapp::options::assert_type_3(&send_and_call)?;
                 ctx.accounts.peer.enforced_options.send_and_call = send_and_call;
             },
+            PeerConfigParam::InboundRateLimit(limit) => {
+                ctx.accounts.peer.inbound_rate_limit = limit.map(RateLimit::new).transpose()?;
+            },
+            PeerConfigParam::OutboundRateLimit(limit) => {
+                ctx.accounts.peer.outbound_rate_limit = limit.map(RateLimit::new).transpose()?;
+            },
+            PeerConfigParam::Paused(paused) => {
+                ctx.accounts.peer.paused = paused;
+            },
         }
         // Store the PDA bump for later validation
         ctx.accounts.peer.bump = ctx.bumps.peer;
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::LayerZeroError;
+
+#[derive(Accounts)]
+pub struct SetVrfCoordinator<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    // Stores created before the coordinator settings existed are resized to fit them
+    #[account(
+        mut,
+        seeds = [STORE_SEED],
+        bump = store.bump,
+        has_one = admin @ LayerZeroError::UnauthorizedAccess,
+        realloc = Store::SIZE,
+        realloc::payer = admin,
+        realloc::zero = false
+    )]
+    pub store: Account<'info, Store>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<SetVrfCoordinator>, config: VrfCoordinatorConfig) -> Result<()> {
+    msg!("VRF coordinator set to: {}", config.vrf_program);
+    msg!("Subscription: {}", config.subscription);
+    msg!("Pool ID: {}", config.pool_id);
+    
+    ctx.accounts.store.vrf_data.coordinator = Some(config);
+    
+    Ok(())
+}
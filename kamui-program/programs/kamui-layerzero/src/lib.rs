@@ -1,5 +1,5 @@
 use anchor_lang::prelude::*;
-use oapp::{endpoint::MessagingFee, endpoint_cpi::LzAccount, LzReceiveParams};
+use oapp::{endpoint::MessagingFee, endpoint_cpi::LzAccount, LzComposeParams, LzReceiveParams};
 use solana_helper::program_id_from_env;
 
 // to build in verifiable mode and using environment variable, run:
@@ -11,16 +11,22 @@ declare_id!(anchor_lang::solana_program::pubkey::Pubkey::new_from_array(program_
     "4ZKyWQwMr25cuNb2BR8zmWwTttN8ADbKVJr3HNi2RpVH" // Default ID if environment variable not set
 )));
 
-// LayerZero OApp standard seeds - required for LayerZero compatibility
-const LZ_RECEIVE_TYPES_SEED: &[u8] = b"LzReceiveTypes"; // The Executor relies on this exact seed to derive the LzReceiveTypes PDA. Keep it the same.
-const STORE_SEED: &[u8] = b"Store"; // You are free to edit this seed.
-const PEER_SEED: &[u8] = b"Peer"; // The Executor relies on this exact seed to derive the Peer PDA. Keep it the same.
+// LayerZero OApp standard seeds - required for LayerZero compatibility, shared
+// with the other OApps through kamui-lz-core
+use kamui_lz_core::constants::{LZ_COMPOSE_TYPES_SEED, LZ_RECEIVE_TYPES_SEED, PEER_SEED, STORE_SEED};
 
 pub mod state;
 pub mod errors;
 pub mod instructions;
 pub mod constants;
-pub mod msg_codec;
+pub mod coordinator;
+pub mod options;
+pub mod consumer;
+pub mod utils;
+
+// The wire format and the proof verifier are shared with the other OApps
+pub use kamui_lz_core::codec as msg_codec;
+pub use kamui_lz_core::ecvrf;
 
 // Import all instruction structs and parameter types
 use instructions::*;
@@ -46,6 +52,14 @@ pub mod kamui_layerzero {
         instructions::set_peer_config::handler(ctx, params)
     }
 
+    /// Resize a peer config created with an older layout to the current size
+    pub fn resize_peer_config(
+        ctx: Context<ResizePeerConfig>,
+        remote_eid: u32,
+    ) -> Result<()> {
+        instructions::resize_peer_config::handler(ctx, remote_eid)
+    }
+
     /// Returns the accounts required for lz_receive
     pub fn lz_receive_types(
         ctx: Context<LzReceiveTypes>,
@@ -55,13 +69,29 @@ pub mod kamui_layerzero {
     }
 
     /// Process an incoming LayerZero message
-    pub fn lz_receive(
-        ctx: Context<LzReceive>,
+    pub fn lz_receive<'info>(
+        ctx: Context<'_, '_, '_, 'info, LzReceive<'info>>,
         params: LzReceiveParams,
     ) -> Result<()> {
         instructions::lz_receive::handler(ctx, params)
     }
 
+    /// Returns the accounts required for lz_compose
+    pub fn lz_compose_types(
+        ctx: Context<LzComposeTypes>,
+        params: LzComposeParams,
+    ) -> Result<Vec<LzAccount>> {
+        instructions::lz_compose_types::handler(ctx, params)
+    }
+
+    /// Deliver a fulfilled VRF request to its consumer program
+    pub fn lz_compose<'info>(
+        ctx: Context<'_, '_, '_, 'info, LzCompose<'info>>,
+        params: LzComposeParams,
+    ) -> Result<()> {
+        instructions::lz_compose::handler(ctx, params)
+    }
+
     /// Set delegate for LayerZero operations
     pub fn set_delegate(
         ctx: Context<SetDelegate>,
@@ -109,4 +139,38 @@ pub mod kamui_layerzero {
     ) -> Result<()> {
         instructions::fulfill_vrf::handler(ctx, params)
     }
+
+    /// Set the kamui-vrf coordinator that inbound VRF requests are forwarded to
+    pub fn set_vrf_coordinator(
+        ctx: Context<SetVrfCoordinator>,
+        config: VrfCoordinatorConfig,
+    ) -> Result<()> {
+        instructions::set_vrf_coordinator::handler(ctx, config)
+    }
+
+    /// Set the oracle allowed to fulfill requests and its ECVRF public key
+    pub fn set_oracle(ctx: Context<SetOracle>, params: SetOracleParams) -> Result<()> {
+        instructions::set_oracle::handler(ctx, params)
+    }
+
+    /// Set the options enforced on VRF messages to a remote endpoint
+    pub fn set_enforced_options(
+        ctx: Context<SetEnforcedOptions>,
+        params: SetEnforcedOptionsParams,
+    ) -> Result<()> {
+        instructions::set_enforced_options::handler(ctx, params)
+    }
+
+    /// Expire a VRF request past its deadline and tell the source chain
+    pub fn expire_cross_chain_request(
+        ctx: Context<ExpireCrossChainRequest>,
+        params: ExpireCrossChainRequestParams,
+    ) -> Result<()> {
+        instructions::expire_cross_chain_request::handler(ctx, params)
+    }
+
+    /// Close a delivered or expired VRF request and refund its rent
+    pub fn close_vrf_request(ctx: Context<CloseVrfRequest>) -> Result<()> {
+        instructions::close_vrf_request::handler(ctx)
+    }
 } 
\ No newline at end of file
@@ -0,0 +1,158 @@
+// -----------------------------------------------------------------------------
+// Builder for LayerZero type-3 options, byte-compatible with `OptionsBuilder`
+// in the LayerZero Solidity libraries.
+//
+// 0          2
+// |----------|--------------------------------------------------------|
+// |  0x0003  |  option*                                               |
+// |----------|--------------------------------------------------------|
+//
+// Each executor option is
+// 0             1               3              4
+// |-------------|---------------|--------------|----------------------|
+// |  worker id  |  size (u16)   |  option type |  params              |
+// |-------------|---------------|--------------|----------------------|
+//
+// where `size` counts the option type byte and the params. Integers are big
+// endian.
+// -----------------------------------------------------------------------------
+
+pub const TYPE_3: u16 = 3;
+
+pub const EXECUTOR_WORKER_ID: u8 = 1;
+
+pub const OPTION_TYPE_LZRECEIVE: u8 = 1;
+pub const OPTION_TYPE_NATIVE_DROP: u8 = 2;
+pub const OPTION_TYPE_LZCOMPOSE: u8 = 3;
+pub const OPTION_TYPE_ORDERED_EXECUTION: u8 = 4;
+
+/// Builds type-3 executor options, e.g.
+///
+/// ```ignore
+/// let options = OptionsBuilder::new()
+///     .add_executor_lz_receive_option(200_000, 0)
+///     .add_executor_ordered_execution_option()
+///     .build();
+/// ```
+#[derive(Clone, Debug)]
+pub struct OptionsBuilder {
+    options: Vec<u8>,
+}
+
+impl Default for OptionsBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OptionsBuilder {
+    pub fn new() -> Self {
+        Self { options: TYPE_3.to_be_bytes().to_vec() }
+    }
+
+    /// Gas and msg.value for the `lzReceive` call on the destination. A zero
+    /// value is left out of the encoding.
+    pub fn add_executor_lz_receive_option(self, gas: u128, value: u128) -> Self {
+        let mut params = gas.to_be_bytes().to_vec();
+        if value != 0 {
+            params.extend_from_slice(&value.to_be_bytes());
+        }
+        self.add_executor_option(OPTION_TYPE_LZRECEIVE, &params)
+    }
+
+    /// Native tokens dropped to `receiver` on the destination.
+    pub fn add_executor_native_drop_option(self, amount: u128, receiver: [u8; 32]) -> Self {
+        let mut params = amount.to_be_bytes().to_vec();
+        params.extend_from_slice(&receiver);
+        self.add_executor_option(OPTION_TYPE_NATIVE_DROP, &params)
+    }
+
+    /// Gas and msg.value for the `lzCompose` call with the given compose index.
+    /// A zero value is left out of the encoding.
+    pub fn add_executor_lz_compose_option(self, index: u16, gas: u128, value: u128) -> Self {
+        let mut params = index.to_be_bytes().to_vec();
+        params.extend_from_slice(&gas.to_be_bytes());
+        if value != 0 {
+            params.extend_from_slice(&value.to_be_bytes());
+        }
+        self.add_executor_option(OPTION_TYPE_LZCOMPOSE, &params)
+    }
+
+    /// Deliver messages from this OApp in nonce order.
+    pub fn add_executor_ordered_execution_option(self) -> Self {
+        self.add_executor_option(OPTION_TYPE_ORDERED_EXECUTION, &[])
+    }
+
+    fn add_executor_option(mut self, option_type: u8, params: &[u8]) -> Self {
+        let size = (params.len() + 1) as u16;
+        self.options.push(EXECUTOR_WORKER_ID);
+        self.options.extend_from_slice(&size.to_be_bytes());
+        self.options.push(option_type);
+        self.options.extend_from_slice(params);
+        self
+    }
+
+    pub fn build(self) -> Vec<u8> {
+        self.options
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_hex(hex_str: &str) -> Vec<u8> {
+        (0..hex_str.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex_str[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    // 16-byte big-endian word
+    fn uint128(hex_str: &str) -> String {
+        format!("{:0>32}", hex_str)
+    }
+
+    #[test]
+    fn test_empty_options() {
+        assert_eq!(OptionsBuilder::new().build(), vec![0, 3]);
+    }
+
+    #[test]
+    fn test_lz_receive_option() {
+        // Options.newOptions().addExecutorLzReceiveOption(200000, 0)
+        let options = OptionsBuilder::new().add_executor_lz_receive_option(200_000, 0).build();
+        assert_eq!(options, from_hex("00030100110100000000000000000000000000030d40"));
+
+        // A value grows the option to 33 bytes
+        let options = OptionsBuilder::new().add_executor_lz_receive_option(200_000, 1).build();
+        assert_eq!(
+            options,
+            from_hex(&["0003", "01", "0021", "01", &uint128("30d40"), &uint128("1")].concat())
+        );
+    }
+
+    #[test]
+    fn test_combined_options() {
+        let options = OptionsBuilder::new()
+            .add_executor_lz_receive_option(200_000, 0)
+            .add_executor_native_drop_option(1, [0xaa; 32])
+            .add_executor_lz_compose_option(0, 100_000, 0)
+            .add_executor_ordered_execution_option()
+            .build();
+        assert_eq!(
+            options,
+            from_hex(&[
+                "0003",
+                // lzReceive
+                "01", "0011", "01", &uint128("30d40"),
+                // native drop
+                "01", "0031", "02", &uint128("1"), &"aa".repeat(32),
+                // lzCompose
+                "01", "0013", "03", "0000", &uint128("186a0"),
+                // ordered execution
+                "01", "0001", "04",
+            ].concat())
+        );
+    }
+}
@@ -1,19 +1,28 @@
 use anchor_lang::prelude::*;
+use crate::constants::MAX_CONSUMER_CALLBACK_ACCOUNTS;
+use crate::errors::LayerZeroError;
+use kamui_lz_core::types::vrf_alpha;
+
+pub const STORE_MAX_STRING_LENGTH: usize = 256;
 
 /// The main OApp Store PDA that acts as the OApp address
 #[account]
+#[derive(InitSpace)]
 pub struct Store {
     pub admin: Pubkey, // This is required and should be consistent.
     pub bump: u8, // This is required and should be consistent.
     pub endpoint_program: Pubkey, // This is required and should be consistent.
+    #[max_len(STORE_MAX_STRING_LENGTH)]
     pub string: String, // This is specific to this string-passing example.
     pub vrf_data: VrfData, // VRF-specific data for the OApp
     // You can add more fields as needed for your OApp implementation.
 }
 
 impl Store {
-    pub const MAX_STRING_LENGTH: usize = 256;
-    pub const SIZE: usize = 8 + std::mem::size_of::<Self>() + Self::MAX_STRING_LENGTH;
+    pub const MAX_STRING_LENGTH: usize = STORE_MAX_STRING_LENGTH;
+    /// Space for the largest Store, with every optional VRF setting present.
+    /// Stores created with a different size are resized by `set_vrf_coordinator`.
+    pub const SIZE: usize = 8 + Self::INIT_SPACE;
 }
 
 // The LzReceiveTypesAccounts PDA is used by the Executor as a prerequisite to calling `lz_receive`.
@@ -26,18 +35,106 @@ impl LzReceiveTypesAccounts {
     pub const SIZE: usize = 8 + std::mem::size_of::<Self>();
 }
 
+// The LzComposeTypesAccounts PDA is used by the Executor as a prerequisite to calling `lz_compose`.
+#[account]
+pub struct LzComposeTypesAccounts {
+    pub store: Pubkey,
+}
+
+impl LzComposeTypesAccounts {
+    pub const SIZE: usize = 8 + std::mem::size_of::<Self>();
+}
+
 pub const ENFORCED_OPTIONS_SEND_MAX_LEN: usize = 512;
 pub const ENFORCED_OPTIONS_SEND_AND_CALL_MAX_LEN: usize = 1024;
 
 #[account]
+#[derive(InitSpace)]
 pub struct PeerConfig {
     pub peer_address: [u8; 32],
     pub enforced_options: EnforcedOptions,
     pub bump: u8,
+    /// Limit on messages received from the peer, `None` for no limit
+    pub inbound_rate_limit: Option<RateLimit>,
+    /// Limit on messages sent to the peer, `None` for no limit
+    pub outbound_rate_limit: Option<RateLimit>,
+    /// Set by the admin to stop all traffic with the peer
+    pub paused: bool,
 }
 
 impl PeerConfig {
-    pub const SIZE: usize = 8 + std::mem::size_of::<Self>();
+    /// Space for the largest PeerConfig, with both rate limits and the longest
+    /// enforced options. Peers created with a different size are resized by
+    /// `resize_peer_config`.
+    pub const SIZE: usize = 8 + Self::INIT_SPACE;
+
+    /// Account for a message from the peer requesting `words` random words.
+    pub fn consume_inbound(&mut self, now: i64, words: u32) -> Result<()> {
+        if self.paused {
+            return Err(LayerZeroError::PeerPaused.into());
+        }
+        match &mut self.inbound_rate_limit {
+            Some(limit) => limit.consume(now, words),
+            None => Ok(()),
+        }
+    }
+
+    /// Account for a message to the peer requesting `words` random words.
+    pub fn consume_outbound(&mut self, now: i64, words: u32) -> Result<()> {
+        if self.paused {
+            return Err(LayerZeroError::PeerPaused.into());
+        }
+        match &mut self.outbound_rate_limit {
+            Some(limit) => limit.consume(now, words),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Limits for one direction of traffic with a peer
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct RateLimitParams {
+    /// Length of a window in seconds
+    pub window: i64,
+    /// Messages allowed per window
+    pub max_messages: u32,
+    /// Random words that may be requested per window
+    pub max_words: u32,
+}
+
+/// Fixed-window counters. The window restarts with the first message after
+/// `params.window` seconds have elapsed since it opened.
+#[derive(Clone, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct RateLimit {
+    pub params: RateLimitParams,
+    pub window_start: i64,
+    pub messages: u32,
+    pub words: u32,
+}
+
+impl RateLimit {
+    pub fn new(params: RateLimitParams) -> Result<Self> {
+        if params.window <= 0 {
+            return Err(LayerZeroError::InvalidRateLimit.into());
+        }
+        Ok(Self { params, ..Default::default() })
+    }
+
+    pub fn consume(&mut self, now: i64, words: u32) -> Result<()> {
+        if now.saturating_sub(self.window_start) >= self.params.window {
+            self.window_start = now;
+            self.messages = 0;
+            self.words = 0;
+        }
+        let messages = self.messages.saturating_add(1);
+        let words = self.words.saturating_add(words);
+        if messages > self.params.max_messages || words > self.params.max_words {
+            return Err(LayerZeroError::RateLimitExceeded.into());
+        }
+        self.messages = messages;
+        self.words = words;
+        Ok(())
+    }
 }
 
 #[derive(Clone, Default, AnchorSerialize, AnchorDeserialize, InitSpace)]
@@ -67,6 +164,38 @@ impl EnforcedOptions {
     }
 }
 
+/// Options enforced on the VRF messages sent to one remote endpoint. Generic
+/// sends keep using the LayerZero-standard options in [`PeerConfig`]. One PDA per
+/// eid, seeded by `[ENFORCED_OPTIONS_SEED, store, eid]`.
+#[account]
+#[derive(InitSpace)]
+pub struct EnforcedOptionsConfig {
+    /// Options for `request_vrf`, e.g. gas for forwarding the request to the
+    /// coordinator on the remote chain
+    #[max_len(ENFORCED_OPTIONS_SEND_MAX_LEN)]
+    pub vrf_request: Vec<u8>,
+    /// Options for `fulfill_vrf`, e.g. gas for the consumer callback on the
+    /// remote chain
+    #[max_len(ENFORCED_OPTIONS_SEND_MAX_LEN)]
+    pub vrf_fulfillment: Vec<u8>,
+    pub bump: u8,
+}
+
+impl EnforcedOptionsConfig {
+    /// Merge the enforced options for `msg_type` with `extra_options` supplied
+    /// by the caller.
+    pub fn combine_options(&self, msg_type: MessageType, extra_options: &Vec<u8>) -> Result<Vec<u8>> {
+        let enforced_options = match msg_type {
+            MessageType::VrfRequest => self.vrf_request.clone(),
+            // Failures answer a request like fulfillments do
+            MessageType::VrfFulfillment | MessageType::VrfFailure => self.vrf_fulfillment.clone(),
+            // Generic messages are covered by the peer's enforced options
+            MessageType::Generic => vec![],
+        };
+        oapp::options::combine_options(enforced_options, extra_options)
+    }
+}
+
 /// Parameters for initializing the Store
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct InitStoreParams {
@@ -89,6 +218,22 @@ pub enum PeerConfigParam {
     PeerAddress([u8; 32]),
     /// Optionally enforce specific send options for this peer
     EnforcedOptions { send: Vec<u8>, send_and_call: Vec<u8> },
+    /// Limit messages received from this peer, or `None` to lift the limit
+    InboundRateLimit(Option<RateLimitParams>),
+    /// Limit messages sent to this peer, or `None` to lift the limit
+    OutboundRateLimit(Option<RateLimitParams>),
+    /// Stop or resume all traffic with this peer
+    Paused(bool),
+}
+
+/// Parameters for setting the enforced options of a remote endpoint
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SetEnforcedOptionsParams {
+    pub remote_eid: u32,
+    /// Type-3 options for VRF requests, or empty for none
+    pub vrf_request: Vec<u8>,
+    /// Type-3 options for VRF fulfillments, or empty for none
+    pub vrf_fulfillment: Vec<u8>,
 }
 
 /// Parameters for sending a message
@@ -113,13 +258,10 @@ pub struct QuoteSendParams {
 
 // MessagingFee is now imported from oapp::endpoint::MessagingFee
 
-/// Message types for LayerZero messages
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
-pub enum MessageType {
-    VrfRequest,
-    VrfFulfillment,
-    Generic,
-}
+// Message types and payloads are part of the wire format shared with the other OApps
+pub use kamui_lz_core::types::{
+    MessageType, VrfFailurePayload, VrfFailureReason, VrfFulfillmentPayload, VrfRequestPayload,
+};
 
 /// Parameters for VRF requests
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -128,36 +270,42 @@ pub struct RequestVrfParams {
     pub seed: [u8; 32],
     pub num_words: u8,
     pub callback_data: Vec<u8>,
+    /// Solana program to hand the randomness to once the fulfillment arrives.
+    /// Without one the result is only recorded in the `OutboundVrfRequest`.
+    pub consumer: Option<ConsumerCallback>,
+    /// Extra type-3 options, merged with the enforced ones
+    pub options: Vec<u8>,
     pub fee: u64,
 }
 
-/// VRF request payload structure
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct VrfRequestPayload {
-    pub msg_type: MessageType,
-    pub requester: [u8; 32],
-    pub seed: [u8; 32],
-    pub num_words: u8,
-    pub callback_data: Vec<u8>,
-}
-
 /// VRF fulfillment parameters
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct FulfillVrfParams {
     pub dst_eid: u32,
     pub request_id: [u8; 32],
-    pub randomness: Vec<u64>,
+    /// 80-byte ECVRF proof over the request's alpha, see [`VrfRequest::alpha`]
     pub proof: Vec<u8>,
+    /// Extra type-3 options, merged with the enforced ones
+    pub options: Vec<u8>,
     pub fee: u64,
 }
 
-/// VRF fulfillment payload structure
+/// Parameters for expiring an inbound VRF request
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct VrfFulfillmentPayload {
-    pub msg_type: MessageType,
+pub struct ExpireCrossChainRequestParams {
     pub request_id: [u8; 32],
-    pub randomness: Vec<u64>,
-    pub proof: Vec<u8>,
+    /// Extra type-3 options, merged with the enforced ones
+    pub options: Vec<u8>,
+    pub fee: u64,
+}
+
+/// Parameters for setting the oracle
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SetOracleParams {
+    /// Signer allowed to call `fulfill_vrf`, or `None` to allow anyone
+    pub oracle: Option<Pubkey>,
+    /// ECVRF public key fulfillments must verify under
+    pub vrf_public_key: [u8; 32],
 }
 
 /// Parameters for lz_send instruction
@@ -170,20 +318,244 @@ pub struct LzSendParams {
 }
 
 /// VRF-specific data for the OApp
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
 pub struct VrfData {
     pub oracle_pubkey: Option<Pubkey>,
-    pub pending_requests: Vec<VrfRequest>,
+    /// ECVRF public key of the oracle. Fulfillments are rejected until it is set.
+    pub oracle_vrf_key: Option<[u8; 32]>,
+    /// Where inbound VRF requests are forwarded. Unset until the admin calls
+    /// `set_vrf_coordinator`.
+    pub coordinator: Option<VrfCoordinatorConfig>,
 }
 
-/// VRF request structure
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+/// kamui-vrf coordinator that inbound VRF requests are bridged into
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, InitSpace)]
+pub struct VrfCoordinatorConfig {
+    /// The kamui-vrf program
+    pub vrf_program: Pubkey,
+    /// Subscription cross-chain requests are charged to. Its owner must have
+    /// registered the Store PDA as the bridge authority.
+    pub subscription: Pubkey,
+    /// Request pool of the subscription to use
+    pub pool_id: u8,
+    pub minimum_confirmations: u8,
+    pub callback_gas_limit: u64,
+}
+
+/// Origin tag attached to bridged requests
+pub use kamui_vrf::state::CrossChainOrigin;
+
+/// An inbound cross-chain VRF request. One PDA per message GUID, seeded by
+/// `[VRF_REQUEST_SEED, store, guid]`.
+#[account]
+#[derive(InitSpace)]
 pub struct VrfRequest {
+    /// Message GUID, also used as the request ID in the fulfillment message
+    pub request_id: [u8; 32],
+    /// Source endpoint the request came from and the response goes back to
+    pub src_eid: u32,
+    /// Requester address on the source chain
+    pub requester: [u8; 32],
+    pub seed: [u8; 32],
+    pub num_words: u32,
+    /// The kamui-vrf `RandomnessRequest` the request was forwarded to
+    pub coordinator_request: Pubkey,
+    pub status: VrfRequestStatus,
+    /// Paid the rent and receives it back when the account is closed
+    pub payer: Pubkey,
+    pub created_at: i64,
+    pub updated_at: i64,
+    /// After this the request can no longer be fulfilled and anyone can expire
+    /// it with `expire_cross_chain_request`
+    pub deadline: i64,
+    pub bump: u8,
+}
+
+/// Lifecycle of a [`VrfRequest`]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum VrfRequestStatus {
+    /// Waiting for the oracle
+    Pending,
+    /// The oracle has produced the randomness
+    Fulfilled,
+    /// The randomness has been handed back to the requester
+    Delivered,
+    /// Not fulfilled in time. The source chain is told with a failure message.
+    Expired,
+}
+
+impl VrfRequest {
+    /// The VRF input for this request, see [`vrf_alpha`]
+    pub fn alpha(&self) -> Vec<u8> {
+        vrf_alpha(&self.request_id, &self.seed)
+    }
+
+    /// Whether the account can be closed and its rent reclaimed.
+    pub fn is_closable(&self) -> bool {
+        match self.status {
+            VrfRequestStatus::Delivered | VrfRequestStatus::Expired => true,
+            // Expired requests must answer the source chain before they go
+            VrfRequestStatus::Pending | VrfRequestStatus::Fulfilled => false,
+        }
+    }
+
+    /// Whether a pending request is past its deadline.
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.status == VrfRequestStatus::Pending && now > self.deadline
+    }
+}
+
+/// A Solana program called with the randomness of a fulfilled outbound request,
+/// see [`crate::consumer`].
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, InitSpace)]
+pub struct ConsumerCallback {
+    pub program_id: Pubkey,
+    /// Accounts passed to the callback after the store and the request
+    #[max_len(MAX_CONSUMER_CALLBACK_ACCOUNTS)]
+    pub accounts: Vec<CallbackAccount>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, InitSpace)]
+pub struct CallbackAccount {
+    pub pubkey: Pubkey,
+    pub is_writable: bool,
+}
+
+/// A VRF request sent from Solana to a remote chain. One PDA per message GUID,
+/// seeded by `[OUTBOUND_VRF_REQUEST_SEED, store, guid]`; the requester derives
+/// it from the GUID the Endpoint will assign to the request message.
+#[account]
+#[derive(InitSpace)]
+pub struct OutboundVrfRequest {
+    /// GUID of the request message, echoed as the request ID by the fulfillment
     pub request_id: [u8; 32],
+    pub dst_eid: u32,
     pub requester: Pubkey,
     pub seed: [u8; 32],
-    pub num_words: u8,
-    pub callback_data: Vec<u8>,
-    pub timestamp: i64,
-    pub fulfilled: bool,
-} 
\ No newline at end of file
+    pub num_words: u32,
+    pub consumer: Option<ConsumerCallback>,
+    pub status: VrfRequestStatus,
+    /// Verified VRF output, set once the fulfillment is received
+    pub randomness: Option<[u8; 64]>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    /// Deadline the remote chain gives the oracle. A request still pending
+    /// after it should be answered with a failure message.
+    pub deadline: i64,
+    pub bump: u8,
+}
+
+impl OutboundVrfRequest {
+    /// The VRF input the remote oracle must prove, same layout as
+    /// [`VrfRequest::alpha`].
+    pub fn alpha(&self) -> Vec<u8> {
+        vrf_alpha(&self.request_id, &self.seed)
+    }
+}
+
+/// Record of a message handled by `lz_receive`, kept as a replay check on top of
+/// the Endpoint's own. One PDA per message, seeded by
+/// `[PROCESSED_GUID_SEED, store, src_eid, guid]`.
+#[account]
+#[derive(InitSpace)]
+pub struct ProcessedGuid {
+    pub guid: [u8; 32],
+    pub src_eid: u32,
+    pub nonce: u64,
+    pub processed_at: i64,
+    pub bump: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit() -> RateLimit {
+        RateLimit::new(RateLimitParams { window: 60, max_messages: 2, max_words: 5 }).unwrap()
+    }
+
+    #[test]
+    fn test_rate_limit_counts_messages_and_words() {
+        let mut limit = limit();
+        limit.consume(1_000, 3).unwrap();
+        // Over the word budget
+        assert!(limit.consume(1_010, 3).is_err());
+        limit.consume(1_010, 2).unwrap();
+        // Over the message budget
+        assert!(limit.consume(1_020, 0).is_err());
+        assert_eq!((limit.messages, limit.words), (2, 5));
+
+        // A new window opens 60 seconds after the first
+        limit.consume(1_060, 5).unwrap();
+        assert_eq!(limit.window_start, 1_060);
+        assert_eq!((limit.messages, limit.words), (1, 5));
+    }
+
+    #[test]
+    fn test_rate_limit_requires_window() {
+        let params = RateLimitParams { window: 0, max_messages: 1, max_words: 1 };
+        assert!(RateLimit::new(params).is_err());
+    }
+
+    #[test]
+    fn test_store_size_fits_largest_store() {
+        let store = Store {
+            admin: Pubkey::new_unique(),
+            bump: 255,
+            endpoint_program: Pubkey::new_unique(),
+            string: "x".repeat(Store::MAX_STRING_LENGTH),
+            vrf_data: VrfData {
+                oracle_pubkey: Some(Pubkey::new_unique()),
+                oracle_vrf_key: Some([1; 32]),
+                coordinator: Some(VrfCoordinatorConfig {
+                    vrf_program: Pubkey::new_unique(),
+                    subscription: Pubkey::new_unique(),
+                    pool_id: 1,
+                    minimum_confirmations: 1,
+                    callback_gas_limit: 200_000,
+                }),
+            },
+        };
+        let mut data = Vec::new();
+        store.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), Store::SIZE);
+    }
+
+    #[test]
+    fn test_peer_config_size_fits_largest_peer() {
+        let peer = PeerConfig {
+            peer_address: [1; 32],
+            enforced_options: EnforcedOptions {
+                send: vec![3; ENFORCED_OPTIONS_SEND_MAX_LEN],
+                send_and_call: vec![3; ENFORCED_OPTIONS_SEND_AND_CALL_MAX_LEN],
+            },
+            bump: 255,
+            inbound_rate_limit: Some(limit()),
+            outbound_rate_limit: Some(limit()),
+            paused: true,
+        };
+        let mut data = Vec::new();
+        peer.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), PeerConfig::SIZE);
+    }
+
+    #[test]
+    fn test_paused_peer_refuses_traffic() {
+        let mut peer = PeerConfig {
+            peer_address: [0; 32],
+            enforced_options: EnforcedOptions::default(),
+            bump: 0,
+            inbound_rate_limit: None,
+            outbound_rate_limit: Some(limit()),
+            paused: false,
+        };
+        peer.consume_inbound(0, 10).unwrap();
+        peer.consume_outbound(0, 1).unwrap();
+
+        peer.paused = true;
+        assert!(peer.consume_inbound(0, 0).is_err());
+        assert!(peer.consume_outbound(0, 0).is_err());
+        // Refused messages don't use up the budget
+        assert_eq!(peer.outbound_rate_limit.as_ref().unwrap().messages, 1);
+    }
+}
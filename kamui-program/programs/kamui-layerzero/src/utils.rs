@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::errors::LayerZeroError;
+
+/// Create the program-owned PDA `account` at `seeds` (without the bump), paid
+/// for by `payer`, and write `value` into it. Used for accounts whose address is
+/// only known inside the instruction, where Anchor's `init` can't be used.
+/// Returns the bump.
+pub fn init_pda_account<'info, T: AccountSerialize>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    seeds: &[&[u8]],
+    space: usize,
+    value: impl FnOnce(u8) -> T,
+) -> Result<u8> {
+    let (expected, bump) = Pubkey::find_program_address(seeds, &crate::ID);
+    if account.key() != expected {
+        return Err(LayerZeroError::AccountConstraintViolation.into());
+    }
+    let bump_seed = [bump];
+    let signer_seeds: Vec<&[u8]> = seeds.iter().copied().chain([&bump_seed[..]]).collect();
+    let signer_seeds = &[&signer_seeds[..]];
+
+    // Same steps as Anchor's `init`, which also copes with an address that was
+    // funded beforehand.
+    let rent = Rent::get()?.minimum_balance(space);
+    if account.lamports() == 0 {
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::CreateAccount { from: payer.clone(), to: account.clone() },
+                signer_seeds,
+            ),
+            rent,
+            space as u64,
+            &crate::ID,
+        )?;
+    } else {
+        let top_up = rent.saturating_sub(account.lamports());
+        if top_up > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    system_program::Transfer { from: payer.clone(), to: account.clone() },
+                ),
+                top_up,
+            )?;
+        }
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::Allocate { account_to_allocate: account.clone() },
+                signer_seeds,
+            ),
+            space as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::Assign { account_to_assign: account.clone() },
+                signer_seeds,
+            ),
+            &crate::ID,
+        )?;
+    }
+
+    let mut data = account.try_borrow_mut_data()?;
+    value(bump).try_serialize(&mut &mut data[..])?;
+    Ok(bump)
+}
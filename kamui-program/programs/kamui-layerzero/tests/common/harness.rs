@@ -0,0 +1,608 @@
+// -----------------------------------------------------------------------------
+// ProgramTest harness running kamui-layerzero against the mock Endpoint, with
+// the harness acting as the Executor.
+//
+// Anchor pins the OApp to its declared program ID, so a test chain holds a
+// single OApp instance. It is configured as its own peer on `EID`, which is
+// also the endpoint ID of the chain: every packet it sends is delivered back
+// to it, and the request and fulfillment sides of a VRF round trip both run
+// through the same Store.
+//
+// Native programs complete the setup: a coordinator that accepts any
+// `request_cross_chain_randomness` call signed by the Store, a consumer that
+// records the randomness it is called with in `consumer_state`, and a
+// malicious consumer that calls the coordinator as the Store whenever the Store
+// signature reaches it, and records in `malicious_consumer_state` that it did.
+//
+// Limitations: with a single instance, the tests cannot catch a mix-up between
+// the requesting and the fulfilling Store, such as a peer or EID checked on the
+// wrong side. And the coordinator is a stub, not kamui-vrf: kamui-vrf is built
+// on Anchor 0.31 and Solana 2.x, so its entrypoint cannot be registered as a
+// builtin next to this Solana 1.18 ProgramTest, and loading it as a program
+// needs an SBF build. The accounts and seeds `coordinator.rs` passes to
+// kamui-vrf are only exercised by the devnet tests.
+// -----------------------------------------------------------------------------
+
+use anchor_lang::{
+    error::ERROR_CODE_OFFSET,
+    prelude::*,
+    solana_program::{
+        entrypoint::ProgramResult, hash::hash, instruction::Instruction, program::invoke,
+        program_error::ProgramError, system_program,
+    },
+    InstructionData,
+};
+use kamui_layerzero::{
+    consumer,
+    errors::LayerZeroError,
+    options::OptionsBuilder,
+    state::*,
+};
+use kamui_lz_core::{instruction as lz_instruction, pda};
+use oapp::{endpoint_cpi::LzAccount, LzComposeParams, LzReceiveParams};
+use solana_program_test::{
+    processor, BanksClientError, ProgramTest, ProgramTestBanksClientExt, ProgramTestContext,
+};
+use solana_sdk::{
+    account::Account,
+    instruction::InstructionError,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+use super::mock_endpoint::{self, Packet};
+use super::oracle::VrfKeypair;
+
+/// Endpoint ID of the test chain, and of the OApp's only peer
+pub const EID: u32 = kamui_layerzero::constants::SOLANA_DEVNET_EID;
+
+/// Bytes of `consumer_state`: request ID followed by randomness
+pub const CONSUMER_STATE_LEN: usize = 32 + 64;
+
+pub struct Harness {
+    pub context: ProgramTestContext,
+    pub store: Pubkey,
+    pub oracle: Keypair,
+    pub vrf_keypair: VrfKeypair,
+    pub coordinator_program: Pubkey,
+    pub consumer_program: Pubkey,
+    pub consumer_state: Pubkey,
+    pub malicious_consumer_program: Pubkey,
+    pub malicious_consumer_state: Pubkey,
+}
+
+impl Harness {
+    /// Start a chain with the OApp initialized, peered with itself on `EID`,
+    /// and configured with an oracle and the mock coordinator.
+    pub async fn start() -> Self {
+        let coordinator_program = Pubkey::new_unique();
+        let consumer_program = Pubkey::new_unique();
+        let consumer_state = Pubkey::new_unique();
+        let malicious_consumer_program = Pubkey::new_unique();
+        let malicious_consumer_state = Pubkey::new_unique();
+
+        let mut program_test = ProgramTest::new(
+            "kamui_layerzero",
+            kamui_layerzero::ID,
+            processor!(process_kamui_layerzero),
+        );
+        program_test.add_program(
+            "mock_endpoint",
+            oapp::endpoint::ID,
+            processor!(mock_endpoint::process_instruction),
+        );
+        program_test.add_program("mock_coordinator", coordinator_program, processor!(process_coordinator));
+        program_test.add_program("mock_consumer", consumer_program, processor!(process_consumer));
+        program_test.add_program(
+            "malicious_consumer",
+            malicious_consumer_program,
+            processor!(process_malicious_consumer),
+        );
+        program_test.add_account(mock_endpoint::settings_address(), mock_endpoint::settings_account(EID));
+        program_test.add_account(
+            consumer_state,
+            Account {
+                lamports: 1_000_000_000,
+                data: vec![0; CONSUMER_STATE_LEN],
+                owner: consumer_program,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+
+        program_test.add_account(
+            malicious_consumer_state,
+            Account {
+                lamports: 1_000_000_000,
+                data: vec![0; 1],
+                owner: malicious_consumer_program,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+
+        let context = program_test.start_with_context().await;
+        let store = pda::store(&kamui_layerzero::ID).0;
+        let mut harness = Self {
+            context,
+            store,
+            oracle: Keypair::new(),
+            vrf_keypair: VrfKeypair::from_seed(b"kamui-layerzero test oracle"),
+            coordinator_program,
+            consumer_program,
+            consumer_state,
+            malicious_consumer_program,
+            malicious_consumer_state,
+        };
+        harness.init().await;
+        harness
+    }
+
+    async fn init(&mut self) {
+        let admin = self.payer();
+        let store = self.store;
+
+        let mut init_store = anchor_instruction(
+            kamui_layerzero::accounts::InitStore {
+                admin,
+                store,
+                lz_receive_types_accounts: pda::lz_receive_types(&kamui_layerzero::ID, &store).0,
+                lz_compose_types_accounts: pda::lz_compose_types(&kamui_layerzero::ID, &store).0,
+                system_program: system_program::ID,
+            },
+            kamui_layerzero::instruction::InitStore {
+                params: InitStoreParams { admin, endpoint: oapp::endpoint::ID },
+            },
+        );
+        init_store.accounts.extend(mock_endpoint::register_oapp_accounts(&admin, &store));
+
+        let set_peer = anchor_instruction(
+            kamui_layerzero::accounts::SetPeerConfig {
+                admin,
+                peer: self.peer(),
+                store,
+                system_program: system_program::ID,
+            },
+            kamui_layerzero::instruction::SetPeerConfig {
+                params: SetPeerConfigParams {
+                    remote_eid: EID,
+                    config: PeerConfigParam::PeerAddress(store.to_bytes()),
+                },
+            },
+        );
+
+        let set_enforced_options = anchor_instruction(
+            kamui_layerzero::accounts::SetEnforcedOptions {
+                admin,
+                enforced_options: self.enforced_options(),
+                store,
+                system_program: system_program::ID,
+            },
+            kamui_layerzero::instruction::SetEnforcedOptions {
+                params: SetEnforcedOptionsParams {
+                    remote_eid: EID,
+                    vrf_request: OptionsBuilder::new()
+                        .add_executor_lz_receive_option(400_000, 0)
+                        .build(),
+                    vrf_fulfillment: OptionsBuilder::new()
+                        .add_executor_lz_receive_option(200_000, 0)
+                        .add_executor_lz_compose_option(0, 200_000, 0)
+                        .build(),
+                },
+            },
+        );
+
+        let set_oracle = anchor_instruction(
+            kamui_layerzero::accounts::SetOracle { admin, store },
+            kamui_layerzero::instruction::SetOracle {
+                params: SetOracleParams {
+                    oracle: Some(self.oracle.pubkey()),
+                    vrf_public_key: self.vrf_keypair.public_key,
+                },
+            },
+        );
+
+        let set_coordinator = anchor_instruction(
+            kamui_layerzero::accounts::SetVrfCoordinator {
+                admin,
+                store,
+                system_program: system_program::ID,
+            },
+            kamui_layerzero::instruction::SetVrfCoordinator {
+                config: VrfCoordinatorConfig {
+                    vrf_program: self.coordinator_program,
+                    subscription: Pubkey::new_unique(),
+                    pool_id: 0,
+                    minimum_confirmations: 1,
+                    callback_gas_limit: 200_000,
+                },
+            },
+        );
+
+        let init_nonce = mock_endpoint::init_nonce_instruction(
+            &admin,
+            mock_endpoint::InitNonceParams {
+                local_oapp: store,
+                remote_eid: EID,
+                remote_oapp: store.to_bytes(),
+            },
+        );
+
+        self.process(
+            &[init_store, set_peer, set_enforced_options, set_oracle, set_coordinator, init_nonce],
+            &[],
+        )
+        .await
+        .unwrap();
+    }
+
+    /// Update the OApp's peer on `EID` as the admin
+    pub async fn set_peer_config(&mut self, config: PeerConfigParam) -> std::result::Result<(), BanksClientError> {
+        let instruction = anchor_instruction(
+            kamui_layerzero::accounts::SetPeerConfig {
+                admin: self.payer(),
+                peer: self.peer(),
+                store: self.store,
+                system_program: system_program::ID,
+            },
+            kamui_layerzero::instruction::SetPeerConfig {
+                params: SetPeerConfigParams { remote_eid: EID, config },
+            },
+        );
+        self.process(&[instruction], &[]).await
+    }
+
+    /// Resize the OApp's peer on `EID` to the current layout as the admin
+    pub async fn resize_peer_config(&mut self) -> std::result::Result<(), BanksClientError> {
+        let instruction = anchor_instruction(
+            kamui_layerzero::accounts::ResizePeerConfig {
+                admin: self.payer(),
+                peer: self.peer(),
+                store: self.store,
+                system_program: system_program::ID,
+            },
+            kamui_layerzero::instruction::ResizePeerConfig { remote_eid: EID },
+        );
+        self.process(&[instruction], &[]).await
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.context.payer.pubkey()
+    }
+
+    pub fn peer(&self) -> Pubkey {
+        pda::peer(&kamui_layerzero::ID, &self.store, EID).0
+    }
+
+    pub fn enforced_options(&self) -> Pubkey {
+        pda::enforced_options(&kamui_layerzero::ID, &self.store, EID).0
+    }
+
+    pub fn inbound_request_address(&self, guid: &[u8; 32]) -> Pubkey {
+        pda::vrf_request(&kamui_layerzero::ID, &self.store, guid).0
+    }
+
+    pub fn processed_guid_address(&self, src_eid: u32, guid: &[u8; 32]) -> Pubkey {
+        pda::processed_guid(&kamui_layerzero::ID, &self.store, src_eid, guid).0
+    }
+
+    pub fn outbound_request_address(&self, guid: &[u8; 32]) -> Pubkey {
+        pda::outbound_vrf_request(&kamui_layerzero::ID, &self.store, guid).0
+    }
+
+    /// Send `instructions` in a fresh transaction paid by the payer
+    pub async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> std::result::Result<(), BanksClientError> {
+        let blockhash = self
+            .context
+            .banks_client
+            .get_new_latest_blockhash(&self.context.last_blockhash)
+            .await?;
+        self.context.last_blockhash = blockhash;
+
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer()),
+            &all_signers,
+            blockhash,
+        );
+        self.context.banks_client.process_transaction(transaction).await
+    }
+
+    /// Simulate `instruction` and decode its return data
+    pub async fn view<T: AnchorDeserialize>(&mut self, instruction: Instruction) -> T {
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&self.payer()),
+            &[&self.context.payer],
+            self.context.last_blockhash,
+        );
+        let simulation = self
+            .context
+            .banks_client
+            .simulate_transaction(transaction)
+            .await
+            .unwrap();
+        let details = simulation.simulation_details.unwrap();
+        assert!(
+            matches!(simulation.result, Some(Ok(()))),
+            "simulation failed: {:?}\n{}",
+            simulation.result,
+            details.logs.join("\n")
+        );
+        T::try_from_slice(&details.return_data.unwrap().data).unwrap()
+    }
+
+    pub async fn account<T: AccountDeserialize>(&mut self, address: Pubkey) -> Option<T> {
+        let account = self.context.banks_client.get_account(address).await.unwrap()?;
+        Some(T::try_deserialize(&mut &account.data[..]).unwrap())
+    }
+
+    pub async fn raw_account(&mut self, address: Pubkey) -> Option<Account> {
+        self.context.banks_client.get_account(address).await.unwrap()
+    }
+
+    /// Packets the OApp has sent to itself, oldest first
+    pub async fn sent_packets(&mut self) -> Vec<Packet> {
+        self.nonce().await.packets
+    }
+
+    async fn nonce(&mut self) -> mock_endpoint::Nonce {
+        let address = mock_endpoint::nonce_address(&self.store, EID, &self.store.to_bytes());
+        let account = self.raw_account(address).await.unwrap();
+        mock_endpoint::decode_nonce(&account.data)
+    }
+
+    /// Send a VRF request from the payer and return its request ID
+    pub async fn request_vrf(
+        &mut self,
+        seed: [u8; 32],
+        consumer: Option<ConsumerCallback>,
+    ) -> std::result::Result<[u8; 32], BanksClientError> {
+        // The request PDA is seeded by the GUID the Endpoint will assign
+        let nonce = self.nonce().await.outbound_nonce + 1;
+        let guid = mock_endpoint::guid(nonce, EID, &self.store, EID, &self.store.to_bytes());
+
+        let mut instruction = anchor_instruction(
+            kamui_layerzero::accounts::RequestVrf {
+                requester: self.payer(),
+                store: self.store,
+                peer: self.peer(),
+                enforced_options: self.enforced_options(),
+                request: self.outbound_request_address(&guid),
+                system_program: system_program::ID,
+            },
+            kamui_layerzero::instruction::RequestVrf {
+                params: RequestVrfParams {
+                    dst_eid: EID,
+                    seed,
+                    num_words: 1,
+                    callback_data: vec![],
+                    consumer,
+                    options: vec![],
+                    fee: 0,
+                },
+            },
+        );
+        instruction.accounts.extend(self.send_accounts());
+        self.process(&[instruction], &[]).await?;
+        Ok(guid)
+    }
+
+    /// Fulfill the inbound request `request_id` as the oracle
+    pub async fn fulfill_vrf(
+        &mut self,
+        request_id: [u8; 32],
+        proof: Vec<u8>,
+    ) -> std::result::Result<(), BanksClientError> {
+        let mut instruction = anchor_instruction(
+            kamui_layerzero::accounts::FulfillVrf {
+                oracle: self.oracle.pubkey(),
+                store: self.store,
+                peer: self.peer(),
+                enforced_options: self.enforced_options(),
+                request: self.inbound_request_address(&request_id),
+                system_program: system_program::ID,
+            },
+            kamui_layerzero::instruction::FulfillVrf {
+                params: FulfillVrfParams {
+                    dst_eid: EID,
+                    request_id,
+                    proof,
+                    options: vec![],
+                    fee: 0,
+                },
+            },
+        );
+        instruction.accounts.extend(self.send_accounts());
+        let oracle = self.oracle.insecure_clone();
+        self.process(&[instruction], &[&oracle]).await
+    }
+
+    /// Expire the inbound request `request_id`, paid by the payer
+    pub async fn expire_cross_chain_request(
+        &mut self,
+        request_id: [u8; 32],
+    ) -> std::result::Result<(), BanksClientError> {
+        let mut instruction = anchor_instruction(
+            kamui_layerzero::accounts::ExpireCrossChainRequest {
+                payer: self.payer(),
+                store: self.store,
+                peer: self.peer(),
+                enforced_options: self.enforced_options(),
+                request: self.inbound_request_address(&request_id),
+                system_program: system_program::ID,
+            },
+            kamui_layerzero::instruction::ExpireCrossChainRequest {
+                params: ExpireCrossChainRequestParams {
+                    request_id,
+                    options: vec![],
+                    fee: 0,
+                },
+            },
+        );
+        instruction.accounts.extend(self.send_accounts());
+        self.process(&[instruction], &[]).await
+    }
+
+    /// Move the clock forward by `seconds`
+    pub async fn advance_clock(&mut self, seconds: i64) {
+        let mut clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp += seconds;
+        self.context.set_sysvar(&clock);
+    }
+
+    fn send_accounts(&self) -> Vec<AccountMeta> {
+        mock_endpoint::send_accounts(&self.store, EID, &self.store.to_bytes())
+    }
+
+    /// Verify `packet` on the Endpoint, then execute `lz_receive`
+    pub async fn deliver(&mut self, packet: &Packet) -> std::result::Result<(), BanksClientError> {
+        let verify = mock_endpoint::verify_instruction(&self.payer(), packet);
+        self.process(&[verify], &[]).await?;
+        self.lz_receive(packet).await
+    }
+
+    /// Execute `lz_receive` for `packet` with the accounts from `lz_receive_types`
+    pub async fn lz_receive(&mut self, packet: &Packet) -> std::result::Result<(), BanksClientError> {
+        assert_eq!(packet.receiver, self.store.to_bytes(), "packet is not for this OApp");
+        let params = LzReceiveParams {
+            src_eid: packet.src_eid,
+            sender: packet.sender.to_bytes(),
+            nonce: packet.nonce,
+            guid: packet.guid,
+            message: packet.message.clone(),
+            extra_data: vec![],
+        };
+
+        // Built the way an Executor would, without the program's own types
+        let types = lz_instruction::lz_receive_types(&kamui_layerzero::ID, &params);
+        let accounts: Vec<LzAccount> = self.view(types).await;
+
+        let instruction =
+            lz_instruction::lz_receive(&kamui_layerzero::ID, account_metas(accounts), &self.payer(), &params);
+        self.process(&[instruction], &[]).await
+    }
+
+    /// The compose message `lz_receive` left for `guid`, if any
+    pub async fn compose_message(&mut self, guid: &[u8; 32], index: u16) -> Option<mock_endpoint::ComposeMessage> {
+        let address = mock_endpoint::compose_message_address(&self.store, &self.store, guid, index);
+        let account = self.raw_account(address).await?;
+        Some(mock_endpoint::decode_compose_message(&account.data))
+    }
+
+    /// Execute `lz_compose` for the compose message sent under `guid`
+    pub async fn deliver_compose(&mut self, guid: [u8; 32], index: u16) -> std::result::Result<(), BanksClientError> {
+        let message = self.compose_message(&guid, index).await.expect("no compose message").message;
+        let params = LzComposeParams {
+            from: self.store,
+            to: self.store,
+            guid,
+            index,
+            message,
+            extra_data: vec![],
+        };
+
+        let types = lz_instruction::lz_compose_types(&kamui_layerzero::ID, &params);
+        let accounts: Vec<LzAccount> = self.view(types).await;
+
+        let instruction =
+            lz_instruction::lz_compose(&kamui_layerzero::ID, account_metas(accounts), &self.payer(), &params);
+        self.process(&[instruction], &[]).await
+    }
+}
+
+/// Account metas for the accounts returned by `lz_receive_types` or
+/// `lz_compose_types`
+fn account_metas(accounts: Vec<LzAccount>) -> impl Iterator<Item = AccountMeta> {
+    accounts.into_iter().map(|account| AccountMeta {
+        pubkey: account.pubkey,
+        is_signer: account.is_signer,
+        is_writable: account.is_writable,
+    })
+}
+
+/// Assert that `result` failed with the OApp error `error`
+pub fn assert_custom_error(result: std::result::Result<(), BanksClientError>, error: LayerZeroError) {
+    let code = ERROR_CODE_OFFSET + error as u32;
+    match result {
+        Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(actual),
+        ))) => assert_eq!(actual, code),
+        other => panic!("expected custom error {}, got {:?}", code, other),
+    }
+}
+
+pub fn anchor_instruction(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: kamui_layerzero::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+fn process_kamui_layerzero(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    // Anchor's entrypoint wants the account slice to live as long as the
+    // accounts themselves
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    kamui_layerzero::entry(program_id, accounts, data)
+}
+
+fn process_coordinator(_program_id: &Pubkey, accounts: &[AccountInfo], _data: &[u8]) -> ProgramResult {
+    // payer, authority (the Store), ...
+    let store = pda::store(&kamui_layerzero::ID).0;
+    match accounts.get(1) {
+        Some(authority) if authority.is_signer && *authority.key == store => Ok(()),
+        _ => Err(ProgramError::MissingRequiredSignature),
+    }
+}
+
+fn process_consumer(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    // callback signer, request, state
+    let [callback_signer, _request, state, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let signer_address = consumer::callback_signer(&kamui_layerzero::ID).0;
+    if !callback_signer.is_signer || *callback_signer.key != signer_address {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if state.owner != program_id
+        || data.len() != 8 + CONSUMER_STATE_LEN
+        || data[..8] != hash(b"global:vrf_callback").to_bytes()[..8]
+    {
+        return Err(ProgramError::InvalidArgument);
+    }
+    state.try_borrow_mut_data()?.copy_from_slice(&data[8..]);
+    Ok(())
+}
+
+fn process_malicious_consumer(program_id: &Pubkey, accounts: &[AccountInfo], _data: &[u8]) -> ProgramResult {
+    // callback signer, request, state, store, coordinator program
+    let [_callback_signer, request, state, store, coordinator, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if state.owner != program_id {
+        return Err(ProgramError::InvalidArgument);
+    }
+    // Reuse the Store signature, if the callback carries it, to act as the
+    // bridge authority
+    if store.is_signer {
+        let instruction = Instruction {
+            program_id: *coordinator.key,
+            accounts: vec![
+                AccountMeta::new_readonly(*request.key, false),
+                AccountMeta::new_readonly(*store.key, true),
+            ],
+            data: vec![],
+        };
+        invoke(&instruction, &[request.clone(), store.clone(), coordinator.clone()])?;
+        state.try_borrow_mut_data()?[0] = 1;
+    }
+    Ok(())
+}
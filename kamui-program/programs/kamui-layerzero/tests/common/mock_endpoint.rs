@@ -0,0 +1,568 @@
+// -----------------------------------------------------------------------------
+// Offline stand-in for the LayerZero Endpoint program, run as a native program
+// at `oapp::endpoint::ID` inside a `ProgramTest`.
+//
+// It implements the instructions the OApp reaches through `oapp::endpoint_cpi`
+// (`register_oapp`, `send`, `quote`, `clear`, `send_compose`, `clear_compose`)
+// with the account order of the real Endpoint. Two extra instructions stand in
+// for the parties the tests do not run:
+// - `init_nonce` creates the nonce account of a pathway (the OApp delegate);
+// - `verify` records the payload hash of an inbound packet (the DVNs and the
+//   receive library).
+//
+// State is plain borsh rather than the Endpoint's Anchor accounts, and there
+// is no message library: its accounts are passed but ignored. Every packet sent
+// on a pathway is appended to the pathway's nonce account so it can be read
+// back and delivered.
+// -----------------------------------------------------------------------------
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    entrypoint::ProgramResult,
+    hash::hash,
+    instruction::{AccountMeta, Instruction},
+    keccak,
+    program::{invoke_signed, set_return_data},
+    program_error::ProgramError,
+    system_instruction, system_program,
+};
+use oapp::endpoint::{
+    instructions::{
+        ClearComposeParams, ClearParams, QuoteParams, RegisterOAppParams, SendComposeParams,
+        SendParams,
+    },
+    MessagingFee, MessagingReceipt, COMPOSED_MESSAGE_HASH_SEED, ENDPOINT_SEED, EVENT_SEED,
+    ID as ENDPOINT_ID, NONCE_SEED, OAPP_SEED, PAYLOAD_HASH_SEED,
+};
+use solana_sdk::account::Account;
+
+/// Native fee returned by `quote`
+pub const QUOTE_NATIVE_FEE: u64 = 1_000;
+
+/// Space of a nonce account, the most a CPI can allocate. Enough for a dozen
+/// VRF messages.
+pub const NONCE_ACCOUNT_SPACE: usize = 10 * 1024;
+
+/// A packet sent through the Endpoint
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct Packet {
+    pub nonce: u64,
+    pub src_eid: u32,
+    pub sender: Pubkey,
+    pub dst_eid: u32,
+    pub receiver: [u8; 32],
+    pub guid: [u8; 32],
+    pub message: Vec<u8>,
+    pub options: Vec<u8>,
+}
+
+/// `[ENDPOINT_SEED]`
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct Settings {
+    pub eid: u32,
+}
+
+/// `[OAPP_SEED, oapp]`
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct OAppRegistry {
+    pub delegate: Pubkey,
+}
+
+/// `[NONCE_SEED, oapp, remote eid, remote oapp]`, shared by both directions
+/// of a pathway like in the real Endpoint
+#[derive(AnchorSerialize, AnchorDeserialize, Default)]
+pub struct Nonce {
+    pub outbound_nonce: u64,
+    pub inbound_nonce: u64,
+    /// Outbound packets, oldest first
+    pub packets: Vec<Packet>,
+}
+
+/// `[PAYLOAD_HASH_SEED, receiver, src eid, sender, nonce]`. Zeroed once cleared.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct PayloadHash {
+    pub hash: [u8; 32],
+}
+
+/// `[COMPOSED_MESSAGE_HASH_SEED, from, to, guid, index]`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ComposeMessage {
+    pub hash: [u8; 32],
+    pub received: bool,
+    /// Kept so the message can be handed to `lz_compose`
+    pub message: Vec<u8>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct InitNonceParams {
+    pub local_oapp: Pubkey,
+    pub remote_eid: u32,
+    pub remote_oapp: [u8; 32],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct VerifyParams {
+    pub receiver: Pubkey,
+    pub src_eid: u32,
+    pub sender: [u8; 32],
+    pub nonce: u64,
+    pub payload_hash: [u8; 32],
+}
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if data.len() < 8 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (discriminator, args) = data.split_at(8);
+    if discriminator == sighash("register_oapp") {
+        register_oapp(program_id, accounts, parse(args)?)
+    } else if discriminator == sighash("send") {
+        send(program_id, accounts, parse(args)?)
+    } else if discriminator == sighash("quote") {
+        quote(parse(args)?)
+    } else if discriminator == sighash("clear") {
+        clear(program_id, accounts, parse(args)?)
+    } else if discriminator == sighash("send_compose") {
+        send_compose(program_id, accounts, parse(args)?)
+    } else if discriminator == sighash("clear_compose") {
+        clear_compose(program_id, accounts, parse(args)?)
+    } else if discriminator == sighash("init_nonce") {
+        init_nonce(program_id, accounts, parse(args)?)
+    } else if discriminator == sighash("verify") {
+        verify(program_id, accounts, parse(args)?)
+    } else {
+        Err(ProgramError::InvalidInstructionData)
+    }
+}
+
+// accounts: payer, oapp, oapp_registry, system_program
+fn register_oapp(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    params: RegisterOAppParams,
+) -> ProgramResult {
+    let [payer, oapp, registry, system, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if !oapp.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let registry_value = OAppRegistry { delegate: params.delegate };
+    create_pda(
+        program_id,
+        payer,
+        registry,
+        system,
+        &[OAPP_SEED, oapp.key.as_ref()],
+        registry_value.try_to_vec()?.len(),
+    )?;
+    write(registry, &registry_value)
+}
+
+// accounts: sender, send_library_program, send_library_config,
+// default_send_library_config, send_library_info, endpoint, nonce, ...
+fn send(program_id: &Pubkey, accounts: &[AccountInfo], params: SendParams) -> ProgramResult {
+    let [sender, _, _, _, _, settings, nonce_account, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if !sender.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_address(settings, &[ENDPOINT_SEED], program_id)?;
+    check_address(
+        nonce_account,
+        &[NONCE_SEED, sender.key.as_ref(), &params.dst_eid.to_be_bytes(), &params.receiver],
+        program_id,
+    )?;
+    let src_eid = read::<Settings>(settings, program_id)?.eid;
+
+    let mut nonce: Nonce = read(nonce_account, program_id)?;
+    nonce.outbound_nonce += 1;
+    let guid = guid(nonce.outbound_nonce, src_eid, sender.key, params.dst_eid, &params.receiver);
+    nonce.packets.push(Packet {
+        nonce: nonce.outbound_nonce,
+        src_eid,
+        sender: *sender.key,
+        dst_eid: params.dst_eid,
+        receiver: params.receiver,
+        guid,
+        message: params.message,
+        options: params.options,
+    });
+    write(nonce_account, &nonce)?;
+
+    let receipt = MessagingReceipt {
+        guid,
+        nonce: nonce.outbound_nonce,
+        fee: MessagingFee { native_fee: params.native_fee, lz_token_fee: params.lz_token_fee },
+    };
+    set_return_data(&receipt.try_to_vec()?);
+    Ok(())
+}
+
+fn quote(_params: QuoteParams) -> ProgramResult {
+    let fee = MessagingFee { native_fee: QUOTE_NATIVE_FEE, lz_token_fee: 0 };
+    set_return_data(&fee.try_to_vec()?);
+    Ok(())
+}
+
+// accounts: signer, oapp_registry, nonce, payload_hash, endpoint, ...
+fn clear(program_id: &Pubkey, accounts: &[AccountInfo], params: ClearParams) -> ProgramResult {
+    let [receiver, _, nonce_account, payload_hash_account, settings, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if !receiver.is_signer || *receiver.key != params.receiver {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_address(settings, &[ENDPOINT_SEED], program_id)?;
+    check_address(
+        nonce_account,
+        &[NONCE_SEED, receiver.key.as_ref(), &params.src_eid.to_be_bytes(), &params.sender],
+        program_id,
+    )?;
+    check_address(
+        payload_hash_account,
+        &[
+            PAYLOAD_HASH_SEED,
+            receiver.key.as_ref(),
+            &params.src_eid.to_be_bytes(),
+            &params.sender,
+            &params.nonce.to_be_bytes(),
+        ],
+        program_id,
+    )?;
+
+    let dst_eid = read::<Settings>(settings, program_id)?.eid;
+    let expected_guid = guid(
+        params.nonce,
+        params.src_eid,
+        &Pubkey::new_from_array(params.sender),
+        dst_eid,
+        &receiver.key.to_bytes(),
+    );
+    let mut payload_hash: PayloadHash = read(payload_hash_account, program_id)?;
+    if params.guid != expected_guid || payload_hash.hash != payload_hash_of(&params.guid, &params.message) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    payload_hash.hash = [0; 32];
+    write(payload_hash_account, &payload_hash)?;
+
+    let mut nonce: Nonce = read(nonce_account, program_id)?;
+    nonce.inbound_nonce = nonce.inbound_nonce.max(params.nonce);
+    write(nonce_account, &nonce)?;
+
+    set_return_data(&params.guid);
+    Ok(())
+}
+
+// accounts: from, payer, compose_message, system_program, ...
+fn send_compose(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    params: SendComposeParams,
+) -> ProgramResult {
+    let [from, payer, compose_account, system, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if !from.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let compose = ComposeMessage {
+        hash: keccak::hash(&params.message).0,
+        received: false,
+        message: params.message,
+    };
+    create_pda(
+        program_id,
+        payer,
+        compose_account,
+        system,
+        &[
+            COMPOSED_MESSAGE_HASH_SEED,
+            from.key.as_ref(),
+            params.to.as_ref(),
+            &params.guid,
+            &params.index.to_be_bytes(),
+        ],
+        compose.try_to_vec()?.len(),
+    )?;
+    write(compose_account, &compose)
+}
+
+// accounts: to, compose_message, ...
+fn clear_compose(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    params: ClearComposeParams,
+) -> ProgramResult {
+    let [to, compose_account, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if !to.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_address(
+        compose_account,
+        &[
+            COMPOSED_MESSAGE_HASH_SEED,
+            params.from.as_ref(),
+            to.key.as_ref(),
+            &params.guid,
+            &params.index.to_be_bytes(),
+        ],
+        program_id,
+    )?;
+    let mut compose: ComposeMessage = read(compose_account, program_id)?;
+    if compose.received || compose.hash != keccak::hash(&params.message).0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    compose.received = true;
+    write(compose_account, &compose)
+}
+
+// accounts: payer, nonce, system_program
+fn init_nonce(program_id: &Pubkey, accounts: &[AccountInfo], params: InitNonceParams) -> ProgramResult {
+    let [payer, nonce_account, system, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    create_pda(
+        program_id,
+        payer,
+        nonce_account,
+        system,
+        &[
+            NONCE_SEED,
+            params.local_oapp.as_ref(),
+            &params.remote_eid.to_be_bytes(),
+            &params.remote_oapp,
+        ],
+        NONCE_ACCOUNT_SPACE,
+    )?;
+    write(nonce_account, &Nonce::default())
+}
+
+// accounts: payer, payload_hash, system_program
+fn verify(program_id: &Pubkey, accounts: &[AccountInfo], params: VerifyParams) -> ProgramResult {
+    let [payer, payload_hash_account, system, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let seeds: &[&[u8]] = &[
+        PAYLOAD_HASH_SEED,
+        params.receiver.as_ref(),
+        &params.src_eid.to_be_bytes(),
+        &params.sender,
+        &params.nonce.to_be_bytes(),
+    ];
+    let payload_hash = PayloadHash { hash: params.payload_hash };
+    // Like the real Endpoint, a packet can be verified again until it is cleared
+    if payload_hash_account.owner == program_id {
+        check_address(payload_hash_account, seeds, program_id)?;
+        if read::<PayloadHash>(payload_hash_account, program_id)?.hash == [0; 32] {
+            return Err(ProgramError::InvalidArgument);
+        }
+    } else {
+        create_pda(program_id, payer, payload_hash_account, system, seeds, payload_hash.try_to_vec()?.len())?;
+    }
+    write(payload_hash_account, &payload_hash)
+}
+
+fn sighash(name: &str) -> [u8; 8] {
+    hash(format!("global:{}", name).as_bytes()).to_bytes()[..8].try_into().unwrap()
+}
+
+fn parse<T: AnchorDeserialize>(mut args: &[u8]) -> std::result::Result<T, ProgramError> {
+    T::deserialize(&mut args).map_err(|_| ProgramError::InvalidInstructionData)
+}
+
+fn check_address(account: &AccountInfo, seeds: &[&[u8]], program_id: &Pubkey) -> ProgramResult {
+    if *account.key != Pubkey::find_program_address(seeds, program_id).0 {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    Ok(())
+}
+
+fn read<T: AnchorDeserialize>(account: &AccountInfo, program_id: &Pubkey) -> std::result::Result<T, ProgramError> {
+    if account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    T::deserialize(&mut &account.try_borrow_data()?[..]).map_err(|_| ProgramError::InvalidAccountData)
+}
+
+fn write<T: AnchorSerialize>(account: &AccountInfo, value: &T) -> ProgramResult {
+    let mut data = account.try_borrow_mut_data()?;
+    value
+        .serialize(&mut &mut data[..])
+        .map_err(|_| ProgramError::AccountDataTooSmall)
+}
+
+fn create_pda<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system: &AccountInfo<'a>,
+    seeds: &[&[u8]],
+    space: usize,
+) -> ProgramResult {
+    let (address, bump) = Pubkey::find_program_address(seeds, program_id);
+    if address != *account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let bump = [bump];
+    let mut signer_seeds = seeds.to_vec();
+    signer_seeds.push(&bump);
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            account.key,
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            program_id,
+        ),
+        &[payer.clone(), account.clone(), system.clone()],
+        &[&signer_seeds],
+    )
+}
+
+// -----------------------------------------------------------------------------
+// Client side
+// -----------------------------------------------------------------------------
+
+/// The GUID the Endpoint assigns to a packet
+pub fn guid(nonce: u64, src_eid: u32, sender: &Pubkey, dst_eid: u32, receiver: &[u8; 32]) -> [u8; 32] {
+    keccak::hashv(&[
+        &nonce.to_be_bytes(),
+        &src_eid.to_be_bytes(),
+        sender.as_ref(),
+        &dst_eid.to_be_bytes(),
+        receiver,
+    ])
+    .0
+}
+
+pub fn payload_hash_of(guid: &[u8; 32], message: &[u8]) -> [u8; 32] {
+    keccak::hashv(&[guid, message]).0
+}
+
+pub fn settings_address() -> Pubkey {
+    Pubkey::find_program_address(&[ENDPOINT_SEED], &ENDPOINT_ID).0
+}
+
+pub fn registry_address(oapp: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[OAPP_SEED, oapp.as_ref()], &ENDPOINT_ID).0
+}
+
+pub fn event_authority_address() -> Pubkey {
+    Pubkey::find_program_address(&[EVENT_SEED], &ENDPOINT_ID).0
+}
+
+pub fn nonce_address(local_oapp: &Pubkey, remote_eid: u32, remote_oapp: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(
+        &[NONCE_SEED, local_oapp.as_ref(), &remote_eid.to_be_bytes(), remote_oapp],
+        &ENDPOINT_ID,
+    )
+    .0
+}
+
+pub fn payload_hash_address(receiver: &Pubkey, src_eid: u32, sender: &[u8; 32], nonce: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[PAYLOAD_HASH_SEED, receiver.as_ref(), &src_eid.to_be_bytes(), sender, &nonce.to_be_bytes()],
+        &ENDPOINT_ID,
+    )
+    .0
+}
+
+pub fn compose_message_address(from: &Pubkey, to: &Pubkey, guid: &[u8; 32], index: u16) -> Pubkey {
+    Pubkey::find_program_address(
+        &[COMPOSED_MESSAGE_HASH_SEED, from.as_ref(), to.as_ref(), guid, &index.to_be_bytes()],
+        &ENDPOINT_ID,
+    )
+    .0
+}
+
+/// Genesis account for the Endpoint settings of a chain with endpoint ID `eid`
+pub fn settings_account(eid: u32) -> Account {
+    let data = Settings { eid }.try_to_vec().unwrap();
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: ENDPOINT_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Accounts for `register_oapp`, passed to `init_store`
+pub fn register_oapp_accounts(payer: &Pubkey, oapp: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(ENDPOINT_ID, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(*oapp, false),
+        AccountMeta::new(registry_address(oapp), false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(event_authority_address(), false),
+        AccountMeta::new_readonly(ENDPOINT_ID, false),
+    ]
+}
+
+/// Accounts for `send` from `sender` to `receiver` on `dst_eid`. The message
+/// library accounts are placeholders.
+pub fn send_accounts(sender: &Pubkey, dst_eid: u32, receiver: &[u8; 32]) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(ENDPOINT_ID, false),
+        AccountMeta::new(*sender, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(settings_address(), false),
+        AccountMeta::new(nonce_address(sender, dst_eid, receiver), false),
+        AccountMeta::new_readonly(event_authority_address(), false),
+        AccountMeta::new_readonly(ENDPOINT_ID, false),
+    ]
+}
+
+pub fn init_nonce_instruction(payer: &Pubkey, params: InitNonceParams) -> Instruction {
+    Instruction {
+        program_id: ENDPOINT_ID,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(nonce_address(&params.local_oapp, params.remote_eid, &params.remote_oapp), false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: [&sighash("init_nonce")[..], &params.try_to_vec().unwrap()].concat(),
+    }
+}
+
+/// Marks `packet` as verified so its receiver can clear it
+pub fn verify_instruction(payer: &Pubkey, packet: &Packet) -> Instruction {
+    let receiver = Pubkey::new_from_array(packet.receiver);
+    let sender = packet.sender.to_bytes();
+    let params = VerifyParams {
+        receiver,
+        src_eid: packet.src_eid,
+        sender,
+        nonce: packet.nonce,
+        payload_hash: payload_hash_of(&packet.guid, &packet.message),
+    };
+    Instruction {
+        program_id: ENDPOINT_ID,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(payload_hash_address(&receiver, packet.src_eid, &sender, packet.nonce), false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: [&sighash("verify")[..], &params.try_to_vec().unwrap()].concat(),
+    }
+}
+
+pub fn decode_nonce(data: &[u8]) -> Nonce {
+    Nonce::deserialize(&mut &data[..]).unwrap()
+}
+
+pub fn decode_compose_message(data: &[u8]) -> ComposeMessage {
+    ComposeMessage::deserialize(&mut &data[..]).unwrap()
+}
@@ -0,0 +1,6 @@
+// Shared by the integration tests; each test binary uses a different subset.
+#![allow(dead_code)]
+
+pub mod harness;
+pub mod mock_endpoint;
+pub mod oracle;
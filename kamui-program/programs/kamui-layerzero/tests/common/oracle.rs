@@ -0,0 +1,69 @@
+// -----------------------------------------------------------------------------
+// ECVRF prover for tests, producing proofs in the format `kamui_layerzero::ecvrf`
+// verifies. Not constant time; test keys only.
+// -----------------------------------------------------------------------------
+
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT,
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+};
+use kamui_layerzero::ecvrf;
+use sha2::{Digest, Sha512};
+use solana_zk_token_sdk::curve25519::ristretto::PodRistrettoPoint;
+
+pub struct VrfKeypair {
+    secret: Scalar,
+    pub public_key: [u8; 32],
+}
+
+impl VrfKeypair {
+    pub fn from_seed(seed: &[u8]) -> Self {
+        let secret = Scalar::from_bytes_mod_order_wide(&Sha512::digest(seed).into());
+        let public_key = (secret * RISTRETTO_BASEPOINT_POINT).compress().to_bytes();
+        Self { secret, public_key }
+    }
+
+    /// An 80-byte proof for `alpha`: Gamma || c || s
+    pub fn prove(&self, alpha: &[u8]) -> Vec<u8> {
+        let h_bytes = ecvrf::encode_to_curve(alpha);
+        let h = decompress(&h_bytes.0);
+        let gamma = self.secret * h;
+
+        let nonce = Scalar::from_bytes_mod_order_wide(
+            &Sha512::new()
+                .chain_update(self.secret.as_bytes())
+                .chain_update(h_bytes.0)
+                .finalize()
+                .into(),
+        );
+        let u = nonce * RISTRETTO_BASEPOINT_POINT;
+        let v = nonce * h;
+
+        let c = ecvrf::challenge([
+            &PodRistrettoPoint(self.public_key),
+            &h_bytes,
+            &pod(&gamma),
+            &pod(&u),
+            &pod(&v),
+        ]);
+        let mut c_bytes = [0u8; 32];
+        c_bytes[..c.len()].copy_from_slice(&c);
+        let s = nonce + Scalar::from_bytes_mod_order(c_bytes) * self.secret;
+
+        [&gamma.compress().to_bytes()[..], &c, s.as_bytes()].concat()
+    }
+
+    /// The VRF output of a proof from [`Self::prove`]
+    pub fn output(proof: &[u8]) -> [u8; 64] {
+        ecvrf::proof_to_hash(&PodRistrettoPoint(proof[..32].try_into().unwrap()))
+    }
+}
+
+fn decompress(bytes: &[u8; 32]) -> RistrettoPoint {
+    CompressedRistretto(*bytes).decompress().expect("valid point")
+}
+
+fn pod(point: &RistrettoPoint) -> PodRistrettoPoint {
+    PodRistrettoPoint(point.compress().to_bytes())
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use common::harness::{assert_custom_error, Harness, EID};
+use kamui_layerzero::{
+    errors::LayerZeroError,
+    state::{
+        PeerConfig, PeerConfigParam, ProcessedGuid, RateLimitParams, VrfRequest,
+        ENFORCED_OPTIONS_SEND_AND_CALL_MAX_LEN, ENFORCED_OPTIONS_SEND_MAX_LEN,
+    },
+};
+
+const SEED: [u8; 32] = [9; 32];
+
+#[tokio::test]
+async fn test_processed_guid_is_recorded() {
+    let mut harness = Harness::start().await;
+
+    let request_id = harness.request_vrf(SEED, None).await.unwrap();
+    let packet = harness.sent_packets().await.remove(0);
+    harness.deliver(&packet).await.unwrap();
+
+    let record: ProcessedGuid =
+        harness.account(harness.processed_guid_address(EID, &request_id)).await.unwrap();
+    assert_eq!(record.guid, request_id);
+    assert_eq!(record.src_eid, EID);
+    assert_eq!(record.nonce, packet.nonce);
+
+    // The record refuses the message before the Endpoint is asked
+    assert_custom_error(harness.lz_receive(&packet).await, LayerZeroError::MessageAlreadyProcessed);
+}
+
+#[tokio::test]
+async fn test_inbound_rate_limit() {
+    let mut harness = Harness::start().await;
+    harness
+        .set_peer_config(PeerConfigParam::InboundRateLimit(Some(RateLimitParams {
+            window: 3_600,
+            max_messages: 10,
+            max_words: 1,
+        })))
+        .await
+        .unwrap();
+
+    harness.request_vrf(SEED, None).await.unwrap();
+    let second = harness.request_vrf(SEED, None).await.unwrap();
+    let packets = harness.sent_packets().await;
+
+    // Each request asks for one word, the budget for the window
+    harness.deliver(&packets[0]).await.unwrap();
+    assert_custom_error(harness.deliver(&packets[1]).await, LayerZeroError::RateLimitExceeded);
+    assert!(harness.account::<VrfRequest>(harness.inbound_request_address(&second)).await.is_none());
+
+    // Lifting the limit lets the message through
+    harness.set_peer_config(PeerConfigParam::InboundRateLimit(None)).await.unwrap();
+    harness.lz_receive(&packets[1]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_outbound_rate_limit() {
+    let mut harness = Harness::start().await;
+    harness
+        .set_peer_config(PeerConfigParam::OutboundRateLimit(Some(RateLimitParams {
+            window: 3_600,
+            max_messages: 1,
+            max_words: 10,
+        })))
+        .await
+        .unwrap();
+
+    harness.request_vrf(SEED, None).await.unwrap();
+    assert_custom_error(harness.request_vrf(SEED, None).await.map(|_| ()), LayerZeroError::RateLimitExceeded);
+
+    let peer: PeerConfig = harness.account(harness.peer()).await.unwrap();
+    let limit = peer.outbound_rate_limit.unwrap();
+    assert_eq!((limit.messages, limit.words), (1, 1));
+
+    // A window must have a length
+    assert_custom_error(
+        harness
+            .set_peer_config(PeerConfigParam::OutboundRateLimit(Some(RateLimitParams {
+                window: 0,
+                max_messages: 1,
+                max_words: 1,
+            })))
+            .await,
+        LayerZeroError::InvalidRateLimit,
+    );
+}
+
+#[tokio::test]
+async fn test_paused_peer() {
+    let mut harness = Harness::start().await;
+    harness.request_vrf(SEED, None).await.unwrap();
+    let packet = harness.sent_packets().await.remove(0);
+
+    harness.set_peer_config(PeerConfigParam::Paused(true)).await.unwrap();
+    assert_custom_error(harness.request_vrf(SEED, None).await.map(|_| ()), LayerZeroError::PeerPaused);
+    assert_custom_error(harness.deliver(&packet).await, LayerZeroError::PeerPaused);
+
+    harness.set_peer_config(PeerConfigParam::Paused(false)).await.unwrap();
+    harness.lz_receive(&packet).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_resize_legacy_peer_config() {
+    let mut harness = Harness::start().await;
+
+    // Peers used to be allocated with the in-memory size of PeerConfig
+    let peer = harness.peer();
+    let mut account = harness.raw_account(peer).await.unwrap();
+    account.data.truncate(8 + std::mem::size_of::<PeerConfig>());
+    harness.context.set_account(&peer, &account.into());
+
+    let options = |len: usize| {
+        let mut options = vec![0; len];
+        options[1] = 3;
+        options
+    };
+    let enforced_options = PeerConfigParam::EnforcedOptions {
+        send: options(ENFORCED_OPTIONS_SEND_MAX_LEN),
+        send_and_call: options(ENFORCED_OPTIONS_SEND_AND_CALL_MAX_LEN),
+    };
+    // Anchor refuses to reuse a peer of another size until it is resized
+    assert!(harness.set_peer_config(enforced_options.clone()).await.is_err());
+
+    harness.resize_peer_config().await.unwrap();
+    assert_eq!(harness.raw_account(peer).await.unwrap().data.len(), PeerConfig::SIZE);
+
+    // The existing settings survive and the longest options now fit
+    harness.set_peer_config(enforced_options).await.unwrap();
+    let config: PeerConfig = harness.account(peer).await.unwrap();
+    assert_eq!(config.peer_address, harness.store.to_bytes());
+    assert_eq!(config.enforced_options.send_and_call.len(), ENFORCED_OPTIONS_SEND_AND_CALL_MAX_LEN);
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use common::harness::{assert_custom_error, Harness};
+use kamui_layerzero::{
+    constants::VRF_REQUEST_EXPIRY_SECONDS,
+    errors::LayerZeroError,
+    msg_codec::MessageCodec,
+    state::{OutboundVrfRequest, VrfFailureReason, VrfRequest, VrfRequestStatus},
+};
+
+const SEED: [u8; 32] = [5; 32];
+
+#[tokio::test]
+async fn test_expired_request_sends_failure() {
+    let mut harness = Harness::start().await;
+
+    let request_id = harness.request_vrf(SEED, None).await.unwrap();
+    let packet = harness.sent_packets().await.remove(0);
+    harness.deliver(&packet).await.unwrap();
+
+    let inbound: VrfRequest =
+        harness.account(harness.inbound_request_address(&request_id)).await.unwrap();
+    assert_eq!(inbound.deadline, inbound.created_at + VRF_REQUEST_EXPIRY_SECONDS);
+    let outbound: OutboundVrfRequest =
+        harness.account(harness.outbound_request_address(&request_id)).await.unwrap();
+    assert_eq!(outbound.deadline, outbound.created_at + VRF_REQUEST_EXPIRY_SECONDS);
+
+    // The oracle still has time
+    assert_custom_error(
+        harness.expire_cross_chain_request(request_id).await,
+        LayerZeroError::RequestNotExpired,
+    );
+
+    // Past the deadline the oracle is refused and anyone can expire the request
+    harness.advance_clock(VRF_REQUEST_EXPIRY_SECONDS + 1).await;
+    let proof = harness.vrf_keypair.prove(&inbound.alpha());
+    assert_custom_error(
+        harness.fulfill_vrf(request_id, proof.clone()).await,
+        LayerZeroError::RequestExpired,
+    );
+    harness.expire_cross_chain_request(request_id).await.unwrap();
+
+    let inbound: VrfRequest =
+        harness.account(harness.inbound_request_address(&request_id)).await.unwrap();
+    assert_eq!(inbound.status, VrfRequestStatus::Expired);
+    assert_custom_error(
+        harness.expire_cross_chain_request(request_id).await,
+        LayerZeroError::RequestAlreadyFulfilled,
+    );
+
+    let packets = harness.sent_packets().await;
+    assert_eq!(packets.len(), 2);
+    let failure = MessageCodec::try_decode_vrf_failure(&packets[1].message).unwrap();
+    assert_eq!(failure.request_id, request_id);
+    assert_eq!(failure.reason, VrfFailureReason::Expired);
+
+    // The failure releases the request on the source side
+    harness.deliver(&packets[1]).await.unwrap();
+    let outbound: OutboundVrfRequest =
+        harness.account(harness.outbound_request_address(&request_id)).await.unwrap();
+    assert_eq!(outbound.status, VrfRequestStatus::Expired);
+    assert_eq!(outbound.randomness, None);
+}
+
+#[tokio::test]
+async fn test_fulfilled_request_cannot_expire() {
+    let mut harness = Harness::start().await;
+
+    let request_id = harness.request_vrf(SEED, None).await.unwrap();
+    let packet = harness.sent_packets().await.remove(0);
+    harness.deliver(&packet).await.unwrap();
+
+    let inbound: VrfRequest =
+        harness.account(harness.inbound_request_address(&request_id)).await.unwrap();
+    harness
+        .fulfill_vrf(request_id, harness.vrf_keypair.prove(&inbound.alpha()))
+        .await
+        .unwrap();
+
+    harness.advance_clock(VRF_REQUEST_EXPIRY_SECONDS + 1).await;
+    assert_custom_error(
+        harness.expire_cross_chain_request(request_id).await,
+        LayerZeroError::RequestAlreadyFulfilled,
+    );
+    assert_eq!(harness.sent_packets().await.len(), 2);
+}
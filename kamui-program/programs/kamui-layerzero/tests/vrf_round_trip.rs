@@ -0,0 +1,183 @@
+mod common;
+
+use common::harness::{assert_custom_error, Harness, CONSUMER_STATE_LEN, EID};
+use common::oracle::VrfKeypair;
+use kamui_layerzero::{
+    constants::VRF_COMPOSE_INDEX,
+    errors::LayerZeroError,
+    msg_codec::MessageCodec,
+    state::{CallbackAccount, ConsumerCallback, OutboundVrfRequest, VrfRequest, VrfRequestStatus},
+};
+
+const SEED: [u8; 32] = [7; 32];
+
+fn consumer(harness: &Harness) -> ConsumerCallback {
+    ConsumerCallback {
+        program_id: harness.consumer_program,
+        accounts: vec![CallbackAccount { pubkey: harness.consumer_state, is_writable: true }],
+    }
+}
+
+#[tokio::test]
+async fn test_vrf_round_trip() {
+    let mut harness = Harness::start().await;
+
+    // Request: the outbound record is created under the GUID of the message
+    let consumer = consumer(&harness);
+    let request_id = harness.request_vrf(SEED, Some(consumer)).await.unwrap();
+    let outbound: OutboundVrfRequest =
+        harness.account(harness.outbound_request_address(&request_id)).await.unwrap();
+    assert_eq!(outbound.status, VrfRequestStatus::Pending);
+    assert_eq!(outbound.dst_eid, EID);
+
+    let packets = harness.sent_packets().await;
+    assert_eq!(packets.len(), 1);
+    assert_eq!(packets[0].guid, request_id);
+    assert!(MessageCodec::try_decode_vrf_request(&packets[0].message).is_some());
+
+    // Delivery records the request and forwards it to the coordinator
+    harness.deliver(&packets[0]).await.unwrap();
+    let inbound: VrfRequest =
+        harness.account(harness.inbound_request_address(&request_id)).await.unwrap();
+    assert_eq!(inbound.status, VrfRequestStatus::Pending);
+    assert_eq!(inbound.src_eid, EID);
+    assert_eq!(inbound.seed, SEED);
+
+    // Fulfillment
+    let proof = harness.vrf_keypair.prove(&inbound.alpha());
+    harness.fulfill_vrf(request_id, proof.clone()).await.unwrap();
+    let inbound: VrfRequest =
+        harness.account(harness.inbound_request_address(&request_id)).await.unwrap();
+    assert_eq!(inbound.status, VrfRequestStatus::Delivered);
+
+    let packets = harness.sent_packets().await;
+    assert_eq!(packets.len(), 2);
+    let fulfillment = MessageCodec::try_decode_vrf_fulfillment(&packets[1].message).unwrap();
+    assert_eq!(fulfillment.request_id, request_id);
+    assert_eq!(fulfillment.beta, VrfKeypair::output(&proof));
+
+    // Delivering the fulfillment verifies the proof and composes to the consumer
+    harness.deliver(&packets[1]).await.unwrap();
+    let outbound: OutboundVrfRequest =
+        harness.account(harness.outbound_request_address(&request_id)).await.unwrap();
+    assert_eq!(outbound.status, VrfRequestStatus::Fulfilled);
+    assert_eq!(outbound.randomness, Some(fulfillment.beta));
+
+    let fulfillment_guid = packets[1].guid;
+    let compose = harness.compose_message(&fulfillment_guid, VRF_COMPOSE_INDEX).await.unwrap();
+    assert!(!compose.received);
+
+    // lz_compose hands the randomness to the consumer
+    harness.deliver_compose(fulfillment_guid, VRF_COMPOSE_INDEX).await.unwrap();
+    let outbound: OutboundVrfRequest =
+        harness.account(harness.outbound_request_address(&request_id)).await.unwrap();
+    assert_eq!(outbound.status, VrfRequestStatus::Delivered);
+    assert!(harness.compose_message(&fulfillment_guid, VRF_COMPOSE_INDEX).await.unwrap().received);
+
+    let state = harness.raw_account(harness.consumer_state).await.unwrap();
+    assert_eq!(state.data.len(), CONSUMER_STATE_LEN);
+    assert_eq!(state.data[..32], request_id);
+    assert_eq!(state.data[32..], fulfillment.beta);
+
+    // Neither message can be executed twice
+    assert!(harness.lz_receive(&packets[1]).await.is_err());
+    assert!(harness.deliver_compose(fulfillment_guid, VRF_COMPOSE_INDEX).await.is_err());
+}
+
+#[tokio::test]
+async fn test_replayed_request_is_rejected() {
+    let mut harness = Harness::start().await;
+
+    let request_id = harness.request_vrf(SEED, None).await.unwrap();
+    let packet = harness.sent_packets().await.remove(0);
+    harness.deliver(&packet).await.unwrap();
+
+    // The payload hash was cleared by the first delivery
+    assert!(harness.lz_receive(&packet).await.is_err());
+    let inbound: VrfRequest =
+        harness.account(harness.inbound_request_address(&request_id)).await.unwrap();
+    assert_eq!(inbound.status, VrfRequestStatus::Pending);
+}
+
+#[tokio::test]
+async fn test_fulfillment_requires_valid_proof() {
+    let mut harness = Harness::start().await;
+
+    let request_id = harness.request_vrf(SEED, None).await.unwrap();
+    let packet = harness.sent_packets().await.remove(0);
+    harness.deliver(&packet).await.unwrap();
+
+    // A proof for another input is refused before anything is sent
+    let wrong_proof = harness.vrf_keypair.prove(b"another input");
+    assert_custom_error(
+        harness.fulfill_vrf(request_id, wrong_proof).await,
+        LayerZeroError::InvalidVrfProof,
+    );
+    assert_eq!(harness.sent_packets().await.len(), 1);
+
+    // A valid proof is relayed, and the fulfillment is checked again on receipt
+    let inbound: VrfRequest =
+        harness.account(harness.inbound_request_address(&request_id)).await.unwrap();
+    harness
+        .fulfill_vrf(request_id, harness.vrf_keypair.prove(&inbound.alpha()))
+        .await
+        .unwrap();
+    let mut fulfillment = harness.sent_packets().await.remove(1);
+
+    // Tampering with the output in flight is caught by lz_receive
+    let genuine_message = fulfillment.message.clone();
+    let last = fulfillment.message.len() - 1;
+    fulfillment.message[last] ^= 1;
+    assert_custom_error(harness.deliver(&fulfillment).await, LayerZeroError::InvalidVrfProof);
+
+    let outbound: OutboundVrfRequest =
+        harness.account(harness.outbound_request_address(&request_id)).await.unwrap();
+    assert_eq!(outbound.status, VrfRequestStatus::Pending);
+    assert_eq!(outbound.randomness, None);
+
+    // The genuine message still goes through. Without a consumer the
+    // randomness is only recorded.
+    fulfillment.message = genuine_message;
+    harness.deliver(&fulfillment).await.unwrap();
+    let outbound: OutboundVrfRequest =
+        harness.account(harness.outbound_request_address(&request_id)).await.unwrap();
+    assert_eq!(outbound.status, VrfRequestStatus::Fulfilled);
+    assert!(outbound.randomness.is_some());
+    assert!(harness.compose_message(&fulfillment.guid, VRF_COMPOSE_INDEX).await.is_none());
+}
+
+#[tokio::test]
+async fn test_consumer_cannot_sign_as_store() {
+    let mut harness = Harness::start().await;
+
+    // The requester picks the consumer and its accounts freely
+    let consumer = ConsumerCallback {
+        program_id: harness.malicious_consumer_program,
+        accounts: vec![
+            CallbackAccount { pubkey: harness.malicious_consumer_state, is_writable: true },
+            CallbackAccount { pubkey: harness.store, is_writable: false },
+            CallbackAccount { pubkey: harness.coordinator_program, is_writable: false },
+        ],
+    };
+    let request_id = harness.request_vrf(SEED, Some(consumer)).await.unwrap();
+    let packet = harness.sent_packets().await.remove(0);
+    harness.deliver(&packet).await.unwrap();
+
+    let inbound: VrfRequest =
+        harness.account(harness.inbound_request_address(&request_id)).await.unwrap();
+    harness
+        .fulfill_vrf(request_id, harness.vrf_keypair.prove(&inbound.alpha()))
+        .await
+        .unwrap();
+    let fulfillment = harness.sent_packets().await.remove(1);
+    harness.deliver(&fulfillment).await.unwrap();
+    harness.deliver_compose(fulfillment.guid, VRF_COMPOSE_INDEX).await.unwrap();
+
+    // The callback is signed by the callback PDA only, so the Store signature
+    // never reached the consumer
+    let state = harness.raw_account(harness.malicious_consumer_state).await.unwrap();
+    assert_eq!(state.data, vec![0]);
+    let outbound: OutboundVrfRequest =
+        harness.account(harness.outbound_request_address(&request_id)).await.unwrap();
+    assert_eq!(outbound.status, VrfRequestStatus::Delivered);
+}
@@ -0,0 +1,172 @@
+use anchor_lang::prelude::*;
+use sha2::{Digest, Sha512};
+use crate::ecvrf::{self, OUTPUT_LENGTH, PROOF_LENGTH};
+
+// -----------------------------------------------------------------------------
+// Chained public randomness beacon, produced by `mangekyou::kamui_vrf::beacon`.
+//
+// Rounds are numbered from 1. Round r is the VRF output of the oracle key on
+//
+// 0         8                                    72
+// |---------|------------------------------------|
+// | r (BE)  |   output of round r - 1 (genesis)  |
+// |---------|------------------------------------|
+//
+// so each round commits to the whole chain before it.
+//
+// The sk and the genesis value determine the whole chain, so the genesis is not
+// chosen by the oracle: the program derives it from the latest entry of the
+// SlotHashes sysvar when the beacon is created and records that slot and hash
+// next to it.
+// -----------------------------------------------------------------------------
+
+pub const BEACON_ALPHA_LENGTH: usize = 8 + OUTPUT_LENGTH;
+
+const GENESIS_DOMAIN: &[u8] = b"kamui-beacon-genesis";
+
+/// The VRF input of `round`, chained to the output of the round before it.
+pub fn beacon_alpha(round: u64, previous: &[u8; OUTPUT_LENGTH]) -> [u8; BEACON_ALPHA_LENGTH] {
+    let mut alpha = [0u8; BEACON_ALPHA_LENGTH];
+    alpha[..8].copy_from_slice(&round.to_be_bytes());
+    alpha[8..].copy_from_slice(previous);
+    alpha
+}
+
+/// The genesis value of the beacon account `beacon`, from the hash of `slot`.
+pub fn beacon_genesis(beacon: &Pubkey, slot: u64, slot_hash: &[u8; 32]) -> [u8; OUTPUT_LENGTH] {
+    Sha512::new()
+        .chain_update(GENESIS_DOMAIN)
+        .chain_update(beacon.as_ref())
+        .chain_update(slot.to_be_bytes())
+        .chain_update(slot_hash)
+        .finalize()
+        .into()
+}
+
+/// The most recent `(slot, hash)` in the raw SlotHashes sysvar data: a u64
+/// count followed by the entries, newest first.
+pub fn latest_slot_hash(slot_hashes: &[u8]) -> Option<(u64, [u8; 32])> {
+    let count = u64::from_le_bytes(slot_hashes.get(..8)?.try_into().ok()?);
+    if count == 0 {
+        return None;
+    }
+    let slot = u64::from_le_bytes(slot_hashes.get(8..16)?.try_into().ok()?);
+    let hash = slot_hashes.get(16..48)?.try_into().ok()?;
+    Some((slot, hash))
+}
+
+/// Verify the link from `previous` to `round` and return the round's output.
+pub fn verify_round(
+    vrf_key: &[u8; 32],
+    round: u64,
+    previous: &[u8; OUTPUT_LENGTH],
+    proof: &[u8; PROOF_LENGTH],
+) -> Result<[u8; OUTPUT_LENGTH]> {
+    ecvrf::verify(vrf_key, &beacon_alpha(round, previous), proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{Beacon, BeaconRound, BEACON_HISTORY};
+
+    fn from_hex<const N: usize>(hex_str: &str) -> [u8; N] {
+        hex::decode(hex_str).unwrap().try_into().unwrap()
+    }
+
+    // Produced by `mangekyou::kamui_vrf::beacon` with a zero genesis value
+    const VRF_KEY: &str = "46dc35b41237542a57d48cb0cf48509ccac0d05f04bacd526fe76d4825d45740";
+    const PROOF_1: &str = "f48871e04e05306ef6dc802237f03e9af41452512527fc2d5e3dee0c3b272d3d6700e4b7c5fdef832385d06aa674ce61ba56f08c6fd0f59a1905e41f7b5ed06253e471c56611f7500a75bcf5e64a9901";
+    const OUTPUT_1: &str = "971fe5574ff96483facb8d7e33ab1394ce3d8078b7a5dee6790c2883732a05326154281f7f49ce8ca44756e716d21afdaeba562207e6259bb8f0a63a33a61163";
+    const PROOF_2: &str = "f87f294558875fb5dc415f61872919444f9d88faf5695214802fb00644ce901ef66d132749207c1216aa8e3a16ceafca0e5b077d83a6b6bba47c151fc16d2fe43a0e4865a9b476f4273433dc71abb107";
+    const OUTPUT_2: &str = "d41de057ac4d1601afb226dfa2d460f29e5de383b830df7467f014d0ad129abb66328cdb678c50bcc75d1cffe4cf116c13df639f28cefb00c6d2ab0273315659";
+    const GENESIS: [u8; 64] = [0u8; 64];
+
+    #[test]
+    fn test_verify_known_rounds() {
+        let vrf_key = from_hex(VRF_KEY);
+        let output_1 = verify_round(&vrf_key, 1, &GENESIS, &from_hex(PROOF_1)).unwrap();
+        assert_eq!(output_1, from_hex::<64>(OUTPUT_1));
+        let output_2 = verify_round(&vrf_key, 2, &output_1, &from_hex(PROOF_2)).unwrap();
+        assert_eq!(output_2, from_hex::<64>(OUTPUT_2));
+    }
+
+    #[test]
+    fn test_verify_rejects_broken_links() {
+        let vrf_key = from_hex(VRF_KEY);
+        let output_1 = from_hex(OUTPUT_1);
+        // Wrong round number, wrong previous output, or skipped round
+        assert!(verify_round(&vrf_key, 2, &GENESIS, &from_hex(PROOF_1)).is_err());
+        assert!(verify_round(&vrf_key, 1, &[1u8; 64], &from_hex(PROOF_1)).is_err());
+        assert!(verify_round(&vrf_key, 2, &GENESIS, &from_hex(PROOF_2)).is_err());
+        assert!(verify_round(&vrf_key, 3, &output_1, &from_hex(PROOF_2)).is_err());
+        assert!(verify_round(&[0u8; 32], 1, &GENESIS, &from_hex(PROOF_1)).is_err());
+    }
+
+    #[test]
+    fn test_genesis_from_slot_hashes() {
+        let mut slot_hashes = 2u64.to_le_bytes().to_vec();
+        for (slot, hash) in [(99u64, [9u8; 32]), (98, [8; 32])] {
+            slot_hashes.extend_from_slice(&slot.to_le_bytes());
+            slot_hashes.extend_from_slice(&hash);
+        }
+        assert_eq!(latest_slot_hash(&slot_hashes), Some((99, [9u8; 32])));
+        assert_eq!(latest_slot_hash(&0u64.to_le_bytes()), None);
+        assert_eq!(latest_slot_hash(&slot_hashes[..40]), None);
+
+        // Bound to the beacon account and the slot hash
+        let beacon = Pubkey::new_unique();
+        let genesis = beacon_genesis(&beacon, 99, &[9u8; 32]);
+        assert_eq!(genesis, beacon_genesis(&beacon, 99, &[9u8; 32]));
+        assert_ne!(genesis, beacon_genesis(&Pubkey::new_unique(), 99, &[9u8; 32]));
+        assert_ne!(genesis, beacon_genesis(&beacon, 98, &[9u8; 32]));
+        assert_ne!(genesis, beacon_genesis(&beacon, 99, &[8u8; 32]));
+    }
+
+    fn beacon() -> Beacon {
+        Beacon {
+            authority: Pubkey::new_unique(),
+            vrf_key: from_hex(VRF_KEY),
+            genesis: GENESIS,
+            genesis_slot: 99,
+            genesis_slot_hash: [0u8; 32],
+            start_slot: 100,
+            slot_interval: 10,
+            latest_round: 0,
+            rounds: Vec::new(),
+        }
+    }
+
+    fn entry(round: u64) -> BeaconRound {
+        BeaconRound {
+            round,
+            slot: round,
+            output: [round as u8; 64],
+            proof: [0u8; 80],
+        }
+    }
+
+    #[test]
+    fn test_ring_buffer() {
+        let mut beacon = beacon();
+        assert_eq!(beacon.latest_output(), GENESIS);
+        assert!(beacon.round(0).is_none());
+        assert!(beacon.round(1).is_none());
+        assert_eq!(beacon.round_slot(1), 100);
+        assert_eq!(beacon.round_slot(4), 130);
+
+        let total = BEACON_HISTORY as u64 + 5;
+        for round in 1..=total {
+            beacon.record(entry(round));
+            assert_eq!(beacon.latest_output(), [round as u8; 64]);
+        }
+        assert_eq!(beacon.latest_round, total);
+        assert_eq!(beacon.rounds.len(), BEACON_HISTORY);
+
+        // Only the latest BEACON_HISTORY rounds are kept
+        assert!(beacon.round(5).is_none());
+        assert_eq!(beacon.round(6), Some(&entry(6)));
+        assert_eq!(beacon.round(total), Some(&entry(total)));
+        assert!(beacon.round(total + 1).is_none());
+    }
+}
@@ -1,4 +1,27 @@
+use anchor_lang::prelude::*;
+use kamui_ecvrf::EcvrfError;
 use rand::RngCore;
+use crate::errors::KamuiVrfError;
+
+// -----------------------------------------------------------------------------
+// On-chain verification of the `sol_vrf` proofs produced by the Kamui oracles.
+// The verifier is `kamui-ecvrf`, shared with the native coordinator and the
+// LayerZero OApps; this only maps its errors onto the program's.
+// -----------------------------------------------------------------------------
+
+pub use kamui_ecvrf::{OUTPUT_LENGTH, PROOF_LENGTH};
+
+/// Verify `proof` for `alpha` under `public_key` and return the VRF output.
+pub fn verify(
+    public_key: &[u8; 32],
+    alpha: &[u8],
+    proof: &[u8; PROOF_LENGTH],
+) -> Result<[u8; OUTPUT_LENGTH]> {
+    kamui_ecvrf::verify(public_key, alpha, proof).map_err(|e| match e {
+        EcvrfError::InvalidPublicKey => KamuiVrfError::InvalidVrfKey.into(),
+        EcvrfError::InvalidProof => KamuiVrfError::ProofVerificationFailed.into(),
+    })
+}
 
 /// Interface for VRF key pair implementations
 pub trait VRFKeyPair {
@@ -95,4 +118,42 @@ impl ECVRFProof {
         
         Some(ECVRFProof { gamma, c, s })
     }
-} 
\ No newline at end of file
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_hex<const N: usize>(hex_str: &str) -> [u8; N] {
+        hex::decode(hex_str).unwrap().try_into().unwrap()
+    }
+
+    // Produced by `ecvrf-cli prove`
+    const PUBLIC_KEY: &str = "7a66a0fe0f2bcdcea5bfb97e3e9f6b298d25899052721bc2b4f3cb570a921b23";
+    const ALPHA: &[u8] = b"Hi Kamui!";
+    const PROOF: &str = "54b58f527e999ceedb24485a7629e3caa9f7deb152852a0f483a6646495fa253c4131e87ff0b48fefacf4b5be04211a77390ca85553aa2c06f0023db34e7b36194eadf11539c0ef1c8dcae09aa35580a";
+    const OUTPUT: &str = "8d9c5b901c05a4edf4dff80bbe970db6ca782fe785ef1375989a3fdb3a93b521f4165ea3a6d1c90ae5641bb528beb98c1eed13d36fb32951ecf163b7900e3da6";
+
+    #[test]
+    fn test_verify_known_proof() {
+        let output = verify(&from_hex(PUBLIC_KEY), ALPHA, &from_hex(PROOF)).unwrap();
+        assert_eq!(output, from_hex::<64>(OUTPUT));
+    }
+
+    #[test]
+    fn test_verify_rejects_tampering() {
+        let (public_key, proof) = (from_hex(PUBLIC_KEY), from_hex(PROOF));
+        assert!(verify(&public_key, b"another input", &proof).is_err());
+
+        for i in [0, 40, 79] {
+            let mut tampered = proof;
+            tampered[i] ^= 1;
+            assert!(verify(&public_key, ALPHA, &tampered).is_err());
+        }
+
+        let mut other_key = public_key;
+        other_key[0] ^= 1;
+        assert!(verify(&other_key, ALPHA, &proof).is_err());
+        assert!(verify(&[0; 32], ALPHA, &proof).is_err());
+    }
+}
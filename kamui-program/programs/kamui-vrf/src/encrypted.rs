@@ -0,0 +1,282 @@
+use anchor_lang::prelude::*;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use sha2::{Digest, Sha512};
+use solana_curve25519::{
+    ristretto::{
+        add_ristretto, multiply_ristretto, subtract_ristretto, validate_ristretto,
+        PodRistrettoPoint,
+    },
+    scalar::PodScalar,
+};
+use kamui_ecvrf::BASEPOINT;
+use crate::errors::KamuiVrfError;
+
+// -----------------------------------------------------------------------------
+// Encrypted VRF outputs, produced by `mangekyou::kamui_vrf::encrypted`.
+//
+// Instead of publishing the VRF point Gamma = x*H(seed), the oracle encrypts
+// it to the requester's key P with ElGamal:
+//
+// 0                    32                   64
+// |--------------------|--------------------|
+// |     C1 = r*B       |  C2 = Gamma + r*P  |
+// |--------------------|--------------------|
+//
+// and proves knowledge of (x, r) with Y = x*B, C1 = r*B and
+// C2 = x*H(seed) + r*P, where Y is its registered VRF key:
+//
+// 0          32         64         96         128        160
+// |----------|----------|----------|----------|----------|
+// |   A_y    |   A_c1   |   A_c2   |   z_x    |   z_r    |
+// |----------|----------|----------|----------|----------|
+//
+// The requester decrypts Gamma off-chain and can later reveal it with a
+// Chaum-Pedersen proof that log_B(P) = log_C1(C2 - Gamma):
+//
+// 0          32         64         96         128
+// |----------|----------|----------|----------|
+// |  Gamma   |   A_g    |   A_u    |    z     |
+// |----------|----------|----------|----------|
+//
+// Both proofs are on merlin transcripts bound to the request id.
+// -----------------------------------------------------------------------------
+
+pub const CIPHERTEXT_LENGTH: usize = 64;
+pub const ENCRYPTION_PROOF_LENGTH: usize = 160;
+pub const REVEAL_LENGTH: usize = 128;
+
+const TRANSCRIPT_LABEL: &[u8] = b"kamui-encrypted-vrf";
+const ENCRYPTION_DOMAIN: &[u8] = b"elgamal-vrf-encryption";
+const DLEQ_DOMAIN: &[u8] = b"chaum-pedersen-dleq";
+
+/// Check that `encryption_key` is a valid ristretto255 point other than the
+/// identity.
+pub fn verify_encryption_key(encryption_key: &[u8; 32]) -> Result<()> {
+    require!(
+        *encryption_key != [0u8; 32] && validate_ristretto(&PodRistrettoPoint(*encryption_key)),
+        KamuiVrfError::InvalidEncryptionKey
+    );
+    Ok(())
+}
+
+/// Check that `ciphertext` encrypts the VRF point of `vrf_key` on `seed` to
+/// `encryption_key` for the request `request_id`.
+pub fn verify_encrypted_output(
+    vrf_key: &[u8; 32],
+    encryption_key: &[u8; 32],
+    seed: &[u8],
+    request_id: &[u8; 32],
+    ciphertext: &[u8; CIPHERTEXT_LENGTH],
+    proof: &[u8; ENCRYPTION_PROOF_LENGTH],
+) -> Result<()> {
+    let y = PodRistrettoPoint(*vrf_key);
+    require!(
+        *vrf_key != [0u8; 32] && validate_ristretto(&y),
+        KamuiVrfError::InvalidVrfKey
+    );
+    verify_encryption_key(encryption_key)?;
+    let p = PodRistrettoPoint(*encryption_key);
+    let h = kamui_ecvrf::encode_to_curve(seed);
+    let c1 = point(&ciphertext[..32]);
+    let c2 = point(&ciphertext[32..]);
+    let a_y = point(&proof[..32]);
+    let a_c1 = point(&proof[32..64]);
+    let a_c2 = point(&proof[64..96]);
+    let z_x = PodScalar(proof[96..128].try_into().unwrap());
+    let z_r = PodScalar(proof[128..].try_into().unwrap());
+
+    let mut transcript = transcript(request_id);
+    transcript.append_message(b"dom-sep", ENCRYPTION_DOMAIN);
+    transcript.append_message(b"y", &y.0);
+    transcript.append_message(b"h", &h.0);
+    transcript.append_message(b"p", &p.0);
+    transcript.append_message(b"c1", &c1.0);
+    transcript.append_message(b"c2", &c2.0);
+    transcript.append_message(b"commitment-y", &a_y.0);
+    transcript.append_message(b"commitment-c1", &a_c1.0);
+    transcript.append_message(b"commitment-c2", &a_c2.0);
+    let c = challenge(&mut transcript);
+
+    // The syscalls reject non-canonical responses and invalid points.
+    let err = KamuiVrfError::InvalidEncryptionProof;
+    require!(
+        multiply(&z_x, &BASEPOINT, err)? == add(&a_y, &multiply(&c, &y, err)?, err)?,
+        KamuiVrfError::InvalidEncryptionProof
+    );
+    require!(
+        multiply(&z_r, &BASEPOINT, err)? == add(&a_c1, &multiply(&c, &c1, err)?, err)?,
+        KamuiVrfError::InvalidEncryptionProof
+    );
+    let lhs = add(&multiply(&z_x, &h, err)?, &multiply(&z_r, &p, err)?, err)?;
+    require!(
+        lhs == add(&a_c2, &multiply(&c, &c2, err)?, err)?,
+        KamuiVrfError::InvalidEncryptionProof
+    );
+
+    Ok(())
+}
+
+/// Check a reveal of `ciphertext` for `request_id` and return the VRF output.
+pub fn verify_reveal(
+    encryption_key: &[u8; 32],
+    request_id: &[u8; 32],
+    ciphertext: &[u8; CIPHERTEXT_LENGTH],
+    reveal: &[u8; REVEAL_LENGTH],
+) -> Result<[u8; 64]> {
+    let err = KamuiVrfError::InvalidReveal;
+    let p = PodRistrettoPoint(*encryption_key);
+    let c1 = point(&ciphertext[..32]);
+    let c2 = point(&ciphertext[32..]);
+    let gamma = point(&reveal[..32]);
+    let a_g = point(&reveal[32..64]);
+    let a_u = point(&reveal[64..96]);
+    let z = PodScalar(reveal[96..].try_into().unwrap());
+    require!(validate_ristretto(&gamma), KamuiVrfError::InvalidReveal);
+    let v = subtract_ristretto(&c2, &gamma).ok_or(err)?;
+
+    let mut transcript = transcript(request_id);
+    transcript.append_message(b"dom-sep", DLEQ_DOMAIN);
+    transcript.append_message(b"g", &BASEPOINT.0);
+    transcript.append_message(b"h", &p.0);
+    transcript.append_message(b"u", &c1.0);
+    transcript.append_message(b"v", &v.0);
+    transcript.append_message(b"commitment-g", &a_g.0);
+    transcript.append_message(b"commitment-u", &a_u.0);
+    let c = challenge(&mut transcript);
+
+    require!(
+        multiply(&z, &BASEPOINT, err)? == add(&a_g, &multiply(&c, &p, err)?, err)?,
+        KamuiVrfError::InvalidReveal
+    );
+    require!(
+        multiply(&z, &c1, err)? == add(&a_u, &multiply(&c, &v, err)?, err)?,
+        KamuiVrfError::InvalidReveal
+    );
+
+    Ok(kamui_ecvrf::proof_to_hash(&gamma))
+}
+
+fn transcript(request_id: &[u8; 32]) -> Transcript {
+    let mut transcript = Transcript::new(TRANSCRIPT_LABEL);
+    transcript.append_message(b"request_id", request_id);
+    transcript
+}
+
+/// The Fiat-Shamir challenge, drawn as in `mangekyou::nizk`.
+fn challenge(transcript: &mut Transcript) -> PodScalar {
+    let mut buffer = [0u8; 64];
+    transcript.challenge_bytes(b"challenge", &mut buffer);
+
+    let wide: [u8; 64] = Sha512::digest(buffer).into();
+    PodScalar(Scalar::from_bytes_mod_order_wide(&wide).to_bytes())
+}
+
+fn point(bytes: &[u8]) -> PodRistrettoPoint {
+    PodRistrettoPoint(bytes.try_into().unwrap())
+}
+
+fn multiply(
+    scalar: &PodScalar,
+    point: &PodRistrettoPoint,
+    err: KamuiVrfError,
+) -> Result<PodRistrettoPoint> {
+    multiply_ristretto(scalar, point).ok_or_else(|| err.into())
+}
+
+fn add(
+    left: &PodRistrettoPoint,
+    right: &PodRistrettoPoint,
+    err: KamuiVrfError,
+) -> Result<PodRistrettoPoint> {
+    add_ristretto(left, right).ok_or_else(|| err.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_hex<const N: usize>(hex_str: &str) -> [u8; N] {
+        hex::decode(hex_str).unwrap().try_into().unwrap()
+    }
+
+    // Produced by `mangekyou::kamui_vrf::encrypted` for the seed [9; 32] and
+    // the request id [5; 32]
+    const VRF_KEY: &str = "b0ce3581b0fdd6db8622fab11e4cbb18b3a9fdc6f035618d3168e3288f291302";
+    const ENCRYPTION_KEY: &str = "6ef06999267ff8ac9a0061204792713efa7c9ae9b5173b6496bbecab80352e2e";
+    const CIPHERTEXT: &str = "40ed2c0d769f0e4b9d906883fc132279f8a54eda565e357b4f9ad957358852724ab454a635c694c80e2ebf2ecce0dc6c775d14b73518550c7f990d41c4201539";
+    const PROOF: &str = "4853a12ccca6968ec1894c2e3ef4f8554b9c472784ba79efadfeacea39e6fd489073d969fe7c51bb548ade04f2e30e8851b52195668a465fca246c49c405bc7fb457f6dfd37cd03fc76f2cd31a5e230e086a377f6d50e03e3194a555d4d32f28de60db4e639dc9fe07da9a0ee29591c9b6538d2c4be80b7ec3fa74c53c9ef000e0fd106781a4f6a6438133829dd83f7a9ae73b22870b5eab4548a76aaf8e460d";
+    const REVEAL: &str = "864ab5730aeecd29f011f6731d92f32346cdd8243570c7e1529f89665eef927056e7d603af582f8ec3cda31e90c998bb5c978525df449e8067d46321da3616115c6d128afb34dc2c7d9039e52694cc6c846e47ce2999afe92cea3557a951f1787f4adf5069583bc83ff8a3998544b13689f8e705ae3125e778b5678032b89205";
+    const OUTPUT: &str = "8e335c0dcd10b0d36ed1b445959d907ceef4c90f234f82132a98c05621d528d5447e08d94cc1924d994bc4b688d63fbe74907067d09e23f086e5219de270ad6f";
+    const SEED: [u8; 32] = [9u8; 32];
+    const REQUEST_ID: [u8; 32] = [5u8; 32];
+
+    fn verify(
+        vrf_key: &[u8; 32],
+        encryption_key: &[u8; 32],
+        seed: &[u8],
+        request_id: &[u8; 32],
+        ciphertext: &[u8; CIPHERTEXT_LENGTH],
+        proof: &[u8; ENCRYPTION_PROOF_LENGTH],
+    ) -> bool {
+        verify_encrypted_output(vrf_key, encryption_key, seed, request_id, ciphertext, proof).is_ok()
+    }
+
+    #[test]
+    fn test_verify_known_encrypted_output() {
+        assert!(verify(
+            &from_hex(VRF_KEY),
+            &from_hex(ENCRYPTION_KEY),
+            &SEED,
+            &REQUEST_ID,
+            &from_hex(CIPHERTEXT),
+            &from_hex(PROOF),
+        ));
+    }
+
+    #[test]
+    fn test_encrypted_output_rejects_other_inputs() {
+        let (vrf_key, encryption_key) = (from_hex(VRF_KEY), from_hex(ENCRYPTION_KEY));
+        let (ciphertext, proof) = (from_hex(CIPHERTEXT), from_hex(PROOF));
+        assert!(!verify(&vrf_key, &encryption_key, &[8u8; 32], &REQUEST_ID, &ciphertext, &proof));
+        assert!(!verify(&vrf_key, &encryption_key, &SEED, &[6u8; 32], &ciphertext, &proof));
+        assert!(!verify(&encryption_key, &encryption_key, &SEED, &REQUEST_ID, &ciphertext, &proof));
+        assert!(!verify(&vrf_key, &vrf_key, &SEED, &REQUEST_ID, &ciphertext, &proof));
+        assert!(!verify(&[0u8; 32], &encryption_key, &SEED, &REQUEST_ID, &ciphertext, &proof));
+        assert!(!verify(&vrf_key, &[0u8; 32], &SEED, &REQUEST_ID, &ciphertext, &proof));
+
+        for i in [0, 63] {
+            let mut tampered = ciphertext;
+            tampered[i] ^= 1;
+            assert!(!verify(&vrf_key, &encryption_key, &SEED, &REQUEST_ID, &tampered, &proof));
+        }
+        for i in [0, 64, 96, 159] {
+            let mut tampered = proof;
+            tampered[i] ^= 1;
+            assert!(!verify(&vrf_key, &encryption_key, &SEED, &REQUEST_ID, &ciphertext, &tampered));
+        }
+    }
+
+    #[test]
+    fn test_verify_known_reveal() {
+        let output = verify_reveal(
+            &from_hex(ENCRYPTION_KEY),
+            &REQUEST_ID,
+            &from_hex(CIPHERTEXT),
+            &from_hex(REVEAL),
+        )
+        .unwrap();
+        assert_eq!(output, from_hex::<64>(OUTPUT));
+    }
+
+    #[test]
+    fn test_reveal_rejects_tampering() {
+        let (encryption_key, ciphertext) = (from_hex(ENCRYPTION_KEY), from_hex(CIPHERTEXT));
+        assert!(verify_reveal(&encryption_key, &[6u8; 32], &ciphertext, &from_hex(REVEAL)).is_err());
+        for i in [0, 32, 64, 127] {
+            let mut tampered: [u8; 128] = from_hex(REVEAL);
+            tampered[i] ^= 1;
+            assert!(verify_reveal(&encryption_key, &REQUEST_ID, &ciphertext, &tampered).is_err());
+        }
+    }
+}
@@ -98,4 +98,37 @@ pub enum KamuiVrfError {
     
     #[msg("Compression initialization failed")]
     CompressionInitFailed,
+    
+    #[msg("Invalid proof of possession of the VRF key")]
+    InvalidProofOfPossession,
+    
+    #[msg("Invalid encryption key")]
+    InvalidEncryptionKey,
+    
+    #[msg("Invalid proof of the encrypted VRF output")]
+    InvalidEncryptionProof,
+    
+    #[msg("Invalid reveal of the encrypted VRF output")]
+    InvalidReveal,
+    
+    #[msg("Request output must be delivered encrypted")]
+    EncryptedRequest,
+    
+    #[msg("Request output is not encrypted")]
+    RequestNotEncrypted,
+    
+    #[msg("Beacon slot interval must be positive")]
+    InvalidBeaconInterval,
+    
+    #[msg("Beacon rounds must be published in order")]
+    BeaconRoundOutOfOrder,
+    
+    #[msg("Beacon round published before its slot")]
+    BeaconRoundTooEarly,
+    
+    #[msg("Beacon round not published or no longer kept")]
+    BeaconRoundUnavailable,
+    
+    #[msg("SlotHashes sysvar has no entries")]
+    SlotHashesUnavailable,
 } 
\ No newline at end of file
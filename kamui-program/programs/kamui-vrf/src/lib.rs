@@ -9,6 +9,9 @@ pub mod state;
 pub mod errors;
 pub mod ecvrf; // Will need to be exported from the main crate
 pub mod utils;
+pub mod pop;
+pub mod encrypted;
+pub mod beacon;
 
 // Light Protocol ZK compression module
 #[cfg(feature = "light-compression")]
@@ -225,71 +228,104 @@ pub mod kamui_vrf {
         callback_gas_limit: u64,
         pool_id: u8,
     ) -> Result<()> {
-        // Validate inputs
-        require!(
-            minimum_confirmations >= MINIMUM_REQUEST_CONFIRMATIONS && 
-            minimum_confirmations <= MAXIMUM_REQUEST_CONFIRMATIONS,
-            KamuiVrfError::InvalidConfirmations
-        );
-        
-        require!(
-            num_words > 0 && num_words <= MAXIMUM_RANDOM_WORDS,
-            KamuiVrfError::InvalidWordCount
-        );
-        
-        require!(
-            callback_gas_limit >= MINIMUM_CALLBACK_GAS_LIMIT && 
-            callback_gas_limit <= MAXIMUM_CALLBACK_GAS_LIMIT,
-            KamuiVrfError::InvalidGasLimit
-        );
-        
-        let subscription = &ctx.accounts.subscription;
-        let pool = &mut ctx.accounts.request_pool;
-        
-        // Verify pool belongs to subscription
-        require!(pool.subscription == subscription.key(), KamuiVrfError::InvalidPoolSubscription);
-        require!(pool.pool_id == pool_id, KamuiVrfError::InvalidPoolId);
-        
-        // Check capacity
-        require!(
-            (pool.request_count as usize) < pool.max_size as usize,
-            KamuiVrfError::PoolCapacityExceeded
-        );
-        
-        // Check subscription limits
-        require!(
-            subscription.active_requests < subscription.max_requests,
-            KamuiVrfError::TooManyRequests
-        );
-        
-        // Initialize VRF request using the proper field names
-        let request = &mut ctx.accounts.request;
-        request.subscription = subscription.key();
-        request.pool_id = pool_id;
-        request.requester = ctx.accounts.owner.key();
-        request.seed = seed;
-        request.callback_data = callback_data;
-        request.num_words = num_words;
-        request.callback_gas_limit = callback_gas_limit;
-        request.status = RequestStatus::Pending;
-        request.request_index = pool.request_count;
-        request.request_slot = Clock::get()?.slot;
-        
-        // Generate request ID
-        request.request_id = RequestPool::generate_request_id(
-            &seed,
-            &ctx.accounts.owner.key(),
-            &subscription.key(),
+        let requester = ctx.accounts.owner.key();
+        initialize_request(
+            &mut ctx.accounts.request,
+            &ctx.accounts.subscription,
+            &mut ctx.accounts.request_pool,
+            requester,
+            seed,
+            callback_data,
+            num_words,
+            minimum_confirmations,
+            callback_gas_limit,
             pool_id,
-            pool.request_count,
-        );
+            None,
+            None,
+        )
+    }
+
+    /// Request randomness whose output only the holder of the decryption key
+    /// for `encryption_key` learns. The oracle stores an encrypted output with
+    /// `fulfill_randomness_encrypted` and the requester later publishes it
+    /// with `reveal_randomness`.
+    pub fn request_encrypted_randomness(
+        ctx: Context<RequestRandomness>,
+        seed: [u8; 32],
+        callback_data: Vec<u8>,
+        num_words: u32,
+        minimum_confirmations: u8,
+        callback_gas_limit: u64,
+        pool_id: u8,
+        encryption_key: [u8; 32],
+    ) -> Result<()> {
+        encrypted::verify_encryption_key(&encryption_key)?;
+        
+        let requester = ctx.accounts.owner.key();
+        initialize_request(
+            &mut ctx.accounts.request,
+            &ctx.accounts.subscription,
+            &mut ctx.accounts.request_pool,
+            requester,
+            seed,
+            callback_data,
+            num_words,
+            minimum_confirmations,
+            callback_gas_limit,
+            pool_id,
+            None,
+            Some(encryption_key),
+        )
+    }
+
+    /// Let `authority` submit requests charged to the caller's subscription.
+    /// Calling it again replaces the authority.
+    pub fn register_cross_chain_bridge(
+        ctx: Context<RegisterCrossChainBridge>,
+        authority: Pubkey,
+    ) -> Result<()> {
+        let bridge = &mut ctx.accounts.bridge;
+        bridge.subscription = ctx.accounts.subscription.key();
+        bridge.authority = authority;
+        bridge.bump = ctx.bumps.bridge;
         
-        // Update pool
-        pool.request_count += 1;
+        msg!("Cross-chain bridge authority set to {}", authority);
         
         Ok(())
     }
 
+    /// Request randomness on behalf of a requester on another chain. The
+    /// request goes through the same pool and oracle fulfillment as
+    /// `request_randomness`, and its account is derived from the message GUID.
+    pub fn request_cross_chain_randomness(
+        ctx: Context<RequestCrossChainRandomness>,
+        seed: [u8; 32],
+        callback_data: Vec<u8>,
+        num_words: u32,
+        minimum_confirmations: u8,
+        callback_gas_limit: u64,
+        pool_id: u8,
+        origin: CrossChainOrigin,
+    ) -> Result<()> {
+        msg!("Cross-chain request from eid {} guid {:?}", origin.src_eid, origin.guid);
+        
+        let requester = ctx.accounts.authority.key();
+        initialize_request(
+            &mut ctx.accounts.request,
+            &ctx.accounts.subscription,
+            &mut ctx.accounts.request_pool,
+            requester,
+            seed,
+            callback_data,
+            num_words,
+            minimum_confirmations,
+            callback_gas_limit,
+            pool_id,
+            Some(origin),
+            None,
+        )
+    }
+
     pub fn fulfill_randomness(
         ctx: Context<FulfillRandomness>,
         proof: Vec<u8>,
@@ -308,6 +344,8 @@ pub mod kamui_vrf {
             KamuiVrfError::RequestNotPending
         );
         
+        require!(request.encryption_key.is_none(), KamuiVrfError::EncryptedRequest);
+        
         // Verify pool information
         require!(request.pool_id == pool_id, KamuiVrfError::InvalidPoolId);
         require!(request.request_index == request_index, KamuiVrfError::InvalidRequestIndex);
@@ -337,6 +375,78 @@ pub mod kamui_vrf {
         Ok(())
     }
 
+    /// Fulfill an encrypted request with the VRF output of the oracle's
+    /// registered key, encrypted to the requester. Only the ciphertext and
+    /// its proof are stored until the requester reveals the output.
+    pub fn fulfill_randomness_encrypted(
+        ctx: Context<FulfillRandomnessEncrypted>,
+        ciphertext: [u8; encrypted::CIPHERTEXT_LENGTH],
+        proof: [u8; encrypted::ENCRYPTION_PROOF_LENGTH],
+        request_id: [u8; 32],
+        pool_id: u8,
+        request_index: u32,
+    ) -> Result<()> {
+        let request = &mut ctx.accounts.request;
+        let vrf_key = ctx.accounts.oracle_config.vrf_key;
+        
+        let encryption_key = request.encryption_key.ok_or(KamuiVrfError::RequestNotEncrypted)?;
+        require!(request.pool_id == pool_id, KamuiVrfError::InvalidPoolId);
+        require!(request.request_index == request_index, KamuiVrfError::InvalidRequestIndex);
+        
+        encrypted::verify_encrypted_output(
+            &vrf_key,
+            &encryption_key,
+            &request.seed,
+            &request_id,
+            &ciphertext,
+            &proof,
+        )?;
+        
+        request.status = RequestStatus::Fulfilled;
+        
+        let encrypted_result = &mut ctx.accounts.encrypted_result;
+        encrypted_result.request_id = request_id;
+        encrypted_result.vrf_key = vrf_key;
+        encrypted_result.ciphertext = ciphertext;
+        encrypted_result.proof = proof;
+        encrypted_result.proof_slot = Clock::get()?.slot;
+        encrypted_result.revealed = false;
+        
+        msg!("VRF request fulfilled with an encrypted output");
+        
+        Ok(())
+    }
+
+    /// Publish the output of an encrypted request. The reveal proves that it
+    /// is the decryption of the stored ciphertext, so only the holder of the
+    /// decryption key can produce it, but anyone may submit it.
+    pub fn reveal_randomness(
+        ctx: Context<RevealRandomness>,
+        reveal: [u8; encrypted::REVEAL_LENGTH],
+    ) -> Result<()> {
+        let request = &ctx.accounts.request;
+        let encrypted_result = &mut ctx.accounts.encrypted_result;
+        
+        let encryption_key = request.encryption_key.ok_or(KamuiVrfError::RequestNotEncrypted)?;
+        let randomness = encrypted::verify_reveal(
+            &encryption_key,
+            &encrypted_result.request_id,
+            &encrypted_result.ciphertext,
+            &reveal,
+        )?;
+        encrypted_result.revealed = true;
+        
+        let vrf_result = &mut ctx.accounts.vrf_result;
+        vrf_result.randomness = vec![randomness];
+        vrf_result.proof = reveal.to_vec();
+        vrf_result.proof_slot = Clock::get()?.slot;
+        vrf_result.request_id = encrypted_result.request_id;
+        
+        msg!("Encrypted VRF output revealed: {:?}", hex::encode(randomness));
+        
+        Ok(())
+    }
+
     pub fn initialize_oracle_registry(
         ctx: Context<InitializeOracleRegistry>,
         min_stake: u64,
@@ -357,11 +467,17 @@ pub mod kamui_vrf {
         ctx: Context<RegisterOracle>,
         vrf_key: [u8; 32],
         stake_amount: u64,
+        proof_of_possession: [u8; pop::POP_LENGTH],
     ) -> Result<()> {
         let registry = &ctx.accounts.registry;
         let oracle_config = &mut ctx.accounts.oracle_config;
         
         require!(stake_amount >= registry.min_stake, KamuiVrfError::InsufficientStake);
+        pop::verify_proof_of_possession(
+            &vrf_key,
+            &ctx.accounts.oracle_authority.key(),
+            &proof_of_possession,
+        )?;
         
         oracle_config.authority = ctx.accounts.oracle_authority.key();
         oracle_config.vrf_key = vrf_key;
@@ -382,6 +498,71 @@ pub mod kamui_vrf {
         Ok(())
     }
 
+    /// Start a public randomness beacon for the caller's registered VRF key.
+    /// Round r may be published from slot `start + (r - 1) * slot_interval`.
+    /// The genesis value is derived from the latest slot hash, so the oracle
+    /// cannot pick it to steer future rounds.
+    pub fn initialize_beacon(
+        ctx: Context<InitializeBeacon>,
+        slot_interval: u64,
+    ) -> Result<()> {
+        require!(slot_interval > 0, KamuiVrfError::InvalidBeaconInterval);
+        
+        let (genesis_slot, genesis_slot_hash) =
+            beacon::latest_slot_hash(&ctx.accounts.slot_hashes.try_borrow_data()?)
+                .ok_or(KamuiVrfError::SlotHashesUnavailable)?;
+        let beacon_key = ctx.accounts.beacon.key();
+        
+        let beacon = &mut ctx.accounts.beacon;
+        beacon.authority = ctx.accounts.authority.key();
+        beacon.vrf_key = ctx.accounts.oracle_config.vrf_key;
+        beacon.genesis = beacon::beacon_genesis(&beacon_key, genesis_slot, &genesis_slot_hash);
+        beacon.genesis_slot = genesis_slot;
+        beacon.genesis_slot_hash = genesis_slot_hash;
+        beacon.start_slot = Clock::get()?.slot;
+        beacon.slot_interval = slot_interval;
+        beacon.latest_round = 0;
+        beacon.rounds = Vec::new();
+        
+        Ok(())
+    }
+
+    /// Publish the next beacon round. The proof must be the VRF of the beacon
+    /// key on `round || output of round - 1`, so the chain cannot be forked or
+    /// re-rolled.
+    pub fn publish_beacon_round(
+        ctx: Context<PublishBeaconRound>,
+        round: u64,
+        proof: [u8; ecvrf::PROOF_LENGTH],
+    ) -> Result<()> {
+        let beacon = &mut ctx.accounts.beacon;
+        let slot = Clock::get()?.slot;
+        
+        require!(round == beacon.latest_round + 1, KamuiVrfError::BeaconRoundOutOfOrder);
+        require!(slot >= beacon.round_slot(round), KamuiVrfError::BeaconRoundTooEarly);
+        
+        let output = beacon::verify_round(&beacon.vrf_key, round, &beacon.latest_output(), &proof)?;
+        beacon.record(BeaconRound {
+            round,
+            slot,
+            output,
+            proof,
+        });
+        
+        msg!("Beacon round {} published: {:?}", round, hex::encode(output));
+        
+        Ok(())
+    }
+
+    /// Read a published beacon round, e.g. through a simulated transaction.
+    pub fn get_beacon_round(ctx: Context<ReadBeacon>, round: u64) -> Result<BeaconRound> {
+        ctx.accounts
+            .beacon
+            .round(round)
+            .cloned()
+            .ok_or_else(|| KamuiVrfError::BeaconRoundUnavailable.into())
+    }
+
     pub fn rotate_oracles(ctx: Context<RotateOracles>) -> Result<()> {
         let registry = &mut ctx.accounts.registry;
         
@@ -395,6 +576,85 @@ pub mod kamui_vrf {
     }
 }
 
+/// Validate a randomness request and record it in its pool.
+#[allow(clippy::too_many_arguments)]
+fn initialize_request(
+    request: &mut RandomnessRequest,
+    subscription: &Account<EnhancedSubscription>,
+    pool: &mut RequestPool,
+    requester: Pubkey,
+    seed: [u8; 32],
+    callback_data: Vec<u8>,
+    num_words: u32,
+    minimum_confirmations: u8,
+    callback_gas_limit: u64,
+    pool_id: u8,
+    origin: Option<CrossChainOrigin>,
+    encryption_key: Option<[u8; 32]>,
+) -> Result<()> {
+    // Validate inputs
+    require!(
+        minimum_confirmations >= MINIMUM_REQUEST_CONFIRMATIONS && 
+        minimum_confirmations <= MAXIMUM_REQUEST_CONFIRMATIONS,
+        KamuiVrfError::InvalidConfirmations
+    );
+    
+    require!(
+        num_words > 0 && num_words <= MAXIMUM_RANDOM_WORDS,
+        KamuiVrfError::InvalidWordCount
+    );
+    
+    require!(
+        callback_gas_limit >= MINIMUM_CALLBACK_GAS_LIMIT && 
+        callback_gas_limit <= MAXIMUM_CALLBACK_GAS_LIMIT,
+        KamuiVrfError::InvalidGasLimit
+    );
+    
+    // Verify pool belongs to subscription
+    require!(pool.subscription == subscription.key(), KamuiVrfError::InvalidPoolSubscription);
+    require!(pool.pool_id == pool_id, KamuiVrfError::InvalidPoolId);
+    
+    // Check capacity
+    require!(
+        (pool.request_count as usize) < pool.max_size as usize,
+        KamuiVrfError::PoolCapacityExceeded
+    );
+    
+    // Check subscription limits
+    require!(
+        subscription.active_requests < subscription.max_requests,
+        KamuiVrfError::TooManyRequests
+    );
+    
+    // Initialize VRF request using the proper field names
+    request.subscription = subscription.key();
+    request.pool_id = pool_id;
+    request.requester = requester;
+    request.seed = seed;
+    request.callback_data = callback_data;
+    request.num_words = num_words;
+    request.callback_gas_limit = callback_gas_limit;
+    request.status = RequestStatus::Pending;
+    request.request_index = pool.request_count;
+    request.request_slot = Clock::get()?.slot;
+    request.origin = origin;
+    request.encryption_key = encryption_key;
+    
+    // Generate request ID
+    request.request_id = RequestPool::generate_request_id(
+        &seed,
+        &requester,
+        &subscription.key(),
+        pool_id,
+        pool.request_count,
+    );
+    
+    // Update pool
+    pool.request_count += 1;
+    
+    Ok(())
+}
+
 pub const MINIMUM_REQUEST_CONFIRMATIONS: u8 = 1;
 pub const MAXIMUM_REQUEST_CONFIRMATIONS: u8 = 255;
 pub const MINIMUM_CALLBACK_GAS_LIMIT: u64 = 10_000;
@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use kamui_ecvrf::EcvrfError;
+use crate::errors::KamuiVrfError;
+
+// -----------------------------------------------------------------------------
+// Proof of possession of an oracle VRF key, produced by `ecvrf-cli pop`
+// (`mangekyou::kamui_vrf::pop`). The Schnorr proof is checked by
+// `kamui_ecvrf::pop`, which the native coordinator uses too.
+// -----------------------------------------------------------------------------
+
+pub use kamui_ecvrf::pop::POP_LENGTH;
+
+/// Check that `authority` holds the secret key of `vrf_key`. The key must be a
+/// valid ristretto255 point other than the identity.
+pub fn verify_proof_of_possession(
+    vrf_key: &[u8; 32],
+    authority: &Pubkey,
+    proof: &[u8; POP_LENGTH],
+) -> Result<()> {
+    kamui_ecvrf::pop::verify_proof_of_possession(vrf_key, &authority.to_bytes(), proof).map_err(|e| match e {
+        EcvrfError::InvalidPublicKey => KamuiVrfError::InvalidVrfKey.into(),
+        EcvrfError::InvalidProof => KamuiVrfError::InvalidProofOfPossession.into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_hex(hex_str: &str) -> Vec<u8> {
+        hex::decode(hex_str).unwrap()
+    }
+
+    // Produced by `ecvrf-cli pop`
+    const VRF_KEY: &str = "7a66a0fe0f2bcdcea5bfb97e3e9f6b298d25899052721bc2b4f3cb570a921b23";
+    const AUTHORITY: &str = "AKnL4NNf3DGWZJS6cPknBuEGnVsV4A4m5tgebLHaRSZ9";
+    const PROOF: &str = "92acfef779459c86015f7a4946800e97b63fb9b8980d6e04ab40537939cedf1539b0a055d697f83bf07491434ed557bb4a4453aef58bf33ecd892901866dec0f";
+
+    fn vrf_key() -> [u8; 32] {
+        from_hex(VRF_KEY).try_into().unwrap()
+    }
+
+    fn authority() -> Pubkey {
+        AUTHORITY.parse().unwrap()
+    }
+
+    fn proof() -> [u8; POP_LENGTH] {
+        from_hex(PROOF).try_into().unwrap()
+    }
+
+    #[test]
+    fn test_verify_known_proof() {
+        assert!(verify_proof_of_possession(&vrf_key(), &authority(), &proof()).is_ok());
+    }
+
+    #[test]
+    fn test_verify_rejects_other_authority_or_key() {
+        assert!(verify_proof_of_possession(&vrf_key(), &Pubkey::new_unique(), &proof()).is_err());
+
+        let mut other_key = vrf_key();
+        other_key[0] ^= 1;
+        assert!(verify_proof_of_possession(&other_key, &authority(), &proof()).is_err());
+        assert!(verify_proof_of_possession(&[0u8; 32], &authority(), &proof()).is_err());
+        assert!(verify_proof_of_possession(&[1u8; 32], &authority(), &proof()).is_err());
+    }
+
+    #[test]
+    fn test_verify_rejects_tampering() {
+        for i in [0, 31, 32, 63] {
+            let mut tampered = proof();
+            tampered[i] ^= 1;
+            assert!(verify_proof_of_possession(&vrf_key(), &authority(), &tampered).is_err());
+        }
+    }
+}
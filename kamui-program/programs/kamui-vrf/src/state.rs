@@ -113,6 +113,33 @@ pub struct RandomnessRequest {
     pub request_index: u32,
     /// Unique request identifier
     pub request_id: [u8; 32],
+    /// Set for requests bridged in from another chain
+    pub origin: Option<CrossChainOrigin>,
+    /// Set for requests whose output is delivered encrypted to this key
+    pub encryption_key: Option<[u8; 32]>,
+}
+
+/// Where a bridged request came from
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, InitSpace)]
+pub struct CrossChainOrigin {
+    /// Source endpoint ID of the message
+    pub src_eid: u32,
+    /// Requester address on the source chain
+    pub requester: [u8; 32],
+    /// Message GUID assigned by the messaging layer
+    pub guid: [u8; 32],
+}
+
+/// Authorizes a bridge program to charge requests to a subscription
+#[account]
+#[derive(InitSpace)]
+pub struct CrossChainBridge {
+    /// The subscription requests are charged to
+    pub subscription: Pubkey,
+    /// The signer allowed to submit requests, usually a bridge program PDA
+    pub authority: Pubkey,
+    /// PDA bump
+    pub bump: u8,
 }
 
 /// VRF result data
@@ -131,6 +158,67 @@ pub struct VrfResult {
     pub request_id: [u8; 32],
 }
 
+/// VRF output encrypted to the requester, kept until it is revealed
+#[account]
+#[derive(InitSpace)]
+pub struct EncryptedVrfResult {
+    /// Request ID this result is for
+    pub request_id: [u8; 32],
+    /// VRF key of the oracle that fulfilled the request
+    pub vrf_key: [u8; 32],
+    /// ElGamal encryption of the VRF point
+    pub ciphertext: [u8; 64],
+    /// Proof that the ciphertext encrypts the VRF point of `vrf_key`
+    pub proof: [u8; 160],
+    /// Block number when proof was generated
+    pub proof_slot: u64,
+    /// Whether the output has been revealed into a `VrfResult`
+    pub revealed: bool,
+}
+
+/// Number of rounds a beacon keeps
+pub const BEACON_HISTORY: usize = 32;
+
+/// Public randomness beacon. Round r is the VRF output of the oracle key on
+/// r || output of round r - 1, published no earlier than its slot. The
+/// latest `BEACON_HISTORY` rounds are kept in a ring buffer.
+#[account]
+#[derive(InitSpace)]
+pub struct Beacon {
+    /// The oracle authority publishing rounds
+    pub authority: Pubkey,
+    /// VRF key the rounds are verified against
+    pub vrf_key: [u8; 32],
+    /// Output preceding round 1, see `beacon::beacon_genesis`
+    pub genesis: [u8; 64],
+    /// Slot whose hash the genesis value was derived from
+    pub genesis_slot: u64,
+    /// Hash of `genesis_slot`, from the SlotHashes sysvar
+    pub genesis_slot_hash: [u8; 32],
+    /// Slot from which round 1 may be published
+    pub start_slot: u64,
+    /// Slots between consecutive rounds
+    pub slot_interval: u64,
+    /// Last published round, 0 if none
+    pub latest_round: u64,
+    /// Ring buffer of the latest rounds, round r at index (r - 1) % BEACON_HISTORY
+    #[max_len(BEACON_HISTORY)]
+    pub rounds: Vec<BeaconRound>,
+}
+
+/// A published beacon round
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, InitSpace)]
+pub struct BeaconRound {
+    /// Round number, starting at 1
+    pub round: u64,
+    /// Slot the round was published in
+    pub slot: u64,
+    /// The randomness of the round
+    pub output: [u8; 64],
+    /// The VRF proof
+    pub proof: [u8; 80],
+}
+
 /// VRF Result stored in a compressed account format
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, InitSpace)]
 pub struct CompressedVrfResult {
@@ -249,6 +337,42 @@ pub struct EnhancedOracle {
     pub failure_count: u64,
 }
 
+impl Beacon {
+    /// First slot in which `round` may be published
+    pub fn round_slot(&self, round: u64) -> u64 {
+        self.start_slot
+            .saturating_add(round.saturating_sub(1).saturating_mul(self.slot_interval))
+    }
+
+    /// Output of the latest round, or the genesis value if none was published
+    pub fn latest_output(&self) -> [u8; 64] {
+        self.round(self.latest_round)
+            .map_or(self.genesis, |entry| entry.output)
+    }
+
+    /// Look up a round. Returns `None` if it was not published yet or has
+    /// been overwritten in the ring buffer.
+    pub fn round(&self, round: u64) -> Option<&BeaconRound> {
+        if round == 0 || round > self.latest_round {
+            return None;
+        }
+        self.rounds
+            .get(((round - 1) % BEACON_HISTORY as u64) as usize)
+            .filter(|entry| entry.round == round)
+    }
+
+    /// Store the next round, overwriting the oldest one once the buffer is full
+    pub fn record(&mut self, entry: BeaconRound) {
+        let index = ((entry.round - 1) % BEACON_HISTORY as u64) as usize;
+        self.latest_round = entry.round;
+        if index < self.rounds.len() {
+            self.rounds[index] = entry;
+        } else {
+            self.rounds.push(entry);
+        }
+    }
+}
+
 // Implement utility functions for RequestPool
 impl RequestPool {
     /// Generate a unique request ID
@@ -424,6 +548,80 @@ pub struct RequestRandomness<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct RegisterCrossChainBridge<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    #[account(
+        constraint = subscription.owner == owner.key() @ crate::errors::KamuiVrfError::Unauthorized
+    )]
+    pub subscription: Account<'info, EnhancedSubscription>,
+    
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + CrossChainBridge::INIT_SPACE,
+        seeds = [b"cross_chain_bridge", subscription.key().as_ref()],
+        bump
+    )]
+    pub bridge: Account<'info, CrossChainBridge>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(seed: [u8; 32], callback_data: Vec<u8>, num_words: u32, minimum_confirmations: u8, callback_gas_limit: u64, pool_id: u8, origin: CrossChainOrigin)]
+pub struct RequestCrossChainRandomness<'info> {
+    /// Pays rent for the request account
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub authority: Signer<'info>,
+    
+    #[account(
+        seeds = [b"cross_chain_bridge", subscription.key().as_ref()],
+        bump = bridge.bump,
+        has_one = authority @ crate::errors::KamuiVrfError::Unauthorized,
+        has_one = subscription @ crate::errors::KamuiVrfError::Unauthorized
+    )]
+    pub bridge: Account<'info, CrossChainBridge>,
+    
+    /// Seeded by the subscription as well as the GUID: GUIDs are public on
+    /// the source chain, so another bridge must not be able to take one first
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + RandomnessRequest::INIT_SPACE,
+        seeds = [b"cross_chain_request", subscription.key().as_ref(), origin.guid.as_ref()],
+        bump
+    )]
+    pub request: Account<'info, RandomnessRequest>,
+    
+    #[account(
+        mut,
+        constraint = subscription.balance >= subscription.min_balance,
+        constraint = subscription.active_requests < subscription.max_requests
+    )]
+    pub subscription: Account<'info, EnhancedSubscription>,
+    
+    #[account(
+        mut,
+        seeds = [
+            b"request_pool", 
+            subscription.key().as_ref(),
+            &[pool_id]
+        ],
+        bump,
+        constraint = request_pool.subscription == subscription.key(),
+        constraint = request_pool.pool_id == pool_id,
+        constraint = request_pool.request_count < request_pool.max_size
+    )]
+    pub request_pool: Account<'info, RequestPool>,
+    
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 #[instruction(proof: Vec<u8>, _public_key: Vec<u8>, request_id: [u8; 32], pool_id: u8, request_index: u32)]
 pub struct FulfillRandomness<'info> {
@@ -468,6 +666,139 @@ pub struct FulfillRandomness<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+#[instruction(ciphertext: [u8; 64], proof: [u8; 160], request_id: [u8; 32], pool_id: u8, request_index: u32)]
+pub struct FulfillRandomnessEncrypted<'info> {
+    #[account(mut)]
+    pub oracle: Signer<'info>,
+    
+    #[account(
+        seeds = [b"oracle_config", oracle.key().as_ref()],
+        bump,
+        constraint = oracle_config.is_active @ crate::errors::KamuiVrfError::OracleNotActive
+    )]
+    pub oracle_config: Account<'info, EnhancedOracle>,
+    
+    #[account(
+        mut,
+        constraint = request.request_id == request_id,
+        constraint = request.status == RequestStatus::Pending
+    )]
+    pub request: Account<'info, RandomnessRequest>,
+    
+    #[account(
+        init,
+        payer = oracle,
+        space = 8 + EncryptedVrfResult::INIT_SPACE,
+        seeds = [b"encrypted_vrf_result", request.key().as_ref()],
+        bump
+    )]
+    pub encrypted_result: Account<'info, EncryptedVrfResult>,
+    
+    #[account(
+        mut,
+        seeds = [
+            b"request_pool", 
+            subscription.key().as_ref(),
+            &[pool_id]
+        ],
+        bump,
+        constraint = request_pool.subscription == subscription.key(),
+        constraint = request_pool.pool_id == pool_id
+    )]
+    pub request_pool: Account<'info, RequestPool>,
+    
+    #[account(
+        mut,
+        constraint = request.subscription == subscription.key()
+    )]
+    pub subscription: Account<'info, EnhancedSubscription>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevealRandomness<'info> {
+    /// Pays rent for the result account
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub request: Account<'info, RandomnessRequest>,
+    
+    #[account(
+        mut,
+        seeds = [b"encrypted_vrf_result", request.key().as_ref()],
+        bump,
+        constraint = !encrypted_result.revealed
+    )]
+    pub encrypted_result: Account<'info, EncryptedVrfResult>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + VrfResult::INIT_SPACE,
+        seeds = [b"vrf_result", request.key().as_ref()],
+        bump
+    )]
+    pub vrf_result: Account<'info, VrfResult>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeBeacon<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        seeds = [b"oracle_config", authority.key().as_ref()],
+        bump,
+        constraint = oracle_config.is_active @ crate::errors::KamuiVrfError::OracleNotActive
+    )]
+    pub oracle_config: Account<'info, EnhancedOracle>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Beacon::INIT_SPACE,
+        seeds = [b"beacon", authority.key().as_ref()],
+        bump
+    )]
+    pub beacon: Account<'info, Beacon>,
+    
+    /// CHECK: the SlotHashes sysvar, read raw because it is too large to
+    /// deserialize on-chain
+    #[account(address = anchor_lang::solana_program::sysvar::slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PublishBeaconRound<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        seeds = [b"oracle_config", authority.key().as_ref()],
+        bump,
+        constraint = oracle_config.is_active @ crate::errors::KamuiVrfError::OracleNotActive
+    )]
+    pub oracle_config: Account<'info, EnhancedOracle>,
+    
+    #[account(
+        mut,
+        seeds = [b"beacon", authority.key().as_ref()],
+        bump,
+        has_one = authority @ crate::errors::KamuiVrfError::Unauthorized
+    )]
+    pub beacon: Account<'info, Beacon>,
+}
+
+#[derive(Accounts)]
+pub struct ReadBeacon<'info> {
+    pub beacon: Account<'info, Beacon>,
+}
+
 #[derive(Accounts)]
 pub struct InitializeOracleRegistry<'info> {
     #[account(mut)]
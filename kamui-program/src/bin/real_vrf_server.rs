@@ -11,13 +11,14 @@ use {
         signature::{Keypair, Signer},
         transaction::Transaction,
     },
-    std::{str::FromStr, fs::File, io::Read, time::Duration, thread},
+    std::{str::FromStr, fs::File, io::Read, os::unix::fs::OpenOptionsExt, time::Duration, thread},
     mangekyou::{
         kamui_vrf::{
             ecvrf::ECVRFKeyPair,
             VRFKeyPair,
             VRFProof,
         },
+        keystore::{KdfParams, Keystore},
     },
     kamui_program::instruction::VerifyVrfInput,
     rand::thread_rng,
@@ -191,23 +192,41 @@ impl RealVRFServer {
     }
 }
 
-/// Load VRF keypair from file
+/// Environment variable holding the VRF keystore password
+const KEYSTORE_PASSWORD_ENV: &str = "VRF_KEYSTORE_PASSWORD";
+
+fn keystore_password() -> Result<String, Box<dyn std::error::Error>> {
+    std::env::var(KEYSTORE_PASSWORD_ENV)
+        .map_err(|_| format!("Set {} to the VRF keystore password", KEYSTORE_PASSWORD_ENV).into())
+}
+
+/// Load VRF keypair from an encrypted keystore
 fn load_vrf_keypair(path: &str) -> Result<ECVRFKeyPair, Box<dyn std::error::Error>> {
     let mut file = File::open(path)?;
     let mut contents = String::new();
     file.read_to_string(&mut contents)?;
     
-    let keypair_data: Vec<u8> = serde_json::from_str(&contents)?;
-    let keypair = ECVRFKeyPair::from_bytes(&keypair_data)?;
+    let keystore = Keystore::from_json(&contents)?;
+    let keypair = keystore.to_ecvrf_keypair(keystore_password()?.as_bytes())?;
     
     Ok(keypair)
 }
 
-/// Save VRF keypair to file
+/// Save VRF keypair to a new encrypted keystore
 fn save_vrf_keypair(keypair: &ECVRFKeyPair, path: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let keypair_bytes = keypair.to_bytes();
-    let json = serde_json::to_string_pretty(&keypair_bytes)?;
-    std::fs::write(path, json)?;
+    let keystore = Keystore::from_ecvrf_keypair(
+        keypair,
+        keystore_password()?.as_bytes(),
+        KdfParams::default(),
+        &mut thread_rng(),
+    )?;
+    // Owner-only from creation, so the keystore is never readable by other users
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)?;
+    std::io::Write::write_all(&mut file, keystore.to_json()?.as_bytes())?;
     
     Ok(())
 }
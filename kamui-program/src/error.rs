@@ -104,6 +104,15 @@ pub enum VrfCoordinatorError {
     
     #[error("Request ID mismatch")]
     RequestIdMismatch,
+    
+    #[error("Batch size limit exceeded")]
+    BatchLimitExceeded,
+    
+    #[error("No request in the batch could be fulfilled")]
+    BatchFulfillmentFailed,
+
+    #[error("Invalid proof of possession of the VRF key")]
+    InvalidProofOfPossession,
 }
 
 impl From<VrfCoordinatorError> for ProgramError {
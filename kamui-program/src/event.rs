@@ -74,6 +74,7 @@ pub enum VrfEvent {
         oracle: Pubkey,
         pool_id: u8,
         count: u32,
+        failed: u32,
     },
     RequestExpired {
         request_id: [u8; 32],
@@ -81,6 +82,12 @@ pub enum VrfEvent {
         pool_id: u8,
         request_index: u32,
     },
+    BatchItemFailed {
+        request_id: [u8; 32],
+        oracle: Pubkey,
+        pool_id: u8,
+        index: u32,
+    },
 }
 
 impl VrfEvent {
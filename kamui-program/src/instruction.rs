@@ -84,6 +84,10 @@ pub enum VrfCoordinatorInstruction {
     /// 4. `[writable]` Subscription account
     /// 5. `[]` Callback program
     /// 6. `[]` System program
+    /// 7. `[]` Oracle config account
+    ///
+    /// The proof is verified against the oracle's registered VRF key on the
+    /// request seed, and the randomness is derived from the VRF output.
     FulfillRandomness {
         proof: Vec<u8>,
         public_key: Vec<u8>,
@@ -123,7 +127,8 @@ pub enum VrfCoordinatorInstruction {
         rotation_frequency: u64,
     },
 
-    /// Register a new oracle with stake
+    /// Register a new oracle with stake. `proof_of_possession` is the proof
+    /// from `ecvrf-cli pop` that the oracle authority holds the VRF key.
     /// Accounts expected:
     /// 0. `[signer]` Oracle authority
     /// 1. `[writable]` Oracle config account (PDA)
@@ -132,6 +137,7 @@ pub enum VrfCoordinatorInstruction {
     RegisterOracle {
         vrf_key: [u8; 32],
         stake_amount: u64,
+        proof_of_possession: [u8; 64],
     },
 
     /// Deactivate an oracle
@@ -144,10 +150,20 @@ pub enum VrfCoordinatorInstruction {
     /// Process a batch of randomness requests
     /// Accounts expected:
     /// 0. `[signer]` Oracle
-    /// 1. `[writable]` Oracle config account
+    /// 1. `[]` Oracle config account
     /// 2. `[writable]` Request pool account
     /// 3. `[]` System program
-    /// + Variable number of request and result accounts
+    ///
+    /// Followed by four accounts per request, in batch order:
+    /// - `[writable]` Request account
+    /// - `[writable]` VRF result account (PDA)
+    /// - `[writable]` Subscription account
+    /// - `[]` Callback program
+    ///
+    /// Every request is verified before any account is written. Requests that
+    /// fail verification are skipped without side effects and reported with a
+    /// `BatchItemFailed` event; the instruction only fails if no request in the
+    /// batch could be fulfilled or the oracle cannot pay for the result accounts.
     ProcessRequestBatch {
         request_ids: Vec<[u8; 32]>,
         proofs: Vec<Vec<u8>>,
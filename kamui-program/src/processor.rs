@@ -5,7 +5,8 @@ use {
         state::{
             RandomnessRequest, RequestStatus, RequestPool, RequestSummary,
             EnhancedSubscription, EnhancedOracle, VrfResult, OracleRegistry,
-            MAX_REQUESTS_PER_SUBSCRIPTION, MAX_ACTIVE_ORACLES, ORACLE_ROTATION_FREQUENCY
+            MAX_REQUESTS_PER_SUBSCRIPTION, MAX_ACTIVE_ORACLES, ORACLE_ROTATION_FREQUENCY,
+            MAX_BATCH_SIZE,
         },
         event::VrfEvent,
         error::VrfCoordinatorError,
@@ -19,14 +20,36 @@ use {
         program_error::ProgramError,
         pubkey::Pubkey,
         system_instruction,
+        system_program,
         sysvar::{rent::Rent, clock::Clock, Sysvar},
     },
-    std::collections::BTreeMap,
+    sha2::{Digest, Sha512},
+    std::collections::{BTreeMap, BTreeSet},
 };
 use spl_token::instruction as token_instruction;
 
 pub struct Processor;
 
+/// Accounts and arguments of one request being fulfilled
+struct FulfillmentItem<'a, 'info> {
+    request_account: &'a AccountInfo<'info>,
+    vrf_result_account: &'a AccountInfo<'info>,
+    subscription_account: &'a AccountInfo<'info>,
+    callback_program: &'a AccountInfo<'info>,
+    proof: &'a [u8],
+    public_key: &'a [u8],
+    request_id: [u8; 32],
+    request_index: u32,
+}
+
+/// A request that passed every fulfillment check, with its derived randomness
+struct VerifiedFulfillment {
+    request: RandomnessRequest,
+    randomness: Vec<[u8; 64]>,
+    result_bump: u8,
+    result_size: usize,
+}
+
 impl Processor {
     pub fn process(
         program_id: &Pubkey,
@@ -107,11 +130,12 @@ impl Processor {
             },
             VrfCoordinatorInstruction::RegisterOracle { 
                 vrf_key, 
-                stake_amount 
+                stake_amount,
+                proof_of_possession,
             } => {
                 msg!("VRF Coordinator: RegisterOracle - vrf_key: {:?}, stake_amount: {}", 
                     vrf_key, stake_amount);
-                Self::process_register_oracle(program_id, accounts, vrf_key, stake_amount)
+                Self::process_register_oracle(program_id, accounts, vrf_key, stake_amount, proof_of_possession)
             },
             VrfCoordinatorInstruction::DeactivateOracle => {
                 msg!("VRF Coordinator: DeactivateOracle");
@@ -606,195 +630,333 @@ impl Processor {
         let subscription_account = next_account_info(accounts_iter)?;
         let callback_program = next_account_info(accounts_iter)?;
         let system_program = next_account_info(accounts_iter)?;
+        let oracle_config_account = next_account_info(accounts_iter)?;
+
+        let oracle_config = Self::load_fulfilling_oracle(program_id, oracle, oracle_config_account)?;
+        let mut request_pool = Self::load_request_pool(program_id, request_pool_account, pool_id)?;
+        let current_slot = Clock::get()?.slot;
+
+        let item = FulfillmentItem {
+            request_account,
+            vrf_result_account,
+            subscription_account,
+            callback_program,
+            proof: &proof,
+            public_key: &public_key,
+            request_id,
+            request_index,
+        };
 
-        // Verify oracle signer
+        // Everything is checked before the first write
+        let verified = Self::verify_fulfillment(program_id, &oracle_config, &request_pool, pool_id, &item, current_slot)?;
+        Self::check_fulfillment_funds(oracle, &[(&item, &verified)])?;
+
+        Self::write_fulfillment(program_id, oracle, system_program, &mut request_pool, &item, verified, current_slot)?;
+        Self::save_request_pool(request_pool_account, &request_pool)
+    }
+
+    /// Load the config of the oracle signing a fulfillment and check it may fulfill
+    fn load_fulfilling_oracle(
+        program_id: &Pubkey,
+        oracle: &AccountInfo,
+        oracle_config_account: &AccountInfo,
+    ) -> Result<EnhancedOracle, ProgramError> {
         if !oracle.is_signer {
+            msg!("VRF Coordinator: Error - Missing oracle signature");
             return Err(ProgramError::MissingRequiredSignature);
         }
 
-        // Load and validate request
-        let mut request_data = request_account.try_borrow_data()?;
-        if request_data.len() <= 8 || &request_data[0..8] != &[82, 69, 81, 85, 69, 83, 84, 0] {
-            msg!("VRF Coordinator: Invalid request account");
-            return Err(ProgramError::InvalidAccountData);
+        if oracle_config_account.owner != program_id {
+            msg!("VRF Coordinator: Oracle config not owned by program");
+            return Err(ProgramError::IncorrectProgramId);
         }
 
-        let mut request = RandomnessRequest::try_from_slice(&request_data[8..])?;
+        let oracle_config = {
+            let oracle_data = oracle_config_account.try_borrow_data()?;
+            if oracle_data.len() <= 8 || &oracle_data[0..8] != &[79, 82, 65, 67, 76, 69, 0, 0] {
+                msg!("VRF Coordinator: Invalid oracle config account");
+                return Err(ProgramError::InvalidAccountData);
+            }
+            EnhancedOracle::try_from_slice(&oracle_data[8..])?
+        };
 
-        // Verify request ID
-        if request.request_id != request_id {
-            msg!("VRF Coordinator: Request ID mismatch");
-            return Err(VrfCoordinatorError::RequestIdMismatch.into());
+        if oracle_config.authority != *oracle.key {
+            msg!("VRF Coordinator: Oracle authority mismatch");
+            return Err(VrfCoordinatorError::InvalidOracleAuthority.into());
         }
 
-        // Verify pool ID and request index
-        if request.pool_id != pool_id || request.request_index != request_index {
-            msg!("VRF Coordinator: Pool ID or request index mismatch");
-            return Err(VrfCoordinatorError::InvalidRequestParameters.into());
+        if !oracle_config.is_active {
+            msg!("VRF Coordinator: Oracle is not active");
+            return Err(VrfCoordinatorError::InvalidOracle.into());
         }
 
-        // Check request status
-        if request.status != RequestStatus::Pending {
-            msg!("VRF Coordinator: Request is not pending");
-            return Err(VrfCoordinatorError::InvalidRequestStatus.into());
+        Ok(oracle_config)
+    }
+
+    fn load_request_pool(
+        program_id: &Pubkey,
+        request_pool_account: &AccountInfo,
+        pool_id: u8,
+    ) -> Result<RequestPool, ProgramError> {
+        if request_pool_account.owner != program_id {
+            msg!("VRF Coordinator: Request pool not owned by program");
+            return Err(ProgramError::IncorrectProgramId);
         }
 
-        // Load and validate request pool
-        let mut pool_data = request_pool_account.try_borrow_data()?;
+        let pool_data = request_pool_account.try_borrow_data()?;
         if pool_data.len() <= 8 || &pool_data[0..8] != &[80, 79, 79, 76, 0, 0, 0, 0] {
             msg!("VRF Coordinator: Invalid request pool account");
             return Err(VrfCoordinatorError::RequestPoolNotInitialized.into());
         }
 
-        let mut request_pool = RequestPool::try_from_slice(&pool_data[8..])?;
-        
-        // Verify pool belongs to subscription
-        if request_pool.subscription != *subscription_account.key || request_pool.pool_id != pool_id {
+        let request_pool = RequestPool::try_from_slice(&pool_data[8..])?;
+        if request_pool.pool_id != pool_id {
+            msg!("VRF Coordinator: Pool ID mismatch");
+            return Err(VrfCoordinatorError::InvalidPoolId.into());
+        }
+
+        Ok(request_pool)
+    }
+
+    fn save_request_pool(request_pool_account: &AccountInfo, request_pool: &RequestPool) -> ProgramResult {
+        let mut pool_data = request_pool_account.try_borrow_mut_data()?;
+        pool_data[0..8].copy_from_slice(&[80, 79, 79, 76, 0, 0, 0, 0]);
+        request_pool.serialize(&mut &mut pool_data[8..])?;
+        Ok(())
+    }
+
+    /// Run every check a fulfillment has to pass, without writing to any account.
+    /// A fulfillment that passes can be written without failing, provided the
+    /// oracle can pay for all result accounts (see `check_fulfillment_funds`).
+    fn verify_fulfillment(
+        program_id: &Pubkey,
+        oracle_config: &EnhancedOracle,
+        request_pool: &RequestPool,
+        pool_id: u8,
+        item: &FulfillmentItem,
+        current_slot: u64,
+    ) -> Result<VerifiedFulfillment, ProgramError> {
+        if item.request_account.owner != program_id || item.subscription_account.owner != program_id {
+            msg!("VRF Coordinator: Request or subscription not owned by program");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        if !item.request_account.is_writable
+            || !item.vrf_result_account.is_writable
+            || !item.subscription_account.is_writable
+        {
+            msg!("VRF Coordinator: Request, result and subscription accounts must be writable");
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // The proof must be made with the VRF key this oracle registered
+        if item.public_key != oracle_config.vrf_key.as_slice() {
+            msg!("VRF Coordinator: Public key does not match registered VRF key");
+            return Err(VrfCoordinatorError::InvalidVrfProof.into());
+        }
+
+        // Load and validate request
+        let request = {
+            let request_data = item.request_account.try_borrow_data()?;
+            if request_data.len() <= 8 || &request_data[0..8] != &[82, 69, 81, 85, 69, 83, 84, 0] {
+                msg!("VRF Coordinator: Invalid request account");
+                return Err(ProgramError::InvalidAccountData);
+            }
+            RandomnessRequest::try_from_slice(&request_data[8..])?
+        };
+
+        if request.request_id != item.request_id {
+            msg!("VRF Coordinator: Request ID mismatch");
+            return Err(VrfCoordinatorError::RequestIdMismatch.into());
+        }
+
+        if request.pool_id != pool_id || request.request_index != item.request_index {
+            msg!("VRF Coordinator: Pool ID or request index mismatch");
+            return Err(VrfCoordinatorError::InvalidRequestParameters.into());
+        }
+
+        if request.subscription != *item.subscription_account.key || request_pool.subscription != request.subscription {
             msg!("VRF Coordinator: Pool and subscription mismatch");
             return Err(ProgramError::InvalidAccountData);
         }
 
-        // Check if request exists in pool
-        let request_summary = request_pool.requests.get_mut(&request_index).ok_or(VrfCoordinatorError::RequestNotFound)?;
-        
-        // Check if request is expired
-        let current_slot = Clock::get()?.slot;
+        if request.status != RequestStatus::Pending {
+            msg!("VRF Coordinator: Request is not pending");
+            return Err(VrfCoordinatorError::InvalidRequestStatus.into());
+        }
+
+        let request_summary = request_pool.requests.get(&item.request_index).ok_or(VrfCoordinatorError::RequestNotFound)?;
         if RequestPool::is_request_expired(request_summary.request_slot, current_slot) {
             msg!("VRF Coordinator: Request is expired");
-            
-            // Mark as expired and update
-            request.status = RequestStatus::Expired;
-            request_summary.status = RequestStatus::Expired;
-            
-            // Update request data
-            request_data[0..8].copy_from_slice(&[82, 69, 81, 85, 69, 83, 84, 0]);
-            request.serialize(&mut &mut request_data[8..])?;
-            
-            // Update pool data
-            pool_data[0..8].copy_from_slice(&[80, 79, 79, 76, 0, 0, 0, 0]);
-            request_pool.serialize(&mut &mut pool_data[8..])?;
-            
-            // Emit request expired event
-            VrfEvent::RequestExpired {
-                request_id,
-                subscription: *subscription_account.key,
-                pool_id,
-                request_index,
-            }.emit();
-            
             return Err(VrfCoordinatorError::RequestExpired.into());
         }
 
-        // Verify VRF proof
-        // TODO: Implement actual VRF proof verification here
-        // This is a placeholder for the verification logic
-        let vrf_proof_valid = true; // Assume valid for now, replace with actual verification
-        
-        if !vrf_proof_valid {
-            msg!("VRF Coordinator: Invalid VRF proof");
-            return Err(VrfCoordinatorError::InvalidVrfProof.into());
+        {
+            let subscription_data = item.subscription_account.try_borrow_data()?;
+            if subscription_data.len() <= 8 || &subscription_data[0..8] != &[83, 85, 66, 83, 67, 82, 73, 80] {
+                msg!("VRF Coordinator: Invalid subscription account");
+                return Err(ProgramError::InvalidAccountData);
+            }
+            // Fulfilled requests shrink the subscription, so the account can hold trailing bytes
+            EnhancedSubscription::deserialize(&mut &subscription_data[8..])?;
         }
 
-        // Generate randomness from proof
-        // This is a placeholder for the actual randomness derivation
-        let mut randomness = Vec::with_capacity(request.num_words as usize);
-        for i in 0..request.num_words {
-            let mut word = [0u8; 64];
-            let hash_input = [&request_id[..], &i.to_le_bytes()[..]].concat();
-            let hash = solana_program::keccak::hash(&hash_input);
-            word[0..32].copy_from_slice(&hash.to_bytes());
-            word[32..64].copy_from_slice(&hash.to_bytes()); // Duplicate for now, replace with actual derivation
-            randomness.push(word);
+        // The callback is made to the requester, which has to be passed in
+        if !request.callback_data.is_empty()
+            && (*item.callback_program.key != request.requester || !item.callback_program.executable)
+        {
+            msg!("VRF Coordinator: Callback program does not match requester");
+            return Err(ProgramError::InvalidAccountData);
         }
 
-        // Create VRF result
-        let vrf_result = VrfResult {
-            randomness,
-            proof: proof.clone(),
-            proof_slot: current_slot,
-            request_id,
-        };
+        // Verify the proof on the request seed. The randomness is derived from the
+        // VRF output, so the oracle cannot choose it.
+        let output = kamui_ecvrf::verify(&oracle_config.vrf_key, &request.seed, item.proof).map_err(|e| {
+            msg!("VRF Coordinator: {}", e);
+            VrfCoordinatorError::InvalidVrfProof
+        })?;
+        let randomness = (0..request.num_words).map(|i| Self::randomness_word(&output, i)).collect();
 
-        // Create VRF result account
-        let result_seeds = &[
-            b"vrf_result",
-            &request_id,
-        ];
-        let (expected_result_address, result_bump) = Pubkey::find_program_address(result_seeds, program_id);
-        
-        if expected_result_address != *vrf_result_account.key {
+        let (expected_result_address, result_bump) = Pubkey::find_program_address(
+            &[b"vrf_result", &item.request_id],
+            program_id,
+        );
+        if expected_result_address != *item.vrf_result_account.key {
             msg!("VRF Coordinator: Result account address mismatch");
             return Err(ProgramError::InvalidArgument);
         }
 
+        // Creating the result account fails if it already holds data
+        if !item.vrf_result_account.data_is_empty() || *item.vrf_result_account.owner != system_program::id() {
+            msg!("VRF Coordinator: Result account already in use");
+            return Err(VrfCoordinatorError::RequestAlreadyFulfilled.into());
+        }
+
         let result_size = 8 + // discriminator
                          4 + (64 * request.num_words as usize) + // randomness
-                         4 + proof.len() + // proof
+                         4 + item.proof.len() + // proof
                          8 + // proof_slot
                          32; // request_id
 
+        Ok(VerifiedFulfillment {
+            request,
+            randomness,
+            result_bump,
+            result_size,
+        })
+    }
+
+    /// Word `index` of the randomness for a VRF output
+    fn randomness_word(output: &[u8; 64], index: u32) -> [u8; 64] {
+        let mut hasher = Sha512::new();
+        hasher.update(output);
+        hasher.update(index.to_le_bytes());
+        hasher.finalize().into()
+    }
+
+    /// Check the proof from `ecvrf-cli pop` that `authority` holds the secret key of `vrf_key`
+    fn verify_proof_of_possession(
+        vrf_key: &[u8; 32],
+        authority: &Pubkey,
+        proof_of_possession: &[u8; 64],
+    ) -> ProgramResult {
+        kamui_ecvrf::pop::verify_proof_of_possession(vrf_key, &authority.to_bytes(), proof_of_possession)
+            .map_err(|e| {
+                msg!("VRF Coordinator: Invalid proof of possession: {:?}", e);
+                VrfCoordinatorError::InvalidProofOfPossession.into()
+            })
+    }
+
+    /// Check the oracle can fund every result account before any of them is created
+    fn check_fulfillment_funds(
+        oracle: &AccountInfo,
+        fulfillments: &[(&FulfillmentItem, &VerifiedFulfillment)],
+    ) -> ProgramResult {
         let rent = Rent::get()?;
-        let result_lamports = rent.minimum_balance(result_size);
+        let required: u64 = fulfillments
+            .iter()
+            .map(|(item, verified)| {
+                rent.minimum_balance(verified.result_size)
+                    .saturating_sub(item.vrf_result_account.lamports())
+            })
+            .sum();
+
+        if oracle.lamports() < required {
+            msg!("VRF Coordinator: Oracle needs {} lamports for result accounts, has {}", required, oracle.lamports());
+            return Err(VrfCoordinatorError::InsufficientBalance.into());
+        }
 
-        // Create result account
-            invoke_signed(
-                &system_instruction::create_account(
-                    oracle.key,
-                    vrf_result_account.key,
-                result_lamports,
-                result_size as u64,
-                    program_id,
-                ),
-                &[
-                    oracle.clone(),
-                    vrf_result_account.clone(),
-                    system_program.clone(),
-                ],
-            &[&[
-                b"vrf_result",
-                &request_id,
-                &[result_bump],
-            ]],
+        Ok(())
+    }
+
+    /// Write a verified fulfillment: create the result account, mark the request
+    /// fulfilled in its account and in `request_pool`, update the subscription and
+    /// call back the requester. The caller saves `request_pool`.
+    fn write_fulfillment<'info>(
+        program_id: &Pubkey,
+        oracle: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+        request_pool: &mut RequestPool,
+        item: &FulfillmentItem<'_, 'info>,
+        verified: VerifiedFulfillment,
+        current_slot: u64,
+    ) -> ProgramResult {
+        let VerifiedFulfillment { mut request, randomness, result_bump, result_size } = verified;
+        let request_id = item.request_id;
+
+        let vrf_result = VrfResult {
+            randomness,
+            proof: item.proof.to_vec(),
+            proof_slot: current_slot,
+            request_id,
+        };
+
+        Self::create_pda_account(
+            program_id,
+            oracle,
+            item.vrf_result_account,
+            system_program,
+            result_size,
+            &[b"vrf_result", &request_id, &[result_bump]],
         )?;
 
         // Write result data
-        let mut result_data = vrf_result_account.try_borrow_mut_data()?;
-        result_data[0..8].copy_from_slice(&[82, 69, 83, 85, 76, 84, 0, 0]); // "RESULT\0\0" as bytes
-        vrf_result.serialize(&mut &mut result_data[8..])?;
+        {
+            let mut result_data = item.vrf_result_account.try_borrow_mut_data()?;
+            result_data[0..8].copy_from_slice(&[82, 69, 83, 85, 76, 84, 0, 0]); // "RESULT\0\0" as bytes
+            vrf_result.serialize(&mut &mut result_data[8..])?;
+        }
 
         // Update request status
+        {
             request.status = RequestStatus::Fulfilled;
-        request_data[0..8].copy_from_slice(&[82, 69, 81, 85, 69, 83, 84, 0]);
-        request.serialize(&mut &mut request_data[8..])?;
+            let mut request_data = item.request_account.try_borrow_mut_data()?;
+            request_data[0..8].copy_from_slice(&[82, 69, 81, 85, 69, 83, 84, 0]);
+            request.serialize(&mut &mut request_data[8..])?;
+        }
 
         // Update request summary in pool
-        request_summary.status = RequestStatus::Fulfilled;
-        pool_data[0..8].copy_from_slice(&[80, 79, 79, 76, 0, 0, 0, 0]);
-        request_pool.serialize(&mut &mut pool_data[8..])?;
+        if let Some(request_summary) = request_pool.requests.get_mut(&item.request_index) {
+            request_summary.status = RequestStatus::Fulfilled;
+        }
 
         // Update subscription data
         {
-            let mut subscription_data = subscription_account.try_borrow_data()?;
-            if subscription_data.len() <= 8 || &subscription_data[0..8] != &[83, 85, 66, 83, 67, 82, 73, 80] {
-                msg!("VRF Coordinator: Invalid subscription account");
-                return Err(ProgramError::InvalidAccountData);
-            }
+            let mut subscription_data = item.subscription_account.try_borrow_mut_data()?;
+            let mut subscription = EnhancedSubscription::deserialize(&mut &subscription_data[8..])?;
 
-            let mut subscription = EnhancedSubscription::try_from_slice(&subscription_data[8..])?;
-            
             // Decrement active requests count
             subscription.active_requests = subscription.active_requests.saturating_sub(1);
-            
+
             // Remove request key from tracking
             let mut truncated_key = [0u8; 16];
             truncated_key.copy_from_slice(&request_id[0..16]);
-            
+
             if let Some(pos) = subscription.request_keys.iter().position(|x| *x == truncated_key) {
                 subscription.request_keys.remove(pos);
             }
-            
+
             // Write back subscription data
-            subscription_data[0..8].copy_from_slice(&[83, 85, 66, 83, 67, 82, 73, 80]);
             subscription.serialize(&mut &mut subscription_data[8..])?;
         }
 
@@ -809,8 +971,8 @@ impl Processor {
             let callback_instruction = Instruction {
                 program_id: request.requester,
                 accounts: vec![
-                    AccountMeta::new_readonly(*request_account.key, false),
-                    AccountMeta::new_readonly(*vrf_result_account.key, false),
+                    AccountMeta::new_readonly(*item.request_account.key, false),
+                    AccountMeta::new_readonly(*item.vrf_result_account.key, false),
                 ],
                 data: callback_data,
             };
@@ -819,9 +981,9 @@ impl Processor {
             match invoke(
                 &callback_instruction,
                 &[
-                request_account.clone(),
-                    vrf_result_account.clone(),
-                    callback_program.clone(),
+                    item.request_account.clone(),
+                    item.vrf_result_account.clone(),
+                    item.callback_program.clone(),
                 ],
             ) {
                 Ok(_) => {
@@ -838,13 +1000,59 @@ impl Processor {
         VrfEvent::RandomnessFulfilled {
             request_id,
             requester: request.requester,
-            randomness: vrf_result.randomness[0],
+            randomness: vrf_result.randomness.first().copied().unwrap_or([0u8; 64]),
             oracle: *oracle.key,
         }.emit();
 
         Ok(())
     }
 
+    /// Create a PDA owned by this program. Anyone can send lamports to the address
+    /// beforehand, which makes `create_account` fail, so an address that already
+    /// holds lamports is topped up, allocated and assigned instead.
+    fn create_pda_account<'info>(
+        program_id: &Pubkey,
+        payer: &AccountInfo<'info>,
+        account: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+        size: usize,
+        seeds: &[&[u8]],
+    ) -> ProgramResult {
+        let required_lamports = Rent::get()?.minimum_balance(size);
+
+        if account.lamports() == 0 {
+            return invoke_signed(
+                &system_instruction::create_account(
+                    payer.key,
+                    account.key,
+                    required_lamports,
+                    size as u64,
+                    program_id,
+                ),
+                &[payer.clone(), account.clone(), system_program.clone()],
+                &[seeds],
+            );
+        }
+
+        let shortfall = required_lamports.saturating_sub(account.lamports());
+        if shortfall > 0 {
+            invoke(
+                &system_instruction::transfer(payer.key, account.key, shortfall),
+                &[payer.clone(), account.clone(), system_program.clone()],
+            )?;
+        }
+        invoke_signed(
+            &system_instruction::allocate(account.key, size as u64),
+            &[account.clone(), system_program.clone()],
+            &[seeds],
+        )?;
+        invoke_signed(
+            &system_instruction::assign(account.key, program_id),
+            &[account.clone(), system_program.clone()],
+            &[seeds],
+        )
+    }
+
     fn process_cancel_request(
         accounts: &[AccountInfo],
         request_id: Pubkey,
@@ -899,6 +1107,7 @@ impl Processor {
         accounts: &[AccountInfo],
         vrf_key: [u8; 32],
         stake_amount: u64,
+        proof_of_possession: [u8; 64],
     ) -> ProgramResult {
         let accounts_iter = &mut accounts.iter();
         let admin = next_account_info(accounts_iter)?;
@@ -909,6 +1118,8 @@ impl Processor {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
+        Self::verify_proof_of_possession(&vrf_key, admin.key, &proof_of_possession)?;
+
         let oracle_config = EnhancedOracle {
             vrf_key,
             stake_amount,
@@ -1124,6 +1335,7 @@ impl Processor {
         accounts: &[AccountInfo],
         vrf_key: [u8; 32],
         stake_amount: u64,
+        proof_of_possession: [u8; 64],
     ) -> ProgramResult {
         msg!("VRF Coordinator: Registering oracle...");
         let accounts_iter = &mut accounts.iter();
@@ -1138,6 +1350,9 @@ impl Processor {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
+        // The authority must hold the VRF key it registers
+        Self::verify_proof_of_possession(&vrf_key, authority.key, &proof_of_possession)?;
+
         // Load registry
         let mut registry_data = registry_account.try_borrow_data()?;
         if registry_data.len() <= 8 || &registry_data[0..8] != &[82, 69, 71, 73, 83, 84, 82, 89] {
@@ -1449,84 +1664,91 @@ impl Processor {
             return Err(VrfCoordinatorError::NoRequestsToProcess.into());
         }
         
-        let accounts_iter = &mut accounts.iter();
-        let oracle = next_account_info(accounts_iter)?;
-        let oracle_config = next_account_info(accounts_iter)?;
-        let request_pool = next_account_info(accounts_iter)?;
-        let system_program = next_account_info(accounts_iter)?;
-        
-        // Verify oracle is signer
-        if !oracle.is_signer {
-            msg!("VRF Coordinator: Error - Missing oracle signature");
-            return Err(ProgramError::MissingRequiredSignature);
+        if request_ids.len() > MAX_BATCH_SIZE {
+            msg!("VRF Coordinator: Error - Batch of {} exceeds limit of {}", request_ids.len(), MAX_BATCH_SIZE);
+            return Err(VrfCoordinatorError::BatchLimitExceeded.into());
         }
         
-        // Load oracle config
-        let oracle_data = oracle_config.try_borrow_data()?;
-        if oracle_data.len() <= 8 || &oracle_data[0..8] != &[79, 82, 65, 67, 76, 69, 0, 0] {
-            msg!("VRF Coordinator: Invalid oracle config account");
-            return Err(ProgramError::InvalidAccountData);
+        // Four fixed accounts followed by four accounts per request. A short or long
+        // account list would shift every later request onto the wrong accounts.
+        if accounts.len() != 4 + 4 * request_ids.len() {
+            msg!("VRF Coordinator: Error - Expected {} accounts, got {}", 4 + 4 * request_ids.len(), accounts.len());
+            return Err(VrfCoordinatorError::InvalidBatchParameters.into());
         }
         
-        let oracle_config = EnhancedOracle::try_from_slice(&oracle_data[8..])?;
-        
-        // Verify oracle authority
-        if oracle_config.authority != *oracle.key {
-            msg!("VRF Coordinator: Oracle authority mismatch");
-            return Err(VrfCoordinatorError::InvalidOracleAuthority.into());
-        }
+        let (fixed_accounts, item_accounts) = accounts.split_at(4);
+        let oracle = &fixed_accounts[0];
+        let oracle_config_account = &fixed_accounts[1];
+        let request_pool_account = &fixed_accounts[2];
+        let system_program = &fixed_accounts[3];
         
-        // Verify oracle is active
-        if !oracle_config.is_active {
-            msg!("VRF Coordinator: Oracle is not active");
-            return Err(VrfCoordinatorError::InvalidOracle.into());
-        }
+        let oracle_config = Self::load_fulfilling_oracle(program_id, oracle, oracle_config_account)?;
+        let mut request_pool = Self::load_request_pool(program_id, request_pool_account, pool_id)?;
+        let current_slot = Clock::get()?.slot;
 
-        // Process each request
-        let mut successful_count = 0;
-        for i in 0..request_ids.len() {
-            // We need to have at least 2 accounts per request - request account and result account
-            if accounts_iter.len() < 2 {
-                msg!("VRF Coordinator: Error - Not enough accounts provided for request {}", i);
-                continue;
-            }
-            
-            let request_account = next_account_info(accounts_iter)?;
-            let result_account = next_account_info(accounts_iter)?;
-            
-            // Try to fulfill each request
-            match Self::process_fulfill_randomness(
-                program_id,
-                &[
-                    oracle.clone(), 
-                    request_account.clone(), 
-                    result_account.clone(),
-                    request_pool.clone(),
-                    next_account_info(accounts_iter)?.clone(), // subscription
-                    next_account_info(accounts_iter)?.clone(), // callback program
-                    system_program.clone(),
-                ],
-                proofs[i].clone(),
-                public_keys[i].clone(),
-                request_ids[i],
-                pool_id,
-                request_indices[i],
-            ) {
-                Ok(_) => {
-                    successful_count += 1;
-                },
+        let items: Vec<FulfillmentItem> = item_accounts
+            .chunks_exact(4)
+            .enumerate()
+            .map(|(i, item)| FulfillmentItem {
+                request_account: &item[0],
+                vrf_result_account: &item[1],
+                subscription_account: &item[2],
+                callback_program: &item[3],
+                proof: &proofs[i],
+                public_key: &public_keys[i],
+                request_id: request_ids[i],
+                request_index: request_indices[i],
+            })
+            .collect();
+
+        // Verify every request before writing anything, so a request that fails
+        // leaves no trace and cannot abort the requests that passed
+        let mut verified = Vec::with_capacity(items.len());
+        let mut seen = BTreeSet::new();
+        for (i, item) in items.iter().enumerate() {
+            let result = if seen.insert(item.request_id) {
+                Self::verify_fulfillment(program_id, &oracle_config, &request_pool, pool_id, item, current_slot)
+            } else {
+                msg!("VRF Coordinator: Request {} appears twice in the batch", i);
+                Err(VrfCoordinatorError::InvalidBatchParameters.into())
+            };
+
+            match result {
+                Ok(fulfillment) => verified.push((item, fulfillment)),
                 Err(e) => {
                     msg!("VRF Coordinator: Failed to process request {}: {:?}", i, e);
-                    // Continue with other requests
+                    VrfEvent::BatchItemFailed {
+                        request_id: item.request_id,
+                        oracle: *oracle.key,
+                        pool_id,
+                        index: i as u32,
+                    }.emit();
                 }
             }
         }
+
+        if verified.is_empty() {
+            msg!("VRF Coordinator: Error - No request in the batch was fulfilled");
+            return Err(VrfCoordinatorError::BatchFulfillmentFailed.into());
+        }
+
+        let funding: Vec<(&FulfillmentItem, &VerifiedFulfillment)> =
+            verified.iter().map(|(item, fulfillment)| (*item, fulfillment)).collect();
+        Self::check_fulfillment_funds(oracle, &funding)?;
+
+        let successful_count = verified.len() as u32;
+        let failed_count = items.len() as u32 - successful_count;
+        for (item, fulfillment) in verified {
+            Self::write_fulfillment(program_id, oracle, system_program, &mut request_pool, item, fulfillment, current_slot)?;
+        }
+        Self::save_request_pool(request_pool_account, &request_pool)?;
         
         // Emit batch processed event
         VrfEvent::BatchProcessed {
             oracle: *oracle.key,
             pool_id,
             count: successful_count,
+            failed: failed_count,
         }.emit();
         
         Ok(())
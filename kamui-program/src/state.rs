@@ -19,6 +19,7 @@ pub const MAX_REQUESTS_PER_SUBSCRIPTION: u16 = 100;
 pub const MAX_ACTIVE_ORACLES: u16 = 10;
 pub const REQUEST_EXPIRY_SLOTS: u64 = 3 * 60 * 60; // 3 hours in slots
 pub const ORACLE_ROTATION_FREQUENCY: u64 = 500; // Rotate oracles every 500 slots
+pub const MAX_BATCH_SIZE: usize = 16;
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Clone)]
 pub enum RequestStatus {
@@ -0,0 +1,403 @@
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    kamui_program::{
+        instruction::VrfCoordinatorInstruction,
+        processor::Processor,
+        state::{
+            EnhancedOracle, EnhancedSubscription, RandomnessRequest, RequestPool, RequestStatus,
+            RequestSummary, VrfResult,
+        },
+    },
+    mangekyou::kamui_vrf::{ecvrf::ECVRFKeyPair, VRFKeyPair, VRFProof},
+    sha2::{Digest, Sha512},
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        system_program,
+    },
+    solana_program_test::{processor, BanksClient, ProgramTest},
+    solana_sdk::{
+        account::Account,
+        hash::Hash,
+        signature::{Keypair, Signer},
+        transaction::Transaction,
+    },
+    std::collections::BTreeMap,
+};
+
+// The coordinator accounts are written directly, so each test only runs the
+// fulfillment it covers.
+
+const POOL_ID: u8 = 0;
+const NUM_WORDS: u32 = 2;
+
+struct TestRequest {
+    address: Pubkey,
+    result: Pubkey,
+    request_id: [u8; 32],
+    request_index: u32,
+    seed: [u8; 32],
+}
+
+struct Coordinator {
+    program_id: Pubkey,
+    oracle: Keypair,
+    oracle_config: Pubkey,
+    vrf_keypair: ECVRFKeyPair,
+    subscription: Pubkey,
+    pool: Pubkey,
+    requests: Vec<TestRequest>,
+}
+
+fn program_account<T: BorshSerialize>(program_id: &Pubkey, discriminator: &[u8; 8], state: &T) -> Account {
+    let mut data = discriminator.to_vec();
+    data.extend_from_slice(&state.try_to_vec().unwrap());
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: *program_id,
+        ..Account::default()
+    }
+}
+
+/// A registered oracle and a subscription with `count` pending requests
+fn coordinator(count: u32, oracle_lamports: u64) -> (ProgramTest, Coordinator) {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new("kamui_program", program_id, processor!(Processor::process));
+
+    let oracle = Keypair::new();
+    let vrf_keypair = ECVRFKeyPair::generate(&mut rand::thread_rng());
+    program_test.add_account(
+        oracle.pubkey(),
+        Account {
+            lamports: oracle_lamports,
+            owner: system_program::id(),
+            ..Account::default()
+        },
+    );
+
+    let (oracle_config, _) = Pubkey::find_program_address(&[b"oracle", oracle.pubkey().as_ref()], &program_id);
+    let vrf_key: [u8; 32] = vrf_keypair.pk.as_ref().try_into().unwrap();
+    program_test.add_account(
+        oracle_config,
+        program_account(
+            &program_id,
+            &[79, 82, 65, 67, 76, 69, 0, 0],
+            &EnhancedOracle {
+                authority: oracle.pubkey(),
+                vrf_key,
+                stake_amount: 0,
+                reputation: 0,
+                last_active: 0,
+                is_active: true,
+                fulfillment_count: 0,
+                failure_count: 0,
+            },
+        ),
+    );
+
+    let subscription = Pubkey::new_unique();
+    let (pool, _) = Pubkey::find_program_address(&[b"request_pool", subscription.as_ref(), &[POOL_ID]], &program_id);
+    let requester = Pubkey::new_unique();
+
+    let mut requests = Vec::new();
+    let mut summaries = BTreeMap::new();
+    for request_index in 0..count {
+        let seed: [u8; 32] = rand::random();
+        let request_id: [u8; 32] = rand::random();
+        let (address, _) = Pubkey::find_program_address(&[b"vrf_request", &request_id], &program_id);
+        let (result, _) = Pubkey::find_program_address(&[b"vrf_result", &request_id], &program_id);
+
+        program_test.add_account(
+            address,
+            program_account(
+                &program_id,
+                &[82, 69, 81, 85, 69, 83, 84, 0],
+                &RandomnessRequest {
+                    subscription,
+                    seed,
+                    requester,
+                    callback_data: Vec::new(),
+                    request_slot: 0,
+                    status: RequestStatus::Pending,
+                    num_words: NUM_WORDS,
+                    callback_gas_limit: 100_000,
+                    pool_id: POOL_ID,
+                    request_index,
+                    request_id,
+                },
+            ),
+        );
+        summaries.insert(
+            request_index,
+            RequestSummary {
+                requester,
+                seed_hash: [0u8; 32],
+                timestamp: 0,
+                status: RequestStatus::Pending,
+                request_slot: 0,
+                callback_gas_limit: 100_000,
+            },
+        );
+        requests.push(TestRequest { address, result, request_id, request_index, seed });
+    }
+
+    program_test.add_account(
+        subscription,
+        program_account(
+            &program_id,
+            &[83, 85, 66, 83, 67, 82, 73, 80],
+            &EnhancedSubscription {
+                owner: Pubkey::new_unique(),
+                balance: 0,
+                min_balance: 0,
+                confirmations: 1,
+                active_requests: count as u16,
+                max_requests: 100,
+                request_counter: count as u64,
+                request_keys: requests.iter().map(|r| r.request_id[..16].try_into().unwrap()).collect(),
+                pool_ids: vec![POOL_ID],
+            },
+        ),
+    );
+    program_test.add_account(
+        pool,
+        program_account(
+            &program_id,
+            &[80, 79, 79, 76, 0, 0, 0, 0],
+            &RequestPool {
+                subscription,
+                pool_id: POOL_ID,
+                request_count: count,
+                max_size: 100,
+                requests: summaries,
+                last_processed_slot: 0,
+            },
+        ),
+    );
+
+    (
+        program_test,
+        Coordinator { program_id, oracle, oracle_config, vrf_keypair, subscription, pool, requests },
+    )
+}
+
+impl Coordinator {
+    fn proof(&self, seed: &[u8]) -> Vec<u8> {
+        self.vrf_keypair.output(seed).1.to_bytes()
+    }
+
+    fn fulfill_instruction(&self, request: &TestRequest, proof: Vec<u8>) -> Instruction {
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new(self.oracle.pubkey(), true),
+                AccountMeta::new(request.address, false),
+                AccountMeta::new(request.result, false),
+                AccountMeta::new(self.pool, false),
+                AccountMeta::new(self.subscription, false),
+                AccountMeta::new_readonly(Pubkey::new_unique(), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(self.oracle_config, false),
+            ],
+            data: VrfCoordinatorInstruction::FulfillRandomness {
+                proof,
+                public_key: self.vrf_keypair.pk.as_ref().to_vec(),
+                request_id: request.request_id,
+                pool_id: POOL_ID,
+                request_index: request.request_index,
+            }
+            .try_to_vec()
+            .unwrap(),
+        }
+    }
+
+    fn batch_instruction(&self, batch: &[(&TestRequest, Vec<u8>)]) -> Instruction {
+        let mut accounts = vec![
+            AccountMeta::new(self.oracle.pubkey(), true),
+            AccountMeta::new_readonly(self.oracle_config, false),
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ];
+        for (request, _) in batch {
+            accounts.push(AccountMeta::new(request.address, false));
+            accounts.push(AccountMeta::new(request.result, false));
+            accounts.push(AccountMeta::new(self.subscription, false));
+            accounts.push(AccountMeta::new_readonly(Pubkey::new_unique(), false));
+        }
+
+        Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: VrfCoordinatorInstruction::ProcessRequestBatch {
+                request_ids: batch.iter().map(|(request, _)| request.request_id).collect(),
+                proofs: batch.iter().map(|(_, proof)| proof.clone()).collect(),
+                public_keys: vec![self.vrf_keypair.pk.as_ref().to_vec(); batch.len()],
+                pool_id: POOL_ID,
+                request_indices: batch.iter().map(|(request, _)| request.request_index).collect(),
+            }
+            .try_to_vec()
+            .unwrap(),
+        }
+    }
+
+    async fn send(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: Hash,
+        instruction: Instruction,
+    ) -> bool {
+        let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+        transaction.sign(&[payer, &self.oracle], recent_blockhash);
+        banks_client.process_transaction(transaction).await.is_ok()
+    }
+}
+
+async fn request_status(banks_client: &mut BanksClient, request: &TestRequest) -> RequestStatus {
+    let account = banks_client.get_account(request.address).await.unwrap().unwrap();
+    RandomnessRequest::try_from_slice(&account.data[8..]).unwrap().status
+}
+
+async fn pool_status(banks_client: &mut BanksClient, coordinator: &Coordinator, request: &TestRequest) -> RequestStatus {
+    let account = banks_client.get_account(coordinator.pool).await.unwrap().unwrap();
+    let pool = RequestPool::try_from_slice(&account.data[8..]).unwrap();
+    pool.requests[&request.request_index].status.clone()
+}
+
+async fn active_requests(banks_client: &mut BanksClient, coordinator: &Coordinator) -> u16 {
+    let account = banks_client.get_account(coordinator.subscription).await.unwrap().unwrap();
+    EnhancedSubscription::deserialize(&mut &account.data[8..]).unwrap().active_requests
+}
+
+/// The result stored for `request` must hold the oracle's VRF output on the seed
+async fn assert_fulfilled(banks_client: &mut BanksClient, coordinator: &Coordinator, request: &TestRequest) {
+    assert_eq!(request_status(banks_client, request).await, RequestStatus::Fulfilled);
+    assert_eq!(pool_status(banks_client, coordinator, request).await, RequestStatus::Fulfilled);
+
+    let account = banks_client.get_account(request.result).await.unwrap().unwrap();
+    assert_eq!(account.owner, coordinator.program_id);
+    assert_eq!(&account.data[..8], &[82, 69, 83, 85, 76, 84, 0, 0]);
+    let result = VrfResult::try_from_slice(&account.data[8..]).unwrap();
+
+    let (output, _) = coordinator.vrf_keypair.output(&request.seed);
+    let expected: Vec<[u8; 64]> = (0..NUM_WORDS)
+        .map(|i| Sha512::new().chain_update(output).chain_update(i.to_le_bytes()).finalize().into())
+        .collect();
+    assert_eq!(result.randomness, expected);
+    assert_eq!(result.request_id, request.request_id);
+}
+
+async fn assert_untouched(banks_client: &mut BanksClient, coordinator: &Coordinator, request: &TestRequest) {
+    assert_eq!(request_status(banks_client, request).await, RequestStatus::Pending);
+    assert_eq!(pool_status(banks_client, coordinator, request).await, RequestStatus::Pending);
+    assert!(banks_client.get_account(request.result).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_fulfill_randomness_verifies_proof() {
+    let (program_test, coordinator) = coordinator(1, 1_000_000_000);
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let request = &coordinator.requests[0];
+
+    // A proof for another input is rejected
+    let wrong_proof = coordinator.proof(b"another seed");
+    let ix = coordinator.fulfill_instruction(request, wrong_proof);
+    assert!(!coordinator.send(&mut banks_client, &payer, recent_blockhash, ix).await);
+    assert_untouched(&mut banks_client, &coordinator, request).await;
+
+    let ix = coordinator.fulfill_instruction(request, coordinator.proof(&request.seed));
+    assert!(coordinator.send(&mut banks_client, &payer, recent_blockhash, ix).await);
+    assert_fulfilled(&mut banks_client, &coordinator, request).await;
+    assert_eq!(active_requests(&mut banks_client, &coordinator).await, 0);
+}
+
+#[tokio::test]
+async fn test_batch_fulfills_every_request() {
+    let (program_test, coordinator) = coordinator(3, 1_000_000_000);
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let batch: Vec<_> = coordinator.requests.iter().map(|r| (r, coordinator.proof(&r.seed))).collect();
+    let ix = coordinator.batch_instruction(&batch);
+    assert!(coordinator.send(&mut banks_client, &payer, recent_blockhash, ix).await);
+
+    for request in &coordinator.requests {
+        assert_fulfilled(&mut banks_client, &coordinator, request).await;
+    }
+    assert_eq!(active_requests(&mut banks_client, &coordinator).await, 0);
+}
+
+#[tokio::test]
+async fn test_batch_skips_failing_requests_without_side_effects() {
+    let (program_test, coordinator) = coordinator(3, 1_000_000_000);
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let [first, second, third] = &coordinator.requests[..] else { unreachable!() };
+
+    // The second proof is for the wrong seed, and the first request appears twice
+    let batch = vec![
+        (first, coordinator.proof(&first.seed)),
+        (second, coordinator.proof(&third.seed)),
+        (third, coordinator.proof(&third.seed)),
+        (first, coordinator.proof(&first.seed)),
+    ];
+    let ix = coordinator.batch_instruction(&batch);
+    assert!(coordinator.send(&mut banks_client, &payer, recent_blockhash, ix).await);
+
+    assert_fulfilled(&mut banks_client, &coordinator, first).await;
+    assert_untouched(&mut banks_client, &coordinator, second).await;
+    assert_fulfilled(&mut banks_client, &coordinator, third).await;
+    assert_eq!(active_requests(&mut banks_client, &coordinator).await, 1);
+}
+
+#[tokio::test]
+async fn test_batch_fails_when_no_request_verifies() {
+    let (program_test, coordinator) = coordinator(2, 1_000_000_000);
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let batch: Vec<_> = coordinator.requests.iter().map(|r| (r, coordinator.proof(b"wrong"))).collect();
+    let ix = coordinator.batch_instruction(&batch);
+    assert!(!coordinator.send(&mut banks_client, &payer, recent_blockhash, ix).await);
+
+    for request in &coordinator.requests {
+        assert_untouched(&mut banks_client, &coordinator, request).await;
+    }
+    assert_eq!(active_requests(&mut banks_client, &coordinator).await, 2);
+}
+
+#[tokio::test]
+async fn test_batch_checks_oracle_funds_before_writing() {
+    // Enough for one result account but not for two
+    let (program_test, coordinator) = coordinator(2, 3_000_000);
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let batch: Vec<_> = coordinator.requests.iter().map(|r| (r, coordinator.proof(&r.seed))).collect();
+    let ix = coordinator.batch_instruction(&batch);
+    assert!(!coordinator.send(&mut banks_client, &payer, recent_blockhash, ix).await);
+
+    for request in &coordinator.requests {
+        assert_untouched(&mut banks_client, &coordinator, request).await;
+    }
+}
+
+#[tokio::test]
+async fn test_batch_adopts_prefunded_result_account() {
+    let (mut program_test, coordinator) = coordinator(2, 1_000_000_000);
+    // Anyone can send lamports to a result address before the oracle fulfills
+    program_test.add_account(
+        coordinator.requests[1].result,
+        Account {
+            lamports: 1_000,
+            owner: system_program::id(),
+            ..Account::default()
+        },
+    );
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let batch: Vec<_> = coordinator.requests.iter().map(|r| (r, coordinator.proof(&r.seed))).collect();
+    let ix = coordinator.batch_instruction(&batch);
+    assert!(coordinator.send(&mut banks_client, &payer, recent_blockhash, ix).await);
+
+    for request in &coordinator.requests {
+        assert_fulfilled(&mut banks_client, &coordinator, request).await;
+    }
+}
@@ -15,6 +15,11 @@ import BN from 'bn.js';
 import { expect } from 'chai';
 import { Program } from '@coral-xyz/anchor';
 
+// VRF key and proof of possession for the oracle authority, from
+// `ecvrf-cli pop --authority <oracle authority>`
+const ORACLE_VRF_KEY = '7a66a0fe0f2bcdcea5bfb97e3e9f6b298d25899052721bc2b4f3cb570a921b23';
+const ORACLE_PROOF_OF_POSSESSION = '92acfef779459c86015f7a4946800e97b63fb9b8980d6e04ab40537939cedf1539b0a055d697f83bf07491434ed557bb4a4453aef58bf33ecd892901866dec0f';
+
 describe('Kamui VRF Comprehensive Tests', () => {
     // Load the IDL
     const idlContent = JSON.parse(fs.readFileSync('./target/idl/kamui_vrf.json', 'utf8'));
@@ -105,8 +110,10 @@ describe('Kamui VRF Comprehensive Tests', () => {
      */
 
     // Create and fund a keypair
-    async function createFundedKeypair(lamports = 0.1 * LAMPORTS_PER_SOL): Promise<Keypair> {
-        const keypair = Keypair.generate();
+    async function createFundedKeypair(
+        lamports = 0.1 * LAMPORTS_PER_SOL,
+        keypair = Keypair.generate()
+    ): Promise<Keypair> {
 
         // Check if payer has enough balance
         const payerBalance = await connection.getBalance(payerKeypair.publicKey);
@@ -206,7 +213,8 @@ describe('Kamui VRF Comprehensive Tests', () => {
 
         // Generate test keypairs
         adminKeypair = await createFundedKeypair();
-        oracleKeypair = await createFundedKeypair();
+        // Fixed so that the proof of possession above is bound to it
+        oracleKeypair = await createFundedKeypair(undefined, Keypair.fromSeed(new Uint8Array(32).fill(1)));
         secondOracleKeypair = await createFundedKeypair();
         userKeypair = await createFundedKeypair();
 
@@ -296,8 +304,9 @@ describe('Kamui VRF Comprehensive Tests', () => {
                 // Create a transaction using the Anchor program
                 const tx = await program.methods
                     .register_oracle(
-                        Array.from(vrfKeypair.publicKey),
-                        new BN(LAMPORTS_PER_SOL)
+                        Array.from(Buffer.from(ORACLE_VRF_KEY, 'hex')),
+                        new BN(LAMPORTS_PER_SOL),
+                        Array.from(Buffer.from(ORACLE_PROOF_OF_POSSESSION, 'hex'))
                     )
                     .accounts({
                         oracleAuthority: oracleKeypair.publicKey,
@@ -557,7 +566,7 @@ describe('Kamui VRF Comprehensive Tests', () => {
                 const tx = await program.methods
                     .fulfill_randomness(
                         Array.from(proof),
-                        Array.from(vrfKeypair.publicKey),
+                        Array.from(Buffer.from(ORACLE_VRF_KEY, 'hex')),
                         Array.from(requestId),
                         poolId,
                         0 // request index
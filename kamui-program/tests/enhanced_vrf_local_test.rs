@@ -2,6 +2,7 @@ use {
     borsh::{BorshDeserialize, BorshSerialize},
     mangekyou::kamui_vrf::{
         ecvrf::ECVRFKeyPair,
+        pop::ECVRFProofOfPossession,
         VRFKeyPair,
     },
     solana_program::{
@@ -137,6 +138,12 @@ async fn test_enhanced_vrf_direct_proof() {
         data: VrfCoordinatorInstruction::RegisterOracle {
             vrf_key: vrf_keypair.pk.as_ref().try_into().unwrap(),
             stake_amount: 10_000_000,
+            proof_of_possession: ECVRFProofOfPossession::prove(
+                &vrf_keypair,
+                &oracle.pubkey().to_bytes(),
+                &mut rand::thread_rng(),
+            )
+            .to_bytes(),
         }
         .try_to_vec()
         .unwrap(),
@@ -237,7 +244,8 @@ async fn test_enhanced_vrf_direct_proof() {
     // Step 6: Request Randomness
     println!("Step 6: Requesting Randomness");
     let seed: [u8; 32] = rand::random();
-    let callback_data = vec![1, 2, 3, 4];
+    // The requester is a wallet rather than a program, so no callback is invoked
+    let callback_data = Vec::new();
     let num_words = 1;
     let request_id = RequestPool::generate_request_id(
         &seed,
@@ -289,7 +297,7 @@ async fn test_enhanced_vrf_direct_proof() {
     // Step 8: Fulfill Randomness with the generated proof
     println!("Step 8: Fulfilling Randomness with generated proof");
     let (result_pda, _) = find_program_address(
-        &[b"vrf_result", request_pda.as_ref()],
+        &[b"vrf_result", &request_id],
         &program_id,
     );
     
@@ -301,7 +309,9 @@ async fn test_enhanced_vrf_direct_proof() {
             AccountMeta::new(result_pda, false),
             AccountMeta::new(pool_pda, false),
             AccountMeta::new(subscription_pda, false),
+            AccountMeta::new_readonly(wallet.pubkey(), false),
             AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(oracle_config_pda, false),
         ],
         data: VrfCoordinatorInstruction::FulfillRandomness {
             proof: proof_bytes,
@@ -358,7 +368,7 @@ async fn test_enhanced_vrf_direct_proof() {
             ],
             data: VrfCoordinatorInstruction::RequestRandomness {
                 seed,
-                callback_data: vec![i as u8],
+                callback_data: Vec::new(),
                 num_words: 1,
                 minimum_confirmations: 1,
                 callback_gas_limit: 100_000,
@@ -385,14 +395,24 @@ async fn test_enhanced_vrf_direct_proof() {
     let batch_proof_bytes: Vec<Vec<u8>> = proofs;
     let batch_public_keys: Vec<Vec<u8>> = vec![vrf_pubkey.clone(); request_ids.len()];
     
+    let mut accounts = vec![
+        AccountMeta::new(oracle.pubkey(), true),
+        AccountMeta::new_readonly(oracle_config_pda, false),
+        AccountMeta::new(pool_pda, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    for request_id in &request_ids {
+        let (request_pda, _) = find_program_address(&[b"vrf_request", request_id], &program_id);
+        let (result_pda, _) = find_program_address(&[b"vrf_result", request_id], &program_id);
+        accounts.push(AccountMeta::new(request_pda, false));
+        accounts.push(AccountMeta::new(result_pda, false));
+        accounts.push(AccountMeta::new(subscription_pda, false));
+        accounts.push(AccountMeta::new_readonly(wallet.pubkey(), false));
+    }
+    
     let ix = Instruction {
         program_id,
-        accounts: vec![
-            AccountMeta::new(oracle.pubkey(), true),
-            AccountMeta::new(oracle_config_pda, false),
-            AccountMeta::new(pool_pda, false),
-            AccountMeta::new_readonly(system_program::id(), false),
-        ],
+        accounts,
         data: VrfCoordinatorInstruction::ProcessRequestBatch {
             request_ids: request_ids.clone(),
             proofs: batch_proof_bytes,
@@ -4,6 +4,11 @@ import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
 import { KamuiVrf } from "../target/types/kamui_vrf";
 import { expect } from "chai";
 
+// VRF key and proof of possession for the oracle authority, from
+// `ecvrf-cli pop --authority <oracle authority>`
+const ORACLE_VRF_KEY = "7a66a0fe0f2bcdcea5bfb97e3e9f6b298d25899052721bc2b4f3cb570a921b23";
+const ORACLE_PROOF_OF_POSSESSION = "92acfef779459c86015f7a4946800e97b63fb9b8980d6e04ab40537939cedf1539b0a055d697f83bf07491434ed557bb4a4453aef58bf33ecd892901866dec0f";
+
 describe("Oracle Tests", () => {
     // Configure the client to use devnet
     const provider = anchor.AnchorProvider.env();
@@ -19,7 +24,8 @@ describe("Oracle Tests", () => {
     before(async () => {
         // Generate test keypairs
         adminKeypair = Keypair.generate();
-        oracleKeypair = Keypair.generate();
+        // Fixed so that the proof of possession below is bound to it
+        oracleKeypair = Keypair.fromSeed(new Uint8Array(32).fill(1));
 
         // Derive PDAs
         [registryPda] = PublicKey.findProgramAddressSync(
@@ -70,11 +76,12 @@ describe("Oracle Tests", () => {
             expect(registry.oracleCount).to.equal(0);
 
             // Register an oracle
-            const vrfKey = Buffer.alloc(32).fill(1);
+            const vrfKey = Buffer.from(ORACLE_VRF_KEY, "hex");
             await program.methods
                 .registerOracle(
                     Array.from(vrfKey),
-                    new anchor.BN(1000000) // stake_amount
+                    new anchor.BN(1000000), // stake_amount
+                    Array.from(Buffer.from(ORACLE_PROOF_OF_POSSESSION, "hex"))
                 )
                 .accounts({
                     oracleAuthority: oracleKeypair.publicKey,
@@ -5,6 +5,11 @@ import { KamuiVrf } from "../target/types/kamui_vrf";
 import { KamuiVrfConsumer } from "../target/types/kamui_vrf_consumer";
 import { expect } from "chai";
 
+// VRF key and proof of possession for the oracle authority, from
+// `ecvrf-cli pop --authority <oracle authority>`
+const ORACLE_VRF_KEY = "42b1b195493d8977f9432c1ea8208a8cf9adba1be06ed555ee1732c5b0637261";
+const ORACLE_PROOF_OF_POSSESSION = "c0d8d21e6878eb638dd9be2da45665f8258ac71a46e19fb6e27cc29f2baf9c279caaaf0062bb08ae740a1664ab96ba2e49e6651313c545c88e0b25cd4a4cc109";
+
 describe("VRF Consumer Tests", () => {
     // Configure the client to use devnet
     const provider = anchor.AnchorProvider.env();
@@ -25,7 +30,8 @@ describe("VRF Consumer Tests", () => {
     before(async () => {
         // Generate test keypairs
         ownerKeypair = Keypair.generate();
-        oracleKeypair = Keypair.generate();
+        // Fixed so that the proof of possession below is bound to it
+        oracleKeypair = Keypair.fromSeed(new Uint8Array(32).fill(2));
 
         // Derive PDAs
         [registryPda] = PublicKey.findProgramAddressSync(
@@ -88,11 +94,12 @@ describe("VRF Consumer Tests", () => {
                 .rpc();
 
             // Register an oracle
-            const vrfKey = Buffer.alloc(32).fill(1);
+            const vrfKey = Buffer.from(ORACLE_VRF_KEY, "hex");
             await vrfProgram.methods
                 .registerOracle(
                     Array.from(vrfKey),
-                    new anchor.BN(1000000) // stake_amount
+                    new anchor.BN(1000000), // stake_amount
+                    Array.from(Buffer.from(ORACLE_PROOF_OF_POSSESSION, "hex"))
                 )
                 .accounts({
                     oracleAuthority: oracleKeypair.publicKey,
@@ -1,24 +1,128 @@
 // Copyright (c) 2022, Mangekyou Network, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+use mangekyou::encoding::{Base58, Encoding};
 use mangekyou::kamui_vrf::ecvrf::{ECVRFKeyPair, ECVRFPrivateKey, ECVRFProof, ECVRFPublicKey};
+use mangekyou::kamui_vrf::pop::{ECVRFProofOfPossession, AUTHORITY_LENGTH};
 use mangekyou::kamui_vrf::{VRFKeyPair, VRFProof};
+use mangekyou::keystore::{KdfParams, Keystore};
 use rand::thread_rng;
-use std::io::{Error, ErrorKind};
+use std::fs::{self, OpenOptions};
+use std::io::{Error, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Environment variable read for the keystore password when no password file is given.
+const PASSWORD_ENV: &str = "ECVRF_KEYSTORE_PASSWORD";
 
 #[derive(Parser)]
 #[command(name = "ecvrf-cli")]
 #[command(about = "Elliptic Curve Verifiable Random Function (ECVRF) over Ristretto255 according to draft-irtf-cfrg-vrf-15.", long_about = None)]
 enum Command {
-    /// Generate a key pair for proving and verification.
-    Keygen,
+    /// Generate a key pair for proving and verification and write it to an encrypted keystore.
+    /// Same as `keystore create`; only the public key is printed.
+    Keygen(CreateArguments),
 
     /// Create an output/hash and a proof.
     Prove(ProveArguments),
 
     /// Verify an output/hash and a proof.
     Verify(VerifyArguments),
+
+    /// Create a proof of possession of a secret key, required to register it as an oracle key.
+    Pop(PopArguments),
+
+    /// Manage password-protected keystores.
+    #[command(subcommand)]
+    Keystore(KeystoreCommand),
+}
+
+#[derive(Subcommand)]
+enum KeystoreCommand {
+    /// Generate a new key pair and write it to an encrypted keystore.
+    Create(CreateArguments),
+
+    /// Encrypt an existing secret key into a keystore.
+    Import(ImportArguments),
+
+    /// Decrypt a keystore and print its hex encoded secret key.
+    Export(ExportArguments),
+
+    /// Print the public metadata of a keystore. Does not require the password.
+    Inspect(InspectArguments),
+}
+
+/// Key derivation function used to encrypt a keystore.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Kdf {
+    Argon2id,
+    Scrypt,
+}
+
+impl From<Kdf> for KdfParams {
+    fn from(kdf: Kdf) -> Self {
+        match kdf {
+            Kdf::Argon2id => KdfParams::default(),
+            Kdf::Scrypt => KdfParams::scrypt(),
+        }
+    }
+}
+
+#[derive(Parser, Clone)]
+struct PasswordArguments {
+    /// File containing the keystore password. If omitted, the password is read from the
+    /// ECVRF_KEYSTORE_PASSWORD environment variable.
+    #[clap(long)]
+    password_file: Option<PathBuf>,
+}
+
+#[derive(Parser, Clone)]
+struct CreateArguments {
+    /// Path of the keystore to create. Must not exist.
+    #[clap(short, long)]
+    output: PathBuf,
+
+    #[clap(flatten)]
+    password: PasswordArguments,
+
+    /// Key derivation function.
+    #[clap(long, value_enum, default_value = "argon2id")]
+    kdf: Kdf,
+}
+
+#[derive(Parser, Clone)]
+struct ImportArguments {
+    /// Path of the keystore to create. Must not exist.
+    #[clap(short, long)]
+    output: PathBuf,
+
+    /// File containing the hex encoded secret key. If omitted, the key is read from stdin.
+    #[clap(long)]
+    secret_key_file: Option<PathBuf>,
+
+    #[clap(flatten)]
+    password: PasswordArguments,
+
+    /// Key derivation function.
+    #[clap(long, value_enum, default_value = "argon2id")]
+    kdf: Kdf,
+}
+
+#[derive(Parser, Clone)]
+struct ExportArguments {
+    /// Path of the keystore.
+    #[clap(short, long)]
+    keystore: PathBuf,
+
+    #[clap(flatten)]
+    password: PasswordArguments,
+}
+
+#[derive(Parser, Clone)]
+struct InspectArguments {
+    /// Path of the keystore.
+    #[clap(short, long)]
+    keystore: PathBuf,
 }
 
 #[derive(Parser, Clone)]
@@ -51,6 +155,20 @@ struct VerifyArguments {
     public_key: String,
 }
 
+#[derive(Parser, Clone)]
+struct PopArguments {
+    /// Path of the keystore holding the oracle key.
+    #[clap(short, long)]
+    keystore: PathBuf,
+
+    #[clap(flatten)]
+    password: PasswordArguments,
+
+    /// Base58 encoding of the oracle authority that will register the key.
+    #[clap(short, long)]
+    authority: String,
+}
+
 fn main() {
     match execute(Command::parse()) {
         Ok(res) => {
@@ -66,19 +184,7 @@ fn main() {
 
 fn execute(cmd: Command) -> Result<String, std::io::Error> {
     match cmd {
-        Command::Keygen => {
-            let keypair = ECVRFKeyPair::generate(&mut thread_rng());
-            let sk_string =
-                hex::encode(&keypair.sk);
-            let pk_string =
-                hex::encode(&keypair.pk);
-
-            let mut result = "Secret key: ".to_string();
-            result.push_str(&sk_string);
-            result.push_str("\nPublic key: ");
-            result.push_str(&pk_string);
-            Ok(result)
-        }
+        Command::Keygen(arguments) => execute_keystore(KeystoreCommand::Create(arguments)),
 
         Command::Prove(arguments) => {
             // Parse inputs
@@ -130,21 +236,187 @@ fn execute(cmd: Command) -> Result<String, std::io::Error> {
             }
             Err(Error::new(ErrorKind::Other, "Proof is not correct."))
         }
+
+        Command::Pop(arguments) => {
+            // Parse inputs
+            let authority: [u8; AUTHORITY_LENGTH] = Base58::decode(&arguments.authority)
+                .ok()
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "Invalid authority."))?;
+
+            let kp = decrypt_keystore(&arguments.keystore, &arguments.password)?;
+            let pop = ECVRFProofOfPossession::prove(&kp, &authority, &mut thread_rng());
+
+            let mut result = "Public key: ".to_string();
+            result.push_str(&hex::encode(&kp.pk));
+            result.push_str("\nProof of possession: ");
+            result.push_str(&hex::encode(pop.to_bytes()));
+            Ok(result)
+        }
+
+        Command::Keystore(command) => execute_keystore(command),
+    }
+}
+
+fn execute_keystore(cmd: KeystoreCommand) -> Result<String, std::io::Error> {
+    match cmd {
+        KeystoreCommand::Create(arguments) => {
+            let password = read_password(&arguments.password)?;
+            let keypair = ECVRFKeyPair::generate(&mut thread_rng());
+            write_keystore(&arguments.output, &keypair, &password, arguments.kdf)?;
+            Ok(format!("Public key: {}", hex::encode(&keypair.pk)))
+        }
+
+        KeystoreCommand::Import(arguments) => {
+            let secret_key_hex = match &arguments.secret_key_file {
+                Some(path) => fs::read_to_string(path)?,
+                None => {
+                    let mut input = String::new();
+                    std::io::stdin().read_to_string(&mut input)?;
+                    input
+                }
+            };
+            let secret_key_bytes = hex::decode(secret_key_hex.trim())
+                .map_err(|_| Error::new(ErrorKind::InvalidInput, "Invalid private key."))?;
+            let secret_key = ECVRFPrivateKey::from_bytes(&secret_key_bytes)?;
+            let keypair = ECVRFKeyPair::from(secret_key);
+
+            let password = read_password(&arguments.password)?;
+            write_keystore(&arguments.output, &keypair, &password, arguments.kdf)?;
+            Ok(format!("Public key: {}", hex::encode(&keypair.pk)))
+        }
+
+        KeystoreCommand::Export(arguments) => {
+            let keypair = decrypt_keystore(&arguments.keystore, &arguments.password)?;
+
+            let mut result = "Secret key: ".to_string();
+            result.push_str(&hex::encode(&keypair.sk));
+            result.push_str("\nPublic key: ");
+            result.push_str(&hex::encode(&keypair.pk));
+            Ok(result)
+        }
+
+        KeystoreCommand::Inspect(arguments) => {
+            let keystore = read_keystore(&arguments.keystore)?;
+            let kdf = match keystore.crypto.kdf {
+                KdfParams::Argon2id { m_cost, t_cost, p_cost } => {
+                    format!("argon2id (m_cost={}, t_cost={}, p_cost={})", m_cost, t_cost, p_cost)
+                }
+                KdfParams::Scrypt { log_n, r, p } => {
+                    format!("scrypt (log_n={}, r={}, p={})", log_n, r, p)
+                }
+            };
+
+            let mut result = format!("Version:    {}", keystore.version);
+            result.push_str(&format!("\nScheme:     {}", keystore.scheme));
+            result.push_str(&format!("\nPublic key: {}", hex::encode(&keystore.public_key)));
+            result.push_str(&format!("\nCipher:     {}", keystore.crypto.cipher));
+            result.push_str(&format!("\nKDF:        {}", kdf));
+            Ok(result)
+        }
+    }
+}
+
+/// Read the password from `--password-file`, falling back to [PASSWORD_ENV].
+fn read_password(arguments: &PasswordArguments) -> Result<String, std::io::Error> {
+    let password = match &arguments.password_file {
+        Some(path) => fs::read_to_string(path)?
+            .trim_end_matches(['\r', '\n'])
+            .to_string(),
+        None => std::env::var(PASSWORD_ENV).map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("No password given. Use --password-file or set {}.", PASSWORD_ENV),
+            )
+        })?,
+    };
+    if password.is_empty() {
+        return Err(Error::new(ErrorKind::InvalidInput, "Password must not be empty."));
     }
+    Ok(password)
+}
+
+fn read_keystore(path: &Path) -> Result<Keystore, std::io::Error> {
+    Keystore::from_json(&fs::read_to_string(path)?)
+        .map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))
+}
+
+/// Read the keystore at `path` and decrypt it with the password from `password`.
+fn decrypt_keystore(
+    path: &Path,
+    password: &PasswordArguments,
+) -> Result<ECVRFKeyPair, std::io::Error> {
+    let keystore = read_keystore(path)?;
+    let password = read_password(password)?;
+    keystore
+        .to_ecvrf_keypair(password.as_bytes())
+        .map_err(|_| Error::new(ErrorKind::InvalidData, "Wrong password or corrupt keystore."))
+}
+
+/// Encrypt `keypair` and write it to a new file at `path`, readable only by the owner.
+fn write_keystore(
+    path: &Path,
+    keypair: &ECVRFKeyPair,
+    password: &str,
+    kdf: Kdf,
+) -> Result<(), std::io::Error> {
+    let keystore =
+        Keystore::from_ecvrf_keypair(keypair, password.as_bytes(), kdf.into(), &mut thread_rng())
+            .map_err(|e| Error::new(ErrorKind::Other, e.to_string()))?;
+    let json = keystore
+        .to_json()
+        .map_err(|e| Error::new(ErrorKind::Other, e.to_string()))?;
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(json.as_bytes())
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::{execute, Command, ProveArguments, VerifyArguments};
+    use crate::{
+        execute, Command, CreateArguments, ExportArguments, ImportArguments, InspectArguments, Kdf,
+        KeystoreCommand, PasswordArguments, PopArguments, ProveArguments, VerifyArguments,
+    };
+    use mangekyou::encoding::{Base58, Encoding};
+    use mangekyou::kamui_vrf::ecvrf::ECVRFPublicKey;
+    use mangekyou::kamui_vrf::pop::ECVRFProofOfPossession;
     use regex::Regex;
+    use std::fs;
+    use tempfile::tempdir;
 
     #[test]
     fn test_keygen() {
-        let result = execute(Command::Keygen).unwrap();
-        let expected =
-            Regex::new(r"Secret key: ([0-9a-fA-F]{64})\nPublic key: ([0-9a-fA-F]{64})").unwrap();
-        assert!(expected.is_match(&result));
+        let dir = tempdir().unwrap();
+        let password_file = dir.path().join("password");
+        fs::write(&password_file, "hunter2").unwrap();
+        let password = PasswordArguments {
+            password_file: Some(password_file),
+        };
+        let keystore = dir.path().join("keystore.json");
+
+        // Only the public key is printed; the key pair goes to the keystore
+        let result = execute(Command::Keygen(CreateArguments {
+            output: keystore.clone(),
+            password: password.clone(),
+            kdf: Kdf::Scrypt,
+        }))
+        .unwrap();
+        let expected = Regex::new(r"^Public key: ([0-9a-f]{64})$").unwrap();
+        let public_key = expected.captures(&result).unwrap()[1].to_string();
+
+        let result = execute(Command::Keystore(KeystoreCommand::Export(ExportArguments {
+            keystore,
+            password,
+        })))
+        .unwrap();
+        assert!(result.ends_with(&format!("\nPublic key: {}", public_key)));
     }
 
     #[test]
@@ -192,4 +464,133 @@ mod tests {
         }));
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_pop() {
+        let dir = tempdir().unwrap();
+        let password_file = dir.path().join("password");
+        fs::write(&password_file, "hunter2").unwrap();
+        let password = PasswordArguments {
+            password_file: Some(password_file),
+        };
+
+        let secret_key = "d354a0525580ab79bf67797b824a7df3ddf81ff45729175fa4d98d9f3dcd150f";
+        let public_key = "7a66a0fe0f2bcdcea5bfb97e3e9f6b298d25899052721bc2b4f3cb570a921b23";
+        let secret_key_file = dir.path().join("secret");
+        fs::write(&secret_key_file, secret_key).unwrap();
+        let keystore = dir.path().join("oracle.json");
+        execute(Command::Keystore(KeystoreCommand::Import(ImportArguments {
+            output: keystore.clone(),
+            secret_key_file: Some(secret_key_file),
+            password: password.clone(),
+            kdf: Kdf::Scrypt,
+        })))
+        .unwrap();
+
+        let authority = [0x42u8; 32];
+        let result = execute(Command::Pop(PopArguments {
+            keystore: keystore.clone(),
+            password: password.clone(),
+            authority: Base58::encode(authority),
+        }))
+        .unwrap();
+
+        let expected =
+            Regex::new(r"^Public key: ([0-9a-f]{64})\nProof of possession: ([0-9a-f]{128})$")
+                .unwrap();
+        let captures = expected.captures(&result).unwrap();
+        assert_eq!(&captures[1], public_key);
+
+        let pk = ECVRFPublicKey::from_bytes(&hex::decode(public_key).unwrap()).unwrap();
+        let pop = ECVRFProofOfPossession::from_bytes(&hex::decode(&captures[2]).unwrap()).unwrap();
+        assert!(pop.verify(&pk, &authority).is_ok());
+        assert!(pop.verify(&pk, &[0x43u8; 32]).is_err());
+
+        // The authority must be a base58 encoded 32 byte key
+        let result = execute(Command::Pop(PopArguments {
+            keystore: keystore.clone(),
+            password,
+            authority: Base58::encode([0x42u8; 31]),
+        }));
+        assert!(result.is_err());
+
+        // The keystore must be decrypted with its password
+        let wrong_password_file = dir.path().join("wrong");
+        fs::write(&wrong_password_file, "hunter3").unwrap();
+        let result = execute(Command::Pop(PopArguments {
+            keystore,
+            password: PasswordArguments {
+                password_file: Some(wrong_password_file),
+            },
+            authority: Base58::encode(authority),
+        }));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_keystore() {
+        let dir = tempdir().unwrap();
+        let password_file = dir.path().join("password");
+        fs::write(&password_file, "hunter2\n").unwrap();
+        let password = PasswordArguments {
+            password_file: Some(password_file),
+        };
+
+        // Import a known key
+        let secret_key = "d354a0525580ab79bf67797b824a7df3ddf81ff45729175fa4d98d9f3dcd150f";
+        let public_key = "7a66a0fe0f2bcdcea5bfb97e3e9f6b298d25899052721bc2b4f3cb570a921b23";
+        let secret_key_file = dir.path().join("secret");
+        fs::write(&secret_key_file, secret_key).unwrap();
+        let keystore = dir.path().join("imported.json");
+        let result = execute(Command::Keystore(KeystoreCommand::Import(ImportArguments {
+            output: keystore.clone(),
+            secret_key_file: Some(secret_key_file),
+            password: password.clone(),
+            kdf: Kdf::Scrypt,
+        })))
+        .unwrap();
+        assert_eq!(format!("Public key: {}", public_key), result);
+
+        // The secret key is not stored in the clear
+        let json = fs::read_to_string(&keystore).unwrap();
+        assert!(!json.contains(secret_key));
+
+        // Inspect without a password
+        let result = execute(Command::Keystore(KeystoreCommand::Inspect(InspectArguments {
+            keystore: keystore.clone(),
+        })))
+        .unwrap();
+        assert!(result.contains(&format!("Public key: {}", public_key)));
+        assert!(result.contains("KDF:        scrypt"));
+
+        // Export
+        let result = execute(Command::Keystore(KeystoreCommand::Export(ExportArguments {
+            keystore: keystore.clone(),
+            password: password.clone(),
+        })))
+        .unwrap();
+        assert_eq!(
+            format!("Secret key: {}\nPublic key: {}", secret_key, public_key),
+            result
+        );
+
+        // Wrong password
+        let wrong_password_file = dir.path().join("wrong");
+        fs::write(&wrong_password_file, "hunter3").unwrap();
+        assert!(execute(Command::Keystore(KeystoreCommand::Export(ExportArguments {
+            keystore: keystore.clone(),
+            password: PasswordArguments {
+                password_file: Some(wrong_password_file),
+            },
+        })))
+        .is_err());
+
+        // Refuse to overwrite an existing keystore
+        assert!(execute(Command::Keystore(KeystoreCommand::Create(CreateArguments {
+            output: keystore,
+            password,
+            kdf: Kdf::Argon2id,
+        })))
+        .is_err());
+    }
 }
@@ -9,13 +9,22 @@ use tempfile::tempdir;
 
 #[test]
 fn integration_test_ecvrf_keygen() {
-    let result = Command::cargo_bin("ecvrf-cli").unwrap().arg("keygen").ok();
+    let dir = tempdir().unwrap();
+    let keystore = dir.path().join("keystore.json");
+    let result = Command::cargo_bin("ecvrf-cli")
+        .unwrap()
+        .arg("keygen")
+        .arg("--output")
+        .arg(&keystore)
+        .env("ECVRF_KEYSTORE_PASSWORD", "correct horse battery staple")
+        .ok();
     assert!(result.is_ok());
 
-    let expected =
-        Regex::new(r"Secret key: ([0-9a-fA-F]{64})\nPublic key: ([0-9a-fA-F]{64})").unwrap();
+    // The secret key is only written to the keystore
+    let expected = Regex::new(r"^Public key: ([0-9a-fA-F]{64})\n$").unwrap();
     let output = String::from_utf8(result.unwrap().stdout).unwrap();
     assert!(expected.is_match(&output));
+    assert!(keystore.exists());
 }
 
 #[test]
@@ -68,8 +77,25 @@ fn integration_test_ecvrf_verify() {
 
 #[test]
 fn integration_test_ecvrf_e2e() {
-    // Keygen
-    let result = Command::cargo_bin("ecvrf-cli").unwrap().arg("keygen").ok();
+    // Keygen, then export the secret key from the keystore
+    let dir = tempdir().unwrap();
+    let keystore = dir.path().join("keystore.json");
+    let result = Command::cargo_bin("ecvrf-cli")
+        .unwrap()
+        .arg("keygen")
+        .arg("--output")
+        .arg(&keystore)
+        .env("ECVRF_KEYSTORE_PASSWORD", "correct horse battery staple")
+        .ok();
+    assert!(result.is_ok());
+    let result = Command::cargo_bin("ecvrf-cli")
+        .unwrap()
+        .arg("keystore")
+        .arg("export")
+        .arg("--keystore")
+        .arg(&keystore)
+        .env("ECVRF_KEYSTORE_PASSWORD", "correct horse battery staple")
+        .ok();
     assert!(result.is_ok());
     let pattern =
         Regex::new(r"Secret key: ([0-9a-fA-F]{64})\nPublic key: ([0-9a-fA-F]{64})").unwrap();
@@ -115,3 +141,73 @@ fn integration_test_ecvrf_e2e() {
     let output = String::from_utf8(result.unwrap().stdout).unwrap();
     assert_eq!(expected, output);
 }
+
+#[test]
+fn integration_test_ecvrf_keystore_e2e() {
+    let dir = tempdir().unwrap();
+    let keystore = dir.path().join("keystore.json");
+    let password_file = dir.path().join("password");
+    let mut file = File::create(&password_file).unwrap();
+    writeln!(file, "correct horse battery staple").unwrap();
+
+    // Create
+    let result = Command::cargo_bin("ecvrf-cli")
+        .unwrap()
+        .arg("keystore")
+        .arg("create")
+        .arg("--output")
+        .arg(&keystore)
+        .arg("--password-file")
+        .arg(&password_file)
+        .ok();
+    assert!(result.is_ok());
+    let pattern = Regex::new(r"Public key: ([0-9a-fA-F]{64})").unwrap();
+    let stdout = String::from_utf8(result.unwrap().stdout).unwrap();
+    let public_key = pattern.captures(&stdout).unwrap().get(1).unwrap().as_str().to_string();
+
+    // Inspect
+    let result = Command::cargo_bin("ecvrf-cli")
+        .unwrap()
+        .arg("keystore")
+        .arg("inspect")
+        .arg("--keystore")
+        .arg(&keystore)
+        .ok();
+    assert!(result.is_ok());
+    let stdout = String::from_utf8(result.unwrap().stdout).unwrap();
+    assert!(stdout.contains(&public_key));
+
+    // Export, with the password taken from the environment
+    let result = Command::cargo_bin("ecvrf-cli")
+        .unwrap()
+        .arg("keystore")
+        .arg("export")
+        .arg("--keystore")
+        .arg(&keystore)
+        .env("ECVRF_KEYSTORE_PASSWORD", "correct horse battery staple")
+        .ok();
+    assert!(result.is_ok());
+    let pattern =
+        Regex::new(r"Secret key: ([0-9a-fA-F]{64})\nPublic key: ([0-9a-fA-F]{64})").unwrap();
+    let stdout = String::from_utf8(result.unwrap().stdout).unwrap();
+    let captures = pattern.captures(&stdout).unwrap();
+    assert_eq!(public_key, captures.get(2).unwrap().as_str());
+
+    // Import the exported key from stdin
+    let imported = dir.path().join("imported.json");
+    let result = Command::cargo_bin("ecvrf-cli")
+        .unwrap()
+        .arg("keystore")
+        .arg("import")
+        .arg("--output")
+        .arg(&imported)
+        .arg("--kdf")
+        .arg("scrypt")
+        .arg("--password-file")
+        .arg(&password_file)
+        .write_stdin(captures.get(1).unwrap().as_str())
+        .ok();
+    assert!(result.is_ok());
+    let stdout = String::from_utf8(result.unwrap().stdout).unwrap();
+    assert_eq!(format!("Public key: {}\n", public_key), stdout);
+}
@@ -5,8 +5,8 @@ extern crate criterion;
 
 mod ecvrf_ristretto_benches {
 
-    use criterion::Criterion;
-    use mangekyou::kamui_vrf::ecvrf::ECVRFKeyPair;
+    use criterion::{BenchmarkGroup, Criterion};
+    use mangekyou::kamui_vrf::ecvrf::{ECVRFKeyPair, ECVRFProver};
     use mangekyou::kamui_vrf::VRFKeyPair;
     use mangekyou::kamui_vrf::VRFProof;
     use rand::rngs::ThreadRng;
@@ -19,11 +19,26 @@ mod ecvrf_ristretto_benches {
         });
     }
 
+    /// [ECVRFKeyPair::prove] and [ECVRFProver::prove] on the same key and input, so the two
+    /// results can be compared directly.
     fn proof(c: &mut Criterion) {
         let kp = ECVRFKeyPair::generate(&mut thread_rng());
+        let prover = ECVRFProver::new(ECVRFKeyPair::from(kp.sk.clone()));
         let input = b"Hello, world!";
-        c.bench_function("ECVRF Ristretto proving", move |b| {
-            b.iter(|| kp.prove(input))
+
+        let mut group: BenchmarkGroup<_> = c.benchmark_group("ECVRF Ristretto proving");
+        group.bench_function("key pair", |b| b.iter(|| kp.prove(input)));
+        group.bench_function(
+            "prover (extended coordinates, precomputed basepoint)",
+            |b| b.iter(|| prover.prove(input)),
+        );
+        group.finish();
+    }
+
+    fn prover_setup(c: &mut Criterion) {
+        let kp = ECVRFKeyPair::generate(&mut thread_rng());
+        c.bench_function("ECVRF Ristretto precomputed prover setup", move |b| {
+            b.iter(|| ECVRFProver::new(ECVRFKeyPair::from(kp.sk.clone())))
         });
     }
 
@@ -39,7 +54,7 @@ mod ecvrf_ristretto_benches {
     criterion_group! {
         name = ecvrf_ristretto_benches;
         config = Criterion::default().sample_size(100);
-        targets = keygen, proof, verify,
+        targets = keygen, proof, prover_setup, verify,
     }
 }
 
@@ -6,6 +6,7 @@ extern crate criterion;
 mod group_benches {
     use criterion::measurement::Measurement;
     use criterion::{measurement, BenchmarkGroup, BenchmarkId, Criterion};
+    use mangekyou::groups::bls12381::{G1Element, G2Element, GTElement};
     use mangekyou::groups::multiplier::windowed::WindowedScalarMultiplier;
     use mangekyou::groups::multiplier::ScalarMultiplier;
     use mangekyou::groups::ristretto255::RistrettoPoint;
@@ -27,6 +28,9 @@ mod group_benches {
 
     fn add(c: &mut Criterion) {
         let mut group: BenchmarkGroup<_> = c.benchmark_group("Add");
+        add_single::<G1Element, _>("BLS12381-G1", &mut group);
+        add_single::<G2Element, _>("BLS12381-G2", &mut group);
+        add_single::<GTElement, _>("BLS12381-GT", &mut group);
         add_single::<RistrettoPoint, _>("Ristretto255", &mut group);
     }
 
@@ -56,6 +60,9 @@ mod group_benches {
 
     fn scale(c: &mut Criterion) {
         let mut group: BenchmarkGroup<_> = c.benchmark_group("Scalar To Point Multiplication");
+        scale_single::<G1Element, _>("BLS12381-G1", &mut group);
+        scale_single::<G2Element, _>("BLS12381-G2", &mut group);
+        scale_single::<GTElement, _>("BLS12381-GT", &mut group);
         scale_single::<RistrettoPoint, _>("Ristretto255", &mut group);
     }
 
@@ -77,6 +84,14 @@ mod group_benches {
         });
     }
 
+    fn msm(c: &mut Criterion) {
+        let mut group: BenchmarkGroup<_> = c.benchmark_group("MSM");
+        for len in [32, 64, 128, 256].iter() {
+            blst_msm_single::<G1Element, _>("BLS12381-G1", len, &mut group);
+            blst_msm_single::<G2Element, _>("BLS12381-G2", len, &mut group);
+        }
+    }
+
     fn double_scale_single<
         G: GroupElement,
         Mul: ScalarMultiplier<G, G::ScalarType>,
@@ -108,6 +123,8 @@ mod group_benches {
 
     fn hash_to_group(c: &mut Criterion) {
         let mut group: BenchmarkGroup<_> = c.benchmark_group("Hash-to-group");
+        hash_to_group_single::<G1Element, _>("BLS12381-G1", &mut group);
+        hash_to_group_single::<G2Element, _>("BLS12381-G2", &mut group);
         hash_to_group_single::<RistrettoPoint, _>("Ristretto255", &mut group);
     }
 
@@ -121,6 +138,11 @@ mod group_benches {
         c.bench_function(&(name.to_string()), move |b| b.iter(|| G::pairing(&x, &y)));
     }
 
+    fn pairing(c: &mut Criterion) {
+        let mut group: BenchmarkGroup<_> = c.benchmark_group("Pairing");
+        pairing_single::<G1Element, _>("BLS12381-G1", &mut group);
+    }
+
     /// Implementation of a `Multiplier` where scalar multiplication is done without any pre-computation by
     /// simply calling the GroupElement implementation. Only used for benchmarking.
     struct DefaultMultiplier<G: GroupElement>(G);
@@ -171,7 +193,9 @@ mod group_benches {
         targets =
             add,
             scale,
+            msm,
             hash_to_group,
+            pairing,
     }
 }
 
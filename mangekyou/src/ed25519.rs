@@ -0,0 +1,334 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module contains an implementation of the [Ed25519](https://en.wikipedia.org/wiki/EdDSA#Ed25519)
+//! signature scheme, built on [ed25519_consensus]. Verification follows the
+//! [ZIP-215](https://zips.z.cash/zip-0215) rules, so single and batch verification accept exactly
+//! the same signatures.
+//!
+//! Messages are signed as given; callers that sign structured data (e.g. attestations of VRF
+//! outputs) should prefix it with their own domain separator.
+//!
+//! # Example
+//! ```rust
+//! # use mangekyou::ed25519::*;
+//! # use mangekyou::traits::{KeyPair, Signer, VerifyingKey};
+//! use rand::thread_rng;
+//! let kp = Ed25519KeyPair::generate(&mut thread_rng());
+//! let message: &[u8] = b"Hello, world!";
+//! let signature = kp.sign(message);
+//! assert!(kp.public().verify(message, &signature).is_ok());
+//! ```
+
+use crate::encoding::{Base64, Encoding};
+use crate::error::MangekyouError;
+use crate::serde_helpers::BytesRepresentation;
+use crate::traits::{
+    AllowedRng, Authenticator, EncodeDecodeBase64, KeyPair, Signer, SigningKey, ToFromBytes,
+    VerifyingKey,
+};
+use crate::{generate_bytes_representation, serialize_deserialize_with_to_from_bytes};
+use ed25519_consensus::{batch, VerificationKeyBytes};
+use eyre::eyre;
+use mangekyou_derive::{SilentDebug, SilentDisplay};
+use std::borrow::Borrow;
+use std::fmt::{self, Debug, Display};
+use std::str::FromStr;
+use zeroize::Zeroize;
+
+/// The length of a private key in bytes.
+pub const ED25519_PRIVATE_KEY_LENGTH: usize = 32;
+
+/// The length of a public key in bytes.
+pub const ED25519_PUBLIC_KEY_LENGTH: usize = 32;
+
+/// The length of a signature in bytes.
+pub const ED25519_SIGNATURE_LENGTH: usize = 64;
+
+/// Ed25519 public key.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Ed25519PublicKey(pub ed25519_consensus::VerificationKey);
+
+/// Ed25519 private key. The secret is elided from `Debug` and `Display` and wiped when the key is
+/// dropped.
+#[derive(SilentDebug, SilentDisplay)]
+pub struct Ed25519PrivateKey(pub ed25519_consensus::SigningKey);
+
+/// Ed25519 key pair. Its byte encoding is the private key.
+#[derive(Debug)]
+pub struct Ed25519KeyPair {
+    public: Ed25519PublicKey,
+    private: Ed25519PrivateKey,
+}
+
+/// Ed25519 signature.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Ed25519Signature {
+    sig: ed25519_consensus::Signature,
+    bytes: [u8; ED25519_SIGNATURE_LENGTH],
+}
+
+//
+// Implement VerifyingKey
+//
+
+impl AsRef<[u8]> for Ed25519PublicKey {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
+impl ToFromBytes for Ed25519PublicKey {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, MangekyouError> {
+        if bytes.len() != ED25519_PUBLIC_KEY_LENGTH {
+            return Err(MangekyouError::InputLengthWrong(ED25519_PUBLIC_KEY_LENGTH));
+        }
+        ed25519_consensus::VerificationKey::try_from(bytes)
+            .map(Ed25519PublicKey)
+            .map_err(|_| MangekyouError::InvalidInput)
+    }
+}
+
+impl Debug for Ed25519PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Base64::encode(self.as_ref()))
+    }
+}
+
+impl Display for Ed25519PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Base64::encode(self.as_ref()))
+    }
+}
+
+serialize_deserialize_with_to_from_bytes!(Ed25519PublicKey, ED25519_PUBLIC_KEY_LENGTH);
+
+impl<'a> From<&'a Ed25519PrivateKey> for Ed25519PublicKey {
+    fn from(private: &'a Ed25519PrivateKey) -> Self {
+        Ed25519PublicKey(private.0.verification_key())
+    }
+}
+
+impl VerifyingKey for Ed25519PublicKey {
+    type PrivKey = Ed25519PrivateKey;
+    type Sig = Ed25519Signature;
+    const LENGTH: usize = ED25519_PUBLIC_KEY_LENGTH;
+
+    fn verify(&self, msg: &[u8], signature: &Ed25519Signature) -> Result<(), MangekyouError> {
+        self.0
+            .verify(&signature.sig, msg)
+            .map_err(|_| MangekyouError::InvalidSignature)
+    }
+
+    fn verify_batch_empty_fail(
+        msg: &[u8],
+        pks: &[Self],
+        sigs: &[Self::Sig],
+    ) -> Result<(), eyre::Report> {
+        if sigs.is_empty() {
+            return Err(eyre!("Critical Error! This behaviour can signal something dangerous, and that someone may be trying to bypass signature verification through providing empty batches."));
+        }
+        if pks.len() != sigs.len() {
+            return Err(eyre!("Mismatch between number of signatures and public keys provided"));
+        }
+        let mut verifier = batch::Verifier::new();
+        for (pk, sig) in pks.iter().zip(sigs) {
+            verifier.queue((VerificationKeyBytes::from(pk.0), sig.sig, msg));
+        }
+        verifier
+            .verify(rand::thread_rng())
+            .map_err(|_| eyre!("Signature verification failed"))
+    }
+
+    fn verify_batch_empty_fail_different_msg<'a, M>(
+        msgs: &[M],
+        pks: &[Self],
+        sigs: &[Self::Sig],
+    ) -> Result<(), eyre::Report>
+    where
+        M: Borrow<[u8]> + 'a,
+    {
+        if sigs.is_empty() {
+            return Err(eyre!("Critical Error! This behaviour can signal something dangerous, and that someone may be trying to bypass signature verification through providing empty batches."));
+        }
+        if pks.len() != sigs.len() || pks.len() != msgs.len() {
+            return Err(eyre!(
+                "Mismatch between number of messages, signatures and public keys provided"
+            ));
+        }
+        let mut verifier = batch::Verifier::new();
+        for ((pk, sig), msg) in pks.iter().zip(sigs).zip(msgs) {
+            verifier.queue((VerificationKeyBytes::from(pk.0), sig.sig, msg.borrow()));
+        }
+        verifier
+            .verify(rand::thread_rng())
+            .map_err(|_| eyre!("Signature verification failed"))
+    }
+}
+
+//
+// Implement SigningKey
+//
+
+impl AsRef<[u8]> for Ed25519PrivateKey {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
+impl ToFromBytes for Ed25519PrivateKey {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, MangekyouError> {
+        let bytes: [u8; ED25519_PRIVATE_KEY_LENGTH] = bytes
+            .try_into()
+            .map_err(|_| MangekyouError::InputLengthWrong(ED25519_PRIVATE_KEY_LENGTH))?;
+        Ok(Ed25519PrivateKey(ed25519_consensus::SigningKey::from(bytes)))
+    }
+}
+
+impl Zeroize for Ed25519PrivateKey {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Drop for Ed25519PrivateKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+serialize_deserialize_with_to_from_bytes!(Ed25519PrivateKey, ED25519_PRIVATE_KEY_LENGTH);
+
+impl SigningKey for Ed25519PrivateKey {
+    type PubKey = Ed25519PublicKey;
+    type Sig = Ed25519Signature;
+    const LENGTH: usize = ED25519_PRIVATE_KEY_LENGTH;
+}
+
+//
+// Implement Authenticator
+//
+
+impl AsRef<[u8]> for Ed25519Signature {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl From<ed25519_consensus::Signature> for Ed25519Signature {
+    fn from(sig: ed25519_consensus::Signature) -> Self {
+        Ed25519Signature {
+            bytes: sig.to_bytes(),
+            sig,
+        }
+    }
+}
+
+impl ToFromBytes for Ed25519Signature {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, MangekyouError> {
+        let bytes: [u8; ED25519_SIGNATURE_LENGTH] = bytes
+            .try_into()
+            .map_err(|_| MangekyouError::InputLengthWrong(ED25519_SIGNATURE_LENGTH))?;
+        Ok(ed25519_consensus::Signature::from(bytes).into())
+    }
+}
+
+impl Debug for Ed25519Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Base64::encode(self.as_ref()))
+    }
+}
+
+impl Display for Ed25519Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Base64::encode(self.as_ref()))
+    }
+}
+
+impl std::hash::Hash for Ed25519Signature {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.bytes.hash(state);
+    }
+}
+
+serialize_deserialize_with_to_from_bytes!(Ed25519Signature, ED25519_SIGNATURE_LENGTH);
+
+generate_bytes_representation!(
+    Ed25519Signature,
+    ED25519_SIGNATURE_LENGTH,
+    Ed25519SignatureAsBytes
+);
+
+impl Authenticator for Ed25519Signature {
+    type PubKey = Ed25519PublicKey;
+    type PrivKey = Ed25519PrivateKey;
+    const LENGTH: usize = ED25519_SIGNATURE_LENGTH;
+}
+
+//
+// Implement KeyPair
+//
+
+impl From<Ed25519PrivateKey> for Ed25519KeyPair {
+    fn from(private: Ed25519PrivateKey) -> Self {
+        Ed25519KeyPair {
+            public: Ed25519PublicKey::from(&private),
+            private,
+        }
+    }
+}
+
+/// The bytes form of the keypair only contains the private key bytes.
+impl ToFromBytes for Ed25519KeyPair {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, MangekyouError> {
+        Ed25519PrivateKey::from_bytes(bytes).map(Ed25519KeyPair::from)
+    }
+}
+
+impl AsRef<[u8]> for Ed25519KeyPair {
+    fn as_ref(&self) -> &[u8] {
+        self.private.as_ref()
+    }
+}
+
+serialize_deserialize_with_to_from_bytes!(Ed25519KeyPair, ED25519_PRIVATE_KEY_LENGTH);
+
+impl KeyPair for Ed25519KeyPair {
+    type PubKey = Ed25519PublicKey;
+    type PrivKey = Ed25519PrivateKey;
+    type Sig = Ed25519Signature;
+
+    fn public(&'_ self) -> &'_ Self::PubKey {
+        &self.public
+    }
+
+    fn private(self) -> Self::PrivKey {
+        self.private
+    }
+
+    #[cfg(feature = "copy_key")]
+    fn copy(&self) -> Self {
+        Ed25519KeyPair {
+            public: self.public,
+            private: Ed25519PrivateKey(self.private.0.clone()),
+        }
+    }
+
+    fn generate<R: AllowedRng>(rng: &mut R) -> Self {
+        Ed25519PrivateKey(ed25519_consensus::SigningKey::new(rng)).into()
+    }
+}
+
+impl Signer<Ed25519Signature> for Ed25519KeyPair {
+    fn sign(&self, msg: &[u8]) -> Ed25519Signature {
+        self.private.0.sign(msg).into()
+    }
+}
+
+impl FromStr for Ed25519KeyPair {
+    type Err = MangekyouError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::decode_base64(s).map_err(|_| MangekyouError::GeneralOpaqueError)
+    }
+}
@@ -0,0 +1,753 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Implementations of the G1, G2 and GT groups of the [BLS12-381](https://hackmd.io/@benjaminion/bls12-381)
+//! pairing-friendly curve, built on [blst]. All three groups have the prime order
+//! r = 0x73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001, and GT is written
+//! additively like the other groups.
+//!
+//! G1 and G2 elements are encoded in the compressed form of the
+//! [ZCash serialization](https://github.com/zcash/librustzcash/blob/main/pairing/src/bls12_381/README.md#serialization)
+//! (48 and 96 bytes). GT elements are encoded as the 12 coefficients of an Fp12 element (576
+//! bytes). Scalars are encoded as 32 big-endian bytes. Decoding checks that points are in the
+//! prime order subgroup.
+//!
+//! EVM contracts use the uncompressed encoding of the BLS12-381 precompiles of
+//! [EIP-2537](https://eips.ethereum.org/EIPS/eip-2537), see [G1Element::to_eip2537_bytes] and
+//! [G2Element::to_eip2537_bytes].
+
+use crate::error::{MangekyouError, MangekyouResult};
+use crate::groups::{
+    Doubling, FiatShamirChallenge, GroupElement, HashToGroupElement, HashToGroupElementWithDst,
+    MultiScalarMul, Pairing, Scalar as ScalarType,
+};
+use crate::hash::{HashFunction, Sha512};
+use crate::serde_helpers::ToFromByteArray;
+use crate::serialize_deserialize_with_to_from_byte_array;
+use crate::traits::AllowedRng;
+use blst::{
+    blst_bendian_from_fp12, blst_bendian_from_scalar, blst_final_exp, blst_fp12,
+    blst_fp12_conjugate, blst_fp12_in_group, blst_fp12_mul, blst_fp12_one, blst_fp12_sqr,
+    blst_fp_from_bendian, blst_fr, blst_fr_add, blst_fr_cneg, blst_fr_from_scalar,
+    blst_fr_from_uint64, blst_fr_inverse, blst_fr_mul, blst_fr_sub, blst_hash_to_g1,
+    blst_hash_to_g2, blst_miller_loop, blst_p1, blst_p1_add_or_double, blst_p1_affine,
+    blst_p1_cneg, blst_p1_compress, blst_p1_double, blst_p1_from_affine, blst_p1_generator,
+    blst_p1_in_g1, blst_p1_is_inf, blst_p1_mult, blst_p1_serialize, blst_p1_to_affine,
+    blst_p1_uncompress, blst_p2, blst_p2_add_or_double, blst_p2_affine, blst_p2_cneg,
+    blst_p2_compress, blst_p2_double, blst_p2_from_affine, blst_p2_generator, blst_p2_in_g2,
+    blst_p2_is_inf, blst_p2_mult, blst_p2_serialize, blst_p2_to_affine, blst_p2_uncompress,
+    blst_scalar, blst_scalar_fr_check, blst_scalar_from_be_bytes, blst_scalar_from_bendian,
+    blst_scalar_from_fr, p1_affines, p2_affines, BLST_ERROR,
+};
+use mangekyou_derive::GroupOpsExtend;
+use once_cell::sync::Lazy;
+use serde::{de, Deserialize};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::ptr;
+use zeroize::Zeroize;
+
+/// Length of a compressed G1 element.
+pub const G1_ELEMENT_BYTE_LENGTH: usize = 48;
+
+/// Length of a compressed G2 element.
+pub const G2_ELEMENT_BYTE_LENGTH: usize = 96;
+
+/// Length of an encoded GT element.
+pub const GT_ELEMENT_BYTE_LENGTH: usize = 576;
+
+/// Length of an encoded scalar.
+pub const SCALAR_LENGTH: usize = 32;
+
+/// Length of a G1 element in the encoding of EIP-2537.
+pub const G1_EIP2537_BYTE_LENGTH: usize = 128;
+
+/// Length of a G2 element in the encoding of EIP-2537.
+pub const G2_EIP2537_BYTE_LENGTH: usize = 256;
+
+/// The domain separation tag of [HashToGroupElement] for [G1Element], as in the basic scheme of
+/// the [BLS signature draft](https://datatracker.ietf.org/doc/html/draft-irtf-cfrg-bls-signature-05).
+pub const G1_HASH_TO_CURVE_DST: &[u8] = b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_";
+
+/// The domain separation tag of [HashToGroupElement] for [G2Element].
+pub const G2_HASH_TO_CURVE_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
+
+const FP_BYTE_LENGTH: usize = 48;
+
+/// Elements of the G1 group.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, GroupOpsExtend)]
+pub struct G1Element(blst_p1);
+
+/// Elements of the G2 group.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, GroupOpsExtend)]
+pub struct G2Element(blst_p2);
+
+/// Elements of the GT group, the target of the pairing. The group operation is multiplication in
+/// Fp12 and is written additively.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, GroupOpsExtend)]
+pub struct GTElement(blst_fp12);
+
+/// Scalars modulo the order of the groups.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, GroupOpsExtend)]
+pub struct Scalar(blst_fr);
+
+/// Little-endian bytes of a scalar, as taken by the blst multiplications.
+fn scalar_to_blst(scalar: &Scalar) -> blst_scalar {
+    let mut blst_scalar = blst_scalar::default();
+    unsafe {
+        blst_scalar_from_fr(&mut blst_scalar, &scalar.0);
+    }
+    blst_scalar
+}
+
+//
+// G1
+//
+
+impl G1Element {
+    /// Hash a message to G1 with `hash_to_curve` of
+    /// [RFC 9380](https://www.rfc-editor.org/rfc/rfc9380) for the suite
+    /// BLS12381G1_XMD:SHA-256_SSWU_RO_ and the given domain separation tag.
+    pub fn hash_to_curve(msg: &[u8], dst: &[u8]) -> Self {
+        let mut ret = blst_p1::default();
+        unsafe {
+            blst_hash_to_g1(
+                &mut ret,
+                msg.as_ptr(),
+                msg.len(),
+                dst.as_ptr(),
+                dst.len(),
+                ptr::null(),
+                0,
+            );
+        }
+        Self(ret)
+    }
+
+    /// Encode this point as in EIP-2537, i.e. x || y with each coordinate as a 64 byte
+    /// big-endian integer. The identity is encoded as zeros.
+    pub fn to_eip2537_bytes(&self) -> [u8; G1_EIP2537_BYTE_LENGTH] {
+        let mut ret = [0u8; G1_EIP2537_BYTE_LENGTH];
+        if *self == Self::zero() {
+            return ret;
+        }
+        let mut uncompressed = [0u8; 2 * FP_BYTE_LENGTH];
+        unsafe {
+            blst_p1_serialize(uncompressed.as_mut_ptr(), &self.0);
+        }
+        for (i, coordinate) in uncompressed.chunks(FP_BYTE_LENGTH).enumerate() {
+            ret[64 * i + 16..64 * (i + 1)].copy_from_slice(coordinate);
+        }
+        ret
+    }
+}
+
+impl Add for G1Element {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let mut ret = blst_p1::default();
+        unsafe {
+            blst_p1_add_or_double(&mut ret, &self.0, &rhs.0);
+        }
+        Self(ret)
+    }
+}
+
+impl Sub for G1Element {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl Neg for G1Element {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        let mut ret = self.0;
+        unsafe {
+            blst_p1_cneg(&mut ret, true);
+        }
+        Self(ret)
+    }
+}
+
+impl Mul<Scalar> for G1Element {
+    type Output = Self;
+
+    fn mul(self, rhs: Scalar) -> Self {
+        let mut ret = blst_p1::default();
+        unsafe {
+            blst_p1_mult(&mut ret, &self.0, scalar_to_blst(&rhs).b.as_ptr(), 255);
+        }
+        Self(ret)
+    }
+}
+
+#[allow(clippy::suspicious_arithmetic_impl)]
+impl Div<Scalar> for G1Element {
+    type Output = Result<Self, MangekyouError>;
+
+    fn div(self, rhs: Scalar) -> Self::Output {
+        let inv = rhs.inverse()?;
+        Ok(self * inv)
+    }
+}
+
+impl GroupElement for G1Element {
+    type ScalarType = Scalar;
+
+    fn zero() -> Self {
+        Self(blst_p1::default())
+    }
+
+    fn generator() -> Self {
+        unsafe { Self(*blst_p1_generator()) }
+    }
+}
+
+impl Doubling for G1Element {
+    fn double(&self) -> Self {
+        let mut ret = blst_p1::default();
+        unsafe {
+            blst_p1_double(&mut ret, &self.0);
+        }
+        Self(ret)
+    }
+}
+
+impl MultiScalarMul for G1Element {
+    fn multi_scalar_mul(scalars: &[Self::ScalarType], points: &[Self]) -> MangekyouResult<Self> {
+        if scalars.len() != points.len() {
+            return Err(MangekyouError::InvalidInput);
+        }
+        if scalars.is_empty() {
+            return Ok(Self::zero());
+        }
+        let points = p1_affines::from(&points.iter().map(|p| p.0).collect::<Vec<_>>());
+        let scalars = scalars
+            .iter()
+            .flat_map(|s| scalar_to_blst(s).b)
+            .collect::<Vec<_>>();
+        Ok(Self(points.mult(&scalars, 255)))
+    }
+}
+
+impl HashToGroupElement for G1Element {
+    fn hash_to_group_element(msg: &[u8]) -> Self {
+        Self::hash_to_curve(msg, G1_HASH_TO_CURVE_DST)
+    }
+}
+
+impl HashToGroupElementWithDst for G1Element {
+    fn hash_to_group_element_with_dst(msg: &[u8], dst: &[u8]) -> Self {
+        Self::hash_to_curve(msg, dst)
+    }
+}
+
+impl Pairing for G1Element {
+    type Other = G2Element;
+    type Output = GTElement;
+
+    fn pairing(&self, other: &G2Element) -> GTElement {
+        let is_identity = unsafe { blst_p1_is_inf(&self.0) || blst_p2_is_inf(&other.0) };
+        if is_identity {
+            return GTElement::zero();
+        }
+        let mut p = blst_p1_affine::default();
+        let mut q = blst_p2_affine::default();
+        let mut ret = blst_fp12::default();
+        unsafe {
+            blst_p1_to_affine(&mut p, &self.0);
+            blst_p2_to_affine(&mut q, &other.0);
+            blst_miller_loop(&mut ret, &q, &p);
+            let ret: *mut blst_fp12 = &mut ret;
+            blst_final_exp(ret, ret);
+        }
+        GTElement(ret)
+    }
+}
+
+impl ToFromByteArray<G1_ELEMENT_BYTE_LENGTH> for G1Element {
+    fn from_byte_array(bytes: &[u8; G1_ELEMENT_BYTE_LENGTH]) -> Result<Self, MangekyouError> {
+        let mut affine = blst_p1_affine::default();
+        let mut ret = blst_p1::default();
+        unsafe {
+            if blst_p1_uncompress(&mut affine, bytes.as_ptr()) != BLST_ERROR::BLST_SUCCESS {
+                return Err(MangekyouError::InvalidInput);
+            }
+            blst_p1_from_affine(&mut ret, &affine);
+            if !blst_p1_in_g1(&ret) {
+                return Err(MangekyouError::InvalidInput);
+            }
+        }
+        Ok(Self(ret))
+    }
+
+    fn to_byte_array(&self) -> [u8; G1_ELEMENT_BYTE_LENGTH] {
+        let mut bytes = [0u8; G1_ELEMENT_BYTE_LENGTH];
+        unsafe {
+            blst_p1_compress(bytes.as_mut_ptr(), &self.0);
+        }
+        bytes
+    }
+}
+
+serialize_deserialize_with_to_from_byte_array!(G1Element);
+
+//
+// G2
+//
+
+impl G2Element {
+    /// Hash a message to G2 with `hash_to_curve` of
+    /// [RFC 9380](https://www.rfc-editor.org/rfc/rfc9380) for the suite
+    /// BLS12381G2_XMD:SHA-256_SSWU_RO_ and the given domain separation tag.
+    pub fn hash_to_curve(msg: &[u8], dst: &[u8]) -> Self {
+        let mut ret = blst_p2::default();
+        unsafe {
+            blst_hash_to_g2(
+                &mut ret,
+                msg.as_ptr(),
+                msg.len(),
+                dst.as_ptr(),
+                dst.len(),
+                ptr::null(),
+                0,
+            );
+        }
+        Self(ret)
+    }
+
+    /// Encode this point as in EIP-2537, i.e. x.c0 || x.c1 || y.c0 || y.c1 with each coefficient
+    /// as a 64 byte big-endian integer. The identity is encoded as zeros.
+    pub fn to_eip2537_bytes(&self) -> [u8; G2_EIP2537_BYTE_LENGTH] {
+        let mut ret = [0u8; G2_EIP2537_BYTE_LENGTH];
+        if *self == Self::zero() {
+            return ret;
+        }
+        // The ZCash encoding is x.c1 || x.c0 || y.c1 || y.c0.
+        let mut uncompressed = [0u8; 4 * FP_BYTE_LENGTH];
+        unsafe {
+            blst_p2_serialize(uncompressed.as_mut_ptr(), &self.0);
+        }
+        for (i, coefficient) in uncompressed.chunks(FP_BYTE_LENGTH).enumerate() {
+            let j = i ^ 1;
+            ret[64 * j + 16..64 * (j + 1)].copy_from_slice(coefficient);
+        }
+        ret
+    }
+}
+
+impl Add for G2Element {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let mut ret = blst_p2::default();
+        unsafe {
+            blst_p2_add_or_double(&mut ret, &self.0, &rhs.0);
+        }
+        Self(ret)
+    }
+}
+
+impl Sub for G2Element {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl Neg for G2Element {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        let mut ret = self.0;
+        unsafe {
+            blst_p2_cneg(&mut ret, true);
+        }
+        Self(ret)
+    }
+}
+
+impl Mul<Scalar> for G2Element {
+    type Output = Self;
+
+    fn mul(self, rhs: Scalar) -> Self {
+        let mut ret = blst_p2::default();
+        unsafe {
+            blst_p2_mult(&mut ret, &self.0, scalar_to_blst(&rhs).b.as_ptr(), 255);
+        }
+        Self(ret)
+    }
+}
+
+#[allow(clippy::suspicious_arithmetic_impl)]
+impl Div<Scalar> for G2Element {
+    type Output = Result<Self, MangekyouError>;
+
+    fn div(self, rhs: Scalar) -> Self::Output {
+        let inv = rhs.inverse()?;
+        Ok(self * inv)
+    }
+}
+
+impl GroupElement for G2Element {
+    type ScalarType = Scalar;
+
+    fn zero() -> Self {
+        Self(blst_p2::default())
+    }
+
+    fn generator() -> Self {
+        unsafe { Self(*blst_p2_generator()) }
+    }
+}
+
+impl Doubling for G2Element {
+    fn double(&self) -> Self {
+        let mut ret = blst_p2::default();
+        unsafe {
+            blst_p2_double(&mut ret, &self.0);
+        }
+        Self(ret)
+    }
+}
+
+impl MultiScalarMul for G2Element {
+    fn multi_scalar_mul(scalars: &[Self::ScalarType], points: &[Self]) -> MangekyouResult<Self> {
+        if scalars.len() != points.len() {
+            return Err(MangekyouError::InvalidInput);
+        }
+        if scalars.is_empty() {
+            return Ok(Self::zero());
+        }
+        let points = p2_affines::from(&points.iter().map(|p| p.0).collect::<Vec<_>>());
+        let scalars = scalars
+            .iter()
+            .flat_map(|s| scalar_to_blst(s).b)
+            .collect::<Vec<_>>();
+        Ok(Self(points.mult(&scalars, 255)))
+    }
+}
+
+impl HashToGroupElement for G2Element {
+    fn hash_to_group_element(msg: &[u8]) -> Self {
+        Self::hash_to_curve(msg, G2_HASH_TO_CURVE_DST)
+    }
+}
+
+impl HashToGroupElementWithDst for G2Element {
+    fn hash_to_group_element_with_dst(msg: &[u8], dst: &[u8]) -> Self {
+        Self::hash_to_curve(msg, dst)
+    }
+}
+
+impl ToFromByteArray<G2_ELEMENT_BYTE_LENGTH> for G2Element {
+    fn from_byte_array(bytes: &[u8; G2_ELEMENT_BYTE_LENGTH]) -> Result<Self, MangekyouError> {
+        let mut affine = blst_p2_affine::default();
+        let mut ret = blst_p2::default();
+        unsafe {
+            if blst_p2_uncompress(&mut affine, bytes.as_ptr()) != BLST_ERROR::BLST_SUCCESS {
+                return Err(MangekyouError::InvalidInput);
+            }
+            blst_p2_from_affine(&mut ret, &affine);
+            if !blst_p2_in_g2(&ret) {
+                return Err(MangekyouError::InvalidInput);
+            }
+        }
+        Ok(Self(ret))
+    }
+
+    fn to_byte_array(&self) -> [u8; G2_ELEMENT_BYTE_LENGTH] {
+        let mut bytes = [0u8; G2_ELEMENT_BYTE_LENGTH];
+        unsafe {
+            blst_p2_compress(bytes.as_mut_ptr(), &self.0);
+        }
+        bytes
+    }
+}
+
+serialize_deserialize_with_to_from_byte_array!(G2Element);
+
+//
+// GT
+//
+
+static GT_GENERATOR: Lazy<GTElement> =
+    Lazy::new(|| G1Element::generator().pairing(&G2Element::generator()));
+
+impl Add for GTElement {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let mut ret = blst_fp12::default();
+        unsafe {
+            blst_fp12_mul(&mut ret, &self.0, &rhs.0);
+        }
+        Self(ret)
+    }
+}
+
+impl Sub for GTElement {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl Neg for GTElement {
+    type Output = Self;
+
+    /// GT is in the cyclotomic subgroup of Fp12, where the inverse is the conjugate.
+    fn neg(self) -> Self {
+        let mut ret = self.0;
+        unsafe {
+            blst_fp12_conjugate(&mut ret);
+        }
+        Self(ret)
+    }
+}
+
+impl Mul<Scalar> for GTElement {
+    type Output = Self;
+
+    /// Square-and-multiply. This is not constant time, so it must not be used with secrets.
+    fn mul(self, rhs: Scalar) -> Self {
+        let mut ret = Self::zero().0;
+        let acc: *mut blst_fp12 = &mut ret;
+        for byte in rhs.to_byte_array() {
+            for i in (0..8).rev() {
+                unsafe {
+                    blst_fp12_sqr(acc, acc);
+                    if (byte >> i) & 1 == 1 {
+                        blst_fp12_mul(acc, acc, &self.0);
+                    }
+                }
+            }
+        }
+        Self(ret)
+    }
+}
+
+#[allow(clippy::suspicious_arithmetic_impl)]
+impl Div<Scalar> for GTElement {
+    type Output = Result<Self, MangekyouError>;
+
+    fn div(self, rhs: Scalar) -> Self::Output {
+        let inv = rhs.inverse()?;
+        Ok(self * inv)
+    }
+}
+
+impl GroupElement for GTElement {
+    type ScalarType = Scalar;
+
+    fn zero() -> Self {
+        unsafe { Self(*blst_fp12_one()) }
+    }
+
+    fn generator() -> Self {
+        *GT_GENERATOR
+    }
+}
+
+impl ToFromByteArray<GT_ELEMENT_BYTE_LENGTH> for GTElement {
+    fn from_byte_array(bytes: &[u8; GT_ELEMENT_BYTE_LENGTH]) -> Result<Self, MangekyouError> {
+        // Same order as blst_bendian_from_fp12.
+        let mut ret = blst_fp12::default();
+        let mut chunks = bytes.chunks(FP_BYTE_LENGTH);
+        for i in 0..3 {
+            for j in 0..2 {
+                for k in 0..2 {
+                    let chunk = chunks.next().expect("The input has 12 coefficients");
+                    unsafe {
+                        blst_fp_from_bendian(&mut ret.fp6[j].fp2[i].fp[k], chunk.as_ptr());
+                    }
+                }
+            }
+        }
+        // Reject coefficients that are not reduced, and elements outside of GT.
+        let element = Self(ret);
+        if element.to_byte_array() != *bytes || unsafe { !blst_fp12_in_group(&ret) } {
+            return Err(MangekyouError::InvalidInput);
+        }
+        Ok(element)
+    }
+
+    fn to_byte_array(&self) -> [u8; GT_ELEMENT_BYTE_LENGTH] {
+        let mut bytes = [0u8; GT_ELEMENT_BYTE_LENGTH];
+        unsafe {
+            blst_bendian_from_fp12(bytes.as_mut_ptr(), &self.0);
+        }
+        bytes
+    }
+}
+
+serialize_deserialize_with_to_from_byte_array!(GTElement);
+
+//
+// Scalar
+//
+
+impl Scalar {
+    /// Construct a [Scalar] by reducing a big-endian integer of any length modulo the group order.
+    pub fn from_bytes_mod_order(bytes: &[u8]) -> Self {
+        let mut scalar = blst_scalar::default();
+        let mut ret = blst_fr::default();
+        unsafe {
+            blst_scalar_from_be_bytes(&mut scalar, bytes.as_ptr(), bytes.len());
+            blst_fr_from_scalar(&mut ret, &scalar);
+        }
+        Self(ret)
+    }
+}
+
+impl Add for Scalar {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let mut ret = blst_fr::default();
+        unsafe {
+            blst_fr_add(&mut ret, &self.0, &rhs.0);
+        }
+        Self(ret)
+    }
+}
+
+impl Sub for Scalar {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        let mut ret = blst_fr::default();
+        unsafe {
+            blst_fr_sub(&mut ret, &self.0, &rhs.0);
+        }
+        Self(ret)
+    }
+}
+
+impl Neg for Scalar {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        let mut ret = blst_fr::default();
+        unsafe {
+            blst_fr_cneg(&mut ret, &self.0, true);
+        }
+        Self(ret)
+    }
+}
+
+impl Mul<Scalar> for Scalar {
+    type Output = Self;
+
+    fn mul(self, rhs: Scalar) -> Self {
+        let mut ret = blst_fr::default();
+        unsafe {
+            blst_fr_mul(&mut ret, &self.0, &rhs.0);
+        }
+        Self(ret)
+    }
+}
+
+#[allow(clippy::suspicious_arithmetic_impl)]
+impl Div<Scalar> for Scalar {
+    type Output = Result<Self, MangekyouError>;
+
+    fn div(self, rhs: Scalar) -> Self::Output {
+        let inv = rhs.inverse()?;
+        Ok(self * inv)
+    }
+}
+
+impl From<u128> for Scalar {
+    fn from(value: u128) -> Self {
+        let limbs = [value as u64, (value >> 64) as u64, 0, 0];
+        let mut ret = blst_fr::default();
+        unsafe {
+            blst_fr_from_uint64(&mut ret, limbs.as_ptr());
+        }
+        Self(ret)
+    }
+}
+
+impl GroupElement for Scalar {
+    type ScalarType = Self;
+
+    fn zero() -> Self {
+        Self(blst_fr::default())
+    }
+
+    fn generator() -> Self {
+        Self::from(1)
+    }
+}
+
+impl ScalarType for Scalar {
+    fn rand<R: AllowedRng>(rng: &mut R) -> Self {
+        // Reducing 64 random bytes gives a negligible bias.
+        let mut bytes = [0u8; 64];
+        rng.fill_bytes(&mut bytes);
+        let scalar = Self::from_bytes_mod_order(&bytes);
+        bytes.zeroize();
+        scalar
+    }
+
+    fn inverse(&self) -> MangekyouResult<Self> {
+        if *self == Self::zero() {
+            return Err(MangekyouError::InvalidInput);
+        }
+        let mut ret = blst_fr::default();
+        unsafe {
+            blst_fr_inverse(&mut ret, &self.0);
+        }
+        Ok(Self(ret))
+    }
+}
+
+impl Zeroize for Scalar {
+    fn zeroize(&mut self) {
+        self.0.l.zeroize();
+    }
+}
+
+impl HashToGroupElement for Scalar {
+    fn hash_to_group_element(msg: &[u8]) -> Self {
+        Self::from_bytes_mod_order(&Sha512::digest(msg).digest)
+    }
+}
+
+impl FiatShamirChallenge for Scalar {
+    fn fiat_shamir_reduction_to_group_element(msg: &[u8]) -> Self {
+        Self::hash_to_group_element(msg)
+    }
+}
+
+impl ToFromByteArray<SCALAR_LENGTH> for Scalar {
+    fn from_byte_array(bytes: &[u8; SCALAR_LENGTH]) -> Result<Self, MangekyouError> {
+        let mut scalar = blst_scalar::default();
+        let mut ret = blst_fr::default();
+        unsafe {
+            blst_scalar_from_bendian(&mut scalar, bytes.as_ptr());
+            if !blst_scalar_fr_check(&scalar) {
+                return Err(MangekyouError::InvalidInput);
+            }
+            blst_fr_from_scalar(&mut ret, &scalar);
+        }
+        Ok(Self(ret))
+    }
+
+    fn to_byte_array(&self) -> [u8; SCALAR_LENGTH] {
+        let mut bytes = [0u8; SCALAR_LENGTH];
+        unsafe {
+            blst_bendian_from_scalar(bytes.as_mut_ptr(), &scalar_to_blst(self));
+        }
+        bytes
+    }
+}
+
+serialize_deserialize_with_to_from_byte_array!(Scalar);
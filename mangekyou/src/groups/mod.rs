@@ -9,7 +9,9 @@ use serde::Serialize;
 use std::fmt::Debug;
 use std::ops::{AddAssign, SubAssign};
 
+pub mod bls12381;
 pub mod ristretto255;
+pub mod secp256k1;
 
 pub mod multiplier;
 
@@ -78,6 +80,15 @@ pub trait HashToGroupElement {
     fn hash_to_group_element(msg: &[u8]) -> Self;
 }
 
+/// Trait for groups that can hash messages to group elements under a caller-chosen domain
+/// separation tag, as in [RFC 9380](https://datatracker.ietf.org/doc/rfc9380/). Protocols should
+/// use a tag which is unique to them.
+pub trait HashToGroupElementWithDst {
+    /// Hashes the given message with the domain separation tag `dst` and maps the result to a group
+    /// element.
+    fn hash_to_group_element_with_dst(msg: &[u8], dst: &[u8]) -> Self;
+}
+
 /// Trait for groups that support multi-scalar multiplication.
 pub trait MultiScalarMul: GroupElement {
     fn multi_scalar_mul(scalars: &[Self::ScalarType], points: &[Self]) -> MangekyouResult<Self>;
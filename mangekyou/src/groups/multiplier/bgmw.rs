@@ -69,10 +69,10 @@ impl<
 
         // Compute cache[i][j] = 2^w * cache[i-1][j] for i > 0.
         for i in 1..HEIGHT {
-            for j in 0..WIDTH {
-                cache[i][j] = cache[i - 1][j];
+            cache[i] = cache[i - 1];
+            for point in cache[i].iter_mut() {
                 for _ in 0..Self::WINDOW_WIDTH {
-                    cache[i][j] = cache[i][j].double();
+                    *point = point.double();
                 }
             }
         }
@@ -101,9 +101,6 @@ impl<
 mod tests {
     use super::*;
     use crate::groups::ristretto255::{RistrettoPoint, RistrettoScalar};
-    use crate::groups::secp256r1::{ProjectivePoint, Scalar};
-    use ark_ff::{BigInteger, PrimeField};
-    use ark_secp256r1::Fr;
 
     #[test]
     fn test_scalar_multiplication_ristretto() {
@@ -130,53 +127,12 @@ mod tests {
             let actual = multiplier.mul(&scalar);
             assert_eq!(expected, actual);
         }
-    }
-
-    #[test]
-    fn test_scalar_multiplication_secp256r1() {
-        let mut modulus_minus_one = Fr::MODULUS_MINUS_ONE_DIV_TWO;
-        modulus_minus_one.mul2();
-        let scalars = [
-            Scalar::from(0),
-            Scalar::from(1),
-            Scalar::from(2),
-            Scalar::from(1234),
-            Scalar::from(123456),
-            Scalar::from(123456789),
-            Scalar::from(0xffffffffffffffff),
-            Scalar(Fr::from(modulus_minus_one)),
-        ];
-
-        for scalar in scalars {
-            let expected = ProjectivePoint::generator() * scalar;
-
-            let multiplier = BGMWScalarMultiplier::<ProjectivePoint, Scalar, 16, 64, 32>::new(
-                ProjectivePoint::generator(),
-                ProjectivePoint::zero(),
-            );
-            let actual = multiplier.mul(&scalar);
-            assert_eq!(expected, actual);
-
-            let multiplier = BGMWScalarMultiplier::<ProjectivePoint, Scalar, 32, 52, 32>::new(
-                ProjectivePoint::generator(),
-                ProjectivePoint::zero(),
-            );
-            let actual = multiplier.mul(&scalar);
-            assert_eq!(expected, actual);
-
-            let multiplier = BGMWScalarMultiplier::<ProjectivePoint, Scalar, 64, 43, 32>::new(
-                ProjectivePoint::generator(),
-                ProjectivePoint::zero(),
-            );
-            let actual = multiplier.mul(&scalar);
-            assert_eq!(expected, actual);
-        }
 
         // Assert a panic due to setting the HEIGHT too small
         assert!(std::panic::catch_unwind(|| {
-            BGMWScalarMultiplier::<ProjectivePoint, Scalar, 16, 63, 32>::new(
-                ProjectivePoint::generator(),
-                ProjectivePoint::zero(),
+            BGMWScalarMultiplier::<RistrettoPoint, RistrettoScalar, 16, 63, 32>::new(
+                RistrettoPoint::generator(),
+                RistrettoPoint::zero(),
             )
         })
         .is_err());
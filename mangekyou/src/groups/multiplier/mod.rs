@@ -4,7 +4,6 @@
 //! This module contains implementations of optimised scalar multiplication algorithms where the
 //! group element is fixed and certain multiples of this may be pre-computed.
 
-#[cfg(feature = "experimental")]
 pub mod bgmw;
 mod integer_utils;
 pub mod windowed;
@@ -7,9 +7,10 @@
 
 use crate::error::MangekyouResult;
 use crate::groups::{
-    Doubling, FiatShamirChallenge, GroupElement, HashToGroupElement, MultiScalarMul, Scalar,
+    Doubling, FiatShamirChallenge, GroupElement, HashToGroupElement, HashToGroupElementWithDst,
+    MultiScalarMul, Scalar,
 };
-use crate::hash::Sha512;
+use crate::hash::{expand_message_xmd, BlockHashFunction, Sha512};
 use crate::serde_helpers::ToFromByteArray;
 use crate::traits::AllowedRng;
 use crate::{
@@ -42,9 +43,20 @@ impl RistrettoPoint {
         RistrettoPoint::from(ExternalRistrettoPoint::from_uniform_bytes(bytes))
     }
 
-    /// Construct a RistrettoPoint from the given data using a given hash function.
-    pub fn map_to_point<H: HashFunction<64>>(bytes: &[u8]) -> Self {
-        Self::from_uniform_bytes(&H::digest(bytes).digest)
+    /// Hash the given message to a RistrettoPoint with the domain separation tag `dst`, using
+    /// `expand_message_xmd` over the given hash function and the Elligator map, as in the
+    /// `hash_to_ristretto255` function of [RFC 9380](https://datatracker.ietf.org/doc/rfc9380/).
+    pub fn map_to_point<H: BlockHashFunction<DIGEST_LEN>, const DIGEST_LEN: usize>(
+        msg: &[u8],
+        dst: &[u8],
+    ) -> Self {
+        let bytes = expand_message_xmd::<H, DIGEST_LEN>(msg, dst, 64)
+            .expect("64 bytes is a valid expand_message_xmd output length");
+        Self::from_uniform_bytes(
+            &bytes
+                .try_into()
+                .expect("expand_message_xmd returns the requested number of bytes"),
+        )
     }
 
     /// Return this point in compressed form.
@@ -120,9 +132,18 @@ impl TryFrom<&[u8]> for RistrettoPoint {
     }
 }
 
+/// A single SHA-512 hash followed by the Elligator map. This predates [HashToGroupElementWithDst]
+/// and is kept as is because multiset hashes depend on it; new protocols should use
+/// [RistrettoPoint::map_to_point] with their own domain separation tag.
 impl HashToGroupElement for RistrettoPoint {
     fn hash_to_group_element(msg: &[u8]) -> Self {
-        RistrettoPoint::map_to_point::<Sha512>(msg)
+        Self::from_uniform_bytes(&Sha512::digest(msg).digest)
+    }
+}
+
+impl HashToGroupElementWithDst for RistrettoPoint {
+    fn hash_to_group_element_with_dst(msg: &[u8], dst: &[u8]) -> Self {
+        RistrettoPoint::map_to_point::<Sha512, 64>(msg, dst)
     }
 }
 
@@ -213,6 +234,18 @@ impl HashToGroupElement for RistrettoScalar {
     }
 }
 
+impl HashToGroupElementWithDst for RistrettoScalar {
+    fn hash_to_group_element_with_dst(msg: &[u8], dst: &[u8]) -> Self {
+        let bytes = expand_message_xmd::<Sha512, 64>(msg, dst, 64)
+            .expect("64 bytes is a valid expand_message_xmd output length");
+        Self::from_bytes_mod_order_wide(
+            &bytes
+                .try_into()
+                .expect("expand_message_xmd returns the requested number of bytes"),
+        )
+    }
+}
+
 impl FiatShamirChallenge for RistrettoScalar {
     fn fiat_shamir_reduction_to_group_element(msg: &[u8]) -> Self {
         Self::hash_to_group_element(msg)
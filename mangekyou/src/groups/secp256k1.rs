@@ -0,0 +1,233 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Implementation of the [secp256k1](https://www.secg.org/sec2-v2.pdf) group, the curve used by
+//! Bitcoin and Ethereum, built on [k256]. The group has prime order
+//! n = 2^{256} - 432420386565659656852420866394968145599.
+//!
+//! Points are encoded in compressed SEC1 form (33 bytes), with the identity encoded as 33 zero
+//! bytes. Scalars are encoded as 32 big-endian bytes.
+
+use crate::error::{MangekyouError, MangekyouResult};
+use crate::groups::{
+    Doubling, FiatShamirChallenge, GroupElement, HashToGroupElement, HashToGroupElementWithDst,
+    MultiScalarMul, Scalar,
+};
+use crate::hash::{expand_message_xmd, HashFunction, Sha256, Sha512};
+use crate::serde_helpers::ToFromByteArray;
+use crate::serialize_deserialize_with_to_from_byte_array;
+use crate::traits::AllowedRng;
+use derive_more::{Add, From, Neg, Sub};
+use k256::elliptic_curve::bigint::{Encoding, U256, U512};
+use k256::elliptic_curve::group::GroupEncoding;
+use k256::elliptic_curve::hash2curve::{GroupDigest, MapToCurve};
+use k256::elliptic_curve::hash2field::FromOkm;
+use k256::elliptic_curve::ops::Reduce;
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::elliptic_curve::{Field, PrimeField};
+use k256::FieldBytes;
+use mangekyou_derive::GroupOpsExtend;
+use serde::{de, Deserialize};
+use std::ops::{Div, Mul};
+use zeroize::Zeroize;
+
+const SECP256K1_POINT_BYTE_LENGTH: usize = 33;
+const SECP256K1_SCALAR_BYTE_LENGTH: usize = 32;
+
+/// The number of bytes of expanded message per field element when hashing to the curve.
+const HASH_TO_FIELD_LENGTH: usize = 48;
+
+/// The domain separation tag of [HashToGroupElement] for [Secp256k1Point].
+pub const SECP256K1_HASH_TO_GROUP_DST: &[u8] =
+    b"MANGEKYOU-V01-CS01-with-secp256k1_XMD:SHA-256_SSWU_RO_";
+
+/// A point on the secp256k1 curve in projective coordinates.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, From, Add, Sub, Neg, GroupOpsExtend)]
+pub struct Secp256k1Point(k256::ProjectivePoint);
+
+impl Secp256k1Point {
+    /// Return this point in uncompressed SEC1 form without the 0x04 tag, i.e. x || y, as used for
+    /// Ethereum addresses. Returns `None` for the identity.
+    pub fn to_uncompressed_coordinates(&self) -> Option<[u8; 64]> {
+        let encoded = self.0.to_affine().to_encoded_point(false);
+        encoded.as_bytes()[1..].try_into().ok()
+    }
+}
+
+impl Doubling for Secp256k1Point {
+    fn double(&self) -> Self {
+        Self(self.0.double())
+    }
+}
+
+/// The `secp256k1_XMD:SHA-256_SSWU_RO_` suite of section 8.7 of
+/// [RFC 9380](https://datatracker.ietf.org/doc/rfc9380/).
+impl HashToGroupElementWithDst for Secp256k1Point {
+    fn hash_to_group_element_with_dst(msg: &[u8], dst: &[u8]) -> Self {
+        let uniform_bytes = expand_message_xmd::<Sha256, 32>(msg, dst, 2 * HASH_TO_FIELD_LENGTH)
+            .expect("96 bytes is a valid expand_message_xmd output length");
+        // The cofactor of secp256k1 is one, so there is no cofactor to clear.
+        Self(
+            uniform_bytes
+                .chunks_exact(HASH_TO_FIELD_LENGTH)
+                .map(|chunk| {
+                    let chunk: [u8; HASH_TO_FIELD_LENGTH] =
+                        chunk.try_into().expect("chunks have the requested length");
+                    <k256::Secp256k1 as GroupDigest>::FieldElement::from_okm(&chunk.into())
+                        .map_to_curve()
+                })
+                .sum(),
+        )
+    }
+}
+
+impl HashToGroupElement for Secp256k1Point {
+    fn hash_to_group_element(msg: &[u8]) -> Self {
+        Self::hash_to_group_element_with_dst(msg, SECP256K1_HASH_TO_GROUP_DST)
+    }
+}
+
+impl MultiScalarMul for Secp256k1Point {
+    fn multi_scalar_mul(scalars: &[Self::ScalarType], points: &[Self]) -> MangekyouResult<Self> {
+        if scalars.len() != points.len() {
+            return Err(MangekyouError::InvalidInput);
+        }
+        Ok(scalars
+            .iter()
+            .zip(points)
+            .fold(Self::zero(), |acc, (s, p)| acc + *p * *s))
+    }
+}
+
+#[allow(clippy::suspicious_arithmetic_impl)]
+impl Div<Secp256k1Scalar> for Secp256k1Point {
+    type Output = Result<Self, MangekyouError>;
+
+    fn div(self, rhs: Secp256k1Scalar) -> Self::Output {
+        let inv = rhs.inverse()?;
+        Ok(self * inv)
+    }
+}
+
+impl Mul<Secp256k1Scalar> for Secp256k1Point {
+    type Output = Secp256k1Point;
+
+    fn mul(self, rhs: Secp256k1Scalar) -> Secp256k1Point {
+        Secp256k1Point::from(self.0 * rhs.0)
+    }
+}
+
+impl GroupElement for Secp256k1Point {
+    type ScalarType = Secp256k1Scalar;
+
+    fn zero() -> Self {
+        Secp256k1Point::from(k256::ProjectivePoint::IDENTITY)
+    }
+
+    fn generator() -> Self {
+        Secp256k1Point::from(k256::ProjectivePoint::GENERATOR)
+    }
+}
+
+impl ToFromByteArray<SECP256K1_POINT_BYTE_LENGTH> for Secp256k1Point {
+    fn from_byte_array(bytes: &[u8; SECP256K1_POINT_BYTE_LENGTH]) -> Result<Self, MangekyouError> {
+        Option::from(k256::ProjectivePoint::from_bytes(bytes.into()))
+            .map(Secp256k1Point)
+            .ok_or(MangekyouError::InvalidInput)
+    }
+
+    fn to_byte_array(&self) -> [u8; SECP256K1_POINT_BYTE_LENGTH] {
+        self.0.to_bytes().into()
+    }
+}
+
+serialize_deserialize_with_to_from_byte_array!(Secp256k1Point);
+
+/// A scalar modulo the order of the secp256k1 group.
+#[derive(
+    Default, Clone, Copy, Debug, PartialEq, Eq, From, Add, Sub, Neg, GroupOpsExtend, Zeroize,
+)]
+pub struct Secp256k1Scalar(k256::Scalar);
+
+impl Secp256k1Scalar {
+    /// Construct a [Secp256k1Scalar] by reducing a 64-byte big-endian integer modulo the group order.
+    pub fn from_bytes_mod_order_wide(bytes: &[u8; 64]) -> Self {
+        Secp256k1Scalar(k256::Scalar::from_uint_reduced(U512::from_be_bytes(*bytes)))
+    }
+}
+
+impl From<u128> for Secp256k1Scalar {
+    fn from(value: u128) -> Secp256k1Scalar {
+        let mut bytes = [0u8; 32];
+        bytes[16..].copy_from_slice(&value.to_be_bytes());
+        Secp256k1Scalar(k256::Scalar::from_uint_reduced(U256::from_be_bytes(bytes)))
+    }
+}
+
+impl Mul<Secp256k1Scalar> for Secp256k1Scalar {
+    type Output = Secp256k1Scalar;
+
+    fn mul(self, rhs: Secp256k1Scalar) -> Secp256k1Scalar {
+        Secp256k1Scalar::from(self.0 * rhs.0)
+    }
+}
+
+#[allow(clippy::suspicious_arithmetic_impl)]
+impl Div<Secp256k1Scalar> for Secp256k1Scalar {
+    type Output = Result<Secp256k1Scalar, MangekyouError>;
+
+    fn div(self, rhs: Secp256k1Scalar) -> Result<Secp256k1Scalar, MangekyouError> {
+        let inv = rhs.inverse()?;
+        Ok(self * inv)
+    }
+}
+
+impl GroupElement for Secp256k1Scalar {
+    type ScalarType = Self;
+
+    fn zero() -> Self {
+        Secp256k1Scalar::from(k256::Scalar::zero())
+    }
+
+    fn generator() -> Self {
+        Secp256k1Scalar::from(k256::Scalar::one())
+    }
+}
+
+impl Scalar for Secp256k1Scalar {
+    fn rand<R: AllowedRng>(rng: &mut R) -> Self {
+        Self(k256::Scalar::random(rng))
+    }
+
+    fn inverse(&self) -> MangekyouResult<Self> {
+        Option::from(self.0.invert())
+            .map(Secp256k1Scalar)
+            .ok_or(MangekyouError::InvalidInput)
+    }
+}
+
+impl HashToGroupElement for Secp256k1Scalar {
+    fn hash_to_group_element(bytes: &[u8]) -> Self {
+        Self::from_bytes_mod_order_wide(&Sha512::digest(bytes).digest)
+    }
+}
+
+impl FiatShamirChallenge for Secp256k1Scalar {
+    fn fiat_shamir_reduction_to_group_element(msg: &[u8]) -> Self {
+        Self::hash_to_group_element(msg)
+    }
+}
+
+impl ToFromByteArray<SECP256K1_SCALAR_BYTE_LENGTH> for Secp256k1Scalar {
+    fn from_byte_array(bytes: &[u8; SECP256K1_SCALAR_BYTE_LENGTH]) -> Result<Self, MangekyouError> {
+        Option::from(k256::Scalar::from_repr(FieldBytes::from(*bytes)))
+            .map(Secp256k1Scalar)
+            .ok_or(MangekyouError::InvalidInput)
+    }
+
+    fn to_byte_array(&self) -> [u8; SECP256K1_SCALAR_BYTE_LENGTH] {
+        self.0.to_bytes().into()
+    }
+}
+
+serialize_deserialize_with_to_from_byte_array!(Secp256k1Scalar);
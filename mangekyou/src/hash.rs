@@ -17,12 +17,14 @@
 //! ```
 
 use core::fmt::Debug;
+use digest::typenum::Unsigned;
 use digest::OutputSizeUser;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use std::fmt;
 
 use crate::encoding::{Base64, Encoding};
+use crate::error::{MangekyouError, MangekyouResult};
 use crate::groups::ristretto255::RistrettoPoint;
 use crate::groups::HashToGroupElement;
 
@@ -177,6 +179,184 @@ pub type Keccak256 = HashFunctionWrapper<sha3::Keccak256, 32>;
 /// The [BLAKE2-256](https://en.wikipedia.org/wiki/BLAKE_(hash_function)#BLAKE2) hash function with 256 bit digests.
 pub type Blake2b256 = HashFunctionWrapper<blake2::Blake2b<typenum::U32>, 32>;
 
+/// Hash functions which process their input in blocks of a fixed length. This is needed by
+/// [expand_message_xmd].
+pub trait BlockHashFunction<const DIGEST_LENGTH: usize>: HashFunction<DIGEST_LENGTH> {
+    /// The length of the input blocks of this hash function in bytes.
+    const BLOCK_LENGTH: usize;
+}
+
+impl<
+        Variant: digest::Digest + digest::core_api::BlockSizeUser + 'static + Default,
+        const DIGEST_LEN: usize,
+    > BlockHashFunction<DIGEST_LEN> for HashFunctionWrapper<Variant, DIGEST_LEN>
+{
+    const BLOCK_LENGTH: usize = <Variant::BlockSize as Unsigned>::USIZE;
+}
+
+/// Trait implemented by extendable-output functions, which can produce outputs of any length.
+pub trait ExtendableOutputFunction: Default {
+    /// The security level of this function in bits.
+    const SECURITY_LEVEL: usize;
+
+    /// Process the given data, and update the internal of the function.
+    fn update<Data: AsRef<[u8]>>(&mut self, data: Data);
+
+    /// Fill `output` with the result and consume the function.
+    fn finalize_into(self, output: &mut [u8]);
+
+    /// Compute `output_length` bytes of output from the given data.
+    fn digest<Data: AsRef<[u8]>>(data: Data, output_length: usize) -> Vec<u8> {
+        let mut h = Self::default();
+        h.update(data);
+        let mut output = vec![0u8; output_length];
+        h.finalize_into(&mut output);
+        output
+    }
+}
+
+/// This wraps a [digest::ExtendableOutput] as an [ExtendableOutputFunction].
+#[derive(Default)]
+pub struct ExtendableOutputFunctionWrapper<
+    Variant: digest::ExtendableOutput + digest::Update + 'static,
+    const SECURITY_LEVEL: usize,
+>(Variant);
+
+impl<
+        Variant: digest::ExtendableOutput + digest::Update + 'static + Default,
+        const SECURITY_LEVEL: usize,
+    > ExtendableOutputFunction for ExtendableOutputFunctionWrapper<Variant, SECURITY_LEVEL>
+{
+    const SECURITY_LEVEL: usize = SECURITY_LEVEL;
+
+    fn update<Data: AsRef<[u8]>>(&mut self, data: Data) {
+        digest::Update::update(&mut self.0, data.as_ref());
+    }
+
+    fn finalize_into(self, output: &mut [u8]) {
+        self.0.finalize_xof_into(output);
+    }
+}
+
+/// The [SHAKE128](https://en.wikipedia.org/wiki/SHA-3) extendable-output function.
+pub type Shake128 = ExtendableOutputFunctionWrapper<sha3::Shake128, 128>;
+
+/// The [SHAKE256](https://en.wikipedia.org/wiki/SHA-3) extendable-output function.
+pub type Shake256 = ExtendableOutputFunctionWrapper<sha3::Shake256, 256>;
+
+/// Prefix of the hash of domain separation tags longer than 255 bytes, see section 5.3.3 of
+/// [RFC 9380](https://datatracker.ietf.org/doc/rfc9380/).
+const OVERSIZE_DST_PREFIX: &[u8] = b"H2C-OVERSIZE-DST-";
+
+/// The maximal length of a domain separation tag.
+const MAX_DST_LENGTH: usize = 255;
+
+/// The maximal length of an `expand_message_xmd` output in digests of the hash function.
+const MAX_XMD_BLOCKS: usize = 255;
+
+/// The maximal length of an `expand_message` output in bytes.
+const MAX_EXPAND_MESSAGE_LENGTH: usize = 65535;
+
+/// The `DST_prime` of section 5.3 of RFC 9380: the domain separation tag, reduced with `reduce`
+/// if it is longer than 255 bytes, followed by its length.
+fn dst_prime(dst: &[u8], reduce: impl FnOnce(&[u8]) -> Vec<u8>) -> Vec<u8> {
+    let mut dst_prime = if dst.len() > MAX_DST_LENGTH {
+        reduce(dst)
+    } else {
+        dst.to_vec()
+    };
+    dst_prime.push(dst_prime.len() as u8);
+    dst_prime
+}
+
+/// Expand a message into `length` uniformly random bytes using the hash function `H`, as in
+/// `expand_message_xmd` of section 5.3.1 of [RFC 9380](https://datatracker.ietf.org/doc/rfc9380/).
+/// Domain separation tags longer than 255 bytes are hashed first as in section 5.3.3.
+///
+/// Fails if `length` is larger than 65535 bytes or 255 digests of `H`.
+///
+/// # Example
+/// ```
+/// # use mangekyou::hash::{expand_message_xmd, Sha256};
+/// let bytes = expand_message_xmd::<Sha256, 32>(b"Hello, world!", b"MY-PROTOCOL-V01", 48).unwrap();
+/// assert_eq!(bytes.len(), 48);
+/// ```
+pub fn expand_message_xmd<H: BlockHashFunction<DIGEST_LEN>, const DIGEST_LEN: usize>(
+    msg: &[u8],
+    dst: &[u8],
+    length: usize,
+) -> MangekyouResult<Vec<u8>> {
+    let ell = length.div_ceil(DIGEST_LEN);
+    if ell > MAX_XMD_BLOCKS || length > MAX_EXPAND_MESSAGE_LENGTH {
+        return Err(MangekyouError::InvalidInput);
+    }
+    let dst_prime = dst_prime(dst, |dst| {
+        H::digest_iterator([OVERSIZE_DST_PREFIX, dst].into_iter()).to_vec()
+    });
+
+    // b_0 = H(Z_pad || msg || l_i_b_str || I2OSP(0, 1) || DST_prime)
+    let mut hasher = H::default();
+    hasher.update(vec![0u8; H::BLOCK_LENGTH]);
+    hasher.update(msg);
+    hasher.update((length as u16).to_be_bytes());
+    hasher.update([0u8]);
+    hasher.update(&dst_prime);
+    let b_0 = hasher.finalize();
+
+    // b_i = H(strxor(b_0, b_(i - 1)) || I2OSP(i, 1) || DST_prime), where b_1 = H(b_0 || ...)
+    let mut output = Vec::with_capacity(ell * DIGEST_LEN);
+    let mut b_i = [0u8; DIGEST_LEN];
+    for i in 1..=ell {
+        let mut hasher = H::default();
+        hasher.update(
+            b_0.digest
+                .iter()
+                .zip(b_i.iter())
+                .map(|(a, b)| a ^ b)
+                .collect::<Vec<u8>>(),
+        );
+        hasher.update([i as u8]);
+        hasher.update(&dst_prime);
+        b_i = hasher.finalize().digest;
+        output.extend_from_slice(&b_i);
+    }
+    output.truncate(length);
+    Ok(output)
+}
+
+/// Expand a message into `length` uniformly random bytes using the extendable-output function
+/// `X`, as in `expand_message_xof` of section 5.3.2 of
+/// [RFC 9380](https://datatracker.ietf.org/doc/rfc9380/). Domain separation tags longer than 255
+/// bytes are hashed first as in section 5.3.3.
+///
+/// Fails if `length` is larger than 65535 bytes.
+pub fn expand_message_xof<X: ExtendableOutputFunction>(
+    msg: &[u8],
+    dst: &[u8],
+    length: usize,
+) -> MangekyouResult<Vec<u8>> {
+    if length > MAX_EXPAND_MESSAGE_LENGTH {
+        return Err(MangekyouError::InvalidInput);
+    }
+    let dst_prime = dst_prime(dst, |dst| {
+        let mut hasher = X::default();
+        hasher.update(OVERSIZE_DST_PREFIX);
+        hasher.update(dst);
+        let mut reduced = vec![0u8; (2 * X::SECURITY_LEVEL).div_ceil(8)];
+        hasher.finalize_into(&mut reduced);
+        reduced
+    });
+
+    // msg_prime = msg || I2OSP(len_in_bytes, 2) || DST_prime
+    let mut hasher = X::default();
+    hasher.update(msg);
+    hasher.update((length as u16).to_be_bytes());
+    hasher.update(&dst_prime);
+    let mut output = vec![0u8; length];
+    hasher.finalize_into(&mut output);
+    Ok(output)
+}
+
 /// A Multiset Hash is a homomorphic hash function, which hashes arbitrary multisets of objects such
 /// that the hash of the union of two multisets is easy to compute from the hashes of the two multisets.
 ///
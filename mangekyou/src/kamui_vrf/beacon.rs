@@ -0,0 +1,123 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A chained public randomness beacon on the `sol_vrf` ECVRF.
+//!
+//! Rounds are numbered from 1. The output of round `r` is the VRF output of the beacon key on
+//!
+//! `alpha_r = r (8 bytes, big-endian) || beta_{r-1}`,
+//!
+//! where `beta_{r-1}` is the output of the previous round and `beta_0` is the genesis value of the
+//! beacon. Every round is unique for the key and commits to the whole chain before it, so the oracle
+//! can neither skip nor re-roll a round. The Kamui program verifies each link when a round is
+//! published and keeps the latest rounds in a ring buffer. It also derives the genesis value from a
+//! recent slot hash when the beacon is created, so read it from the beacon account rather than
+//! choosing one.
+
+use super::ecvrf::{ECVRFKeyPair, ECVRFProof, ECVRFProver, ECVRFPublicKey, OUTPUT_LENGTH};
+use super::VRFProof;
+use crate::error::{MangekyouError, MangekyouResult};
+
+/// Length of the VRF input of a round.
+pub const BEACON_ALPHA_LENGTH: usize = 8 + OUTPUT_LENGTH;
+
+/// The VRF input of `round`, chained to the output of the round before it.
+pub fn beacon_alpha(round: u64, previous: &[u8; OUTPUT_LENGTH]) -> [u8; BEACON_ALPHA_LENGTH] {
+    let mut alpha = [0u8; BEACON_ALPHA_LENGTH];
+    alpha[..8].copy_from_slice(&round.to_be_bytes());
+    alpha[8..].copy_from_slice(previous);
+    alpha
+}
+
+/// A published round of the beacon.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BeaconRound {
+    pub round: u64,
+    pub proof: ECVRFProof,
+}
+
+impl BeaconRound {
+    /// The randomness of this round.
+    pub fn output(&self) -> [u8; OUTPUT_LENGTH] {
+        self.proof.to_hash()
+    }
+
+    /// Verify this round under `public_key`, given the output of the round before it (or the
+    /// genesis value for round 1).
+    pub fn verify(
+        &self,
+        public_key: &ECVRFPublicKey,
+        previous: &[u8; OUTPUT_LENGTH],
+    ) -> MangekyouResult<()> {
+        if self.round == 0 {
+            return Err(MangekyouError::InvalidInput);
+        }
+        self.proof
+            .verify(&beacon_alpha(self.round, previous), public_key)
+    }
+}
+
+/// Verify consecutive rounds, the first of which follows `previous`, and return the output of the
+/// last one. An empty slice returns `previous`.
+pub fn verify_chain(
+    public_key: &ECVRFPublicKey,
+    previous: &[u8; OUTPUT_LENGTH],
+    rounds: &[BeaconRound],
+) -> MangekyouResult<[u8; OUTPUT_LENGTH]> {
+    let mut output = *previous;
+    for (i, round) in rounds.iter().enumerate() {
+        if i > 0 && round.round != rounds[i - 1].round.wrapping_add(1) {
+            return Err(MangekyouError::InvalidInput);
+        }
+        round.verify(public_key, &output)?;
+        output = round.output();
+    }
+    Ok(output)
+}
+
+/// Produces the rounds of a beacon in order. Keep one per beacon and persist [Self::round] and
+/// [Self::output] to resume after a restart.
+pub struct BeaconProver {
+    prover: ECVRFProver,
+    round: u64,
+    output: [u8; OUTPUT_LENGTH],
+}
+
+impl BeaconProver {
+    /// A beacon that has not published any round yet.
+    pub fn new(keypair: ECVRFKeyPair, genesis: [u8; OUTPUT_LENGTH]) -> Self {
+        Self::resume(keypair, 0, genesis)
+    }
+
+    /// A beacon whose last published round is `round` with output `output`.
+    pub fn resume(keypair: ECVRFKeyPair, round: u64, output: [u8; OUTPUT_LENGTH]) -> Self {
+        Self {
+            prover: ECVRFProver::new(keypair),
+            round,
+            output,
+        }
+    }
+
+    pub fn public_key(&self) -> &ECVRFPublicKey {
+        &self.prover.keypair().pk
+    }
+
+    /// The last round produced, or 0 if none.
+    pub fn round(&self) -> u64 {
+        self.round
+    }
+
+    /// The output of the last round produced, or the genesis value if none.
+    pub fn output(&self) -> &[u8; OUTPUT_LENGTH] {
+        &self.output
+    }
+
+    /// Produce the next round.
+    pub fn next_round(&mut self) -> BeaconRound {
+        let round = self.round + 1;
+        let (output, proof) = self.prover.output(&beacon_alpha(round, &self.output));
+        self.round = round;
+        self.output = output;
+        BeaconRound { round, proof }
+    }
+}
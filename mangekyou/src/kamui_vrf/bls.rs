@@ -0,0 +1,424 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A VRF from BLS unique signatures over BLS12-381. The proof for an input alpha is the signature
+//! sk * H(alpha) in G1, where H hashes to G1 with [BLS_VRF_DST], and the output is a hash of the
+//! proof. Public keys are in G2, so a proof is checked with the pairing equation
+//! e(proof, g2) = e(H(alpha), pk), which EVM contracts can evaluate with the BLS12-381 precompiles
+//! of EIP-2537.
+//!
+//! Since signatures are unique and linear in the key, proofs can be
+//! - aggregated: the sum of the proofs of several oracles for the same input is the proof for the
+//!   sum of their keys. Keys must come with a [BLSVRFProofOfPossession] to rule out rogue keys.
+//! - computed with a threshold: a key split into shares with [BLSVRFKeyPair::split] gives partial
+//!   proofs, and any `threshold` of them combine into the proof of the full key, with the same
+//!   output.
+
+use super::generic::{invalid_encoding, invalid_length};
+use super::{VRFKeyPair, VRFPrivateKey, VRFProof, VRFPublicKey};
+use crate::error::{MangekyouError, MangekyouResult};
+use crate::groups::bls12381::{
+    G1Element, G2Element, Scalar, G1_ELEMENT_BYTE_LENGTH, G2_ELEMENT_BYTE_LENGTH, SCALAR_LENGTH,
+};
+use crate::groups::{self, GroupElement, MultiScalarMul, Pairing};
+use crate::hash::{HashFunction, Sha512};
+use crate::serde_helpers::ToFromByteArray;
+use crate::traits::AllowedRng;
+use mangekyou_derive::{SilentDebug, SilentDisplay};
+use std::collections::HashSet;
+use zeroize::{Zeroize, Zeroizing};
+
+/// Domain separation tag used to hash inputs to G1.
+pub const BLS_VRF_DST: &[u8] = b"KAMUI-BLS-VRF-V01-CS01-with-BLS12381G1_XMD:SHA-256_SSWU_RO_";
+
+/// Domain separation tag used to hash public keys to G1 for proofs of possession.
+pub const BLS_VRF_POP_DST: &[u8] =
+    b"KAMUI-BLS-VRF-POP-V01-CS01-with-BLS12381G1_XMD:SHA-256_SSWU_RO_";
+
+/// Suite string hashed into outputs, as in proof_to_hash of draft-irtf-cfrg-vrf-15.
+const SUITE_STRING: &[u8] = b"bls_vrf";
+
+/// Length of an encoded public key.
+pub const PUBLIC_KEY_LENGTH: usize = G2_ELEMENT_BYTE_LENGTH;
+
+/// Length of an encoded private key.
+pub const PRIVATE_KEY_LENGTH: usize = SCALAR_LENGTH;
+
+/// Length of an encoded proof.
+pub const PROOF_LENGTH: usize = G1_ELEMENT_BYTE_LENGTH;
+
+/// Length of the VRF output.
+pub const OUTPUT_LENGTH: usize = 64;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BLSVRFPublicKey {
+    point: G2Element,
+    bytes: [u8; PUBLIC_KEY_LENGTH],
+}
+
+impl VRFPublicKey for BLSVRFPublicKey {
+    type PrivateKey = BLSVRFPrivateKey;
+}
+
+impl BLSVRFPublicKey {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, std::io::Error> {
+        let bytes: [u8; PUBLIC_KEY_LENGTH] = bytes
+            .try_into()
+            .map_err(|_| invalid_length("BLSVRFPublicKey"))?;
+        let point =
+            G2Element::from_byte_array(&bytes).map_err(|_| invalid_encoding("BLSVRFPublicKey"))?;
+        Ok(Self { point, bytes })
+    }
+
+    /// The public key as a group element.
+    pub fn point(&self) -> &G2Element {
+        &self.point
+    }
+
+    /// Aggregate public keys into the key that verifies aggregated proofs. Each key must have been
+    /// checked with [BLSVRFProofOfPossession::verify] first.
+    pub fn aggregate(public_keys: &[Self]) -> MangekyouResult<Self> {
+        if public_keys.is_empty() {
+            return Err(MangekyouError::NotEnoughInputs);
+        }
+        Ok(Self::from_point(
+            public_keys
+                .iter()
+                .fold(G2Element::zero(), |acc, pk| acc + pk.point),
+        ))
+    }
+
+    fn from_point(point: G2Element) -> Self {
+        Self {
+            bytes: point.to_byte_array(),
+            point,
+        }
+    }
+
+    fn valid(&self) -> bool {
+        self.point != G2Element::zero()
+    }
+}
+
+impl AsRef<[u8]> for BLSVRFPublicKey {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// The secret scalar is elided from `Debug` and `Display` and wiped when the key is dropped.
+#[derive(Clone, SilentDebug, SilentDisplay)]
+pub struct BLSVRFPrivateKey {
+    bytes: [u8; PRIVATE_KEY_LENGTH],
+}
+
+impl VRFPrivateKey for BLSVRFPrivateKey {
+    type PublicKey = BLSVRFPublicKey;
+}
+
+impl Zeroize for BLSVRFPrivateKey {
+    fn zeroize(&mut self) {
+        self.bytes.zeroize();
+    }
+}
+
+impl Drop for BLSVRFPrivateKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl BLSVRFPrivateKey {
+    /// Parse a private key. The bytes must be a canonical encoding of a non-zero scalar.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, std::io::Error> {
+        let bytes: [u8; PRIVATE_KEY_LENGTH] = bytes
+            .try_into()
+            .map_err(|_| invalid_length("BLSVRFPrivateKey"))?;
+        let key = Self { bytes };
+        match key.scalar() {
+            Ok(scalar) if *scalar != Scalar::zero() => Ok(key),
+            _ => Err(invalid_encoding("BLSVRFPrivateKey")),
+        }
+    }
+
+    fn from_scalar(scalar: &Scalar) -> Self {
+        Self {
+            bytes: scalar.to_byte_array(),
+        }
+    }
+
+    fn scalar(&self) -> Result<Zeroizing<Scalar>, MangekyouError> {
+        Scalar::from_byte_array(&self.bytes).map(Zeroizing::new)
+    }
+}
+
+impl AsRef<[u8]> for BLSVRFPrivateKey {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+pub struct BLSVRFKeyPair {
+    pub pk: BLSVRFPublicKey,
+    pub sk: BLSVRFPrivateKey,
+}
+
+impl BLSVRFKeyPair {
+    fn sign(&self, message: &G1Element) -> G1Element {
+        let sk = self
+            .sk
+            .scalar()
+            .expect("Private keys are checked to be canonical when constructed");
+        *message * *sk
+    }
+
+    /// Prove possession of the private key, which is required before the public key is aggregated.
+    pub fn prove_possession(&self) -> BLSVRFProofOfPossession {
+        BLSVRFProofOfPossession(self.sign(&hash_public_key(&self.pk)))
+    }
+
+    /// Split the private key into `n` shares with Shamir secret sharing, such that any `threshold`
+    /// of them can compute proofs for the public key of this key pair. Shares have the indices
+    /// 1 to n.
+    pub fn split<R: AllowedRng>(
+        &self,
+        threshold: u16,
+        n: u16,
+        rng: &mut R,
+    ) -> MangekyouResult<Vec<BLSVRFKeyShare>> {
+        if threshold == 0 || threshold > n {
+            return Err(MangekyouError::InvalidInput);
+        }
+        let mut coefficients = Zeroizing::new(Vec::with_capacity(threshold as usize));
+        coefficients.push(
+            *self
+                .sk
+                .scalar()
+                .expect("Private keys are checked to be canonical when constructed"),
+        );
+        for _ in 1..threshold {
+            coefficients.push(<Scalar as groups::Scalar>::rand(rng));
+        }
+
+        Ok((1..=n)
+            .map(|index| {
+                // Horner's method
+                let x = Scalar::from(index as u128);
+                let share = Zeroizing::new(
+                    coefficients
+                        .iter()
+                        .rev()
+                        .fold(Scalar::zero(), |acc, c| acc * x + c),
+                );
+                BLSVRFKeyShare {
+                    index,
+                    keypair: BLSVRFPrivateKey::from_scalar(&share).into(),
+                }
+            })
+            .collect())
+    }
+}
+
+impl From<BLSVRFPrivateKey> for BLSVRFKeyPair {
+    fn from(sk: BLSVRFPrivateKey) -> Self {
+        let scalar = sk
+            .scalar()
+            .expect("Private keys are checked to be canonical when constructed");
+        let pk = BLSVRFPublicKey::from_point(G2Element::generator() * *scalar);
+        BLSVRFKeyPair { pk, sk }
+    }
+}
+
+impl VRFKeyPair<OUTPUT_LENGTH> for BLSVRFKeyPair {
+    type Proof = BLSVRFProof;
+    type PrivateKey = BLSVRFPrivateKey;
+    type PublicKey = BLSVRFPublicKey;
+
+    fn generate<R: AllowedRng>(rng: &mut R) -> Self {
+        loop {
+            let scalar = Zeroizing::new(<Scalar as groups::Scalar>::rand(rng));
+            if *scalar != Scalar::zero() {
+                return BLSVRFPrivateKey::from_scalar(&scalar).into();
+            }
+        }
+    }
+
+    fn prove(&self, alpha_string: &[u8]) -> BLSVRFProof {
+        BLSVRFProof {
+            point: self.sign(&hash_input(alpha_string)),
+        }
+    }
+}
+
+fn hash_input(alpha_string: &[u8]) -> G1Element {
+    G1Element::hash_to_curve(alpha_string, BLS_VRF_DST)
+}
+
+fn hash_public_key(public_key: &BLSVRFPublicKey) -> G1Element {
+    G1Element::hash_to_curve(&public_key.bytes, BLS_VRF_POP_DST)
+}
+
+/// Check that e(signature, g2) = e(message, public_key).
+fn verify_signature(
+    signature: &G1Element,
+    message: &G1Element,
+    public_key: &BLSVRFPublicKey,
+) -> MangekyouResult<()> {
+    if !public_key.valid() {
+        return Err(MangekyouError::InvalidInput);
+    }
+    if signature.pairing(&G2Element::generator()) != message.pairing(&public_key.point) {
+        return Err(MangekyouError::GeneralOpaqueError);
+    }
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BLSVRFProof {
+    point: G1Element,
+}
+
+impl BLSVRFProof {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, std::io::Error> {
+        let bytes: [u8; PROOF_LENGTH] = bytes
+            .try_into()
+            .map_err(|_| invalid_length("BLSVRFProof"))?;
+        Ok(Self {
+            point: G1Element::from_byte_array(&bytes)
+                .map_err(|_| invalid_encoding("BLSVRFProof"))?,
+        })
+    }
+
+    /// The proof as a group element.
+    pub fn point(&self) -> &G1Element {
+        &self.point
+    }
+
+    /// Aggregate proofs for the same input. The result verifies against the aggregate of the
+    /// public keys, see [BLSVRFPublicKey::aggregate].
+    pub fn aggregate(proofs: &[Self]) -> MangekyouResult<Self> {
+        if proofs.is_empty() {
+            return Err(MangekyouError::NotEnoughInputs);
+        }
+        Ok(Self {
+            point: proofs
+                .iter()
+                .fold(G1Element::zero(), |acc, proof| acc + proof.point),
+        })
+    }
+
+    /// Combine partial proofs for the same input from `threshold` distinct shares into the proof
+    /// for the public key the shares were split from. Partial proofs should be verified against
+    /// the public keys of their shares first. Only the first `threshold` partial proofs are used.
+    pub fn combine(threshold: u16, partial_proofs: &[BLSVRFPartialProof]) -> MangekyouResult<Self> {
+        if threshold == 0 || partial_proofs.len() < threshold as usize {
+            return Err(MangekyouError::NotEnoughInputs);
+        }
+        let partial_proofs = &partial_proofs[..threshold as usize];
+        let indices = partial_proofs
+            .iter()
+            .map(|partial| partial.index)
+            .collect::<HashSet<_>>();
+        if indices.len() != partial_proofs.len() || indices.contains(&0) {
+            return Err(MangekyouError::InvalidInput);
+        }
+
+        // Lagrange interpolation at zero: the coefficient of share i is the product of
+        // x_j / (x_j - x_i) over the other shares j.
+        let xs = partial_proofs
+            .iter()
+            .map(|partial| Scalar::from(partial.index as u128))
+            .collect::<Vec<_>>();
+        let coefficients = xs
+            .iter()
+            .map(|x_i| {
+                xs.iter()
+                    .filter(|x_j| *x_j != x_i)
+                    .try_fold(Scalar::generator(), |acc, x_j| {
+                        Ok(acc * (*x_j / (*x_j - x_i))?)
+                    })
+            })
+            .collect::<MangekyouResult<Vec<_>>>()?;
+        let points = partial_proofs
+            .iter()
+            .map(|partial| partial.proof.point)
+            .collect::<Vec<_>>();
+
+        Ok(Self {
+            point: G1Element::multi_scalar_mul(&coefficients, &points)?,
+        })
+    }
+}
+
+impl VRFProof<OUTPUT_LENGTH> for BLSVRFProof {
+    type PublicKey = BLSVRFPublicKey;
+
+    fn verify(
+        &self,
+        alpha_string: &[u8],
+        public_key: &Self::PublicKey,
+    ) -> Result<(), MangekyouError> {
+        verify_signature(&self.point, &hash_input(alpha_string), public_key)
+    }
+
+    fn to_hash(&self) -> [u8; OUTPUT_LENGTH] {
+        let mut hash = Sha512::default();
+        hash.update(SUITE_STRING);
+        hash.update([0x03]); // proof_to_hash_domain_separator_front
+        hash.update(self.point.to_byte_array());
+        hash.update([0x00]); // proof_to_hash_domain_separator_back
+        hash.finalize().digest
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.point.to_byte_array().to_vec()
+    }
+}
+
+/// Proof that the holder of a public key knows its private key: a signature on the public key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BLSVRFProofOfPossession(G1Element);
+
+impl BLSVRFProofOfPossession {
+    /// Verify the proof for `public_key`. The identity is rejected as a key.
+    pub fn verify(&self, public_key: &BLSVRFPublicKey) -> MangekyouResult<()> {
+        verify_signature(&self.0, &hash_public_key(public_key), public_key)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, std::io::Error> {
+        let bytes: [u8; PROOF_LENGTH] = bytes
+            .try_into()
+            .map_err(|_| invalid_length("BLSVRFProofOfPossession"))?;
+        G1Element::from_byte_array(&bytes)
+            .map(Self)
+            .map_err(|_| invalid_encoding("BLSVRFProofOfPossession"))
+    }
+
+    pub fn to_bytes(&self) -> [u8; PROOF_LENGTH] {
+        self.0.to_byte_array()
+    }
+}
+
+/// A share of a private key, see [BLSVRFKeyPair::split]. Its public key verifies the partial
+/// proofs of the share.
+pub struct BLSVRFKeyShare {
+    pub index: u16,
+    pub keypair: BLSVRFKeyPair,
+}
+
+impl BLSVRFKeyShare {
+    /// Compute the partial proof of this share for the given input.
+    pub fn prove(&self, alpha_string: &[u8]) -> BLSVRFPartialProof {
+        BLSVRFPartialProof {
+            index: self.index,
+            proof: self.keypair.prove(alpha_string),
+        }
+    }
+}
+
+/// A proof computed with a key share, together with the index of the share.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BLSVRFPartialProof {
+    pub index: u16,
+    pub proof: BLSVRFProof,
+}
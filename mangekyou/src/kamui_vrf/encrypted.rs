@@ -0,0 +1,384 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Private delivery of `sol_vrf` outputs with verifiable ElGamal encryption over ristretto255.
+//!
+//! The output of a `sol_vrf` proof is a hash of its point `Gamma = x * H(alpha)`. Instead of
+//! publishing `Gamma`, the oracle encrypts it to the requester's [EncryptionKey] `P`:
+//!
+//! `C1 = r * G`, `C2 = Gamma + r * P`,
+//!
+//! and proves with an [EncryptionProof] that the ciphertext holds `x * H(alpha)` for the secret key
+//! `x` of its VRF public key `Y`, i.e. that it knows `(x, r)` with
+//!
+//! `Y = x * G`, `C1 = r * G`, `C2 = x * H(alpha) + r * P`.
+//!
+//! Only the holder of the [DecryptionKey] learns the output, which is the same as
+//! [super::VRFKeyPair::output] for `alpha`. It can later publish a [VRFReveal]: `Gamma` and a
+//! [DLEQProof] that `log_G(P) = log_C1(C2 - Gamma)`, from which anyone derives the output.
+//!
+//! Both proofs are made on a transcript bound to the request id, so a ciphertext or reveal cannot
+//! be replayed for another request. The Kamui program replays the same transcripts.
+
+use super::ecvrf::{ECVRFKeyPair, ECVRFPublicKey, SolVrf, OUTPUT_LENGTH};
+use super::generic::{gamma_to_hash, invalid_encoding, invalid_length};
+use super::solana::SolanaRistrettoPoint;
+use crate::error::{MangekyouError, MangekyouResult};
+use crate::groups::ristretto255::{RistrettoPoint, RistrettoScalar};
+use crate::groups::{GroupElement, HashToGroupElement, MultiScalarMul, Scalar};
+use crate::nizk::{nonce, DLEQProof, TranscriptProtocol};
+use crate::serde_helpers::ToFromByteArray;
+use crate::traits::AllowedRng;
+use mangekyou_derive::{SilentDebug, SilentDisplay};
+use merlin::Transcript;
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, Zeroizing};
+
+/// Label of the transcripts the proofs are made on.
+pub const ENCRYPTED_VRF_TRANSCRIPT_LABEL: &[u8] = b"kamui-encrypted-vrf";
+
+/// Length of a request id.
+pub const REQUEST_ID_LENGTH: usize = 32;
+
+/// Length of an encoded key.
+pub const KEY_LENGTH: usize = 32;
+
+/// Length of an encoded ciphertext: C1 (32 bytes) || C2 (32 bytes).
+pub const CIPHERTEXT_LENGTH: usize = 64;
+
+/// Length of an encoded [EncryptionProof]: three commitments followed by two responses.
+pub const ENCRYPTION_PROOF_LENGTH: usize = 160;
+
+/// Length of an encoded [VRFReveal]: Gamma (32 bytes) || DLEQ proof (96 bytes).
+pub const REVEAL_LENGTH: usize = 128;
+
+fn transcript(request_id: &[u8; REQUEST_ID_LENGTH]) -> Transcript {
+    let mut transcript = Transcript::new(ENCRYPTED_VRF_TRANSCRIPT_LABEL);
+    transcript.append_message(b"request_id", request_id);
+    transcript
+}
+
+/// The point `H(alpha)` of the `sol_vrf` suite.
+fn hash_to_point(alpha_string: &[u8]) -> RistrettoPoint {
+    SolanaRistrettoPoint::hash_to_group_element(alpha_string).into()
+}
+
+fn output(gamma: &RistrettoPoint) -> [u8; OUTPUT_LENGTH] {
+    gamma_to_hash::<SolVrf>(&SolanaRistrettoPoint::from(*gamma))
+}
+
+fn vrf_key(public_key: &ECVRFPublicKey) -> MangekyouResult<RistrettoPoint> {
+    let point = RistrettoPoint::from(*public_key.point());
+    if point == RistrettoPoint::zero() {
+        return Err(MangekyouError::InvalidInput);
+    }
+    Ok(point)
+}
+
+fn point_from_bytes(bytes: &[u8], name: &str) -> Result<RistrettoPoint, std::io::Error> {
+    let bytes: [u8; 32] = bytes.try_into().map_err(|_| invalid_length(name))?;
+    RistrettoPoint::from_byte_array(&bytes).map_err(|_| invalid_encoding(name))
+}
+
+/// The secret scalar is elided from `Debug` and `Display` and wiped when the key is dropped.
+#[derive(Clone, SilentDebug, SilentDisplay)]
+pub struct DecryptionKey(RistrettoScalar);
+
+impl Zeroize for DecryptionKey {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Drop for DecryptionKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl DecryptionKey {
+    pub fn generate<R: AllowedRng>(rng: &mut R) -> Self {
+        Self(RistrettoScalar::rand(rng))
+    }
+
+    /// Parse a decryption key. The bytes must be a canonical encoding of a non-zero scalar.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, std::io::Error> {
+        let bytes: Zeroizing<[u8; KEY_LENGTH]> = Zeroizing::new(
+            bytes
+                .try_into()
+                .map_err(|_| invalid_length("DecryptionKey"))?,
+        );
+        match RistrettoScalar::from_byte_array(&bytes) {
+            Ok(scalar) if scalar != RistrettoScalar::zero() => Ok(Self(scalar)),
+            _ => Err(invalid_encoding("DecryptionKey")),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Zeroizing<[u8; KEY_LENGTH]> {
+        Zeroizing::new(self.0.to_byte_array())
+    }
+
+    pub fn encryption_key(&self) -> EncryptionKey {
+        EncryptionKey(RistrettoPoint::generator() * self.0)
+    }
+
+    /// Decrypt a ciphertext to the VRF output. The ciphertext should be checked with
+    /// [EncryptedVRFOutput::verify] first, otherwise the result is meaningless.
+    pub fn decrypt(&self, ciphertext: &Ciphertext) -> [u8; OUTPUT_LENGTH] {
+        output(&self.gamma(ciphertext))
+    }
+
+    /// Publish the VRF output of a ciphertext for `request_id`, with a proof that it is the
+    /// decryption of the ciphertext.
+    pub fn reveal<R: AllowedRng>(
+        &self,
+        ciphertext: &Ciphertext,
+        request_id: &[u8; REQUEST_ID_LENGTH],
+        rng: &mut R,
+    ) -> VRFReveal {
+        VRFReveal {
+            gamma: self.gamma(ciphertext),
+            proof: DLEQProof::prove(
+                &mut transcript(request_id),
+                &RistrettoPoint::generator(),
+                &ciphertext.c1,
+                &self.0,
+                rng,
+            ),
+        }
+    }
+
+    fn gamma(&self, ciphertext: &Ciphertext) -> RistrettoPoint {
+        ciphertext.c2 - ciphertext.c1 * self.0
+    }
+}
+
+/// The public key a requester receives encrypted VRF outputs under.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EncryptionKey(RistrettoPoint);
+
+impl EncryptionKey {
+    /// Parse an encryption key. The identity is rejected.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, std::io::Error> {
+        let point = point_from_bytes(bytes, "EncryptionKey")?;
+        if point == RistrettoPoint::zero() {
+            return Err(invalid_encoding("EncryptionKey"));
+        }
+        Ok(Self(point))
+    }
+
+    pub fn to_bytes(&self) -> [u8; KEY_LENGTH] {
+        self.0.to_byte_array()
+    }
+}
+
+/// ElGamal encryption `(r * G, Gamma + r * P)` of the VRF point `Gamma`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ciphertext {
+    c1: RistrettoPoint,
+    c2: RistrettoPoint,
+}
+
+impl Ciphertext {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, std::io::Error> {
+        if bytes.len() != CIPHERTEXT_LENGTH {
+            return Err(invalid_length("Ciphertext"));
+        }
+        Ok(Self {
+            c1: point_from_bytes(&bytes[..32], "Ciphertext")?,
+            c2: point_from_bytes(&bytes[32..], "Ciphertext")?,
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; CIPHERTEXT_LENGTH] {
+        let mut bytes = [0u8; CIPHERTEXT_LENGTH];
+        bytes[..32].copy_from_slice(&self.c1.to_byte_array());
+        bytes[32..].copy_from_slice(&self.c2.to_byte_array());
+        bytes
+    }
+}
+
+/// Proof that a [Ciphertext] encrypts the `sol_vrf` point of an input under a VRF public key.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptionProof {
+    commitment_y: RistrettoPoint,
+    commitment_c1: RistrettoPoint,
+    commitment_c2: RistrettoPoint,
+    response_x: RistrettoScalar,
+    response_r: RistrettoScalar,
+}
+
+impl EncryptionProof {
+    const DOMAIN: &'static [u8] = b"elgamal-vrf-encryption";
+
+    #[allow(clippy::too_many_arguments)]
+    fn challenge(
+        transcript: &mut Transcript,
+        y: &RistrettoPoint,
+        h: &RistrettoPoint,
+        p: &EncryptionKey,
+        ciphertext: &Ciphertext,
+        commitment_y: &RistrettoPoint,
+        commitment_c1: &RistrettoPoint,
+        commitment_c2: &RistrettoPoint,
+    ) -> RistrettoScalar {
+        transcript.domain_separator(Self::DOMAIN);
+        transcript.append_element(b"y", y);
+        transcript.append_element(b"h", h);
+        transcript.append_element(b"p", &p.0);
+        transcript.append_element(b"c1", &ciphertext.c1);
+        transcript.append_element(b"c2", &ciphertext.c2);
+        transcript.append_element(b"commitment-y", commitment_y);
+        transcript.append_element(b"commitment-c1", commitment_c1);
+        transcript.append_element(b"commitment-c2", commitment_c2);
+        transcript.challenge_scalar(b"challenge")
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, std::io::Error> {
+        if bytes.len() != ENCRYPTION_PROOF_LENGTH {
+            return Err(invalid_length("EncryptionProof"));
+        }
+        bincode::deserialize(bytes).map_err(|_| invalid_encoding("EncryptionProof"))
+    }
+
+    pub fn to_bytes(&self) -> [u8; ENCRYPTION_PROOF_LENGTH] {
+        bincode::serialize(self)
+            .expect("Serialization of proofs is infallible")
+            .try_into()
+            .expect("Proofs are encoded as five 32 byte elements")
+    }
+}
+
+/// A VRF output encrypted to a requester, with a proof that it was computed correctly.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EncryptedVRFOutput {
+    pub ciphertext: Ciphertext,
+    pub proof: EncryptionProof,
+}
+
+impl EncryptedVRFOutput {
+    /// Compute the VRF output of `keypair` on `alpha_string` and encrypt it to `encryption_key`
+    /// for the request `request_id`.
+    pub fn encrypt<R: AllowedRng>(
+        keypair: &ECVRFKeyPair,
+        alpha_string: &[u8],
+        request_id: &[u8; REQUEST_ID_LENGTH],
+        encryption_key: &EncryptionKey,
+        rng: &mut R,
+    ) -> Self {
+        let x = keypair
+            .sk
+            .scalar()
+            .expect("Private keys are checked to be canonical when constructed");
+        let y = RistrettoPoint::generator() * *x;
+        let h = hash_to_point(alpha_string);
+        let r = Zeroizing::new(RistrettoScalar::rand(rng));
+        let ciphertext = Ciphertext {
+            c1: RistrettoPoint::generator() * *r,
+            c2: h * *x + encryption_key.0 * *r,
+        };
+
+        let mut transcript = transcript(request_id);
+        let k_x = Zeroizing::new(nonce(&transcript, &*x, rng));
+        let k_r = Zeroizing::new(nonce(&transcript, &*r, rng));
+        let commitment_y = RistrettoPoint::generator() * *k_x;
+        let commitment_c1 = RistrettoPoint::generator() * *k_r;
+        let commitment_c2 = h * *k_x + encryption_key.0 * *k_r;
+        let c = EncryptionProof::challenge(
+            &mut transcript,
+            &y,
+            &h,
+            encryption_key,
+            &ciphertext,
+            &commitment_y,
+            &commitment_c1,
+            &commitment_c2,
+        );
+
+        Self {
+            ciphertext,
+            proof: EncryptionProof {
+                commitment_y,
+                commitment_c1,
+                commitment_c2,
+                response_x: *k_x + c * *x,
+                response_r: *k_r + c * *r,
+            },
+        }
+    }
+
+    /// Verify that the ciphertext encrypts the VRF point of `public_key` on `alpha_string` to
+    /// `encryption_key` for `request_id`. The identity is rejected as a VRF key.
+    pub fn verify(
+        &self,
+        alpha_string: &[u8],
+        public_key: &ECVRFPublicKey,
+        request_id: &[u8; REQUEST_ID_LENGTH],
+        encryption_key: &EncryptionKey,
+    ) -> MangekyouResult<()> {
+        let y = vrf_key(public_key)?;
+        let h = hash_to_point(alpha_string);
+        let proof = &self.proof;
+        let c = EncryptionProof::challenge(
+            &mut transcript(request_id),
+            &y,
+            &h,
+            encryption_key,
+            &self.ciphertext,
+            &proof.commitment_y,
+            &proof.commitment_c1,
+            &proof.commitment_c2,
+        );
+
+        let g = RistrettoPoint::generator();
+        if g * proof.response_x != proof.commitment_y + y * c
+            || g * proof.response_r != proof.commitment_c1 + self.ciphertext.c1 * c
+            || RistrettoPoint::multi_scalar_mul(
+                &[proof.response_x, proof.response_r],
+                &[h, encryption_key.0],
+            )? != proof.commitment_c2 + self.ciphertext.c2 * c
+        {
+            return Err(MangekyouError::InvalidProof);
+        }
+        Ok(())
+    }
+}
+
+/// The VRF point of an [EncryptedVRFOutput], published by the holder of the decryption key with a
+/// proof that it is the decryption of the ciphertext.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VRFReveal {
+    gamma: RistrettoPoint,
+    proof: DLEQProof<RistrettoPoint>,
+}
+
+impl VRFReveal {
+    /// Verify the reveal of `ciphertext` for `request_id` and return the VRF output.
+    pub fn verify(
+        &self,
+        ciphertext: &Ciphertext,
+        request_id: &[u8; REQUEST_ID_LENGTH],
+        encryption_key: &EncryptionKey,
+    ) -> MangekyouResult<[u8; OUTPUT_LENGTH]> {
+        self.proof.verify(
+            &mut transcript(request_id),
+            (&RistrettoPoint::generator(), &encryption_key.0),
+            (&ciphertext.c1, &(ciphertext.c2 - self.gamma)),
+        )?;
+        Ok(output(&self.gamma))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, std::io::Error> {
+        if bytes.len() != REVEAL_LENGTH {
+            return Err(invalid_length("VRFReveal"));
+        }
+        bincode::deserialize(bytes).map_err(|_| invalid_encoding("VRFReveal"))
+    }
+
+    pub fn to_bytes(&self) -> [u8; REVEAL_LENGTH] {
+        bincode::serialize(self)
+            .expect("Serialization of proofs is infallible")
+            .try_into()
+            .expect("Reveals are encoded as four 32 byte elements")
+    }
+}
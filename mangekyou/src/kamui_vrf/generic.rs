@@ -0,0 +1,338 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! An Elliptic Curve VRF (ECVRF) written once over the [GroupElement] abstraction. The construction
+//! follows draft-irtf-cfrg-vrf-15 (https://datatracker.ietf.org/doc/draft-irtf-cfrg-vrf/), with the
+//! group, scalar field, hash function and suite string supplied by an [ECVRFSuite].
+
+use super::*;
+use crate::groups::{self, GroupElement, HashToGroupElement, MultiScalarMul};
+use crate::hash::HashFunction;
+use crate::serde_helpers::ToFromByteArray;
+use mangekyou_derive::{SilentDebug, SilentDisplay};
+use std::marker::PhantomData;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+/// Length of challenges. Must not exceed the length of field elements which is 32 in this case.
+/// We set C_LEN = 16 which is the same as the existing ECVRF suites in draft-irtf-cfrg-vrf-15.
+pub const C_LEN: usize = 16;
+
+/// Length of an encoded group element.
+pub const POINT_LENGTH: usize = 32;
+
+/// Length of an encoded scalar.
+pub const SCALAR_LENGTH: usize = 32;
+
+/// Length of an encoded proof: gamma || c || s.
+pub const PROOF_LENGTH: usize = POINT_LENGTH + C_LEN + SCALAR_LENGTH;
+
+/// Length of the VRF output.
+pub const OUTPUT_LENGTH: usize = 64;
+
+/// The parameters of an ECVRF instantiation.
+pub trait ECVRFSuite: Clone + Copy + std::fmt::Debug + PartialEq + Eq {
+    /// The group keys, gammas and hashed inputs live in. [HashToGroupElement] is used as
+    /// encode_to_curve, so it must be domain separated for this suite.
+    type Group: GroupElement<ScalarType = Self::Scalar>
+        + HashToGroupElement
+        + MultiScalarMul
+        + ToFromByteArray<POINT_LENGTH>;
+
+    /// The scalar field of [Self::Group]. [HashToGroupElement] is used to derive nonces from a
+    /// [Self::Hash] digest of the secret key and the hashed input.
+    type Scalar: groups::Scalar + HashToGroupElement + ToFromByteArray<SCALAR_LENGTH> + Zeroize;
+
+    /// Hash function used for nonces, challenges and proof_to_hash.
+    type Hash: HashFunction<OUTPUT_LENGTH>;
+
+    /// draft-irtf-cfrg-vrf-15 notes that designs outside of the specified suites should use their
+    /// own suite_string.
+    const SUITE_STRING: &'static [u8];
+}
+
+pub(super) fn invalid_length(name: &str) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        format!("Invalid byte length for {}", name),
+    )
+}
+
+pub(super) fn invalid_encoding(name: &str) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        format!("Invalid encoding for {}", name),
+    )
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ECVRFPublicKey<S: ECVRFSuite> {
+    point: S::Group,
+    bytes: [u8; POINT_LENGTH],
+}
+
+impl<S: ECVRFSuite> VRFPublicKey for ECVRFPublicKey<S> {
+    type PrivateKey = ECVRFPrivateKey<S>;
+}
+
+impl<S: ECVRFSuite> ECVRFPublicKey<S> {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, std::io::Error> {
+        let bytes: [u8; POINT_LENGTH] = bytes
+            .try_into()
+            .map_err(|_| invalid_length("ECVRFPublicKey"))?;
+        let point =
+            S::Group::from_byte_array(&bytes).map_err(|_| invalid_encoding("ECVRFPublicKey"))?;
+        Ok(Self { point, bytes })
+    }
+
+    /// The public key as a group element.
+    pub fn point(&self) -> &S::Group {
+        &self.point
+    }
+
+    fn from_point(point: S::Group) -> Self {
+        Self {
+            bytes: point.to_byte_array(),
+            point,
+        }
+    }
+
+    fn valid(&self) -> bool {
+        self.point != S::Group::zero()
+    }
+}
+
+impl<S: ECVRFSuite> AsRef<[u8]> for ECVRFPublicKey<S> {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// The secret scalar is elided from `Debug` and `Display` and wiped when the key is dropped.
+#[derive(Clone, SilentDebug, SilentDisplay, Zeroize, ZeroizeOnDrop)]
+pub struct ECVRFPrivateKey<S: ECVRFSuite> {
+    bytes: [u8; SCALAR_LENGTH],
+    #[zeroize(skip)]
+    suite: PhantomData<S>,
+}
+
+impl<S: ECVRFSuite> VRFPrivateKey for ECVRFPrivateKey<S> {
+    type PublicKey = ECVRFPublicKey<S>;
+}
+
+impl<S: ECVRFSuite> ECVRFPrivateKey<S> {
+    /// Parse a private key. The bytes must be a canonical encoding of a scalar.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, std::io::Error> {
+        let bytes: [u8; SCALAR_LENGTH] = bytes
+            .try_into()
+            .map_err(|_| invalid_length("ECVRFPrivateKey"))?;
+        let key = Self {
+            bytes,
+            suite: PhantomData,
+        };
+        key.scalar()
+            .map_err(|_| invalid_encoding("ECVRFPrivateKey"))?;
+        Ok(key)
+    }
+
+    pub(super) fn scalar(&self) -> Result<Zeroizing<S::Scalar>, MangekyouError> {
+        S::Scalar::from_byte_array(&self.bytes).map(Zeroizing::new)
+    }
+
+    /// Derive the nonce k from the secret key and h_string. See section 5.4.2.2 of
+    /// draft-irtf-cfrg-vrf-15. All intermediate values are wiped when they go out of scope.
+    fn ecvrf_nonce_generation(&self, h_string: &[u8]) -> Zeroizing<S::Scalar> {
+        let hashed_sk_string = Zeroizing::new(S::Hash::digest(self.bytes).digest);
+        let mut k_string = Zeroizing::new(Vec::with_capacity(32 + h_string.len()));
+        k_string.extend_from_slice(&hashed_sk_string[32..64]);
+        k_string.extend_from_slice(h_string);
+        Zeroizing::new(S::Scalar::hash_to_group_element(&k_string))
+    }
+}
+
+impl<S: ECVRFSuite> AsRef<[u8]> for ECVRFPrivateKey<S> {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+pub struct ECVRFKeyPair<S: ECVRFSuite> {
+    pub pk: ECVRFPublicKey<S>,
+    pub sk: ECVRFPrivateKey<S>,
+}
+
+impl<S: ECVRFSuite> ECVRFKeyPair<S> {
+    /// Parse a key pair encoded as pk || sk. The public key is not checked against the private key.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, std::io::Error> {
+        if bytes.len() != POINT_LENGTH + SCALAR_LENGTH {
+            return Err(invalid_length("KeyPair"));
+        }
+        Ok(Self {
+            pk: ECVRFPublicKey::from_bytes(&bytes[..POINT_LENGTH])?,
+            sk: ECVRFPrivateKey::from_bytes(&bytes[POINT_LENGTH..])?,
+        })
+    }
+}
+
+impl<S: ECVRFSuite> ECVRFKeyPair<S> {
+    /// Same as [VRFKeyPair::prove], but `multiply` computes `[x*H, k*B, k*H]` from H, the secret
+    /// key x and the nonce k, e.g. in another representation of the group. Proofs are identical.
+    pub(super) fn prove_with(
+        &self,
+        alpha_string: &[u8],
+        multiply: impl FnOnce(&S::Group, &S::Scalar, &S::Scalar) -> [S::Group; 3],
+    ) -> ECVRFProof<S> {
+        let sk = self
+            .sk
+            .scalar()
+            .expect("Private keys are checked to be canonical when constructed");
+        let h_point = S::Group::hash_to_group_element(alpha_string);
+        let k = self.sk.ecvrf_nonce_generation(&h_point.to_byte_array());
+        let [gamma, k_b, k_h] = multiply(&h_point, &sk, &k);
+
+        let c = ecvrf_challenge_generation::<S>([&self.pk.point, &h_point, &gamma, &k_b, &k_h]);
+        let s = *k + challenge_to_scalar::<S>(&c) * *sk;
+
+        ECVRFProof { gamma, c, s }
+    }
+}
+
+impl<S: ECVRFSuite> From<ECVRFPrivateKey<S>> for ECVRFKeyPair<S> {
+    fn from(sk: ECVRFPrivateKey<S>) -> Self {
+        let scalar = sk
+            .scalar()
+            .expect("Private keys are checked to be canonical when constructed");
+        let pk = ECVRFPublicKey::from_point(S::Group::generator() * *scalar);
+        ECVRFKeyPair { pk, sk }
+    }
+}
+
+/// Generate challenge from five points. See section 5.4.3. of draft-irtf-cfrg-vrf-15.
+fn ecvrf_challenge_generation<S: ECVRFSuite>(points: [&S::Group; 5]) -> [u8; C_LEN] {
+    let mut hasher = S::Hash::default();
+    hasher.update(S::SUITE_STRING);
+    hasher.update([0x02]); // challenge_generation_domain_separator_front
+    for p in points.iter() {
+        hasher.update(p.to_byte_array());
+    }
+    hasher.update([0x00]); // challenge_generation_domain_separator_back
+    let digest = hasher.finalize();
+
+    let mut challenge = [0u8; C_LEN];
+    challenge.copy_from_slice(&digest.digest[..C_LEN]);
+    challenge
+}
+
+/// The VRF output of a proof with the given Gamma. See section 5.2 of draft-irtf-cfrg-vrf-15.
+pub(super) fn gamma_to_hash<S: ECVRFSuite>(gamma: &S::Group) -> [u8; OUTPUT_LENGTH] {
+    let mut hash = S::Hash::default();
+    hash.update(S::SUITE_STRING);
+    hash.update([0x03]); // proof_to_hash_domain_separator_front
+    hash.update(gamma.to_byte_array());
+    hash.update([0x00]); // proof_to_hash_domain_separator_back
+    hash.finalize().digest
+}
+
+/// Interpret a challenge as a little-endian integer.
+fn challenge_to_scalar<S: ECVRFSuite>(c: &[u8; C_LEN]) -> S::Scalar {
+    S::Scalar::from(u128::from_le_bytes(*c))
+}
+
+impl<S: ECVRFSuite> VRFKeyPair<OUTPUT_LENGTH> for ECVRFKeyPair<S> {
+    type Proof = ECVRFProof<S>;
+    type PrivateKey = ECVRFPrivateKey<S>;
+    type PublicKey = ECVRFPublicKey<S>;
+
+    fn generate<R: AllowedRng>(rng: &mut R) -> Self {
+        let scalar = Zeroizing::new(<S::Scalar as groups::Scalar>::rand(rng));
+        ECVRFKeyPair::from(ECVRFPrivateKey {
+            bytes: scalar.to_byte_array(),
+            suite: PhantomData,
+        })
+    }
+
+    fn prove(&self, alpha_string: &[u8]) -> ECVRFProof<S> {
+        self.prove_with(alpha_string, |h, x, k| {
+            [*h * *x, S::Group::generator() * *k, *h * *k]
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ECVRFProof<S: ECVRFSuite> {
+    gamma: S::Group,
+    c: [u8; C_LEN],
+    s: S::Scalar,
+}
+
+impl<S: ECVRFSuite> ECVRFProof<S> {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, std::io::Error> {
+        if bytes.len() != PROOF_LENGTH {
+            return Err(invalid_length("ECVRFProof"));
+        }
+        let mut gamma = [0u8; POINT_LENGTH];
+        let mut c = [0u8; C_LEN];
+        let mut s = [0u8; SCALAR_LENGTH];
+        gamma.copy_from_slice(&bytes[..POINT_LENGTH]);
+        c.copy_from_slice(&bytes[POINT_LENGTH..POINT_LENGTH + C_LEN]);
+        s.copy_from_slice(&bytes[POINT_LENGTH + C_LEN..]);
+
+        Ok(Self {
+            gamma: S::Group::from_byte_array(&gamma).map_err(|_| invalid_encoding("ECVRFProof"))?,
+            c,
+            s: S::Scalar::from_byte_array(&s).map_err(|_| invalid_encoding("ECVRFProof"))?,
+        })
+    }
+}
+
+impl<S: ECVRFSuite> VRFProof<OUTPUT_LENGTH> for ECVRFProof<S> {
+    type PublicKey = ECVRFPublicKey<S>;
+
+    fn verify(
+        &self,
+        alpha_string: &[u8],
+        public_key: &Self::PublicKey,
+    ) -> Result<(), MangekyouError> {
+        if !public_key.valid() {
+            return Err(MangekyouError::InvalidInput);
+        }
+
+        let h_point = S::Group::hash_to_group_element(alpha_string);
+        let neg_challenge = -challenge_to_scalar::<S>(&self.c);
+
+        // U = s*B - c*Y
+        let u_point = S::Group::multi_scalar_mul(
+            &[self.s, neg_challenge],
+            &[S::Group::generator(), public_key.point],
+        )?;
+
+        // V = s*H - c*Gamma
+        let v_point = S::Group::multi_scalar_mul(&[self.s, neg_challenge], &[h_point, self.gamma])?;
+
+        let c_prime = ecvrf_challenge_generation::<S>([
+            &public_key.point,
+            &h_point,
+            &self.gamma,
+            &u_point,
+            &v_point,
+        ]);
+
+        if c_prime != self.c {
+            return Err(MangekyouError::GeneralOpaqueError);
+        }
+
+        Ok(())
+    }
+
+    fn to_hash(&self) -> [u8; OUTPUT_LENGTH] {
+        gamma_to_hash::<S>(&self.gamma)
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        [
+            self.gamma.to_byte_array().as_ref(),
+            self.c.as_ref(),
+            self.s.to_byte_array().as_ref(),
+        ]
+        .concat()
+    }
+}
@@ -0,0 +1,75 @@
+use crate::error::MangekyouError;
+use crate::traits::AllowedRng;
+
+pub mod beacon;
+pub mod bls;
+pub mod ecvrf;
+pub mod encrypted;
+pub mod generic;
+pub mod pop;
+pub mod solana;
+
+/// The Ristretto basepoint encoded as bytes
+pub const BASEPOINT_BYTES: [u8; 32] = [
+    0xe2, 0xf2, 0xae, 0x0a, 0x6a, 0xbc, 0x4e, 0x71,
+    0xa8, 0x84, 0xa9, 0x61, 0xc5, 0x00, 0x51, 0x5f,
+    0x58, 0xe3, 0x0b, 0x6a, 0xa5, 0x82, 0xdd, 0x8d,
+    0xb6, 0xa6, 0x59, 0x45, 0xe0, 0x8d, 0x2d, 0x76,
+];
+
+/// Represents a public key of which is use to verify outputs for a verifiable random function (VRF).
+pub trait VRFPublicKey {
+    type PrivateKey: VRFPrivateKey<PublicKey = Self>;
+}
+
+/// Represents a private key used to compute outputs for a verifiable random function (VRF).
+pub trait VRFPrivateKey {
+    type PublicKey: VRFPublicKey<PrivateKey = Self>;
+}
+
+/// A keypair for a verifiable random function (VRF).
+pub trait VRFKeyPair<const OUTPUT_SIZE: usize> {
+    type Proof: VRFProof<OUTPUT_SIZE, PublicKey = Self::PublicKey>;
+    type PrivateKey: VRFPrivateKey<PublicKey = Self::PublicKey>;
+    type PublicKey: VRFPublicKey<PrivateKey = Self::PrivateKey>;
+
+    /// Generate a new keypair using the given RNG.
+    fn generate<R: AllowedRng>(rng: &mut R) -> Self;
+
+    /// Generate a proof for the given input.
+    fn prove(&self, input: &[u8]) -> Self::Proof;
+
+    /// Compute both hash and proof for the given input.
+    fn output(&self, input: &[u8]) -> ([u8; OUTPUT_SIZE], Self::Proof) {
+        let proof = self.prove(input);
+        let output = proof.to_hash();
+        (output, proof)
+    }
+}
+
+/// A proof that the output of a VRF was computed correctly.
+pub trait VRFProof<const OUTPUT_SIZE: usize> {
+    type PublicKey: VRFPublicKey;
+
+    /// Verify the correctness of this proof.
+    fn verify(&self, input: &[u8], public_key: &Self::PublicKey) -> Result<(), MangekyouError>;
+
+    /// Verify the correctness of this proof and VRF output.
+    fn verify_output(
+        &self,
+        input: &[u8],
+        public_key: &Self::PublicKey,
+        output: &[u8; OUTPUT_SIZE],
+    ) -> Result<(), MangekyouError> {
+        self.verify(input, public_key)?;
+        if &self.to_hash() != output {
+            return Err(MangekyouError::GeneralOpaqueError);
+        }
+        Ok(())
+    }
+
+    /// Compute the output of the VRF with this proof.
+    fn to_hash(&self) -> [u8; OUTPUT_SIZE];
+
+    fn to_bytes(&self) -> Vec<u8>;
+}
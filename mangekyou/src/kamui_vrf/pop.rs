@@ -0,0 +1,90 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Proof of possession of a `sol_vrf` key, required when an oracle registers its VRF key on-chain.
+//!
+//! The proof is a [SchnorrProof] over ristretto255 of knowledge of the secret key, on a transcript
+//! bound to the oracle authority, so it cannot be replayed to register the same key under another
+//! authority. It is encoded as commitment (32 bytes) || response (32 bytes). The Kamui program
+//! replays the same transcript and checks the proof with the curve25519 syscalls.
+
+use super::ecvrf::{ECVRFKeyPair, ECVRFPublicKey};
+use super::generic::{invalid_encoding, invalid_length};
+use crate::error::{MangekyouError, MangekyouResult};
+use crate::groups::ristretto255::RistrettoPoint;
+use crate::groups::GroupElement;
+use crate::nizk::SchnorrProof;
+use crate::traits::AllowedRng;
+use merlin::Transcript;
+
+/// Length of an encoded proof of possession.
+pub const POP_LENGTH: usize = 64;
+
+/// Label of the transcript the proof is made on.
+pub const POP_TRANSCRIPT_LABEL: &[u8] = b"kamui-oracle-pop";
+
+/// Length of an oracle authority, i.e. a Solana public key.
+pub const AUTHORITY_LENGTH: usize = 32;
+
+fn transcript(authority: &[u8; AUTHORITY_LENGTH]) -> Transcript {
+    let mut transcript = Transcript::new(POP_TRANSCRIPT_LABEL);
+    transcript.append_message(b"authority", authority);
+    transcript
+}
+
+/// Proof that the holder of an oracle authority knows the secret key of a VRF public key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ECVRFProofOfPossession(SchnorrProof<RistrettoPoint>);
+
+impl ECVRFProofOfPossession {
+    /// Prove possession of `keypair` for registration by `authority`.
+    pub fn prove<R: AllowedRng>(
+        keypair: &ECVRFKeyPair,
+        authority: &[u8; AUTHORITY_LENGTH],
+        rng: &mut R,
+    ) -> Self {
+        let sk = keypair
+            .sk
+            .scalar()
+            .expect("Private keys are checked to be canonical when constructed");
+        Self(SchnorrProof::prove(
+            &mut transcript(authority),
+            &RistrettoPoint::generator(),
+            &sk,
+            rng,
+        ))
+    }
+
+    /// Verify the proof for `public_key` and `authority`. The identity is rejected as a key.
+    pub fn verify(
+        &self,
+        public_key: &ECVRFPublicKey,
+        authority: &[u8; AUTHORITY_LENGTH],
+    ) -> MangekyouResult<()> {
+        let public = RistrettoPoint::from(*public_key.point());
+        if public == RistrettoPoint::zero() {
+            return Err(MangekyouError::InvalidInput);
+        }
+        self.0.verify(
+            &mut transcript(authority),
+            &RistrettoPoint::generator(),
+            &public,
+        )
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, std::io::Error> {
+        if bytes.len() != POP_LENGTH {
+            return Err(invalid_length("ECVRFProofOfPossession"));
+        }
+        bincode::deserialize(bytes)
+            .map(Self)
+            .map_err(|_| invalid_encoding("ECVRFProofOfPossession"))
+    }
+
+    pub fn to_bytes(&self) -> [u8; POP_LENGTH] {
+        bincode::serialize(&self.0)
+            .expect("Serialization of proofs is infallible")
+            .try_into()
+            .expect("Proofs are encoded as two 32 byte elements")
+    }
+}
@@ -0,0 +1,265 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A Ristretto255 [GroupElement] backed by the curve25519 operations of `solana-zk-token-sdk`.
+//! On-chain these are the curve25519 syscalls; on the host they fall back to the same arithmetic,
+//! so the ECVRF prover and the Solana verifier share one implementation.
+//!
+//! [ExtendedRistrettoPoint] is the host-side counterpart for chains of operations: the same
+//! `curve25519_dalek` points, kept in extended coordinates between operations.
+
+use super::BASEPOINT_BYTES;
+use crate::error::{MangekyouError, MangekyouResult};
+use crate::groups::ristretto255::{RistrettoPoint, RistrettoScalar};
+use crate::groups::{Doubling, GroupElement, HashToGroupElement, MultiScalarMul, Scalar};
+use crate::hash::{HashFunction, Sha512};
+use crate::serde_helpers::ToFromByteArray;
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto;
+use curve25519_dalek::scalar::Scalar as DalekScalar;
+use curve25519_dalek::traits::Identity;
+use mangekyou_derive::GroupOpsExtend;
+use solana_zk_token_sdk::curve25519::ristretto::{
+    add_ristretto, multiply_ristretto, multiscalar_multiply_ristretto, subtract_ristretto,
+    validate_ristretto, PodRistrettoPoint,
+};
+use solana_zk_token_sdk::curve25519::scalar::PodScalar;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// A Ristretto255 point in its compressed encoding. Values are only constructed from valid
+/// encodings, so the arithmetic below cannot fail.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, GroupOpsExtend)]
+pub struct SolanaRistrettoPoint(PodRistrettoPoint);
+
+impl SolanaRistrettoPoint {
+    /// The compressed encoding of this point.
+    pub fn to_pod(&self) -> PodRistrettoPoint {
+        self.0
+    }
+}
+
+impl From<RistrettoPoint> for SolanaRistrettoPoint {
+    fn from(point: RistrettoPoint) -> Self {
+        Self(PodRistrettoPoint(point.compress()))
+    }
+}
+
+impl From<SolanaRistrettoPoint> for RistrettoPoint {
+    fn from(point: SolanaRistrettoPoint) -> Self {
+        RistrettoPoint::decompress(&point.0 .0).expect("Points are valid")
+    }
+}
+
+fn pod_scalar(scalar: &RistrettoScalar) -> PodScalar {
+    PodScalar(scalar.to_byte_array())
+}
+
+impl Add for SolanaRistrettoPoint {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(add_ristretto(&self.0, &rhs.0).expect("Points are valid"))
+    }
+}
+
+impl Sub for SolanaRistrettoPoint {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(subtract_ristretto(&self.0, &rhs.0).expect("Points are valid"))
+    }
+}
+
+impl Neg for SolanaRistrettoPoint {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::zero() - self
+    }
+}
+
+impl Mul<RistrettoScalar> for SolanaRistrettoPoint {
+    type Output = Self;
+
+    fn mul(self, rhs: RistrettoScalar) -> Self {
+        Self(multiply_ristretto(&pod_scalar(&rhs), &self.0).expect("Points are valid"))
+    }
+}
+
+#[allow(clippy::suspicious_arithmetic_impl)]
+impl Div<RistrettoScalar> for SolanaRistrettoPoint {
+    type Output = Result<Self, MangekyouError>;
+
+    fn div(self, rhs: RistrettoScalar) -> Self::Output {
+        Ok(self * rhs.inverse()?)
+    }
+}
+
+impl GroupElement for SolanaRistrettoPoint {
+    type ScalarType = RistrettoScalar;
+
+    fn zero() -> Self {
+        Self(PodRistrettoPoint([0u8; 32]))
+    }
+
+    fn generator() -> Self {
+        Self(PodRistrettoPoint(BASEPOINT_BYTES))
+    }
+}
+
+impl MultiScalarMul for SolanaRistrettoPoint {
+    fn multi_scalar_mul(scalars: &[Self::ScalarType], points: &[Self]) -> MangekyouResult<Self> {
+        if scalars.len() != points.len() {
+            return Err(MangekyouError::InvalidInput);
+        }
+        let scalars: Vec<PodScalar> = scalars.iter().map(pod_scalar).collect();
+        let points: Vec<PodRistrettoPoint> = points.iter().map(|p| p.0).collect();
+        multiscalar_multiply_ristretto(&scalars, &points)
+            .map(Self)
+            .ok_or(MangekyouError::InvalidInput)
+    }
+}
+
+impl ToFromByteArray<32> for SolanaRistrettoPoint {
+    fn from_byte_array(bytes: &[u8; 32]) -> Result<Self, MangekyouError> {
+        let point = PodRistrettoPoint(*bytes);
+        if !validate_ristretto(&point) {
+            return Err(MangekyouError::InvalidInput);
+        }
+        Ok(Self(point))
+    }
+
+    fn to_byte_array(&self) -> [u8; 32] {
+        self.0 .0
+    }
+}
+
+/// Domain separation tag used in ecvrf_encode_to_curve.
+const DST: &[u8; 49] = b"ECVRF_ristretto255_XMD:SHA-512_R255MAP_RO_sol_vrf";
+
+/// The try-and-increment encode_to_curve of the `sol_vrf` suite. It is not the Elligator map of
+/// RFC 9380, but it is kept as is so existing keys, proofs and outputs stay valid. For the same
+/// reason it does not use [crate::hash::expand_message_xmd]: its `b_0` omits the `I2OSP(0, 1)`
+/// byte between `l_i_b_str` and the DST, and the on-chain verifiers hash the same way.
+impl HashToGroupElement for SolanaRistrettoPoint {
+    fn hash_to_group_element(alpha_string: &[u8]) -> Self {
+        // Number of bytes requested from expand_message_xmd
+        const LEN_IN_BYTES: usize = 64;
+
+        // b_0 = H(Z_pad || msg || l_i_b_str || DST || DST_len)
+        let mut hasher = Sha512::default();
+        hasher.update([0u8; 128]); // SHA-512 block size is 128 bytes
+        hasher.update(alpha_string);
+        hasher.update([(LEN_IN_BYTES >> 8) as u8, LEN_IN_BYTES as u8]);
+        hasher.update(DST);
+        hasher.update([DST.len() as u8]);
+        let b_0 = hasher.finalize();
+
+        // b_1 = H(b_0 || 0x01 || DST || DST_len)
+        let mut hasher = Sha512::default();
+        hasher.update(b_0.digest);
+        hasher.update([1u8]);
+        hasher.update(DST);
+        hasher.update([DST.len() as u8]);
+        let b_1 = hasher.finalize();
+
+        let mut point_bytes = [0u8; 32];
+        point_bytes.copy_from_slice(&b_1.digest[..32]);
+        point_bytes[31] &= 0b0111_1111; // Clear top bit
+
+        for _ in 0..256 {
+            if let Ok(point) = Self::from_byte_array(&point_bytes) {
+                return point;
+            }
+            point_bytes[0] = point_bytes[0].wrapping_add(1);
+        }
+
+        // Fallback to basepoint if no valid point found
+        Self::generator()
+    }
+}
+
+/// A Ristretto255 point in extended coordinates, on the `curve25519_dalek` that the curve25519
+/// operations use on the host. Each operation on a [SolanaRistrettoPoint] decompresses its inputs
+/// and compresses its result; with this type that is only done when converting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, GroupOpsExtend)]
+pub struct ExtendedRistrettoPoint(ristretto::RistrettoPoint);
+
+impl From<SolanaRistrettoPoint> for ExtendedRistrettoPoint {
+    fn from(point: SolanaRistrettoPoint) -> Self {
+        Self(
+            ristretto::CompressedRistretto(point.0 .0)
+                .decompress()
+                .expect("Points are valid"),
+        )
+    }
+}
+
+impl From<ExtendedRistrettoPoint> for SolanaRistrettoPoint {
+    fn from(point: ExtendedRistrettoPoint) -> Self {
+        Self(PodRistrettoPoint(point.0.compress().to_bytes()))
+    }
+}
+
+fn dalek_scalar(scalar: &RistrettoScalar) -> DalekScalar {
+    DalekScalar::from_bytes_mod_order(scalar.to_byte_array())
+}
+
+impl Add for ExtendedRistrettoPoint {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl Sub for ExtendedRistrettoPoint {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl Neg for ExtendedRistrettoPoint {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(-self.0)
+    }
+}
+
+impl Mul<RistrettoScalar> for ExtendedRistrettoPoint {
+    type Output = Self;
+
+    fn mul(self, rhs: RistrettoScalar) -> Self {
+        Self(self.0 * dalek_scalar(&rhs))
+    }
+}
+
+#[allow(clippy::suspicious_arithmetic_impl)]
+impl Div<RistrettoScalar> for ExtendedRistrettoPoint {
+    type Output = Result<Self, MangekyouError>;
+
+    fn div(self, rhs: RistrettoScalar) -> Self::Output {
+        Ok(self * rhs.inverse()?)
+    }
+}
+
+impl Doubling for ExtendedRistrettoPoint {
+    fn double(&self) -> Self {
+        Self(self.0 + self.0)
+    }
+}
+
+impl GroupElement for ExtendedRistrettoPoint {
+    type ScalarType = RistrettoScalar;
+
+    fn zero() -> Self {
+        Self(ristretto::RistrettoPoint::identity())
+    }
+
+    fn generator() -> Self {
+        Self(RISTRETTO_BASEPOINT_POINT)
+    }
+}
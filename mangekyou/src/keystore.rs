@@ -0,0 +1,377 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Password-protected keystore for secret keys.
+//!
+//! A keystore is a versioned JSON envelope. The secret key is encrypted with ChaCha20-Poly1305
+//! under a key derived from a password using Argon2id or scrypt. The public key is stored in the
+//! clear so a keystore can be identified without the password. The version, scheme, public key,
+//! cipher, KDF parameters and salt are authenticated as associated data, so changing any part of
+//! the envelope makes decryption fail.
+//!
+//! KDF parameters are read from the envelope, so they are capped (see [MAX_KDF_MEMORY]) before a key
+//! is derived; a crafted keystore cannot make loading it exhaust memory or CPU.
+//!
+//! # Example
+//! ```
+//! # use mangekyou::keystore::*;
+//! # use mangekyou::kamui_vrf::{ecvrf::ECVRFKeyPair, VRFKeyPair};
+//! let keypair = ECVRFKeyPair::generate(&mut rand::thread_rng());
+//! let kdf = KdfParams::Argon2id { m_cost: 1024, t_cost: 1, p_cost: 1 };
+//! let keystore = Keystore::from_ecvrf_keypair(&keypair, b"password", kdf, &mut rand::thread_rng()).unwrap();
+//!
+//! let json = keystore.to_json().unwrap();
+//! let restored = Keystore::from_json(&json).unwrap().to_ecvrf_keypair(b"password").unwrap();
+//! assert_eq!(restored.pk.as_ref(), keypair.pk.as_ref());
+//! ```
+
+use chacha20poly1305::aead::{Aead, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, KeyInit, Nonce};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use zeroize::Zeroizing;
+
+use crate::ed25519::{Ed25519KeyPair, Ed25519PrivateKey};
+use crate::encoding::Hex;
+use crate::error::{MangekyouError, MangekyouResult};
+use crate::kamui_vrf::ecvrf::{ECVRFKeyPair, ECVRFPrivateKey};
+use crate::traits::{AllowedRng, KeyPair, ToFromBytes};
+
+/// The current keystore format version.
+pub const KEYSTORE_VERSION: u8 = 1;
+
+/// Scheme identifier for ECVRF keys over Ristretto255.
+pub const ECVRF_SCHEME: &str = "ecvrf-ristretto255";
+
+/// Scheme identifier for Ed25519 signing keys.
+pub const ED25519_SCHEME: &str = "ed25519";
+
+/// The only cipher supported by version 1.
+pub const CIPHER: &str = "chacha20poly1305";
+
+const SALT_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 12;
+const KEY_LENGTH: usize = 32;
+
+/// Domain separator prepended to the associated data.
+const AAD_DOMAIN: &[u8] = b"mangekyou-keystore";
+
+/// Largest amount of memory a KDF may use, in bytes (1 GiB).
+pub const MAX_KDF_MEMORY: u64 = 1 << 30;
+
+/// Largest number of Argon2id passes.
+pub const MAX_ARGON2_T_COST: u32 = 64;
+
+/// Largest degree of parallelism, for both Argon2id and scrypt.
+pub const MAX_KDF_PARALLELISM: u32 = 16;
+
+/// Key derivation function and its parameters.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kdf", rename_all = "lowercase")]
+pub enum KdfParams {
+    /// Argon2id with memory cost in KiB, number of iterations and degree of parallelism.
+    Argon2id { m_cost: u32, t_cost: u32, p_cost: u32 },
+    /// scrypt with cost parameter `2^log_n`, block size `r` and parallelism `p`.
+    Scrypt { log_n: u8, r: u32, p: u32 },
+}
+
+impl Default for KdfParams {
+    /// Argon2id with 64 MiB of memory and three passes.
+    fn default() -> Self {
+        KdfParams::Argon2id {
+            m_cost: 64 * 1024,
+            t_cost: 3,
+            p_cost: 1,
+        }
+    }
+}
+
+impl KdfParams {
+    /// Default scrypt parameters (N = 2^17, r = 8, p = 1).
+    pub fn scrypt() -> Self {
+        KdfParams::Scrypt { log_n: 17, r: 8, p: 1 }
+    }
+
+    /// Reject parameters above the caps, which would make deriving the key too expensive.
+    pub fn validate(&self) -> MangekyouResult<()> {
+        let within_caps = match *self {
+            KdfParams::Argon2id { m_cost, t_cost, p_cost } => {
+                m_cost as u64 * 1024 <= MAX_KDF_MEMORY
+                    && t_cost <= MAX_ARGON2_T_COST
+                    && p_cost <= MAX_KDF_PARALLELISM
+            }
+            // scrypt uses 128 * r * N bytes, plus 128 * r * p for the parallel blocks
+            KdfParams::Scrypt { log_n, r, p } => {
+                let memory = 1u64
+                    .checked_shl(log_n as u32)
+                    .and_then(|n| n.checked_mul(128 * r as u64));
+                matches!(memory, Some(memory) if memory <= MAX_KDF_MEMORY) && p <= MAX_KDF_PARALLELISM
+            }
+        };
+        if !within_caps {
+            return Err(MangekyouError::InvalidInput);
+        }
+        Ok(())
+    }
+
+    /// Bytes of the parameters bound into the associated data.
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(13);
+        match *self {
+            KdfParams::Argon2id { m_cost, t_cost, p_cost } => {
+                bytes.push(0);
+                bytes.extend_from_slice(&m_cost.to_le_bytes());
+                bytes.extend_from_slice(&t_cost.to_le_bytes());
+                bytes.extend_from_slice(&p_cost.to_le_bytes());
+            }
+            KdfParams::Scrypt { log_n, r, p } => {
+                bytes.push(1);
+                bytes.push(log_n);
+                bytes.extend_from_slice(&r.to_le_bytes());
+                bytes.extend_from_slice(&p.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    fn derive_key(&self, password: &[u8], salt: &[u8]) -> MangekyouResult<Zeroizing<[u8; KEY_LENGTH]>> {
+        self.validate()?;
+        let mut key = Zeroizing::new([0u8; KEY_LENGTH]);
+        match self {
+            KdfParams::Argon2id { m_cost, t_cost, p_cost } => {
+                let params = argon2::Params::new(*m_cost, *t_cost, *p_cost, Some(KEY_LENGTH))
+                    .map_err(|_| MangekyouError::InvalidInput)?;
+                argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+                    .hash_password_into(password, salt, key.as_mut())
+                    .map_err(|_| MangekyouError::GeneralOpaqueError)?;
+            }
+            KdfParams::Scrypt { log_n, r, p } => {
+                let params = scrypt::Params::new(*log_n, *r, *p, KEY_LENGTH)
+                    .map_err(|_| MangekyouError::InvalidInput)?;
+                scrypt::scrypt(password, salt, &params, key.as_mut())
+                    .map_err(|_| MangekyouError::GeneralOpaqueError)?;
+            }
+        }
+        Ok(key)
+    }
+}
+
+/// Encrypted part of a keystore.
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct KeystoreCrypto {
+    /// Name of the AEAD used to encrypt the secret key.
+    pub cipher: String,
+    /// Key derivation function used to turn the password into an encryption key.
+    #[serde(flatten)]
+    pub kdf: KdfParams,
+    #[serde_as(as = "Hex")]
+    pub salt: Vec<u8>,
+    #[serde_as(as = "Hex")]
+    pub nonce: Vec<u8>,
+    /// Encrypted secret key followed by the authentication tag.
+    #[serde_as(as = "Hex")]
+    pub ciphertext: Vec<u8>,
+}
+
+/// A versioned, password-protected envelope around a secret key.
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Keystore {
+    pub version: u8,
+    /// Identifies the key type, e.g. [ECVRF_SCHEME].
+    pub scheme: String,
+    /// The public key, stored in the clear.
+    #[serde_as(as = "Hex")]
+    pub public_key: Vec<u8>,
+    pub crypto: KeystoreCrypto,
+}
+
+impl Keystore {
+    /// Encrypt `secret_key` under `password`.
+    pub fn encrypt<R: AllowedRng>(
+        scheme: &str,
+        secret_key: &[u8],
+        public_key: &[u8],
+        password: &[u8],
+        kdf: KdfParams,
+        rng: &mut R,
+    ) -> MangekyouResult<Self> {
+        let mut salt = vec![0u8; SALT_LENGTH];
+        rng.fill_bytes(&mut salt);
+        let mut nonce = [0u8; NONCE_LENGTH];
+        rng.fill_bytes(&mut nonce);
+
+        let aad = associated_data(KEYSTORE_VERSION, scheme, public_key, CIPHER, &kdf, &salt)?;
+        let key = kdf.derive_key(password, &salt)?;
+        let ciphertext = ChaCha20Poly1305::new(&Key::from(*key))
+            .encrypt(
+                &Nonce::from(nonce),
+                Payload {
+                    msg: secret_key,
+                    aad: &aad,
+                },
+            )
+            .map_err(|_| MangekyouError::GeneralOpaqueError)?;
+
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            scheme: scheme.to_string(),
+            public_key: public_key.to_vec(),
+            crypto: KeystoreCrypto {
+                cipher: CIPHER.to_string(),
+                kdf,
+                salt,
+                nonce: nonce.to_vec(),
+                ciphertext,
+            },
+        })
+    }
+
+    /// Decrypt the secret key. Fails if the password is wrong or any part of the envelope,
+    /// including the public key, was modified.
+    pub fn decrypt(&self, password: &[u8]) -> MangekyouResult<Zeroizing<Vec<u8>>> {
+        if self.version != KEYSTORE_VERSION || self.crypto.cipher != CIPHER {
+            return Err(MangekyouError::InvalidInput);
+        }
+        let nonce: [u8; NONCE_LENGTH] = self
+            .crypto
+            .nonce
+            .as_slice()
+            .try_into()
+            .map_err(|_| MangekyouError::InputLengthWrong(NONCE_LENGTH))?;
+
+        let aad = associated_data(
+            self.version,
+            &self.scheme,
+            &self.public_key,
+            &self.crypto.cipher,
+            &self.crypto.kdf,
+            &self.crypto.salt,
+        )?;
+        let key = self.crypto.kdf.derive_key(password, &self.crypto.salt)?;
+        ChaCha20Poly1305::new(&Key::from(*key))
+            .decrypt(
+                &Nonce::from(nonce),
+                Payload {
+                    msg: &self.crypto.ciphertext,
+                    aad: &aad,
+                },
+            )
+            .map(Zeroizing::new)
+            .map_err(|_| MangekyouError::GeneralOpaqueError)
+    }
+
+    /// Encrypt an ECVRF keypair.
+    pub fn from_ecvrf_keypair<R: AllowedRng>(
+        keypair: &ECVRFKeyPair,
+        password: &[u8],
+        kdf: KdfParams,
+        rng: &mut R,
+    ) -> MangekyouResult<Self> {
+        Self::encrypt(
+            ECVRF_SCHEME,
+            keypair.sk.as_ref(),
+            keypair.pk.as_ref(),
+            password,
+            kdf,
+            rng,
+        )
+    }
+
+    /// Decrypt an ECVRF keypair, checking that the secret key matches the stored public key.
+    pub fn to_ecvrf_keypair(&self, password: &[u8]) -> MangekyouResult<ECVRFKeyPair> {
+        if self.scheme != ECVRF_SCHEME {
+            return Err(MangekyouError::InvalidInput);
+        }
+        let secret_key = self.decrypt(password)?;
+        let secret_key =
+            ECVRFPrivateKey::from_bytes(&secret_key).map_err(|_| MangekyouError::InvalidInput)?;
+        let keypair = ECVRFKeyPair::from(secret_key);
+        if keypair.pk.as_ref() != self.public_key.as_slice() {
+            return Err(MangekyouError::InvalidInput);
+        }
+        Ok(keypair)
+    }
+
+    /// Encrypt an Ed25519 keypair.
+    pub fn from_ed25519_keypair<R: AllowedRng>(
+        keypair: &Ed25519KeyPair,
+        password: &[u8],
+        kdf: KdfParams,
+        rng: &mut R,
+    ) -> MangekyouResult<Self> {
+        Self::encrypt(
+            ED25519_SCHEME,
+            keypair.as_ref(),
+            keypair.public().as_ref(),
+            password,
+            kdf,
+            rng,
+        )
+    }
+
+    /// Decrypt an Ed25519 keypair, checking that the secret key matches the stored public key.
+    pub fn to_ed25519_keypair(&self, password: &[u8]) -> MangekyouResult<Ed25519KeyPair> {
+        if self.scheme != ED25519_SCHEME {
+            return Err(MangekyouError::InvalidInput);
+        }
+        let secret_key = self.decrypt(password)?;
+        let secret_key = Ed25519PrivateKey::from_bytes(&secret_key)
+            .map_err(|_| MangekyouError::InvalidInput)?;
+        let keypair = Ed25519KeyPair::from(secret_key);
+        if keypair.public().as_ref() != self.public_key.as_slice() {
+            return Err(MangekyouError::InvalidInput);
+        }
+        Ok(keypair)
+    }
+
+    /// Serialize as pretty-printed JSON.
+    pub fn to_json(&self) -> MangekyouResult<String> {
+        serde_json::to_string_pretty(self).map_err(|e| MangekyouError::GeneralError(e.to_string()))
+    }
+
+    /// Parse a keystore from JSON.
+    pub fn from_json(json: &str) -> MangekyouResult<Self> {
+        let keystore: Self =
+            serde_json::from_str(json).map_err(|e| MangekyouError::GeneralError(e.to_string()))?;
+        if keystore.version != KEYSTORE_VERSION {
+            return Err(MangekyouError::GeneralError(format!(
+                "Unsupported keystore version {}",
+                keystore.version
+            )));
+        }
+        Ok(keystore)
+    }
+}
+
+/// Everything in the envelope that is not encrypted is bound to the ciphertext. The nonce is
+/// bound by the cipher itself. Variable-length fields are length-prefixed, so the scheme is
+/// limited to 255 bytes.
+fn associated_data(
+    version: u8,
+    scheme: &str,
+    public_key: &[u8],
+    cipher: &str,
+    kdf: &KdfParams,
+    salt: &[u8],
+) -> MangekyouResult<Vec<u8>> {
+    let length_prefix = |len: usize| u8::try_from(len).map_err(|_| MangekyouError::InvalidInput);
+    let kdf = kdf.to_bytes();
+
+    let mut aad = Vec::with_capacity(
+        AAD_DOMAIN.len() + 5 + scheme.len() + public_key.len() + cipher.len() + kdf.len() + salt.len(),
+    );
+    aad.extend_from_slice(AAD_DOMAIN);
+    aad.push(version);
+    aad.push(length_prefix(scheme.len())?);
+    aad.extend_from_slice(scheme.as_bytes());
+    aad.push(length_prefix(public_key.len())?);
+    aad.extend_from_slice(public_key);
+    aad.push(length_prefix(cipher.len())?);
+    aad.extend_from_slice(cipher.as_bytes());
+    aad.extend_from_slice(&kdf);
+    aad.push(length_prefix(salt.len())?);
+    aad.extend_from_slice(salt);
+    Ok(aad)
+}
@@ -7,18 +7,50 @@
     rust_2021_compatibility
 )]
 
-// #[cfg(test)]
-// #[path = "tests/hash_tests.rs"]
-// pub mod hash_tests;
+#[cfg(test)]
+#[path = "tests/hash_tests.rs"]
+pub mod hash_tests;
+
+#[cfg(test)]
+#[path = "tests/bls12381_tests.rs"]
+pub mod bls12381_tests;
+
+#[cfg(test)]
+#[path = "tests/bls_vrf_tests.rs"]
+pub mod bls_vrf_tests;
+
+#[cfg(test)]
+#[path = "tests/ed25519_tests.rs"]
+pub mod ed25519_tests;
 
 #[cfg(test)]
 #[path = "tests/encoding_tests.rs"]
 pub mod encoding_tests;
 
+#[cfg(test)]
+#[path = "tests/kamui_vrf_tests.rs"]
+pub mod kamui_vrf_tests;
+
+#[cfg(test)]
+#[path = "tests/keystore_tests.rs"]
+pub mod keystore_tests;
+
+#[cfg(test)]
+#[path = "tests/nizk_tests.rs"]
+pub mod nizk_tests;
+
 #[cfg(test)]
 #[path = "tests/ristretto255_tests.rs"]
 pub mod ristretto255_tests;
 
+#[cfg(test)]
+#[path = "tests/secp256k1_group_tests.rs"]
+pub mod secp256k1_group_tests;
+
+#[cfg(test)]
+#[path = "tests/secp256k1_tests.rs"]
+pub mod secp256k1_tests;
+
 #[cfg(test)]
 #[path = "tests/test_helpers.rs"]
 pub mod test_helpers;
@@ -29,10 +61,14 @@ pub mod utils_tests;
 
 pub mod traits;
 
+pub mod ed25519;
 pub mod encoding;
 pub mod error;
 pub mod groups;
 pub mod hash;
+pub mod keystore;
+pub mod nizk;
+pub mod secp256k1;
 pub mod serde_helpers;
 pub mod utils;
 pub mod kamui_vrf;
@@ -0,0 +1,320 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Non-interactive zero-knowledge proofs over any [GroupElement], made non-interactive with
+//! [merlin] transcripts:
+//!
+//! * [SchnorrProof]: knowledge of `x` such that `P = x * B`.
+//! * [DLEQProof]: Chaum-Pedersen proof that `log_G(H) = log_U(V)`, with a batched variant for one
+//!   secret used against many bases.
+//!
+//! Callers create the transcript with their own label and append whatever context the proof
+//! should be bound to (e.g. an account or a round) before proving or verifying. Each proof adds
+//! its own domain separator, so the same transcript label can be shared between protocols.
+//!
+//! # Example
+//! ```
+//! # use mangekyou::groups::ristretto255::{RistrettoPoint, RistrettoScalar};
+//! # use mangekyou::groups::{GroupElement, Scalar};
+//! # use mangekyou::nizk::SchnorrProof;
+//! # use merlin::Transcript;
+//! let x = RistrettoScalar::rand(&mut rand::thread_rng());
+//! let public = RistrettoPoint::generator() * x;
+//!
+//! let mut transcript = Transcript::new(b"example");
+//! let proof = SchnorrProof::prove(&mut transcript, &RistrettoPoint::generator(), &x, &mut rand::thread_rng());
+//!
+//! let mut transcript = Transcript::new(b"example");
+//! assert!(proof.verify(&mut transcript, &RistrettoPoint::generator(), &public).is_ok());
+//! ```
+
+use crate::error::{MangekyouError, MangekyouResult};
+use crate::groups::{FiatShamirChallenge, GroupElement, MultiScalarMul};
+use crate::traits::AllowedRng;
+use merlin::Transcript;
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, Zeroizing};
+
+/// Length of the buffer reduced to a scalar for challenges and nonces.
+const CHALLENGE_LENGTH: usize = 64;
+
+/// Extension of [Transcript] for appending group elements and drawing scalar challenges.
+pub trait TranscriptProtocol {
+    /// Append a domain separator for the protocol about to run.
+    fn domain_separator(&mut self, protocol: &'static [u8]);
+
+    /// Append the canonical (bincode) encoding of a group element or scalar.
+    fn append_element<T: Serialize>(&mut self, label: &'static [u8], element: &T);
+
+    /// Draw a challenge scalar.
+    fn challenge_scalar<S: FiatShamirChallenge>(&mut self, label: &'static [u8]) -> S;
+}
+
+impl TranscriptProtocol for Transcript {
+    fn domain_separator(&mut self, protocol: &'static [u8]) {
+        self.append_message(b"dom-sep", protocol);
+    }
+
+    fn append_element<T: Serialize>(&mut self, label: &'static [u8], element: &T) {
+        let bytes =
+            bincode::serialize(element).expect("Serialization of group elements is infallible");
+        self.append_message(label, &bytes);
+    }
+
+    fn challenge_scalar<S: FiatShamirChallenge>(&mut self, label: &'static [u8]) -> S {
+        let mut buffer = [0u8; CHALLENGE_LENGTH];
+        self.challenge_bytes(label, &mut buffer);
+        S::fiat_shamir_reduction_to_group_element(&buffer)
+    }
+}
+
+/// Derive a nonce from the transcript, the witness and fresh randomness, so that neither a bad RNG
+/// nor a repeated transcript alone leads to nonce reuse.
+pub(crate) fn nonce<S: FiatShamirChallenge + Serialize, R: AllowedRng>(
+    transcript: &Transcript,
+    witness: &S,
+    rng: &mut R,
+) -> S {
+    let witness_bytes = Zeroizing::new(
+        bincode::serialize(witness).expect("Serialization of scalars is infallible"),
+    );
+    let mut transcript_rng = transcript
+        .build_rng()
+        .rekey_with_witness_bytes(b"witness", &witness_bytes)
+        .finalize(rng);
+    let mut buffer = Zeroizing::new([0u8; CHALLENGE_LENGTH]);
+    rand::RngCore::fill_bytes(&mut transcript_rng, buffer.as_mut());
+    S::fiat_shamir_reduction_to_group_element(buffer.as_ref())
+}
+
+/// Schnorr proof of knowledge of a discrete logarithm.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchnorrProof<G: GroupElement> {
+    commitment: G,
+    response: G::ScalarType,
+}
+
+impl<G> SchnorrProof<G>
+where
+    G: GroupElement + Serialize,
+    G::ScalarType: FiatShamirChallenge + Zeroize,
+{
+    const DOMAIN: &'static [u8] = b"schnorr-pok";
+
+    fn challenge(
+        transcript: &mut Transcript,
+        base: &G,
+        public: &G,
+        commitment: &G,
+    ) -> G::ScalarType {
+        transcript.domain_separator(Self::DOMAIN);
+        transcript.append_element(b"base", base);
+        transcript.append_element(b"public", public);
+        transcript.append_element(b"commitment", commitment);
+        transcript.challenge_scalar(b"challenge")
+    }
+
+    /// Prove knowledge of `x` such that `public = x * base`.
+    pub fn prove<R: AllowedRng>(
+        transcript: &mut Transcript,
+        base: &G,
+        x: &G::ScalarType,
+        rng: &mut R,
+    ) -> Self {
+        let public = *base * x;
+        let k = Zeroizing::new(nonce(transcript, x, rng));
+        let commitment = *base * *k;
+        let c = Self::challenge(transcript, base, &public, &commitment);
+        Self {
+            commitment,
+            response: *k + c * x,
+        }
+    }
+
+    /// Verify a proof that the prover knows the discrete logarithm of `public` to the base `base`.
+    pub fn verify(&self, transcript: &mut Transcript, base: &G, public: &G) -> MangekyouResult<()> {
+        let c = Self::challenge(transcript, base, public, &self.commitment);
+        if *base * self.response != self.commitment + *public * c {
+            return Err(MangekyouError::InvalidProof);
+        }
+        Ok(())
+    }
+}
+
+impl<G> SchnorrProof<G>
+where
+    G: GroupElement + MultiScalarMul + Serialize,
+    G::ScalarType: FiatShamirChallenge + Zeroize,
+{
+    /// Verify many proofs over the same base at once with a random linear combination. Each proof
+    /// is checked against its own transcript. Fails if any proof is invalid, without saying which.
+    pub fn verify_batch<R: AllowedRng>(
+        transcripts: &mut [Transcript],
+        base: &G,
+        publics: &[G],
+        proofs: &[Self],
+        rng: &mut R,
+    ) -> MangekyouResult<()> {
+        if transcripts.len() != proofs.len() || publics.len() != proofs.len() {
+            return Err(MangekyouError::InvalidInput);
+        }
+
+        // Check sum_i r_i * (z_i * B - A_i - c_i * P_i) = 0 for random r_i.
+        let mut base_scalar = G::ScalarType::zero();
+        let mut scalars = Vec::with_capacity(2 * proofs.len() + 1);
+        let mut points = Vec::with_capacity(2 * proofs.len() + 1);
+        for ((transcript, public), proof) in transcripts.iter_mut().zip(publics).zip(proofs) {
+            let c = Self::challenge(transcript, base, public, &proof.commitment);
+            let r = <G::ScalarType as crate::groups::Scalar>::rand(rng);
+            base_scalar += r * proof.response;
+            scalars.push(-r);
+            points.push(proof.commitment);
+            scalars.push(-(r * c));
+            points.push(*public);
+        }
+        scalars.push(base_scalar);
+        points.push(*base);
+
+        if G::multi_scalar_mul(&scalars, &points)? != G::zero() {
+            return Err(MangekyouError::InvalidProof);
+        }
+        Ok(())
+    }
+}
+
+/// Chaum-Pedersen proof that two pairs of group elements share a discrete logarithm, i.e. that
+/// `h = x * g` and `v = x * u` for the same `x`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DLEQProof<G: GroupElement> {
+    commitment_g: G,
+    commitment_u: G,
+    response: G::ScalarType,
+}
+
+impl<G> DLEQProof<G>
+where
+    G: GroupElement + Serialize,
+    G::ScalarType: FiatShamirChallenge + Zeroize,
+{
+    const DOMAIN: &'static [u8] = b"chaum-pedersen-dleq";
+
+    fn challenge(
+        transcript: &mut Transcript,
+        (g, h): (&G, &G),
+        (u, v): (&G, &G),
+        (a, b): (&G, &G),
+    ) -> G::ScalarType {
+        transcript.domain_separator(Self::DOMAIN);
+        transcript.append_element(b"g", g);
+        transcript.append_element(b"h", h);
+        transcript.append_element(b"u", u);
+        transcript.append_element(b"v", v);
+        transcript.append_element(b"commitment-g", a);
+        transcript.append_element(b"commitment-u", b);
+        transcript.challenge_scalar(b"challenge")
+    }
+
+    /// Prove that `x * g` and `x * u` share the secret `x`.
+    pub fn prove<R: AllowedRng>(
+        transcript: &mut Transcript,
+        g: &G,
+        u: &G,
+        x: &G::ScalarType,
+        rng: &mut R,
+    ) -> Self {
+        let h = *g * x;
+        let v = *u * x;
+        let k = Zeroizing::new(nonce(transcript, x, rng));
+        let commitment_g = *g * *k;
+        let commitment_u = *u * *k;
+        let c = Self::challenge(transcript, (g, &h), (u, &v), (&commitment_g, &commitment_u));
+        Self {
+            commitment_g,
+            commitment_u,
+            response: *k + c * x,
+        }
+    }
+
+    /// Verify that `log_g(h) = log_u(v)`.
+    pub fn verify(
+        &self,
+        transcript: &mut Transcript,
+        (g, h): (&G, &G),
+        (u, v): (&G, &G),
+    ) -> MangekyouResult<()> {
+        let c = Self::challenge(
+            transcript,
+            (g, h),
+            (u, v),
+            (&self.commitment_g, &self.commitment_u),
+        );
+        if *g * self.response != self.commitment_g + *h * c
+            || *u * self.response != self.commitment_u + *v * c
+        {
+            return Err(MangekyouError::InvalidProof);
+        }
+        Ok(())
+    }
+}
+
+impl<G> DLEQProof<G>
+where
+    G: GroupElement + MultiScalarMul + Serialize,
+    G::ScalarType: FiatShamirChallenge + Zeroize,
+{
+    const BATCH_DOMAIN: &'static [u8] = b"chaum-pedersen-dleq-batch";
+
+    /// Fold the pairs `(u_i, v_i)` into one pair with weights drawn from the transcript. The
+    /// weights are drawn after `g` and `h`, so `h` cannot be chosen once the weights are known.
+    fn combine(
+        transcript: &mut Transcript,
+        (g, h): (&G, &G),
+        us: &[G],
+        vs: &[G],
+    ) -> MangekyouResult<(G, G)> {
+        if us.is_empty() || us.len() != vs.len() {
+            return Err(MangekyouError::InvalidInput);
+        }
+        transcript.domain_separator(Self::BATCH_DOMAIN);
+        transcript.append_element(b"g", g);
+        transcript.append_element(b"h", h);
+        transcript.append_u64(b"n", us.len() as u64);
+        for (u, v) in us.iter().zip(vs) {
+            transcript.append_element(b"u", u);
+            transcript.append_element(b"v", v);
+        }
+        let weights: Vec<G::ScalarType> = (0..us.len())
+            .map(|_| transcript.challenge_scalar(b"weight"))
+            .collect();
+        Ok((
+            G::multi_scalar_mul(&weights, us)?,
+            G::multi_scalar_mul(&weights, vs)?,
+        ))
+    }
+
+    /// Prove that `x * g` and every `x * u_i` share the secret `x` with a single proof.
+    pub fn prove_batch<R: AllowedRng>(
+        transcript: &mut Transcript,
+        g: &G,
+        us: &[G],
+        x: &G::ScalarType,
+        rng: &mut R,
+    ) -> MangekyouResult<Self> {
+        let h = *g * x;
+        let vs: Vec<G> = us.iter().map(|u| *u * x).collect();
+        let (u, _) = Self::combine(transcript, (g, &h), us, &vs)?;
+        Ok(Self::prove(transcript, g, &u, x, rng))
+    }
+
+    /// Verify that `log_g(h) = log_{u_i}(v_i)` for all `i`.
+    pub fn verify_batch(
+        &self,
+        transcript: &mut Transcript,
+        (g, h): (&G, &G),
+        us: &[G],
+        vs: &[G],
+    ) -> MangekyouResult<()> {
+        let (u, v) = Self::combine(transcript, (g, h), us, vs)?;
+        self.verify(transcript, (g, h), (&u, &v))
+    }
+}
@@ -0,0 +1,125 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Helpers to produce secp256k1 signatures that EVM contracts can check with `ecrecover`.
+//!
+//! A contract recovers the signer of a 32 byte digest from the `(r, s, v)` tuple of a signature
+//! and compares it to an expected address. Digests are built either as in
+//! [EIP-191](https://eips.ethereum.org/EIPS/eip-191) (`personal_sign`), or as in
+//! [EIP-712](https://eips.ethereum.org/EIPS/eip-712) for typed structured data, where the
+//! contract computes the hash of the struct it is given.
+//!
+//! # Example
+//! ```rust
+//! # use mangekyou::secp256k1::Secp256k1KeyPair;
+//! # use mangekyou::secp256k1::evm::*;
+//! # use mangekyou::traits::KeyPair;
+//! use rand::thread_rng;
+//! let kp = Secp256k1KeyPair::generate(&mut thread_rng());
+//! let digest = eip191_digest(b"VRF output");
+//! let signature = kp.sign_prehashed(&digest);
+//! let (r, s, v) = signature.to_rsv();
+//! // ecrecover(digest, v, r, s) on chain returns the address of the oracle.
+//! let recovered = signature.recover_prehashed(&digest).unwrap();
+//! assert_eq!(to_evm_address(&recovered), to_evm_address(kp.public()));
+//! ```
+
+use super::{Secp256k1PublicKey, Secp256k1RecoverableSignature};
+use crate::error::MangekyouError;
+use crate::hash::{HashFunction, Keccak256};
+use crate::traits::ToFromBytes;
+
+/// Length of an EVM address in bytes.
+pub const EVM_ADDRESS_LENGTH: usize = 20;
+
+/// An EVM address.
+pub type EvmAddress = [u8; EVM_ADDRESS_LENGTH];
+
+const EIP191_PREFIX: &[u8] = b"\x19Ethereum Signed Message:\n";
+
+const EIP712_DOMAIN_TYPE: &[u8] =
+    b"EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+
+/// The address of the account controlled by the given key, i.e. the last 20 bytes of the
+/// Keccak-256 hash of its uncompressed coordinates.
+pub fn to_evm_address(public_key: &Secp256k1PublicKey) -> EvmAddress {
+    Keccak256::digest(public_key.to_uncompressed_coordinates()).digest[12..]
+        .try_into()
+        .expect("Addresses are 20 bytes")
+}
+
+/// The digest signed by `personal_sign`, i.e. the Keccak-256 hash of
+/// "\x19Ethereum Signed Message:\n" || len(message) || message with the length in decimal.
+pub fn eip191_digest(message: &[u8]) -> [u8; 32] {
+    let mut hash = Keccak256::default();
+    hash.update(EIP191_PREFIX);
+    hash.update(message.len().to_string());
+    hash.update(message);
+    hash.finalize().digest
+}
+
+/// The digest of an EIP-712 struct, i.e. the Keccak-256 hash of
+/// 0x19 || 0x01 || domain separator || struct hash.
+pub fn eip712_digest(domain_separator: &[u8; 32], struct_hash: &[u8; 32]) -> [u8; 32] {
+    let mut hash = Keccak256::default();
+    hash.update([0x19, 0x01]);
+    hash.update(domain_separator);
+    hash.update(struct_hash);
+    hash.finalize().digest
+}
+
+/// An EIP-712 domain with the fields name, version, chainId and verifyingContract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Eip712Domain {
+    pub name: String,
+    pub version: String,
+    pub chain_id: u64,
+    pub verifying_contract: EvmAddress,
+}
+
+impl Eip712Domain {
+    /// The domain separator, as computed by the verifying contract.
+    pub fn separator(&self) -> [u8; 32] {
+        let mut chain_id = [0u8; 32];
+        chain_id[24..].copy_from_slice(&self.chain_id.to_be_bytes());
+        let mut verifying_contract = [0u8; 32];
+        verifying_contract[12..].copy_from_slice(&self.verifying_contract);
+
+        let mut hash = Keccak256::default();
+        hash.update(Keccak256::digest(EIP712_DOMAIN_TYPE).digest);
+        hash.update(Keccak256::digest(self.name.as_bytes()).digest);
+        hash.update(Keccak256::digest(self.version.as_bytes()).digest);
+        hash.update(chain_id);
+        hash.update(verifying_contract);
+        hash.finalize().digest
+    }
+
+    /// The digest of a struct with the given hash in this domain.
+    pub fn digest(&self, struct_hash: &[u8; 32]) -> [u8; 32] {
+        eip712_digest(&self.separator(), struct_hash)
+    }
+}
+
+impl Secp256k1RecoverableSignature {
+    /// The signature as the `(r, s, v)` arguments of `ecrecover`, with v = 27 + recovery id.
+    pub fn to_rsv(&self) -> ([u8; 32], [u8; 32], u8) {
+        let bytes = self.as_ref();
+        (
+            bytes[..32].try_into().expect("r is 32 bytes"),
+            bytes[32..64].try_into().expect("s is 32 bytes"),
+            27 + self.recovery_id(),
+        )
+    }
+
+    /// Parse a signature from its `(r, s, v)` form. Only v = 27 and v = 28 are accepted.
+    pub fn from_rsv(r: &[u8; 32], s: &[u8; 32], v: u8) -> Result<Self, MangekyouError> {
+        if v != 27 && v != 28 {
+            return Err(MangekyouError::InvalidInput);
+        }
+        let mut bytes = [0u8; 65];
+        bytes[..32].copy_from_slice(r);
+        bytes[32..64].copy_from_slice(s);
+        bytes[64] = v - 27;
+        Self::from_bytes(&bytes)
+    }
+}
@@ -0,0 +1,415 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module contains an implementation of recoverable ECDSA signatures over
+//! [secp256k1](https://www.secg.org/sec2-v2.pdf), built on [rust_secp256k1]. Messages are hashed
+//! with Keccak-256 by default and nonces are derived deterministically as in
+//! [RFC 6979](https://www.rfc-editor.org/rfc/rfc6979), so signatures can be checked on EVM chains
+//! with `ecrecover`. The [evm] module has the helpers to produce the `(r, s, v)` tuple and the
+//! EIP-191 and EIP-712 digests a contract recovers the signer from.
+//!
+//! A signature is encoded as r (32 bytes) || s (32 bytes) || recovery id (1 byte, 0 or 1). Signing
+//! always produces a low `s`.
+//!
+//! # Example
+//! ```rust
+//! # use mangekyou::secp256k1::*;
+//! # use mangekyou::traits::{KeyPair, RecoverableSignature, RecoverableSigner};
+//! use rand::thread_rng;
+//! let kp = Secp256k1KeyPair::generate(&mut thread_rng());
+//! let message: &[u8] = b"Hello, world!";
+//! let signature = kp.sign_recoverable(message);
+//! assert_eq!(&signature.recover(message).unwrap(), kp.public());
+//! ```
+
+use crate::encoding::{Base64, Encoding};
+use crate::error::MangekyouError;
+use crate::hash::{HashFunction, Keccak256};
+use crate::serde_helpers::BytesRepresentation;
+use crate::traits::{
+    AllowedRng, Authenticator, EncodeDecodeBase64, KeyPair, RecoverableSignature,
+    RecoverableSigner, Signer, SigningKey, ToFromBytes, VerifyRecoverable, VerifyingKey,
+};
+use crate::{generate_bytes_representation, serialize_deserialize_with_to_from_bytes};
+use mangekyou_derive::{SilentDebug, SilentDisplay};
+use rust_secp256k1::ecdsa::{RecoverableSignature as ExternalRecoverableSignature, RecoveryId};
+use rust_secp256k1::{Message, PublicKey, SecretKey, SECP256K1};
+use std::fmt::{self, Debug, Display};
+use std::str::FromStr;
+use zeroize::Zeroize;
+
+pub mod evm;
+
+/// The length of a private key in bytes.
+pub const SECP256K1_PRIVATE_KEY_LENGTH: usize = 32;
+
+/// The length of a compressed public key in bytes.
+pub const SECP256K1_PUBLIC_KEY_LENGTH: usize = 33;
+
+/// The length of a recoverable signature in bytes.
+pub const SECP256K1_RECOVERABLE_SIGNATURE_LENGTH: usize = 65;
+
+/// Secp256k1 public key. Its byte encoding is the compressed SEC1 point.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Secp256k1PublicKey {
+    pubkey: PublicKey,
+    bytes: [u8; SECP256K1_PUBLIC_KEY_LENGTH],
+}
+
+/// Secp256k1 private key. The secret is elided from `Debug` and `Display` and wiped when the key
+/// is dropped.
+#[derive(SilentDebug, SilentDisplay)]
+pub struct Secp256k1PrivateKey {
+    privkey: SecretKey,
+    bytes: [u8; SECP256K1_PRIVATE_KEY_LENGTH],
+}
+
+/// Secp256k1 key pair. Its byte encoding is the private key.
+#[derive(Debug)]
+pub struct Secp256k1KeyPair {
+    public: Secp256k1PublicKey,
+    private: Secp256k1PrivateKey,
+}
+
+/// Recoverable ECDSA signature over secp256k1.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Secp256k1RecoverableSignature {
+    sig: ExternalRecoverableSignature,
+    bytes: [u8; SECP256K1_RECOVERABLE_SIGNATURE_LENGTH],
+}
+
+//
+// Implement VerifyingKey
+//
+
+impl Secp256k1PublicKey {
+    /// Return the public key as an uncompressed point without the 0x04 tag, i.e. x || y.
+    pub fn to_uncompressed_coordinates(&self) -> [u8; 64] {
+        self.pubkey.serialize_uncompressed()[1..]
+            .try_into()
+            .expect("Uncompressed keys are 65 bytes")
+    }
+}
+
+impl From<PublicKey> for Secp256k1PublicKey {
+    fn from(pubkey: PublicKey) -> Self {
+        Secp256k1PublicKey {
+            bytes: pubkey.serialize(),
+            pubkey,
+        }
+    }
+}
+
+impl AsRef<[u8]> for Secp256k1PublicKey {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl ToFromBytes for Secp256k1PublicKey {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, MangekyouError> {
+        if bytes.len() != SECP256K1_PUBLIC_KEY_LENGTH {
+            return Err(MangekyouError::InputLengthWrong(
+                SECP256K1_PUBLIC_KEY_LENGTH,
+            ));
+        }
+        PublicKey::from_slice(bytes)
+            .map(Secp256k1PublicKey::from)
+            .map_err(|_| MangekyouError::InvalidInput)
+    }
+}
+
+impl Debug for Secp256k1PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Base64::encode(self.as_ref()))
+    }
+}
+
+impl Display for Secp256k1PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Base64::encode(self.as_ref()))
+    }
+}
+
+serialize_deserialize_with_to_from_bytes!(Secp256k1PublicKey, SECP256K1_PUBLIC_KEY_LENGTH);
+
+impl<'a> From<&'a Secp256k1PrivateKey> for Secp256k1PublicKey {
+    fn from(private: &'a Secp256k1PrivateKey) -> Self {
+        PublicKey::from_secret_key_global(&private.privkey).into()
+    }
+}
+
+impl VerifyingKey for Secp256k1PublicKey {
+    type PrivKey = Secp256k1PrivateKey;
+    type Sig = Secp256k1RecoverableSignature;
+    const LENGTH: usize = SECP256K1_PUBLIC_KEY_LENGTH;
+
+    fn verify(
+        &self,
+        msg: &[u8],
+        signature: &Secp256k1RecoverableSignature,
+    ) -> Result<(), MangekyouError> {
+        self.verify_recoverable(msg, signature)
+    }
+}
+
+impl VerifyRecoverable for Secp256k1PublicKey {
+    type Sig = Secp256k1RecoverableSignature;
+}
+
+//
+// Implement SigningKey
+//
+
+impl AsRef<[u8]> for Secp256k1PrivateKey {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl ToFromBytes for Secp256k1PrivateKey {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, MangekyouError> {
+        if bytes.len() != SECP256K1_PRIVATE_KEY_LENGTH {
+            return Err(MangekyouError::InputLengthWrong(
+                SECP256K1_PRIVATE_KEY_LENGTH,
+            ));
+        }
+        SecretKey::from_slice(bytes)
+            .map(Secp256k1PrivateKey::from)
+            .map_err(|_| MangekyouError::InvalidInput)
+    }
+}
+
+impl From<SecretKey> for Secp256k1PrivateKey {
+    fn from(privkey: SecretKey) -> Self {
+        Secp256k1PrivateKey {
+            bytes: privkey.secret_bytes(),
+            privkey,
+        }
+    }
+}
+
+impl Zeroize for Secp256k1PrivateKey {
+    fn zeroize(&mut self) {
+        self.privkey.non_secure_erase();
+        self.bytes.zeroize();
+    }
+}
+
+impl Drop for Secp256k1PrivateKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+serialize_deserialize_with_to_from_bytes!(Secp256k1PrivateKey, SECP256K1_PRIVATE_KEY_LENGTH);
+
+impl SigningKey for Secp256k1PrivateKey {
+    type PubKey = Secp256k1PublicKey;
+    type Sig = Secp256k1RecoverableSignature;
+    const LENGTH: usize = SECP256K1_PRIVATE_KEY_LENGTH;
+}
+
+//
+// Implement Authenticator
+//
+
+impl Secp256k1RecoverableSignature {
+    /// Recover the public key of the signer of a 32 byte message digest.
+    pub fn recover_prehashed(
+        &self,
+        digest: &[u8; 32],
+    ) -> Result<Secp256k1PublicKey, MangekyouError> {
+        let message = Message::from_slice(digest).expect("Digests are 32 bytes");
+        SECP256K1
+            .recover_ecdsa(&message, &self.sig)
+            .map(Secp256k1PublicKey::from)
+            .map_err(|_| MangekyouError::InvalidSignature)
+    }
+
+    /// The recovery id, 0 or 1.
+    pub fn recovery_id(&self) -> u8 {
+        self.bytes[SECP256K1_RECOVERABLE_SIGNATURE_LENGTH - 1]
+    }
+}
+
+impl AsRef<[u8]> for Secp256k1RecoverableSignature {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl From<ExternalRecoverableSignature> for Secp256k1RecoverableSignature {
+    fn from(sig: ExternalRecoverableSignature) -> Self {
+        let (recovery_id, compact) = sig.serialize_compact();
+        let mut bytes = [0u8; SECP256K1_RECOVERABLE_SIGNATURE_LENGTH];
+        bytes[..64].copy_from_slice(&compact);
+        bytes[64] = recovery_id.to_i32() as u8;
+        Secp256k1RecoverableSignature { sig, bytes }
+    }
+}
+
+impl ToFromBytes for Secp256k1RecoverableSignature {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, MangekyouError> {
+        if bytes.len() != SECP256K1_RECOVERABLE_SIGNATURE_LENGTH {
+            return Err(MangekyouError::InputLengthWrong(
+                SECP256K1_RECOVERABLE_SIGNATURE_LENGTH,
+            ));
+        }
+        // Only the recovery ids 0 and 1 are accepted, since Ethereum cannot express the others.
+        if bytes[64] > 1 {
+            return Err(MangekyouError::InvalidInput);
+        }
+        let recovery_id =
+            RecoveryId::from_i32(bytes[64] as i32).map_err(|_| MangekyouError::InvalidInput)?;
+        ExternalRecoverableSignature::from_compact(&bytes[..64], recovery_id)
+            .map(Secp256k1RecoverableSignature::from)
+            .map_err(|_| MangekyouError::InvalidInput)
+    }
+}
+
+impl Debug for Secp256k1RecoverableSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Base64::encode(self.as_ref()))
+    }
+}
+
+impl Display for Secp256k1RecoverableSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Base64::encode(self.as_ref()))
+    }
+}
+
+impl std::hash::Hash for Secp256k1RecoverableSignature {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.bytes.hash(state);
+    }
+}
+
+serialize_deserialize_with_to_from_bytes!(
+    Secp256k1RecoverableSignature,
+    SECP256K1_RECOVERABLE_SIGNATURE_LENGTH
+);
+
+generate_bytes_representation!(
+    Secp256k1RecoverableSignature,
+    SECP256K1_RECOVERABLE_SIGNATURE_LENGTH,
+    Secp256k1RecoverableSignatureAsBytes
+);
+
+impl Authenticator for Secp256k1RecoverableSignature {
+    type PubKey = Secp256k1PublicKey;
+    type PrivKey = Secp256k1PrivateKey;
+    const LENGTH: usize = SECP256K1_RECOVERABLE_SIGNATURE_LENGTH;
+}
+
+impl RecoverableSignature for Secp256k1RecoverableSignature {
+    type PubKey = Secp256k1PublicKey;
+    type Signer = Secp256k1KeyPair;
+    type DefaultHash = Keccak256;
+
+    fn recover_with_hash<H: HashFunction<32>>(
+        &self,
+        msg: &[u8],
+    ) -> Result<Secp256k1PublicKey, MangekyouError> {
+        self.recover_prehashed(&H::digest(msg).digest)
+    }
+}
+
+//
+// Implement KeyPair
+//
+
+impl Secp256k1KeyPair {
+    /// Sign a 32 byte message digest, e.g. an EIP-191 or EIP-712 digest from [evm].
+    pub fn sign_prehashed(&self, digest: &[u8; 32]) -> Secp256k1RecoverableSignature {
+        let message = Message::from_slice(digest).expect("Digests are 32 bytes");
+        SECP256K1
+            .sign_ecdsa_recoverable(&message, &self.private.privkey)
+            .into()
+    }
+}
+
+impl From<Secp256k1PrivateKey> for Secp256k1KeyPair {
+    fn from(private: Secp256k1PrivateKey) -> Self {
+        Secp256k1KeyPair {
+            public: Secp256k1PublicKey::from(&private),
+            private,
+        }
+    }
+}
+
+/// The bytes form of the keypair only contains the private key bytes.
+impl ToFromBytes for Secp256k1KeyPair {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, MangekyouError> {
+        Secp256k1PrivateKey::from_bytes(bytes).map(Secp256k1KeyPair::from)
+    }
+}
+
+impl AsRef<[u8]> for Secp256k1KeyPair {
+    fn as_ref(&self) -> &[u8] {
+        self.private.as_ref()
+    }
+}
+
+serialize_deserialize_with_to_from_bytes!(Secp256k1KeyPair, SECP256K1_PRIVATE_KEY_LENGTH);
+
+impl KeyPair for Secp256k1KeyPair {
+    type PubKey = Secp256k1PublicKey;
+    type PrivKey = Secp256k1PrivateKey;
+    type Sig = Secp256k1RecoverableSignature;
+
+    fn public(&'_ self) -> &'_ Self::PubKey {
+        &self.public
+    }
+
+    fn private(self) -> Self::PrivKey {
+        self.private
+    }
+
+    #[cfg(feature = "copy_key")]
+    fn copy(&self) -> Self {
+        Secp256k1KeyPair {
+            public: self.public,
+            private: Secp256k1PrivateKey::from(self.private.privkey),
+        }
+    }
+
+    fn generate<R: AllowedRng>(rng: &mut R) -> Self {
+        // Rejection sampling; a random 32 byte string is a valid key with overwhelming probability.
+        let mut bytes = [0u8; SECP256K1_PRIVATE_KEY_LENGTH];
+        loop {
+            rng.fill_bytes(&mut bytes);
+            if let Ok(privkey) = SecretKey::from_slice(&bytes) {
+                bytes.zeroize();
+                return Secp256k1PrivateKey::from(privkey).into();
+            }
+        }
+    }
+}
+
+impl Signer<Secp256k1RecoverableSignature> for Secp256k1KeyPair {
+    fn sign(&self, msg: &[u8]) -> Secp256k1RecoverableSignature {
+        self.sign_recoverable(msg)
+    }
+}
+
+impl RecoverableSigner for Secp256k1KeyPair {
+    type PubKey = Secp256k1PublicKey;
+    type Sig = Secp256k1RecoverableSignature;
+
+    fn sign_recoverable_with_hash<H: HashFunction<32>>(
+        &self,
+        msg: &[u8],
+    ) -> Secp256k1RecoverableSignature {
+        self.sign_prehashed(&H::digest(msg).digest)
+    }
+}
+
+impl FromStr for Secp256k1KeyPair {
+    type Err = MangekyouError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::decode_base64(s).map_err(|_| MangekyouError::GeneralOpaqueError)
+    }
+}
@@ -0,0 +1,232 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::groups::bls12381::{G1Element, G2Element, GTElement, Scalar};
+use crate::groups::{
+    Doubling, GroupElement, HashToGroupElement, HashToGroupElementWithDst, MultiScalarMul, Pairing,
+    Scalar as ScalarTrait,
+};
+use crate::serde_helpers::ToFromByteArray;
+use rand::thread_rng;
+
+// The order of the groups, big-endian.
+const ORDER: &str = "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001";
+
+const G1_GENERATOR: &str = "97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb";
+const G2_GENERATOR: &str = "93e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8";
+
+fn order_minus_one() -> Scalar {
+    let mut bytes: [u8; 32] = hex::decode(ORDER).unwrap().try_into().unwrap();
+    bytes[31] -= 1;
+    Scalar::from_byte_array(&bytes).unwrap()
+}
+
+#[test]
+fn test_scalar_arithmetic() {
+    let two = Scalar::from(2);
+    let three = Scalar::from(3);
+    assert_eq!(two + three, Scalar::from(5));
+    assert_eq!(two - three, -Scalar::generator());
+    assert_eq!(two * three, Scalar::from(6));
+    assert_eq!((Scalar::from(6) / three).unwrap(), two);
+    assert!((two / Scalar::zero()).is_err());
+    assert!(Scalar::zero().inverse().is_err());
+    assert_eq!(order_minus_one() + Scalar::generator(), Scalar::zero());
+
+    let x: u128 = 2 << 66;
+    assert_eq!(
+        (Scalar::from(x) / Scalar::from(8)).unwrap(),
+        Scalar::from(2 << 63)
+    );
+
+    let s = Scalar::rand(&mut thread_rng());
+    assert_eq!(s * s.inverse().unwrap(), Scalar::generator());
+}
+
+#[test]
+fn test_scalar_serialization() {
+    let order: [u8; 32] = hex::decode(ORDER).unwrap().try_into().unwrap();
+    assert!(Scalar::from_byte_array(&order).is_err());
+    assert_eq!(Scalar::from_bytes_mod_order(&order), Scalar::zero());
+    assert_eq!(Scalar::from(258).to_byte_array()[30..], [1, 2]);
+
+    let s = Scalar::rand(&mut thread_rng());
+    let bytes = bincode::serialize(&s).unwrap();
+    assert_eq!(bytes, s.to_byte_array());
+    assert_eq!(bincode::deserialize::<Scalar>(&bytes).unwrap(), s);
+}
+
+#[test]
+fn test_g1_arithmetic() {
+    let g = G1Element::generator();
+    let five_g = g * Scalar::from(5);
+    assert_eq!(five_g, g + g + g + g + g + g - g);
+    assert_eq!(five_g, g.double().double() + g);
+    assert_eq!(g * order_minus_one(), -g);
+    assert_eq!(g - g, G1Element::zero());
+    assert_eq!((five_g / Scalar::from(5)).unwrap(), g);
+
+    let mut p = G1Element::zero();
+    p += five_g;
+    p *= Scalar::from(2);
+    assert_eq!(p, g * Scalar::from(10));
+}
+
+#[test]
+fn test_g2_arithmetic() {
+    let g = G2Element::generator();
+    let five_g = g * Scalar::from(5);
+    assert_eq!(five_g, g + g + g + g + g + g - g);
+    assert_eq!(five_g, g.double().double() + g);
+    assert_eq!(g * order_minus_one(), -g);
+    assert_eq!(g - g, G2Element::zero());
+    assert_eq!((five_g / Scalar::from(5)).unwrap(), g);
+}
+
+#[test]
+fn test_multi_scalar_mul() {
+    let scalars = (0..10)
+        .map(|_| Scalar::rand(&mut thread_rng()))
+        .collect::<Vec<_>>();
+    let g1 = scalars
+        .iter()
+        .map(|s| G1Element::generator() * s)
+        .collect::<Vec<_>>();
+    let g2 = scalars
+        .iter()
+        .map(|s| G2Element::generator() * s)
+        .collect::<Vec<_>>();
+    let expected = scalars.iter().fold(Scalar::zero(), |acc, s| acc + *s * *s);
+
+    assert_eq!(
+        G1Element::multi_scalar_mul(&scalars, &g1).unwrap(),
+        G1Element::generator() * expected
+    );
+    assert_eq!(
+        G2Element::multi_scalar_mul(&scalars, &g2).unwrap(),
+        G2Element::generator() * expected
+    );
+    assert_eq!(
+        G1Element::multi_scalar_mul(&[], &[]).unwrap(),
+        G1Element::zero()
+    );
+    assert!(G1Element::multi_scalar_mul(&scalars, &g1[1..]).is_err());
+}
+
+#[test]
+fn test_pairing() {
+    let a = Scalar::rand(&mut thread_rng());
+    let b = Scalar::rand(&mut thread_rng());
+    let p = G1Element::generator();
+    let q = G2Element::generator();
+
+    let e = p.pairing(&q);
+    assert_eq!(e, GTElement::generator());
+    assert_ne!(e, GTElement::zero());
+    assert_eq!((p * a).pairing(&(q * b)), e * (a * b));
+    assert_eq!((p * a).pairing(&q), p.pairing(&(q * a)));
+    assert_eq!(e + e, e * Scalar::from(2));
+    assert_eq!(e - e, GTElement::zero());
+    assert_eq!(e * order_minus_one(), -e);
+    assert_eq!(G1Element::zero().pairing(&q), GTElement::zero());
+    assert_eq!(p.pairing(&G2Element::zero()), GTElement::zero());
+}
+
+#[test]
+fn test_serialization() {
+    assert_eq!(
+        hex::encode(G1Element::generator().to_byte_array()),
+        G1_GENERATOR
+    );
+    assert_eq!(
+        hex::encode(G2Element::generator().to_byte_array()),
+        G2_GENERATOR
+    );
+    let mut identity = [0u8; 48];
+    identity[0] = 0xc0;
+    assert_eq!(G1Element::zero().to_byte_array(), identity);
+    assert_eq!(
+        G1Element::from_byte_array(&identity).unwrap(),
+        G1Element::zero()
+    );
+
+    let s = Scalar::rand(&mut thread_rng());
+    let p = G1Element::generator() * s;
+    let bytes = bincode::serialize(&p).unwrap();
+    assert_eq!(bytes, p.to_byte_array());
+    assert_eq!(bincode::deserialize::<G1Element>(&bytes).unwrap(), p);
+
+    let q = G2Element::generator() * s;
+    let bytes = bincode::serialize(&q).unwrap();
+    assert_eq!(bincode::deserialize::<G2Element>(&bytes).unwrap(), q);
+
+    let e = GTElement::generator() * s;
+    let bytes = e.to_byte_array();
+    assert_eq!(GTElement::from_byte_array(&bytes).unwrap(), e);
+    assert_eq!(
+        bincode::deserialize::<GTElement>(&bincode::serialize(&e).unwrap()).unwrap(),
+        e
+    );
+
+    // Invalid encodings.
+    let mut invalid = G1Element::generator().to_byte_array();
+    invalid[0] &= 0x7f;
+    assert!(G1Element::from_byte_array(&invalid).is_err());
+    let mut invalid = G2Element::generator().to_byte_array();
+    invalid[95] ^= 1;
+    assert!(G2Element::from_byte_array(&invalid).is_err());
+    let mut invalid = bytes;
+    invalid[575] ^= 1;
+    assert!(GTElement::from_byte_array(&invalid).is_err());
+    assert!(GTElement::from_byte_array(&[0xff; 576]).is_err());
+}
+
+#[test]
+fn test_eip2537_encoding() {
+    let g1 = hex::encode(G1Element::generator().to_eip2537_bytes());
+    assert_eq!(
+        g1,
+        [
+            "00000000000000000000000000000000",
+            "17f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb",
+            "00000000000000000000000000000000",
+            "08b3f481e3aaa0f1a09e30ed741d8ae4fcf5e095d5d00af600db18cb2c04b3edd03cc744a2888ae40caa232946c5e7e1",
+        ]
+        .concat()
+    );
+    let g2 = hex::encode(G2Element::generator().to_eip2537_bytes());
+    assert_eq!(
+        g2[..256],
+        [
+            "00000000000000000000000000000000",
+            "024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8",
+            "00000000000000000000000000000000",
+            "13e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e",
+        ]
+        .concat()
+    );
+    assert_eq!(G1Element::zero().to_eip2537_bytes(), [0u8; 128]);
+    assert_eq!(G2Element::zero().to_eip2537_bytes(), [0u8; 256]);
+}
+
+#[test]
+fn test_hash_to_curve() {
+    // Appendix J.9.1 of RFC 9380, msg = "".
+    let p = G1Element::hash_to_group_element_with_dst(
+        b"",
+        b"QUUX-V01-CS02-with-BLS12381G1_XMD:SHA-256_SSWU_RO_",
+    );
+    assert_eq!(
+        hex::encode(&p.to_eip2537_bytes()[16..64]),
+        "052926add2207b76ca4fa57a8734416c8dc95e24501772c814278700eed6d1e4e8cf62d9c09db0fac349612b759e79a1"
+    );
+
+    assert_ne!(
+        G1Element::hash_to_group_element(b"a"),
+        G1Element::hash_to_group_element(b"b")
+    );
+    assert_ne!(
+        G2Element::hash_to_group_element(b"a"),
+        G2Element::hash_to_group_element(b"b")
+    );
+}
@@ -0,0 +1,132 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::kamui_vrf::bls::{
+    BLSVRFKeyPair, BLSVRFPrivateKey, BLSVRFProof, BLSVRFProofOfPossession, BLSVRFPublicKey,
+    PROOF_LENGTH,
+};
+use crate::kamui_vrf::{VRFKeyPair, VRFProof};
+use rand::thread_rng;
+use static_assertions::assert_impl_all;
+use zeroize::Zeroize;
+
+assert_impl_all!(BLSVRFPrivateKey: Zeroize, Clone);
+
+#[test]
+fn test_prove_and_verify() {
+    let kp = BLSVRFKeyPair::generate(&mut thread_rng());
+    let input = b"Hello, world!";
+    let (output, proof) = kp.output(input);
+
+    assert!(proof.verify(input, &kp.pk).is_ok());
+    assert!(proof.verify_output(input, &kp.pk, &output).is_ok());
+    assert!(proof.verify(b"Other input", &kp.pk).is_err());
+
+    // The proof is a unique signature, so the output is deterministic.
+    assert_eq!(kp.prove(input), proof);
+    assert_ne!(kp.prove(b"Other input").to_hash(), output);
+
+    let other = BLSVRFKeyPair::generate(&mut thread_rng());
+    assert!(proof.verify(input, &other.pk).is_err());
+}
+
+#[test]
+fn test_serialization() {
+    let kp = BLSVRFKeyPair::generate(&mut thread_rng());
+    let proof = kp.prove(b"Hello, world!");
+
+    let bytes = proof.to_bytes();
+    assert_eq!(bytes.len(), PROOF_LENGTH);
+    assert_eq!(BLSVRFProof::from_bytes(&bytes).unwrap(), proof);
+    assert!(BLSVRFProof::from_bytes(&bytes[1..]).is_err());
+
+    assert_eq!(BLSVRFPublicKey::from_bytes(kp.pk.as_ref()).unwrap(), kp.pk);
+    assert!(BLSVRFPublicKey::from_bytes(&[0u8; 96]).is_err());
+
+    let sk = BLSVRFPrivateKey::from_bytes(kp.sk.as_ref()).unwrap();
+    assert_eq!(BLSVRFKeyPair::from(sk).pk, kp.pk);
+    assert!(BLSVRFPrivateKey::from_bytes(&[0u8; 32]).is_err());
+    assert!(BLSVRFPrivateKey::from_bytes(&[0xff; 32]).is_err());
+}
+
+#[test]
+fn test_private_key_is_silent() {
+    let kp = BLSVRFKeyPair::generate(&mut thread_rng());
+    let secret = hex::encode(kp.sk.as_ref());
+    assert!(!format!("{:?} {}", kp.sk, kp.sk).contains(&secret));
+}
+
+#[test]
+fn test_aggregation() {
+    let input = b"Hello, world!";
+    let kps = (0..5)
+        .map(|_| BLSVRFKeyPair::generate(&mut thread_rng()))
+        .collect::<Vec<_>>();
+
+    for kp in &kps {
+        let pop = kp.prove_possession();
+        assert!(pop.verify(&kp.pk).is_ok());
+        assert_eq!(
+            BLSVRFProofOfPossession::from_bytes(&pop.to_bytes()).unwrap(),
+            pop
+        );
+    }
+    assert!(kps[0].prove_possession().verify(&kps[1].pk).is_err());
+
+    let pks = kps.iter().map(|kp| kp.pk.clone()).collect::<Vec<_>>();
+    let proofs = kps.iter().map(|kp| kp.prove(input)).collect::<Vec<_>>();
+    let aggregated_pk = BLSVRFPublicKey::aggregate(&pks).unwrap();
+    let aggregated_proof = BLSVRFProof::aggregate(&proofs).unwrap();
+    assert!(aggregated_proof.verify(input, &aggregated_pk).is_ok());
+    assert!(aggregated_proof
+        .verify(b"Other input", &aggregated_pk)
+        .is_err());
+    assert!(BLSVRFProof::aggregate(&proofs[1..])
+        .unwrap()
+        .verify(input, &aggregated_pk)
+        .is_err());
+
+    assert!(BLSVRFPublicKey::aggregate(&[]).is_err());
+    assert!(BLSVRFProof::aggregate(&[]).is_err());
+}
+
+#[test]
+fn test_threshold() {
+    let input = b"Hello, world!";
+    let kp = BLSVRFKeyPair::generate(&mut thread_rng());
+    let shares = kp.split(3, 5, &mut thread_rng()).unwrap();
+    let partials = shares
+        .iter()
+        .map(|share| share.prove(input))
+        .collect::<Vec<_>>();
+    for (share, partial) in shares.iter().zip(&partials) {
+        assert!(partial.proof.verify(input, &share.keypair.pk).is_ok());
+    }
+
+    // Any three shares give the proof of the full key.
+    let expected = kp.prove(input);
+    let combined = BLSVRFProof::combine(3, &partials[..3]).unwrap();
+    assert_eq!(combined, expected);
+    assert!(combined.verify(input, &kp.pk).is_ok());
+    let subset = [
+        partials[4].clone(),
+        partials[1].clone(),
+        partials[3].clone(),
+    ];
+    assert_eq!(BLSVRFProof::combine(3, &subset).unwrap(), expected);
+
+    // Too few or repeated shares are rejected.
+    assert!(BLSVRFProof::combine(3, &partials[..2]).is_err());
+    let repeated = [
+        partials[0].clone(),
+        partials[0].clone(),
+        partials[1].clone(),
+    ];
+    assert!(BLSVRFProof::combine(3, &repeated).is_err());
+
+    // Two shares do not suffice.
+    assert_ne!(BLSVRFProof::combine(2, &partials[..2]).unwrap(), expected);
+
+    assert!(kp.split(0, 5, &mut thread_rng()).is_err());
+    assert!(kp.split(6, 5, &mut thread_rng()).is_err());
+}
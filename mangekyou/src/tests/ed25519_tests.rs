@@ -0,0 +1,122 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::ed25519::{
+    Ed25519KeyPair, Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature, Ed25519SignatureAsBytes,
+    ED25519_PRIVATE_KEY_LENGTH,
+};
+use crate::hash::{HashFunction, Sha256};
+use crate::test_helpers::{keys, signature_test_inputs_different_msg, verify_serialization};
+use crate::traits::{EncodeDecodeBase64, KeyPair, Signer, ToFromBytes, VerifyingKey};
+use rand::thread_rng;
+use static_assertions::assert_impl_all;
+use zeroize::Zeroize;
+
+assert_impl_all!(Ed25519PrivateKey: Zeroize);
+
+// Test 1 of RFC 8032, section 7.1.
+const RFC8032_SECRET_KEY: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+const RFC8032_PUBLIC_KEY: &str = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+const RFC8032_SIGNATURE: &str = "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b";
+
+#[test]
+fn test_rfc8032_vector() {
+    let kp = Ed25519KeyPair::from_bytes(&hex::decode(RFC8032_SECRET_KEY).unwrap()).unwrap();
+    assert_eq!(hex::encode(kp.public()), RFC8032_PUBLIC_KEY);
+
+    let signature = kp.sign(b"");
+    assert_eq!(hex::encode(signature), RFC8032_SIGNATURE);
+    assert!(kp.public().verify(b"", &signature).is_ok());
+    assert!(kp.public().verify(b"other", &signature).is_err());
+}
+
+#[test]
+fn test_serialization() {
+    let kp = keys::<Ed25519KeyPair>(1).pop().unwrap();
+    let signature = kp.sign(b"Hello, world!");
+    verify_serialization(kp.public(), Some(kp.public().as_ref()));
+    verify_serialization(&signature, Some(signature.as_ref()));
+
+    let private = kp.private();
+    let bytes = bincode::serialize(&private).unwrap();
+    assert_eq!(bytes, private.as_ref());
+    let restored: Ed25519PrivateKey = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(restored.as_ref(), private.as_ref());
+
+    let as_bytes = Ed25519SignatureAsBytes::from(&signature);
+    assert_eq!(Ed25519Signature::try_from(&as_bytes).unwrap(), signature);
+}
+
+#[test]
+fn test_bytes_conversion() {
+    let kp = Ed25519KeyPair::generate(&mut thread_rng());
+    let pk = Ed25519PublicKey::from_bytes(kp.public().as_ref()).unwrap();
+    assert_eq!(&pk, kp.public());
+    assert_eq!(
+        Ed25519PublicKey::decode_base64(&kp.public().encode_base64()).unwrap(),
+        pk
+    );
+    assert!(Ed25519PublicKey::from_bytes(&[0u8; 31]).is_err());
+    assert!(Ed25519PrivateKey::from_bytes(&[0u8; ED25519_PRIVATE_KEY_LENGTH + 1]).is_err());
+    assert!(Ed25519Signature::from_bytes(&[0u8; 63]).is_err());
+
+    let encoded = kp.encode_base64();
+    let restored: Ed25519KeyPair = encoded.parse().unwrap();
+    assert_eq!(restored.public(), kp.public());
+    assert!("not a key".parse::<Ed25519KeyPair>().is_err());
+}
+
+#[test]
+fn test_private_key_is_silent() {
+    let kp = Ed25519KeyPair::generate(&mut thread_rng());
+    let secret = hex::encode(&kp);
+    let formatted = format!("{:?}", kp);
+    assert!(!formatted.contains(&secret));
+    let private = kp.private();
+    assert!(!format!("{:?} {}", private, private).contains(&secret));
+}
+
+#[test]
+fn test_verify_batch() {
+    let message = Sha256::digest(b"Hello, world!").to_vec();
+    let kps = keys::<Ed25519KeyPair>(3);
+    let pks: Vec<_> = kps.iter().map(|kp| *kp.public()).collect();
+    let mut sigs: Vec<_> = kps.iter().map(|kp| kp.sign(&message)).collect();
+
+    assert!(Ed25519PublicKey::verify_batch_empty_fail(&message, &pks, &sigs).is_ok());
+    assert!(Ed25519PublicKey::verify_batch_empty_fail(&message, &pks[..2], &sigs).is_err());
+    assert!(Ed25519PublicKey::verify_batch_empty_fail(&message, &[], &[]).is_err());
+
+    sigs.swap(0, 1);
+    assert!(Ed25519PublicKey::verify_batch_empty_fail(&message, &pks, &sigs).is_err());
+}
+
+#[test]
+fn test_verify_batch_different_msg() {
+    let inputs = signature_test_inputs_different_msg::<Ed25519KeyPair>();
+    let digests: Vec<&[u8]> = inputs.digests.iter().map(|d| d.as_slice()).collect();
+    assert!(Ed25519PublicKey::verify_batch_empty_fail_different_msg(
+        &digests,
+        &inputs.pubkeys,
+        &inputs.signatures
+    )
+    .is_ok());
+
+    let mut signatures = inputs.signatures.clone();
+    signatures[2] = signatures[0];
+    assert!(Ed25519PublicKey::verify_batch_empty_fail_different_msg(
+        &digests,
+        &inputs.pubkeys,
+        &signatures
+    )
+    .is_err());
+    assert!(Ed25519PublicKey::verify_batch_empty_fail_different_msg(
+        &digests[..2],
+        &inputs.pubkeys,
+        &inputs.signatures
+    )
+    .is_err());
+    assert!(
+        Ed25519PublicKey::verify_batch_empty_fail_different_msg::<&[u8]>(&[], &[], &[]).is_err()
+    );
+}
@@ -0,0 +1,327 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::groups::ristretto255::{RistrettoPoint, RistrettoScalar};
+use crate::groups::{GroupElement, HashToGroupElement, Scalar};
+use crate::hash::Sha512;
+use crate::kamui_vrf::beacon::{beacon_alpha, verify_chain, BeaconProver, BeaconRound};
+use crate::kamui_vrf::ecvrf::{
+    ECVRFKeyPair, ECVRFPrivateKey, ECVRFProof, ECVRFProver, ECVRFPublicKey,
+};
+use crate::kamui_vrf::encrypted::{
+    Ciphertext, DecryptionKey, EncryptedVRFOutput, EncryptionKey, EncryptionProof, VRFReveal,
+    CIPHERTEXT_LENGTH, ENCRYPTION_PROOF_LENGTH, REVEAL_LENGTH,
+};
+use crate::kamui_vrf::generic::{self, ECVRFSuite};
+use crate::kamui_vrf::pop::{ECVRFProofOfPossession, POP_LENGTH};
+use crate::kamui_vrf::solana::SolanaRistrettoPoint;
+use crate::kamui_vrf::{VRFKeyPair, VRFProof};
+use crate::serde_helpers::ToFromByteArray;
+use rand::thread_rng;
+use static_assertions::assert_impl_all;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+assert_impl_all!(ECVRFPrivateKey: Zeroize, ZeroizeOnDrop, Clone);
+assert_impl_all!(DecryptionKey: Zeroize, Clone);
+assert_impl_all!(RistrettoScalar: Zeroize);
+
+/// Formatting a secret must not reveal its bytes in any common encoding.
+fn assert_no_secret_in(formatted: &str, secret: &[u8]) {
+    assert!(!formatted.contains(&hex::encode(secret)));
+    assert!(!formatted.contains(&format!("{:?}", secret)));
+    assert!(!formatted.contains(&format!("{:02x?}", secret)));
+}
+
+#[test]
+fn test_private_key_debug_and_display_are_silent() {
+    let kp = ECVRFKeyPair::generate(&mut thread_rng());
+    let secret = kp.sk.as_ref().to_vec();
+
+    let debug = format!("{:?}", kp.sk);
+    let pretty = format!("{:#?}", kp.sk);
+    let display = format!("{}", kp.sk);
+    assert_eq!(debug, "<elided secret for ECVRFPrivateKey>");
+    assert_eq!(display, "<elided secret for ECVRFPrivateKey>");
+    for formatted in [debug, pretty, display] {
+        assert_no_secret_in(&formatted, &secret);
+    }
+}
+
+#[test]
+fn test_private_key_zeroize() {
+    let kp = ECVRFKeyPair::generate(&mut thread_rng());
+    let mut sk = kp.sk.clone();
+    assert!(sk.as_ref().iter().any(|&b| b != 0));
+    sk.zeroize();
+    assert!(sk.as_ref().iter().all(|&b| b == 0));
+
+    // Zeroizing a clone leaves the original key usable
+    let input = b"Hello, world!";
+    let (output, proof) = kp.output(input);
+    assert!(proof.verify_output(input, &kp.pk, &output).is_ok());
+}
+
+#[test]
+fn test_prove_is_deterministic() {
+    let sk = ECVRFPrivateKey::from_bytes(
+        &hex::decode("d354a0525580ab79bf67797b824a7df3ddf81ff45729175fa4d98d9f3dcd150f").unwrap(),
+    )
+    .unwrap();
+    let kp = ECVRFKeyPair::from(sk);
+    let input = hex::decode("4869204b616d756921").unwrap();
+    assert_eq!(
+        hex::encode(kp.prove(&input).to_bytes()),
+        "54b58f527e999ceedb24485a7629e3caa9f7deb152852a0f483a6646495fa253c4131e87ff0b48fefacf4b5be04211a77390ca85553aa2c06f0023db34e7b36194eadf11539c0ef1c8dcae09aa35580a"
+    );
+}
+
+/// The same construction over the dalek-backed group, to check nothing in it is specific to the
+/// Solana adapter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct DalekSuite;
+
+impl ECVRFSuite for DalekSuite {
+    type Group = RistrettoPoint;
+    type Scalar = RistrettoScalar;
+    type Hash = Sha512;
+    const SUITE_STRING: &'static [u8] = b"test_vrf";
+}
+
+#[test]
+fn test_generic_suite_round_trip() {
+    let kp = generic::ECVRFKeyPair::<DalekSuite>::generate(&mut thread_rng());
+    let input = b"Hello, world!";
+    let (output, proof) = kp.output(input);
+    assert!(proof.verify_output(input, &kp.pk, &output).is_ok());
+    assert!(proof.verify(b"Goodbye, world!", &kp.pk).is_err());
+
+    let other = generic::ECVRFKeyPair::<DalekSuite>::generate(&mut thread_rng());
+    assert!(proof.verify(input, &other.pk).is_err());
+
+    let bytes = proof.to_bytes();
+    let parsed = generic::ECVRFProof::<DalekSuite>::from_bytes(&bytes).unwrap();
+    assert_eq!(parsed, proof);
+    let mut tampered = bytes.clone();
+    tampered[generic::POINT_LENGTH] ^= 1;
+    let tampered = generic::ECVRFProof::<DalekSuite>::from_bytes(&tampered).unwrap();
+    assert!(tampered.verify(input, &kp.pk).is_err());
+}
+
+#[test]
+fn test_solana_group_matches_ristretto() {
+    let mut rng = thread_rng();
+    let a = RistrettoScalar::rand(&mut rng);
+    let b = RistrettoScalar::rand(&mut rng);
+    let expected = RistrettoPoint::generator() * a - RistrettoPoint::generator() * b;
+    let actual = SolanaRistrettoPoint::generator() * a - SolanaRistrettoPoint::generator() * b;
+    assert_eq!(actual.to_byte_array(), expected.to_byte_array());
+    assert_eq!((-actual).to_byte_array(), (-expected).to_byte_array());
+    assert_eq!(
+        SolanaRistrettoPoint::zero().to_byte_array(),
+        RistrettoPoint::zero().to_byte_array()
+    );
+    assert!(SolanaRistrettoPoint::from_byte_array(&[0xff; 32]).is_err());
+}
+
+#[test]
+fn test_sol_vrf_rejects_malformed_inputs() {
+    let kp = ECVRFKeyPair::generate(&mut thread_rng());
+    let proof = kp.prove(b"input").to_bytes();
+    assert!(ECVRFProof::from_bytes(&proof[..proof.len() - 1]).is_err());
+    assert!(ECVRFPrivateKey::from_bytes(&[0xff; 32]).is_err());
+    assert!(ECVRFKeyPair::from_bytes(&[0u8; 63]).is_err());
+    let input_point = SolanaRistrettoPoint::hash_to_group_element(b"input");
+    assert_ne!(input_point, SolanaRistrettoPoint::generator());
+}
+
+#[test]
+fn test_precomputed_prover_matches_keypair() {
+    let prover = ECVRFProver::new(ECVRFKeyPair::generate(&mut thread_rng()));
+    for input in [&b""[..], b"Hello, world!", &[0xab; 300]] {
+        let proof = prover.prove(input);
+        assert_eq!(proof, prover.keypair().prove(input));
+        assert!(proof.verify(input, &prover.keypair().pk).is_ok());
+    }
+}
+
+#[test]
+fn test_proof_of_possession() {
+    let kp = ECVRFKeyPair::generate(&mut thread_rng());
+    let authority = [7u8; 32];
+    let pop = ECVRFProofOfPossession::prove(&kp, &authority, &mut thread_rng());
+    assert!(pop.verify(&kp.pk, &authority).is_ok());
+
+    let bytes = pop.to_bytes();
+    assert_eq!(ECVRFProofOfPossession::from_bytes(&bytes).unwrap(), pop);
+    assert!(ECVRFProofOfPossession::from_bytes(&bytes[..POP_LENGTH - 1]).is_err());
+
+    // Bound to the authority and the key
+    assert!(pop.verify(&kp.pk, &[8u8; 32]).is_err());
+    let other = ECVRFKeyPair::generate(&mut thread_rng());
+    assert!(pop.verify(&other.pk, &authority).is_err());
+
+    let identity = ECVRFPublicKey::from_bytes(&[0u8; 32]).unwrap();
+    assert!(pop.verify(&identity, &authority).is_err());
+}
+
+#[test]
+fn test_encrypted_output() {
+    let kp = ECVRFKeyPair::generate(&mut thread_rng());
+    let dk = DecryptionKey::generate(&mut thread_rng());
+    let ek = dk.encryption_key();
+    let alpha = b"Hello, world!";
+    let request_id = [3u8; 32];
+
+    let encrypted = EncryptedVRFOutput::encrypt(&kp, alpha, &request_id, &ek, &mut thread_rng());
+    assert!(encrypted.verify(alpha, &kp.pk, &request_id, &ek).is_ok());
+    assert_eq!(dk.decrypt(&encrypted.ciphertext), kp.output(alpha).0);
+
+    // Bound to the input, the VRF key, the request and the encryption key
+    assert!(encrypted
+        .verify(b"other", &kp.pk, &request_id, &ek)
+        .is_err());
+    let other = ECVRFKeyPair::generate(&mut thread_rng());
+    assert!(encrypted
+        .verify(alpha, &other.pk, &request_id, &ek)
+        .is_err());
+    assert!(encrypted.verify(alpha, &kp.pk, &[4u8; 32], &ek).is_err());
+    let other_ek = DecryptionKey::generate(&mut thread_rng()).encryption_key();
+    assert!(encrypted
+        .verify(alpha, &kp.pk, &request_id, &other_ek)
+        .is_err());
+    let identity = ECVRFPublicKey::from_bytes(&[0u8; 32]).unwrap();
+    assert!(encrypted
+        .verify(alpha, &identity, &request_id, &ek)
+        .is_err());
+
+    // A ciphertext of another output does not verify with the original proof
+    let reencrypted = EncryptedVRFOutput::encrypt(&kp, alpha, &request_id, &ek, &mut thread_rng());
+    let mixed = EncryptedVRFOutput {
+        ciphertext: reencrypted.ciphertext,
+        proof: encrypted.proof.clone(),
+    };
+    assert!(mixed.verify(alpha, &kp.pk, &request_id, &ek).is_err());
+
+    let ciphertext = encrypted.ciphertext.to_bytes();
+    assert_eq!(
+        Ciphertext::from_bytes(&ciphertext).unwrap(),
+        encrypted.ciphertext
+    );
+    assert!(Ciphertext::from_bytes(&ciphertext[..CIPHERTEXT_LENGTH - 1]).is_err());
+    let proof = encrypted.proof.to_bytes();
+    assert_eq!(
+        EncryptionProof::from_bytes(&proof).unwrap(),
+        encrypted.proof
+    );
+    assert!(EncryptionProof::from_bytes(&proof[..ENCRYPTION_PROOF_LENGTH - 1]).is_err());
+
+    assert_eq!(EncryptionKey::from_bytes(&ek.to_bytes()).unwrap(), ek);
+    assert!(EncryptionKey::from_bytes(&[0u8; 32]).is_err());
+}
+
+#[test]
+fn test_encrypted_output_reveal() {
+    let kp = ECVRFKeyPair::generate(&mut thread_rng());
+    let dk = DecryptionKey::generate(&mut thread_rng());
+    let ek = dk.encryption_key();
+    let alpha = b"Hello, world!";
+    let request_id = [3u8; 32];
+    let encrypted = EncryptedVRFOutput::encrypt(&kp, alpha, &request_id, &ek, &mut thread_rng());
+
+    let reveal = dk.reveal(&encrypted.ciphertext, &request_id, &mut thread_rng());
+    assert_eq!(
+        reveal
+            .verify(&encrypted.ciphertext, &request_id, &ek)
+            .unwrap(),
+        kp.output(alpha).0
+    );
+    assert!(reveal
+        .verify(&encrypted.ciphertext, &[4u8; 32], &ek)
+        .is_err());
+
+    let bytes = reveal.to_bytes();
+    assert_eq!(VRFReveal::from_bytes(&bytes).unwrap(), reveal);
+    assert!(VRFReveal::from_bytes(&bytes[..REVEAL_LENGTH - 1]).is_err());
+
+    // A reveal with another Gamma, e.g. from another key pair, is rejected
+    let other = EncryptedVRFOutput::encrypt(
+        &ECVRFKeyPair::generate(&mut thread_rng()),
+        alpha,
+        &request_id,
+        &ek,
+        &mut thread_rng(),
+    );
+    let mut forged = bytes;
+    forged[..32].copy_from_slice(
+        &dk.reveal(&other.ciphertext, &request_id, &mut thread_rng())
+            .to_bytes()[..32],
+    );
+    let forged = VRFReveal::from_bytes(&forged).unwrap();
+    assert!(forged
+        .verify(&encrypted.ciphertext, &request_id, &ek)
+        .is_err());
+}
+
+#[test]
+fn test_decryption_key() {
+    let dk = DecryptionKey::generate(&mut thread_rng());
+    let secret = dk.to_bytes();
+    let parsed = DecryptionKey::from_bytes(secret.as_ref()).unwrap();
+    assert_eq!(parsed.encryption_key(), dk.encryption_key());
+    assert!(DecryptionKey::from_bytes(&[0u8; 32]).is_err());
+    assert!(DecryptionKey::from_bytes(&[0xffu8; 32]).is_err());
+
+    let debug = format!("{:?}", dk);
+    let display = format!("{}", dk);
+    assert_eq!(debug, "<elided secret for DecryptionKey>");
+    assert_eq!(display, "<elided secret for DecryptionKey>");
+    for formatted in [debug, format!("{:#?}", dk), display] {
+        assert_no_secret_in(&formatted, secret.as_ref());
+    }
+}
+
+#[test]
+fn test_beacon_chain() {
+    let kp = ECVRFKeyPair::generate(&mut thread_rng());
+    let pk = kp.pk.clone();
+    let genesis = [1u8; 64];
+    let mut beacon = BeaconProver::new(ECVRFKeyPair::from(kp.sk.clone()), genesis);
+    let rounds: Vec<BeaconRound> = (0..5).map(|_| beacon.next_round()).collect();
+    assert_eq!(beacon.round(), 5);
+    assert_eq!(rounds[0].round, 1);
+
+    // Each round is the VRF on the round number and the previous output
+    assert_eq!(rounds[0].output(), kp.output(&beacon_alpha(1, &genesis)).0);
+    assert_eq!(
+        rounds[1].output(),
+        kp.output(&beacon_alpha(2, &rounds[0].output())).0
+    );
+
+    assert_eq!(
+        verify_chain(&pk, &genesis, &rounds).unwrap(),
+        *beacon.output()
+    );
+    assert_eq!(
+        verify_chain(&pk, &rounds[1].output(), &rounds[2..]).unwrap(),
+        rounds[4].output()
+    );
+    assert_eq!(verify_chain(&pk, &genesis, &[]).unwrap(), genesis);
+
+    // Links must follow each other
+    assert!(verify_chain(&pk, &[2u8; 64], &rounds).is_err());
+    assert!(verify_chain(&pk, &genesis, &rounds[1..]).is_err());
+    assert!(verify_chain(&pk, &genesis, &[rounds[0].clone(), rounds[2].clone()]).is_err());
+    let other = ECVRFKeyPair::generate(&mut thread_rng());
+    assert!(verify_chain(&other.pk, &genesis, &rounds).is_err());
+
+    // A round cannot be moved to another position in the chain
+    let mut moved = rounds[1].clone();
+    moved.round = 3;
+    assert!(moved.verify(&pk, &rounds[0].output()).is_err());
+    moved.round = 0;
+    assert!(moved.verify(&pk, &rounds[0].output()).is_err());
+
+    // Resuming continues the same chain
+    let mut resumed = BeaconProver::resume(kp, 2, rounds[1].output());
+    assert_eq!(resumed.next_round(), rounds[2]);
+}
@@ -0,0 +1,212 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::ed25519::Ed25519KeyPair;
+use crate::kamui_vrf::ecvrf::ECVRFKeyPair;
+use crate::kamui_vrf::VRFKeyPair;
+use crate::keystore::{
+    KdfParams, Keystore, ECVRF_SCHEME, ED25519_SCHEME, KEYSTORE_VERSION, MAX_KDF_MEMORY,
+};
+use crate::traits::KeyPair;
+use rand::thread_rng;
+
+// Cheap parameters so the tests run quickly.
+fn test_argon2() -> KdfParams {
+    KdfParams::Argon2id {
+        m_cost: 256,
+        t_cost: 1,
+        p_cost: 1,
+    }
+}
+
+fn test_scrypt() -> KdfParams {
+    KdfParams::Scrypt { log_n: 4, r: 8, p: 1 }
+}
+
+#[test]
+fn test_ecvrf_roundtrip() {
+    for kdf in [test_argon2(), test_scrypt()] {
+        let keypair = ECVRFKeyPair::generate(&mut thread_rng());
+        let keystore =
+            Keystore::from_ecvrf_keypair(&keypair, b"hunter2", kdf.clone(), &mut thread_rng())
+                .unwrap();
+        assert_eq!(keystore.version, KEYSTORE_VERSION);
+        assert_eq!(keystore.scheme, ECVRF_SCHEME);
+        assert_eq!(keystore.crypto.kdf, kdf);
+        assert_eq!(keystore.public_key, keypair.pk.as_ref());
+
+        let json = keystore.to_json().unwrap();
+        let restored = Keystore::from_json(&json).unwrap();
+        assert_eq!(restored, keystore);
+
+        let decrypted = restored.to_ecvrf_keypair(b"hunter2").unwrap();
+        assert_eq!(decrypted.sk.as_ref(), keypair.sk.as_ref());
+        assert_eq!(decrypted.pk.as_ref(), keypair.pk.as_ref());
+    }
+}
+
+#[test]
+fn test_ed25519_roundtrip() {
+    let keypair = Ed25519KeyPair::generate(&mut thread_rng());
+    let keystore =
+        Keystore::from_ed25519_keypair(&keypair, b"hunter2", test_argon2(), &mut thread_rng())
+            .unwrap();
+    assert_eq!(keystore.scheme, ED25519_SCHEME);
+    assert_eq!(keystore.public_key, keypair.public().as_ref());
+
+    let restored = Keystore::from_json(&keystore.to_json().unwrap()).unwrap();
+    let decrypted = restored.to_ed25519_keypair(b"hunter2").unwrap();
+    assert_eq!(decrypted.public(), keypair.public());
+    assert_eq!(decrypted.as_ref(), keypair.as_ref());
+
+    // Keys of one scheme are not decrypted as another
+    assert!(restored.to_ecvrf_keypair(b"hunter2").is_err());
+    let keystore = Keystore::from_ecvrf_keypair(
+        &ECVRFKeyPair::generate(&mut thread_rng()),
+        b"hunter2",
+        test_argon2(),
+        &mut thread_rng(),
+    )
+    .unwrap();
+    assert!(keystore.to_ed25519_keypair(b"hunter2").is_err());
+}
+
+#[test]
+fn test_secret_not_in_json() {
+    let keypair = ECVRFKeyPair::generate(&mut thread_rng());
+    let keystore =
+        Keystore::from_ecvrf_keypair(&keypair, b"hunter2", test_argon2(), &mut thread_rng())
+            .unwrap();
+    let json = keystore.to_json().unwrap();
+    assert!(!json.contains(&hex::encode(keypair.sk.as_ref())));
+    assert!(json.contains(&hex::encode(keypair.pk.as_ref())));
+}
+
+#[test]
+fn test_wrong_password() {
+    let keypair = ECVRFKeyPair::generate(&mut thread_rng());
+    let keystore =
+        Keystore::from_ecvrf_keypair(&keypair, b"hunter2", test_argon2(), &mut thread_rng())
+            .unwrap();
+    assert!(keystore.decrypt(b"hunter3").is_err());
+    assert!(keystore.to_ecvrf_keypair(b"").is_err());
+}
+
+#[test]
+fn test_tampered_envelope() {
+    let keypair = ECVRFKeyPair::generate(&mut thread_rng());
+    let keystore =
+        Keystore::from_ecvrf_keypair(&keypair, b"hunter2", test_argon2(), &mut thread_rng())
+            .unwrap();
+
+    let mut tampered = keystore.clone();
+    tampered.public_key[0] ^= 1;
+    assert!(tampered.decrypt(b"hunter2").is_err());
+
+    let mut tampered = keystore.clone();
+    tampered.scheme = "other".to_string();
+    assert!(tampered.decrypt(b"hunter2").is_err());
+
+    let mut tampered = keystore.clone();
+    tampered.crypto.ciphertext[0] ^= 1;
+    assert!(tampered.decrypt(b"hunter2").is_err());
+
+    let mut tampered = keystore.clone();
+    tampered.crypto.salt[0] ^= 1;
+    assert!(tampered.decrypt(b"hunter2").is_err());
+
+    let mut tampered = keystore.clone();
+    tampered.crypto.kdf = test_scrypt();
+    assert!(tampered.decrypt(b"hunter2").is_err());
+
+    let mut tampered = keystore;
+    tampered.crypto.kdf = KdfParams::Argon2id {
+        m_cost: 512,
+        t_cost: 1,
+        p_cost: 1,
+    };
+    assert!(tampered.decrypt(b"hunter2").is_err());
+}
+
+#[test]
+fn test_kdf_params_capped() {
+    assert!(KdfParams::default().validate().is_ok());
+    assert!(KdfParams::scrypt().validate().is_ok());
+
+    let too_expensive = [
+        KdfParams::Argon2id {
+            m_cost: (MAX_KDF_MEMORY / 1024) as u32 + 1,
+            t_cost: 1,
+            p_cost: 1,
+        },
+        KdfParams::Argon2id {
+            m_cost: 256,
+            t_cost: u32::MAX,
+            p_cost: 1,
+        },
+        KdfParams::Argon2id {
+            m_cost: 256,
+            t_cost: 1,
+            p_cost: 1024,
+        },
+        KdfParams::Scrypt { log_n: 21, r: 8, p: 1 },
+        KdfParams::Scrypt { log_n: 200, r: 8, p: 1 },
+        KdfParams::Scrypt { log_n: 4, r: u32::MAX, p: 1 },
+        KdfParams::Scrypt { log_n: 4, r: 8, p: u32::MAX },
+    ];
+
+    // A crafted envelope is rejected before any key is derived
+    let keypair = ECVRFKeyPair::generate(&mut thread_rng());
+    let keystore =
+        Keystore::from_ecvrf_keypair(&keypair, b"hunter2", test_argon2(), &mut thread_rng())
+            .unwrap();
+    for kdf in too_expensive {
+        assert!(kdf.validate().is_err());
+        assert!(Keystore::encrypt(
+            ECVRF_SCHEME,
+            keypair.sk.as_ref(),
+            keypair.pk.as_ref(),
+            b"hunter2",
+            kdf.clone(),
+            &mut thread_rng()
+        )
+        .is_err());
+
+        let mut crafted = keystore.clone();
+        crafted.crypto.kdf = kdf;
+        assert!(crafted.decrypt(b"hunter2").is_err());
+    }
+}
+
+#[test]
+fn test_long_scheme_rejected() {
+    let keypair = ECVRFKeyPair::generate(&mut thread_rng());
+    let scheme = "s".repeat(256);
+    assert!(Keystore::encrypt(
+        &scheme,
+        keypair.sk.as_ref(),
+        keypair.pk.as_ref(),
+        b"hunter2",
+        test_argon2(),
+        &mut thread_rng()
+    )
+    .is_err());
+
+    let mut keystore =
+        Keystore::from_ecvrf_keypair(&keypair, b"hunter2", test_argon2(), &mut thread_rng())
+            .unwrap();
+    keystore.scheme = scheme;
+    assert!(keystore.decrypt(b"hunter2").is_err());
+}
+
+#[test]
+fn test_unsupported_version() {
+    let keypair = ECVRFKeyPair::generate(&mut thread_rng());
+    let mut keystore =
+        Keystore::from_ecvrf_keypair(&keypair, b"hunter2", test_argon2(), &mut thread_rng())
+            .unwrap();
+    keystore.version = KEYSTORE_VERSION + 1;
+    let json = serde_json::to_string(&keystore).unwrap();
+    assert!(Keystore::from_json(&json).is_err());
+    assert!(keystore.decrypt(b"hunter2").is_err());
+}
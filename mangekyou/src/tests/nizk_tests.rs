@@ -0,0 +1,174 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::groups::ristretto255::{RistrettoPoint, RistrettoScalar};
+use crate::groups::{GroupElement, HashToGroupElement, Scalar};
+use crate::nizk::{DLEQProof, SchnorrProof, TranscriptProtocol};
+use merlin::Transcript;
+use rand::thread_rng;
+
+fn transcript(context: &[u8]) -> Transcript {
+    let mut transcript = Transcript::new(b"nizk-tests");
+    transcript.append_message(b"context", context);
+    transcript
+}
+
+#[test]
+fn test_schnorr_round_trip() {
+    let g = RistrettoPoint::generator();
+    let x = RistrettoScalar::rand(&mut thread_rng());
+    let public = g * x;
+    let proof = SchnorrProof::prove(&mut transcript(b"a"), &g, &x, &mut thread_rng());
+
+    assert!(proof.verify(&mut transcript(b"a"), &g, &public).is_ok());
+    // Bound to the transcript context, the base and the public element
+    assert!(proof.verify(&mut transcript(b"b"), &g, &public).is_err());
+    assert!(proof
+        .verify(
+            &mut transcript(b"a"),
+            &(g * RistrettoScalar::from(2)),
+            &public
+        )
+        .is_err());
+    assert!(proof
+        .verify(&mut transcript(b"a"), &g, &(public + g))
+        .is_err());
+
+    let serialized = bincode::serialize(&proof).unwrap();
+    let deserialized: SchnorrProof<RistrettoPoint> = bincode::deserialize(&serialized).unwrap();
+    assert_eq!(deserialized, proof);
+}
+
+#[test]
+fn test_schnorr_batch_verification() {
+    let g = RistrettoPoint::generator();
+    let secrets: Vec<_> = (0..5)
+        .map(|_| RistrettoScalar::rand(&mut thread_rng()))
+        .collect();
+    let publics: Vec<_> = secrets.iter().map(|x| g * x).collect();
+    let proofs: Vec<_> = secrets
+        .iter()
+        .enumerate()
+        .map(|(i, x)| SchnorrProof::prove(&mut transcript(&[i as u8]), &g, x, &mut thread_rng()))
+        .collect();
+    let transcripts = || (0..5).map(|i| transcript(&[i as u8])).collect::<Vec<_>>();
+
+    assert!(SchnorrProof::verify_batch(
+        &mut transcripts(),
+        &g,
+        &publics,
+        &proofs,
+        &mut thread_rng()
+    )
+    .is_ok());
+
+    let mut swapped = proofs.clone();
+    swapped.swap(0, 1);
+    assert!(SchnorrProof::verify_batch(
+        &mut transcripts(),
+        &g,
+        &publics,
+        &swapped,
+        &mut thread_rng()
+    )
+    .is_err());
+    assert!(SchnorrProof::verify_batch(
+        &mut transcripts()[..4],
+        &g,
+        &publics,
+        &proofs,
+        &mut thread_rng()
+    )
+    .is_err());
+}
+
+#[test]
+fn test_dleq_round_trip() {
+    let g = RistrettoPoint::generator();
+    let u = RistrettoPoint::hash_to_group_element(b"u");
+    let x = RistrettoScalar::rand(&mut thread_rng());
+    let proof = DLEQProof::prove(&mut transcript(b""), &g, &u, &x, &mut thread_rng());
+
+    assert!(proof
+        .verify(&mut transcript(b""), (&g, &(g * x)), (&u, &(u * x)))
+        .is_ok());
+    let y = x + RistrettoScalar::from(1);
+    assert!(proof
+        .verify(&mut transcript(b""), (&g, &(g * x)), (&u, &(u * y)))
+        .is_err());
+    assert!(proof
+        .verify(&mut transcript(b"other"), (&g, &(g * x)), (&u, &(u * x)))
+        .is_err());
+}
+
+#[test]
+fn test_dleq_batch() {
+    let g = RistrettoPoint::generator();
+    let x = RistrettoScalar::rand(&mut thread_rng());
+    let h = g * x;
+    let us: Vec<_> = (0..4u8)
+        .map(|i| RistrettoPoint::hash_to_group_element(&[i]))
+        .collect();
+    let mut vs: Vec<_> = us.iter().map(|u| *u * x).collect();
+    let proof =
+        DLEQProof::prove_batch(&mut transcript(b""), &g, &us, &x, &mut thread_rng()).unwrap();
+
+    assert!(proof
+        .verify_batch(&mut transcript(b""), (&g, &h), &us, &vs)
+        .is_ok());
+    assert!(proof
+        .verify_batch(&mut transcript(b""), (&g, &h), &us[..3], &vs[..3])
+        .is_err());
+    vs[2] += g;
+    assert!(proof
+        .verify_batch(&mut transcript(b""), (&g, &h), &us, &vs)
+        .is_err());
+    assert!(DLEQProof::prove_batch(&mut transcript(b""), &g, &[], &x, &mut thread_rng()).is_err());
+}
+
+#[test]
+fn test_dleq_batch_binds_statement_before_weights() {
+    // With u_i = a_i * g and v_i = y_i * u_i for known a_i and unequal y_i, a prover who saw the
+    // weights before committing to h could pick h so that the folded pair has a common discrete
+    // logarithm. Replay the batch transcript without g and h to get those weights.
+    let g = RistrettoPoint::generator();
+    let a: Vec<_> = (0..3).map(|_| RistrettoScalar::rand(&mut thread_rng())).collect();
+    let y: Vec<_> = (0..3).map(|_| RistrettoScalar::rand(&mut thread_rng())).collect();
+    let us: Vec<_> = a.iter().map(|a| g * a).collect();
+    let vs: Vec<_> = us.iter().zip(&y).map(|(u, y)| *u * y).collect();
+
+    let mut forged = transcript(b"");
+    forged.domain_separator(b"chaum-pedersen-dleq-batch");
+    forged.append_u64(b"n", us.len() as u64);
+    for (u, v) in us.iter().zip(&vs) {
+        forged.append_element(b"u", u);
+        forged.append_element(b"v", v);
+    }
+    let weights: Vec<RistrettoScalar> = (0..us.len())
+        .map(|_| forged.challenge_scalar(b"weight"))
+        .collect();
+    let mut log_u = RistrettoScalar::zero();
+    let mut log_v = RistrettoScalar::zero();
+    for ((w, a), y) in weights.iter().zip(&a).zip(&y) {
+        log_u += *w * a;
+        log_v += *w * a * y;
+    }
+    let x = (log_v / log_u).unwrap();
+    let u = g * log_u;
+    let proof = DLEQProof::prove(&mut forged, &g, &u, &x, &mut thread_rng());
+
+    assert!(proof
+        .verify_batch(&mut transcript(b""), (&g, &(g * x)), &us, &vs)
+        .is_err());
+}
+
+#[test]
+fn test_transcript_challenges_are_domain_separated() {
+    let mut a = transcript(b"");
+    let mut b = transcript(b"");
+    a.domain_separator(b"one");
+    b.domain_separator(b"two");
+    let ca: RistrettoScalar = a.challenge_scalar(b"c");
+    let cb: RistrettoScalar = b.challenge_scalar(b"c");
+    assert_ne!(ca, cb);
+}
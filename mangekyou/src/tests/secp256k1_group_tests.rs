@@ -0,0 +1,147 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::groups::secp256k1::{Secp256k1Point, Secp256k1Scalar};
+use crate::groups::{
+    Doubling, GroupElement, HashToGroupElement, HashToGroupElementWithDst, MultiScalarMul, Scalar,
+};
+use crate::secp256k1::Secp256k1KeyPair;
+use crate::serde_helpers::ToFromByteArray;
+use crate::traits::{KeyPair, ToFromBytes};
+use rand::thread_rng;
+
+// The order of the group minus one, big-endian.
+const ORDER_MINUS_ONE: &str = "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364140";
+
+#[test]
+fn test_arithmetic() {
+    let g = Secp256k1Point::generator();
+    // 5G in compressed form.
+    let five_g = Secp256k1Point::from_byte_array(
+        &hex::decode("022f8bde4d1a07209355b4a7250a5c5128e88b84bddc619ab7cba8d569b240efe4")
+            .unwrap()
+            .try_into()
+            .unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(five_g, g * Secp256k1Scalar::from(5));
+    assert_eq!(five_g, g + g + g + g + g + g - g);
+    assert_eq!(five_g, g.double().double() + g);
+    assert_eq!(
+        five_g,
+        g * (Secp256k1Scalar::from(7) - Secp256k1Scalar::from(2))
+    );
+
+    let mut p = Secp256k1Point::zero();
+    p += five_g;
+    p *= Secp256k1Scalar::from(2);
+    assert_eq!((p / Secp256k1Scalar::from(2)).unwrap(), five_g);
+    assert!((g / Secp256k1Scalar::zero()).is_err());
+    assert!(Secp256k1Scalar::zero().inverse().is_err());
+
+    // (n - 1) * G = -G
+    let order_minus_one = Secp256k1Scalar::from_byte_array(
+        &hex::decode(ORDER_MINUS_ONE).unwrap().try_into().unwrap(),
+    )
+    .unwrap();
+    assert_eq!(g * order_minus_one, -g);
+    assert_eq!(g * order_minus_one + g, Secp256k1Point::zero());
+    assert_eq!(
+        order_minus_one + Secp256k1Scalar::generator(),
+        Secp256k1Scalar::zero()
+    );
+}
+
+#[test]
+fn test_serialization() {
+    let g = Secp256k1Point::generator();
+    assert_eq!(
+        hex::encode(g.to_byte_array()),
+        "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+    );
+    assert_eq!(Secp256k1Point::zero().to_byte_array(), [0u8; 33]);
+    assert_eq!(
+        Secp256k1Point::from_byte_array(&[0u8; 33]).unwrap(),
+        Secp256k1Point::zero()
+    );
+    let mut invalid = g.to_byte_array();
+    invalid[0] = 0x05;
+    assert!(Secp256k1Point::from_byte_array(&invalid).is_err());
+
+    let p = g * Secp256k1Scalar::rand(&mut thread_rng());
+    let bytes = bincode::serialize(&p).unwrap();
+    assert_eq!(bytes, p.to_byte_array());
+    assert_eq!(bincode::deserialize::<Secp256k1Point>(&bytes).unwrap(), p);
+
+    // Scalars must be canonical.
+    let mut order = hex::decode(ORDER_MINUS_ONE).unwrap();
+    order[31] += 1;
+    assert!(Secp256k1Scalar::from_byte_array(&order.try_into().unwrap()).is_err());
+    let s = Secp256k1Scalar::rand(&mut thread_rng());
+    let bytes = bincode::serialize(&s).unwrap();
+    assert_eq!(bincode::deserialize::<Secp256k1Scalar>(&bytes).unwrap(), s);
+    assert_eq!(
+        Secp256k1Scalar::from(1u128 << 100).to_byte_array()[..18],
+        [0u8; 18]
+    );
+}
+
+#[test]
+fn test_multi_scalar_mul() {
+    let g = Secp256k1Point::generator();
+    let scalars = [Secp256k1Scalar::from(2), Secp256k1Scalar::from(3)];
+    let points = [g, g.double()];
+    assert_eq!(
+        Secp256k1Point::multi_scalar_mul(&scalars, &points).unwrap(),
+        g * Secp256k1Scalar::from(8)
+    );
+    assert!(Secp256k1Point::multi_scalar_mul(&scalars, &points[..1]).is_err());
+}
+
+#[test]
+fn test_consistent_with_signing_keys() {
+    let kp = Secp256k1KeyPair::generate(&mut thread_rng());
+    let sk = Secp256k1Scalar::from_byte_array(&kp.as_ref().try_into().unwrap()).unwrap();
+    let pk = Secp256k1Point::generator() * sk;
+    assert_eq!(pk.to_byte_array().as_slice(), kp.public().as_bytes());
+    assert_eq!(
+        pk.to_uncompressed_coordinates().unwrap(),
+        kp.public().to_uncompressed_coordinates()
+    );
+    assert!(Secp256k1Point::zero()
+        .to_uncompressed_coordinates()
+        .is_none());
+}
+
+#[test]
+fn test_hash_to_curve() {
+    // Test vectors from appendix J.8.1 of RFC 9380.
+    const DST: &[u8] = b"QUUX-V01-CS02-with-secp256k1_XMD:SHA-256_SSWU_RO_";
+    let vectors: [(&[u8], &str); 3] = [
+        (
+            b"",
+            "c1cae290e291aee617ebaef1be6d73861479c48b841eaba9b7b5852ddfeb134664fa678e07ae116126f08b022a94af6de15985c996c3a91b64c406a960e51067",
+        ),
+        (
+            b"abc",
+            "3377e01eab42db296b512293120c6cee72b6ecf9f9205760bd9ff11fb3cb2c4b7f95890f33efebd1044d382a01b1bee0900fb6116f94688d487c6c7b9c8371f6",
+        ),
+        (
+            b"abcdef0123456789",
+            "bac54083f293f1fe08e4a70137260aa90783a5cb84d3f35848b324d0674b0e3a4436476085d4c3c4508b60fcf4389c40176adce756b398bdee27bca19758d828",
+        ),
+    ];
+    for (msg, expected) in vectors {
+        let p = Secp256k1Point::hash_to_group_element_with_dst(msg, DST);
+        assert_eq!(
+            hex::encode(p.to_uncompressed_coordinates().unwrap()),
+            expected
+        );
+    }
+
+    assert_ne!(
+        Secp256k1Point::hash_to_group_element(b"abc"),
+        Secp256k1Point::hash_to_group_element_with_dst(b"abc", DST)
+    );
+}
@@ -0,0 +1,199 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::hash::{HashFunction, Keccak256, Sha256};
+use crate::secp256k1::evm::{eip191_digest, to_evm_address, Eip712Domain};
+use crate::secp256k1::{
+    Secp256k1KeyPair, Secp256k1PrivateKey, Secp256k1PublicKey, Secp256k1RecoverableSignature,
+    Secp256k1RecoverableSignatureAsBytes,
+};
+use crate::test_helpers::{keys, verify_serialization};
+use crate::traits::{
+    EncodeDecodeBase64, KeyPair, RecoverableSignature, RecoverableSigner, Signer, ToFromBytes,
+    VerifyRecoverable, VerifyingKey,
+};
+use rand::thread_rng;
+use static_assertions::assert_impl_all;
+use zeroize::Zeroize;
+
+assert_impl_all!(Secp256k1PrivateKey: Zeroize);
+
+fn keypair_from_hex(secret: &str) -> Secp256k1KeyPair {
+    Secp256k1KeyPair::from_bytes(&hex::decode(secret).unwrap()).unwrap()
+}
+
+fn hex32(value: &str) -> [u8; 32] {
+    hex::decode(value).unwrap().try_into().unwrap()
+}
+
+#[test]
+fn test_evm_address() {
+    let kp = keypair_from_hex("0000000000000000000000000000000000000000000000000000000000000001");
+    assert_eq!(
+        hex::encode(to_evm_address(kp.public())),
+        "7e5f4552091a69125d5dfcb7b8c2659029395bdf"
+    );
+}
+
+#[test]
+fn test_eip191_digest() {
+    // hashMessage("hello world") in ethers.js
+    assert_eq!(
+        hex::encode(eip191_digest(b"hello world")),
+        "d9eba16ed0ecae432b71fe008c98cc872bb4cc214d3220a36f365326cf807d68"
+    );
+}
+
+#[test]
+fn test_eip712_example() {
+    // The "Ether Mail" example of EIP-712, signed by the key keccak256("cow").
+    let domain = Eip712Domain {
+        name: "Ether Mail".to_string(),
+        version: "1".to_string(),
+        chain_id: 1,
+        verifying_contract: [0xcc; 20],
+    };
+    assert_eq!(
+        hex::encode(domain.separator()),
+        "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f"
+    );
+    let struct_hash = hex32("c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e");
+    let digest = domain.digest(&struct_hash);
+    assert_eq!(
+        hex::encode(digest),
+        "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+    );
+
+    let kp = Secp256k1KeyPair::from_bytes(&Keccak256::digest(b"cow").digest).unwrap();
+    assert_eq!(
+        hex::encode(to_evm_address(kp.public())),
+        "cd2a3d9f938e13cd947ec05abc7fe734df8dd826"
+    );
+
+    let signature = kp.sign_prehashed(&digest);
+    let (r, s, v) = signature.to_rsv();
+    assert_eq!(v, 28);
+    assert_eq!(
+        hex::encode(r),
+        "4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d"
+    );
+    assert_eq!(
+        hex::encode(s),
+        "07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b91562"
+    );
+    assert_eq!(
+        Secp256k1RecoverableSignature::from_rsv(&r, &s, v).unwrap(),
+        signature
+    );
+    assert_eq!(&signature.recover_prehashed(&digest).unwrap(), kp.public());
+}
+
+#[test]
+fn test_rsv() {
+    let kp = Secp256k1KeyPair::generate(&mut thread_rng());
+    let signature = kp.sign(b"Hello, world!");
+    let (r, s, v) = signature.to_rsv();
+    assert!(v == 27 || v == 28);
+    assert_eq!(
+        Secp256k1RecoverableSignature::from_rsv(&r, &s, v).unwrap(),
+        signature
+    );
+    assert!(Secp256k1RecoverableSignature::from_rsv(&r, &s, v - 27).is_err());
+    assert!(Secp256k1RecoverableSignature::from_rsv(&r, &s, 29).is_err());
+}
+
+#[test]
+fn test_sign_and_recover() {
+    let kp = Secp256k1KeyPair::generate(&mut thread_rng());
+    let message: &[u8] = b"Hello, world!";
+    let signature = kp.sign_recoverable(message);
+
+    assert_eq!(&signature.recover(message).unwrap(), kp.public());
+    assert!(kp.public().verify_recoverable(message, &signature).is_ok());
+    assert!(kp.public().verify(message, &signature).is_ok());
+    assert!(kp.public().verify(b"other", &signature).is_err());
+
+    // The default hash is Keccak-256, as used by ecrecover.
+    assert_eq!(
+        signature,
+        kp.sign_prehashed(&Keccak256::digest(message).digest)
+    );
+    assert_eq!(kp.sign(message), signature);
+
+    let other_hash = kp.sign_recoverable_with_hash::<Sha256>(message);
+    assert!(kp
+        .public()
+        .verify_recoverable_with_hash::<Sha256>(message, &other_hash)
+        .is_ok());
+    assert!(kp.public().verify(message, &other_hash).is_err());
+
+    let other = Secp256k1KeyPair::generate(&mut thread_rng());
+    assert!(other.public().verify(message, &signature).is_err());
+}
+
+#[test]
+fn test_serialization() {
+    let kp = keys::<Secp256k1KeyPair>(1).pop().unwrap();
+    let signature = kp.sign(b"Hello, world!");
+    verify_serialization(kp.public(), Some(kp.public().as_ref()));
+    verify_serialization(&signature, Some(signature.as_ref()));
+
+    let private = kp.private();
+    let bytes = bincode::serialize(&private).unwrap();
+    assert_eq!(bytes, private.as_ref());
+    let restored: Secp256k1PrivateKey = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(restored.as_ref(), private.as_ref());
+
+    let as_bytes = Secp256k1RecoverableSignatureAsBytes::from(&signature);
+    assert_eq!(
+        Secp256k1RecoverableSignature::try_from(&as_bytes).unwrap(),
+        signature
+    );
+}
+
+#[test]
+fn test_bytes_conversion() {
+    let kp = Secp256k1KeyPair::generate(&mut thread_rng());
+    let pk = Secp256k1PublicKey::from_bytes(kp.public().as_ref()).unwrap();
+    assert_eq!(&pk, kp.public());
+    assert_eq!(
+        Secp256k1PublicKey::decode_base64(&kp.public().encode_base64()).unwrap(),
+        pk
+    );
+    assert!(Secp256k1PublicKey::from_bytes(&[0u8; 33]).is_err());
+    assert!(Secp256k1PublicKey::from_bytes(&kp.public().to_uncompressed_coordinates()).is_err());
+    assert!(Secp256k1PrivateKey::from_bytes(&[0u8; 32]).is_err());
+    assert!(Secp256k1PrivateKey::from_bytes(&[1u8; 31]).is_err());
+
+    let mut signature_bytes = kp.sign(b"Hello, world!").as_ref().to_vec();
+    assert!(Secp256k1RecoverableSignature::from_bytes(&signature_bytes[..64]).is_err());
+    signature_bytes[64] = 2;
+    assert!(Secp256k1RecoverableSignature::from_bytes(&signature_bytes).is_err());
+
+    let restored: Secp256k1KeyPair = kp.encode_base64().parse().unwrap();
+    assert_eq!(restored.public(), kp.public());
+    assert!("not a key".parse::<Secp256k1KeyPair>().is_err());
+}
+
+#[test]
+fn test_private_key_is_silent() {
+    let kp = Secp256k1KeyPair::generate(&mut thread_rng());
+    let secret = hex::encode(&kp);
+    assert!(!format!("{:?}", kp).contains(&secret));
+    let private = kp.private();
+    assert!(!format!("{:?} {}", private, private).contains(&secret));
+}
+
+#[test]
+fn test_compatible_with_k256() {
+    use k256::ecdsa::recoverable;
+
+    let kp = Secp256k1KeyPair::generate(&mut thread_rng());
+    let message: &[u8] = b"Hello, world!";
+    let signature = kp.sign(message);
+
+    // k256 also recovers from the Keccak-256 hash of the message.
+    let k256_signature = recoverable::Signature::try_from(signature.as_ref()).unwrap();
+    let recovered = k256_signature.recover_verify_key(message).unwrap();
+    assert_eq!(&recovered.to_bytes()[..], kp.public().as_ref());
+}
@@ -5,7 +5,6 @@ use crate::{
     error::MangekyouError,
     hash::HashFunction,
 };
-#[cfg(any(test, feature = "experimental"))]
 use eyre::eyre;
 use rand::rngs::{StdRng, ThreadRng};
 use rand::{CryptoRng, RngCore};
@@ -109,7 +108,6 @@ pub trait VerifyingKey:
     /// let signatures = [signature1.clone(), signature2.clone()];
     /// assert!(Ed25519PublicKey::verify_batch_empty_fail(message, &public_keys, &signatures).is_ok());
     /// ```
-    #[cfg(any(test, feature = "experimental"))]
     fn verify_batch_empty_fail(msg: &[u8], pks: &[Self], sigs: &[Self::Sig]) -> Result<(), eyre::Report> {
         if sigs.is_empty() {
             return Err(eyre!("Critical Error! This behaviour can signal something dangerous, and that someone may be trying to bypass signature verification through providing empty batches."));
@@ -143,7 +141,6 @@ pub trait VerifyingKey:
     /// let signatures = [signature1.clone(), signature2.clone()];
     /// assert!(Ed25519PublicKey::verify_batch_empty_fail_different_msg(&messages, &public_keys, &signatures).is_ok());
     /// ```
-    #[cfg(any(test, feature = "experimental"))]
     fn verify_batch_empty_fail_different_msg<'a, M>(msgs: &[M], pks: &[Self], sigs: &[Self::Sig]) -> Result<(), eyre::Report> where M: Borrow<[u8]> + 'a {
         if sigs.is_empty() {
             return Err(eyre!("Critical Error! This behaviour can signal something dangerous, and that someone may be trying to bypass signature verification through providing empty batches."));
@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: MIT
+
+pragma solidity ^0.8.22;
+
+error UnsupportedVrfMsgVersion(uint8 version);
+error UnexpectedVrfMsgType(uint8 msgType);
+
+/// @notice Cross-chain VRF messages exchanged with the kamui-layerzero program.
+/// A message is `abi.encodePacked(VERSION, msgType, abi.encode(payload))`.
+library VrfMsgCodec {
+    uint8 public constant VERSION = 1;
+    uint8 public constant VRF_REQUEST = 0;
+    uint8 public constant VRF_FULFILLMENT = 1;
+    uint8 public constant VRF_FAILURE = 2;
+
+    /// @notice The oracle did not fulfill the request before its deadline
+    uint8 public constant FAILURE_EXPIRED = 0;
+
+    struct VrfRequest {
+        bytes32 requester;
+        bytes32 seed;
+        uint32 numWords;
+        bytes callbackData;
+    }
+
+    /// @dev `beta` is only trusted once `proof` has been verified for `alpha`
+    /// under `publicKey`.
+    struct VrfFulfillment {
+        bytes32 requestId;
+        bytes alpha;
+        bytes proof;
+        bytes32 publicKey;
+        bytes beta;
+    }
+
+    /// @dev Sent instead of a fulfillment when a request will not be answered,
+    /// so the requester can release whatever was waiting on it.
+    struct VrfFailure {
+        bytes32 requestId;
+        uint8 reason;
+    }
+
+    function encodeRequest(VrfRequest memory _request) internal pure returns (bytes memory) {
+        return abi.encodePacked(VERSION, VRF_REQUEST, abi.encode(_request));
+    }
+
+    function encodeFulfillment(VrfFulfillment memory _fulfillment) internal pure returns (bytes memory) {
+        return abi.encodePacked(VERSION, VRF_FULFILLMENT, abi.encode(_fulfillment));
+    }
+
+    function encodeFailure(VrfFailure memory _failure) internal pure returns (bytes memory) {
+        return abi.encodePacked(VERSION, VRF_FAILURE, abi.encode(_failure));
+    }
+
+    /// @notice Returns the message type after checking the version.
+    function msgType(bytes calldata _msg) internal pure returns (uint8) {
+        if (uint8(_msg[0]) != VERSION) revert UnsupportedVrfMsgVersion(uint8(_msg[0]));
+        return uint8(_msg[1]);
+    }
+
+    function decodeRequest(bytes calldata _msg) internal pure returns (VrfRequest memory) {
+        if (msgType(_msg) != VRF_REQUEST) revert UnexpectedVrfMsgType(uint8(_msg[1]));
+        return abi.decode(_msg[2:], (VrfRequest));
+    }
+
+    function decodeFulfillment(bytes calldata _msg) internal pure returns (VrfFulfillment memory) {
+        if (msgType(_msg) != VRF_FULFILLMENT) revert UnexpectedVrfMsgType(uint8(_msg[1]));
+        return abi.decode(_msg[2:], (VrfFulfillment));
+    }
+
+    function decodeFailure(bytes calldata _msg) internal pure returns (VrfFailure memory) {
+        if (msgType(_msg) != VRF_FAILURE) revert UnexpectedVrfMsgType(uint8(_msg[1]));
+        return abi.decode(_msg[2:], (VrfFailure));
+    }
+}
@@ -1,10 +1,11 @@
 mod errors;
 mod instructions;
-mod msg_codec;
 mod state;
 
 use anchor_lang::prelude::*;
 use instructions::*;
+// The string codec is shared with the other OApps
+use kamui_lz_core::codec as msg_codec;
 use oapp::{endpoint::MessagingFee, endpoint_cpi::LzAccount, LzReceiveParams};
 use solana_helper::program_id_from_env;
 use state::*;
@@ -18,9 +19,8 @@ declare_id!(anchor_lang::solana_program::pubkey::Pubkey::new_from_array(program_
     "41NCdrEvXhQ4mZgyJkmqYxL6A1uEmnraGj31UJ6PsXd3" // It's not necessary to change the ID here if you are building using environment variable
 )));
 
-const LZ_RECEIVE_TYPES_SEED: &[u8] = b"LzReceiveTypes"; // The Executor relies on this exact seed to derive the LzReceiveTypes PDA. Keep it the same.
-const STORE_SEED: &[u8] = b"Store"; // You are free to edit this seed.
-const PEER_SEED: &[u8] = b"Peer"; // The Executor relies on this exact seed to derive the LzReceiveTypes PDA. Keep it the same.
+// The Executor relies on these seeds, see kamui-lz-core
+use kamui_lz_core::constants::{LZ_RECEIVE_TYPES_SEED, PEER_SEED, STORE_SEED};
 
 #[program]
 pub mod my_oapp {
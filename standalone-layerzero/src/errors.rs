@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use kamui_lz_core::validation::ValidationError;
 
 #[error_code]
 pub enum LayerZeroError {
@@ -110,4 +111,22 @@ pub enum LayerZeroError {
 
     #[msg("Too many pending VRF requests")]
     TooManyPendingRequests,
-} 
\ No newline at end of file
+
+    #[msg("Invalid VRF proof")]
+    InvalidVrfProof,
+}
+
+impl From<ValidationError> for LayerZeroError {
+    fn from(error: ValidationError) -> Self {
+        match error {
+            ValidationError::UnknownEndpoint => LayerZeroError::InvalidEndpointId,
+            ValidationError::MessageTooLarge => LayerZeroError::MessageTooLarge,
+            ValidationError::InvalidPeerAddress => LayerZeroError::InvalidPeerAddress,
+            ValidationError::InvalidSender => LayerZeroError::InvalidSender,
+            ValidationError::InvalidVrfParams => LayerZeroError::InvalidVrfParams,
+            ValidationError::InvalidCallbackDataSize => LayerZeroError::InvalidCallbackDataSize,
+            ValidationError::UnknownOracle => LayerZeroError::UnauthorizedOracle,
+            ValidationError::InvalidVrfProof => LayerZeroError::InvalidVrfProof,
+        }
+    }
+}
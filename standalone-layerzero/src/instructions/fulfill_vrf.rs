@@ -3,6 +3,7 @@ use crate::state::*;
 use crate::constants::{STORE_SEED, PEER_SEED};
 use crate::errors::LayerZeroError;
 use crate::msg_codec::MessageCodec;
+use kamui_lz_core::handler;
 use oapp::{endpoint_cpi::{self}, endpoint::{ID as ENDPOINT_ID, instructions::SendParams}};
 
 #[derive(Accounts)]
@@ -36,25 +37,28 @@ pub fn handler(ctx: Context<FulfillVrf>, params: FulfillVrfParams) -> Result<()>
     }
 
     // Find the request to fulfill
-    let mut request_found = false;
-    for request in &mut ctx.accounts.store.vrf_data.pending_requests {
-        if request.request_id == params.request_id && !request.fulfilled {
-            request.fulfilled = true;
-            request_found = true;
-            break;
-        }
-    }
-
-    if !request_found {
-        return Err(LayerZeroError::RequestNotFound.into());
+    let vrf_data = &mut ctx.accounts.store.vrf_data;
+    let request = vrf_data
+        .pending_requests
+        .iter_mut()
+        .find(|request| request.request_id == params.request_id && !request.fulfilled)
+        .ok_or(LayerZeroError::RequestNotFound)?;
+    // The fulfillment goes back to the chain the request came from
+    if request.eid != params.dst_eid {
+        return Err(LayerZeroError::InvalidDestinationChain.into());
     }
 
-    // Create VRF fulfillment payload - EVM compatible format
-    let fulfillment_payload = VrfFulfillmentPayload {
-        msg_type: MessageType::VrfFulfillment,
-        request_id: params.request_id,
-        randomness: params.randomness, // Fixed 64 bytes for EVM compatibility
-    };
+    // Only a valid proof for this request's input is relayed. The fulfillment
+    // carries it, so the requesting chain can verify it again.
+    let fulfillment_payload = handler::vrf_fulfillment(
+        vrf_data.oracle_vrf_key,
+        request.request_id,
+        &request.seed,
+        params.proof,
+    )
+    .map_err(LayerZeroError::from)?;
+    request.fulfilled = true;
+    request.randomness = Some(fulfillment_payload.beta);
 
     // Encode the fulfillment message
     let message = MessageCodec::encode_vrf_fulfillment(&fulfillment_payload)?;
@@ -81,7 +85,7 @@ pub fn handler(ctx: Context<FulfillVrf>, params: FulfillVrfParams) -> Result<()>
 
     msg!("VRF fulfillment sent to chain {}", params.dst_eid);
     msg!("Request ID: {:?}", params.request_id);
-    msg!("Randomness: {:?}", params.randomness);
+    msg!("Randomness: {:?}", fulfillment_payload.beta);
 
     Ok(())
 } 
\ No newline at end of file
@@ -1,8 +1,10 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
+use crate::constants::{MAX_CALLBACK_DATA_SIZE, MAX_VRF_REQUESTS};
 use crate::errors::LayerZeroError;
-use crate::msg_codec::{self, MessageCodec};
 use crate::{PEER_SEED, STORE_SEED};
+use kamui_lz_core::handler::{self, InboundMessage};
+use kamui_lz_core::validation;
 use oapp::{
     endpoint::{
         cpi::accounts::Clear,
@@ -58,90 +60,79 @@ pub fn handler(ctx: Context<LzReceive>, params: LzReceiveParams) -> Result<()> {
 
     // Process the message based on its type
     let store = &mut ctx.accounts.store;
-    
-    // Try to decode the message type first
-    match MessageCodec::decode_message_type(&params.message) {
-        Ok(MessageType::VrfRequest) => {
-            // Handle VRF request message - EVM compatible format
-            match MessageCodec::decode_vrf_request(&params.message) {
-                Ok(vrf_request) => {
-                    // Generate a unique request ID based on the GUID
-                    let request_id = params.guid;
-                    
-                    // Create a VRF request record - EVM compatible format
-                    let vrf_req = VrfRequest {
-                        request_id,
-                        requester: Pubkey::try_from(vrf_request.requester).map_err(|_| LayerZeroError::InvalidRequester)?,
-                        seed: vrf_request.seed,
-                        num_words: vrf_request.num_words,        // Now u32 for EVM compatibility
-                        pool_id: vrf_request.pool_id,            // New field for EVM compatibility
-                        callback_data: vrf_request.callback_data, // Fixed 32 bytes for EVM compatibility
-                        timestamp: Clock::get()?.unix_timestamp,
-                        fulfilled: false,
-                    };
-                    
-                    // Add to pending requests (limit check)
-                    if store.vrf_data.pending_requests.len() < 100 {
-                        store.vrf_data.pending_requests.push(vrf_req);
-                    } else {
-                        return Err(LayerZeroError::TooManyPendingRequests.into());
-                    }
-                    
-                    msg!("VRF request received and stored (EVM compatible)");
-                    msg!("Request ID: {:?}", request_id);
-                    msg!("Requester: {:?}", vrf_request.requester);
-                    msg!("Seed: {:?}", vrf_request.seed);
-                    msg!("Num words: {}", vrf_request.num_words);
-                    msg!("Pool ID: {}", vrf_request.pool_id);
-                    msg!("Callback data: {:?}", vrf_request.callback_data);
-                }
-                Err(e) => {
-                    msg!("Failed to decode VRF request: {:?}", e);
-                    return Err(LayerZeroError::InvalidMessageFormat.into());
-                }
+    match InboundMessage::decode(&params.message)? {
+        InboundMessage::VrfRequest(vrf_request) => {
+            validation::validate_vrf_request(
+                vrf_request.num_words,
+                &vrf_request.callback_data,
+                MAX_CALLBACK_DATA_SIZE,
+            )
+            .map_err(LayerZeroError::from)?;
+            if store.vrf_data.pending_requests.len() >= MAX_VRF_REQUESTS {
+                return Err(LayerZeroError::TooManyPendingRequests.into());
             }
+
+            // The GUID of the message identifies the request in the fulfillment
+            let request_id = params.guid;
+            store.vrf_data.pending_requests.push(VrfRequest {
+                request_id,
+                eid: params.src_eid,
+                requester: Pubkey::new_from_array(vrf_request.requester),
+                seed: vrf_request.seed,
+                num_words: vrf_request.num_words,
+                callback_data: vrf_request.callback_data.clone(),
+                timestamp: Clock::get()?.unix_timestamp,
+                fulfilled: false,
+                randomness: None,
+            });
+
+            msg!("VRF request received and stored (EVM compatible)");
+            msg!("Request ID: {:?}", request_id);
+            msg!("Requester: {:?}", vrf_request.requester);
+            msg!("Seed: {:?}", vrf_request.seed);
+            msg!("Num words: {}", vrf_request.num_words);
+            msg!("Callback data: {:?}", vrf_request.callback_data);
         }
-        Ok(MessageType::VrfFulfillment) => {
-            // Handle VRF fulfillment message - EVM compatible format
-            match MessageCodec::decode_vrf_fulfillment(&params.message) {
-                Ok(vrf_fulfillment) => {
-                    // Find the corresponding request and mark it as fulfilled
-                    let mut request_found = false;
-                    for req in &mut store.vrf_data.pending_requests {
-                        if req.request_id == vrf_fulfillment.request_id && !req.fulfilled {
-                            req.fulfilled = true;
-                            request_found = true;
-                            break;
-                        }
-                    }
-                    
-                    if !request_found {
-                        msg!("Warning: VRF fulfillment received for unknown request ID");
-                    }
-                    
-                    msg!("VRF fulfillment received and processed (EVM compatible)");
-                    msg!("Request ID: {:?}", vrf_fulfillment.request_id);
-                    msg!("Randomness: 64 bytes (EVM compatible format)");
-                    msg!("Randomness data: {:?}", vrf_fulfillment.randomness);
-                }
-                Err(e) => {
-                    msg!("Failed to decode VRF fulfillment: {:?}", e);
-                    return Err(LayerZeroError::InvalidMessageFormat.into());
-                }
-            }
+        InboundMessage::VrfFulfillment(vrf_fulfillment) => {
+            // Fulfillments answer requests sent from here, and are only
+            // accepted with a valid proof for the request's input
+            let oracle_vrf_key = store.vrf_data.oracle_vrf_key;
+            let request = outbound_request(
+                &mut store.vrf_data.outbound_requests,
+                &vrf_fulfillment.request_id,
+                params.src_eid,
+            )?;
+            let beta = handler::verify_vrf_fulfillment(oracle_vrf_key, &request.seed, &vrf_fulfillment)
+                .map_err(LayerZeroError::from)?;
+            request.fulfilled = true;
+            request.randomness = Some(beta);
+
+            msg!("VRF fulfillment received and verified (EVM compatible)");
+            msg!("Request ID: {:?}", vrf_fulfillment.request_id);
+            msg!("Randomness data: {:?}", beta);
+        }
+        InboundMessage::VrfFailure(vrf_failure) => {
+            // The remote chain gave up on a request it received from us
+            outbound_request(
+                &mut store.vrf_data.outbound_requests,
+                &vrf_failure.request_id,
+                params.src_eid,
+            )?;
+            store
+                .vrf_data
+                .outbound_requests
+                .retain(|req| req.request_id != vrf_failure.request_id);
+
+            msg!("VRF failure received");
+            msg!("Request ID: {:?}", vrf_failure.request_id);
+            msg!("Reason: {:?}", vrf_failure.reason);
         }
-        Ok(MessageType::Generic) | Err(_) => {
-            // Handle generic string message (fallback to original logic)
-            match msg_codec::decode(&params.message) {
-                Ok(string_value) => {
-                    store.string = string_value;
-                    msg!("Generic string message received: {}", store.string);
-                }
-                Err(e) => {
-                    msg!("Failed to decode generic message: {:?}", e);
-                    return Err(LayerZeroError::InvalidMessageFormat.into());
-                }
+        InboundMessage::Generic(string_value) => {
+            if string_value.len() > Store::MAX_STRING_LENGTH {
+                return Err(LayerZeroError::MessageTooLarge.into());
             }
+            store.string = string_value;
+            msg!("Generic string message received: {}", store.string);
         }
     }
 
@@ -151,4 +142,23 @@ pub fn handler(ctx: Context<LzReceive>, params: LzReceiveParams) -> Result<()> {
     msg!("Message type determined and processed");
     
     Ok(())
-} 
\ No newline at end of file
+}
+
+/// The unfulfilled request `request_id` sent to `src_eid`
+fn outbound_request<'a>(
+    requests: &'a mut [VrfRequest],
+    request_id: &[u8; 32],
+    src_eid: u32,
+) -> Result<&'a mut VrfRequest> {
+    let request = requests
+        .iter_mut()
+        .find(|request| &request.request_id == request_id)
+        .ok_or(LayerZeroError::RequestNotFound)?;
+    if request.eid != src_eid {
+        return Err(LayerZeroError::InvalidSourceChain.into());
+    }
+    if request.fulfilled {
+        return Err(LayerZeroError::RequestAlreadyFulfilled.into());
+    }
+    Ok(request)
+}
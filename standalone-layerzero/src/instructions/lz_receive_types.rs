@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
-use crate::{PEER_SEED, STORE_SEED};
+use crate::STORE_SEED;
+use kamui_lz_core::pda;
 use oapp::endpoint_cpi::{get_accounts_for_clear, LzAccount};
 use oapp::{endpoint::ID as ENDPOINT_ID, LzReceiveParams};
 
@@ -24,8 +25,7 @@ pub fn handler(ctx: Context<LzReceiveTypes>, params: LzReceiveParams) -> Result<
     let store = ctx.accounts.store.key();
 
     // 2. The peer PDA for the remote chain needs to be retrieved, for later verification of the `params.sender`.
-    let peer_seeds = [PEER_SEED, &store.to_bytes(), &params.src_eid.to_be_bytes()];
-    let (peer, _) = Pubkey::find_program_address(&peer_seeds, ctx.program_id);
+    let (peer, _) = pda::peer(ctx.program_id, &store, params.src_eid);
 
     // Accounts used directly by `lz_receive`
     let mut accounts = vec![
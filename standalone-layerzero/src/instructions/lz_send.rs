@@ -2,6 +2,7 @@ use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::constants::{STORE_SEED, PEER_SEED};
 use crate::errors::LayerZeroError;
+use kamui_lz_core::validation;
 use oapp::{endpoint_cpi::{self}, endpoint::{ID as ENDPOINT_ID, instructions::SendParams}};
 
 #[derive(Accounts)]
@@ -28,11 +29,8 @@ pub struct LzSend<'info> {
 }
 
 pub fn handler(ctx: Context<LzSend>, params: LzSendParams) -> Result<()> {
-    // TODO: Add endpoint validation if needed
-    // utils::validate_endpoint_id(params.dst_eid)?;
-    
-    // TODO: Add message size validation if needed
-    // utils::validate_message_size(&params.message)?;
+    validation::validate_endpoint_id(params.dst_eid).map_err(LayerZeroError::from)?;
+    validation::validate_message_size(&params.message).map_err(LayerZeroError::from)?;
     
     // Get message size before moving
     let message_size = params.message.len();
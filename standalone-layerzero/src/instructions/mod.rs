@@ -9,6 +9,7 @@ pub mod quote_send;
 pub mod lz_send;
 pub mod request_vrf;
 pub mod fulfill_vrf;
+pub mod set_oracle;
 
 // Re-export all instruction types and handlers
 pub use init_store::*;
@@ -20,4 +21,5 @@ pub use send::*;
 pub use quote_send::*;
 pub use lz_send::*;
 pub use request_vrf::*;
-pub use fulfill_vrf::*; 
\ No newline at end of file
+pub use fulfill_vrf::*;
+pub use set_oracle::*;
\ No newline at end of file
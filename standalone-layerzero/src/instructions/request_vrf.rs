@@ -1,8 +1,9 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
-use crate::constants::{STORE_SEED, PEER_SEED, MAX_CALLBACK_DATA_SIZE};
+use crate::constants::{STORE_SEED, PEER_SEED, MAX_CALLBACK_DATA_SIZE, MAX_VRF_REQUESTS};
 use crate::errors::LayerZeroError;
 use crate::msg_codec::MessageCodec;
+use kamui_lz_core::{handler, validation};
 use oapp::{endpoint_cpi::{self}, endpoint::{ID as ENDPOINT_ID, instructions::SendParams}};
 
 #[derive(Accounts)]
@@ -29,22 +30,21 @@ pub struct RequestVrf<'info> {
 
 pub fn handler(ctx: Context<RequestVrf>, params: RequestVrfParams) -> Result<()> {
     // Validate parameters
-    // TODO: Add endpoint validation if needed
-    // utils::validate_endpoint_id(params.dst_eid)?;
+    validation::validate_endpoint_id(params.dst_eid).map_err(LayerZeroError::from)?;
     
-    if params.num_words == 0 || params.num_words > 10 {
-        return Err(LayerZeroError::InvalidVrfParams.into());
+    if ctx.accounts.store.vrf_data.outbound_requests.len() >= MAX_VRF_REQUESTS {
+        return Err(LayerZeroError::TooManyPendingRequests.into());
     }
     
     // Create VRF request payload - EVM compatible format
-    let vrf_request = VrfRequestPayload {
-        msg_type: MessageType::VrfRequest,
-        requester: ctx.accounts.requester.key().to_bytes(),
-        seed: params.seed,
-        callback_data: params.callback_data, // Already 32 bytes for EVM compatibility
-        num_words: params.num_words,         // Now u32 for EVM compatibility
-        pool_id: params.pool_id,             // New field for EVM compatibility
-    };
+    let vrf_request = handler::vrf_request(
+        ctx.accounts.requester.key().to_bytes(),
+        params.seed,
+        params.num_words,
+        params.callback_data.to_vec(),
+        MAX_CALLBACK_DATA_SIZE,
+    )
+    .map_err(LayerZeroError::from)?;
     
     // Encode the message
     let message = MessageCodec::encode_vrf_request(&vrf_request)?;
@@ -62,7 +62,7 @@ pub fn handler(ctx: Context<RequestVrf>, params: RequestVrfParams) -> Result<()>
     // Send the VRF request via LayerZero
     let seeds = &[STORE_SEED, &[ctx.accounts.store.bump]];
     
-    endpoint_cpi::send(
+    let receipt = endpoint_cpi::send(
         ENDPOINT_ID,
         ctx.accounts.store.key(),
         ctx.remaining_accounts,
@@ -70,10 +70,23 @@ pub fn handler(ctx: Context<RequestVrf>, params: RequestVrfParams) -> Result<()>
         send_params,
     )?;
     
+    // Track the request under its GUID so the fulfillment can be verified
+    ctx.accounts.store.vrf_data.outbound_requests.push(VrfRequest {
+        request_id: receipt.guid,
+        eid: params.dst_eid,
+        requester: ctx.accounts.requester.key(),
+        seed: params.seed,
+        num_words: params.num_words,
+        callback_data: params.callback_data.to_vec(),
+        timestamp: Clock::get()?.unix_timestamp,
+        fulfilled: false,
+        randomness: None,
+    });
+    
     msg!("VRF request sent to endpoint: {}", params.dst_eid);
+    msg!("GUID: {:?}", receipt.guid);
     msg!("Seed: {:?}", params.seed);
     msg!("Num words: {}", params.num_words);
-    msg!("Pool ID: {}", params.pool_id);
     msg!("Callback data: {:?}", params.callback_data);
     msg!("Fee: {}", params.fee);
     
@@ -1,6 +1,8 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
+use crate::errors::LayerZeroError;
 use crate::{PEER_SEED, STORE_SEED};
+use kamui_lz_core::validation;
 
 #[derive(Accounts)]
 #[instruction(params: SetPeerConfigParams)]
@@ -28,6 +30,7 @@ impl<'info> SetPeerConfig<'info> {
         // Update or create the peer config PDA
         match params.config.clone() {
             PeerConfigParam::PeerAddress(peer_address) => {
+                validation::validate_peer_address(&peer_address).map_err(LayerZeroError::from)?;
                 ctx.accounts.peer.peer_address = peer_address;
             },
             PeerConfigParam::EnforcedOptions { send, send_and_call } => {
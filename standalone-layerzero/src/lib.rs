@@ -11,16 +11,17 @@ declare_id!(anchor_lang::solana_program::pubkey::Pubkey::new_from_array(program_
     "E8ka62cKB63dqbC3CLNReWXRVF4rHJy3qvaXcBimJQSU" // Default ID if environment variable not set
 )));
 
-// LayerZero OApp standard seeds - required for LayerZero compatibility
-const LZ_RECEIVE_TYPES_SEED: &[u8] = b"LzReceiveTypes"; // The Executor relies on this exact seed to derive the LzReceiveTypes PDA. Keep it the same.
-const STORE_SEED: &[u8] = b"Store"; // You are free to edit this seed.
-const PEER_SEED: &[u8] = b"Peer"; // The Executor relies on this exact seed to derive the Peer PDA. Keep it the same.
+// LayerZero OApp standard seeds - required for LayerZero compatibility, shared
+// with the other OApps through kamui-lz-core
+use kamui_lz_core::constants::{LZ_RECEIVE_TYPES_SEED, PEER_SEED, STORE_SEED};
 
 pub mod state;
 pub mod errors;
 pub mod instructions;
 pub mod constants;
-pub mod msg_codec;
+
+// The wire format is shared with the other OApps
+pub use kamui_lz_core::codec as msg_codec;
 
 // Import all instruction structs and parameter types
 use instructions::*;
@@ -109,4 +110,9 @@ pub mod kamui_layerzero {
     ) -> Result<()> {
         instructions::fulfill_vrf::handler(ctx, params)
     }
+
+    /// Set the oracle allowed to fulfill requests and its ECVRF public key
+    pub fn set_oracle(ctx: Context<SetOracle>, params: SetOracleParams) -> Result<()> {
+        instructions::set_oracle::handler(ctx, params)
+    }
 } 
\ No newline at end of file
@@ -1,19 +1,25 @@
 use anchor_lang::prelude::*;
+use crate::constants::{MAX_CALLBACK_DATA_SIZE, MAX_VRF_REQUESTS};
+
+pub const STORE_MAX_STRING_LENGTH: usize = 256;
 
 /// The main OApp Store PDA that acts as the OApp address
 #[account]
+#[derive(InitSpace)]
 pub struct Store {
     pub admin: Pubkey, // This is required and should be consistent.
     pub bump: u8, // This is required and should be consistent.
     pub endpoint_program: Pubkey, // This is required and should be consistent.
+    #[max_len(STORE_MAX_STRING_LENGTH)]
     pub string: String, // This is specific to this string-passing example.
     pub vrf_data: VrfData, // VRF-specific data for the OApp
     // You can add more fields as needed for your OApp implementation.
 }
 
 impl Store {
-    pub const MAX_STRING_LENGTH: usize = 256;
-    pub const SIZE: usize = 8 + std::mem::size_of::<Self>() + Self::MAX_STRING_LENGTH;
+    pub const MAX_STRING_LENGTH: usize = STORE_MAX_STRING_LENGTH;
+    /// Space for the largest Store, with both request lists full
+    pub const SIZE: usize = 8 + Self::INIT_SPACE;
 }
 
 // The LzReceiveTypesAccounts PDA is used by the Executor as a prerequisite to calling `lz_receive`.
@@ -113,13 +119,11 @@ pub struct QuoteSendParams {
 
 // MessagingFee is now imported from oapp::endpoint::MessagingFee
 
-/// Message types for LayerZero messages
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
-pub enum MessageType {
-    VrfRequest,
-    VrfFulfillment,
-    Generic,
-}
+// Message types and payloads are part of the wire format shared with the other OApps
+pub use kamui_lz_core::types::{
+    vrf_alpha, MessageType, VrfFailurePayload, VrfFailureReason, VrfFulfillmentPayload,
+    VrfRequestPayload,
+};
 
 /// Parameters for VRF requests
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -127,37 +131,28 @@ pub struct RequestVrfParams {
     pub dst_eid: u32,
     pub seed: [u8; 32],
     pub num_words: u32,          // Changed from u8 to u32 for EVM compatibility
-    pub pool_id: u8,             // Added pool_id field for EVM compatibility
-    pub callback_data: [u8; 32], // Request ID (32 bytes, no length prefix)
+    pub callback_data: [u8; 32], // Request ID (32 bytes)
     pub fee: u64,
 }
 
-/// VRF request payload structure
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct VrfRequestPayload {
-    pub msg_type: MessageType,
-    pub requester: [u8; 32],     // EVM address padded to 32 bytes
-    pub seed: [u8; 32],          // 32 bytes seed
-    pub callback_data: [u8; 32], // Request ID (32 bytes, no length prefix)
-    pub num_words: u32,          // Changed from u8 to u32 for EVM compatibility
-    pub pool_id: u8,             // Added pool_id field for EVM compatibility
-}
-
 /// VRF fulfillment parameters
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct FulfillVrfParams {
     pub dst_eid: u32,
     pub request_id: [u8; 32],
-    pub randomness: [u8; 64],    // Fixed 64 bytes randomness for EVM compatibility
+    /// 80-byte ECVRF proof over the request's alpha, see [`vrf_alpha`]. It must
+    /// verify under the oracle VRF key set with `set_oracle`.
+    pub proof: Vec<u8>,
     pub fee: u64,
 }
 
-/// VRF fulfillment payload structure
+/// Parameters for setting the oracle
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct VrfFulfillmentPayload {
-    pub msg_type: MessageType,
-    pub request_id: [u8; 32],    // 32 bytes request ID
-    pub randomness: [u8; 64],    // Fixed 64 bytes randomness (EVM expects this format)
+pub struct SetOracleParams {
+    /// Signer allowed to call `fulfill_vrf`, or `None` to allow anyone
+    pub oracle: Option<Pubkey>,
+    /// ECVRF public key fulfillments must verify under
+    pub vrf_public_key: [u8; 32],
 }
 
 /// Parameters for lz_send instruction
@@ -170,21 +165,33 @@ pub struct LzSendParams {
 }
 
 /// VRF-specific data for the OApp
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
 pub struct VrfData {
     pub oracle_pubkey: Option<Pubkey>,
+    /// ECVRF public key of the oracle. Fulfillments are rejected until it is set.
+    pub oracle_vrf_key: Option<[u8; 32]>,
+    /// Requests received from peers, answered by `fulfill_vrf`
+    #[max_len(MAX_VRF_REQUESTS)]
     pub pending_requests: Vec<VrfRequest>,
+    /// Requests sent by `request_vrf`, answered by the peer's oracle
+    #[max_len(MAX_VRF_REQUESTS)]
+    pub outbound_requests: Vec<VrfRequest>,
 }
 
 /// VRF request structure
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
 pub struct VrfRequest {
+    /// GUID of the request message
     pub request_id: [u8; 32],
+    /// Endpoint the request came from, or was sent to
+    pub eid: u32,
     pub requester: Pubkey,
     pub seed: [u8; 32],
     pub num_words: u32,          // Changed from u8 to u32 for EVM compatibility
-    pub pool_id: u8,             // Added pool_id field for EVM compatibility
-    pub callback_data: [u8; 32], // Request ID (32 bytes, no length prefix)
+    #[max_len(MAX_CALLBACK_DATA_SIZE)]
+    pub callback_data: Vec<u8>,
     pub timestamp: i64,
     pub fulfilled: bool,
+    /// Output of the verified proof, once fulfilled
+    pub randomness: Option<[u8; 64]>,
 } 
\ No newline at end of file
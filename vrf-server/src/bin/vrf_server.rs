@@ -1,5 +1,9 @@
 use {
-    vrf_server::vrf_server::VRFServer,
+    vrf_server::{
+        signer::{read_keystore_password, save_keystore, LocalVrfSigner, RemoteVrfSigner, VrfSigner},
+        submitter::SubmitterConfig,
+        vrf_server::VRFServer,
+    },
     solana_sdk::{
         signature::{Keypair, read_keypair_file},
         signer::Signer,
@@ -10,10 +14,10 @@ use {
         VRFProof,
     },
     std::{
-        fs::{self, File},
-        io::{Read, Write},
+        fs,
         error::Error,
         path::Path,
+        sync::Arc,
     },
     tokio,
     clap::{Parser, Subcommand},
@@ -30,20 +34,26 @@ struct Cli {
 
 #[derive(Subcommand, Debug)]
 enum Commands {
-    /// Generate a new VRF keypair
+    /// Generate a new VRF keypair in an encrypted keystore
     Generate {
-        /// The output file for the keypair
+        /// The output file for the keystore
         #[clap(short, long, default_value = "vrf-keypair.json")]
         output: String,
+        /// File containing the keystore password (defaults to $VRF_KEYSTORE_PASSWORD)
+        #[clap(long)]
+        password_file: Option<String>,
     },
     /// Test a VRF keypair by generating a proof
     Test {
-        /// The input keypair file
+        /// The input keystore or keypair file
         #[clap(short, long)]
         keypair: String,
         /// The alpha value (seed) for proof generation
         #[clap(short, long)]
         alpha: String,
+        /// File containing the keystore password (defaults to $VRF_KEYSTORE_PASSWORD)
+        #[clap(long)]
+        password_file: Option<String>,
     },
     /// Run the VRF server
     Run {
@@ -53,15 +63,33 @@ enum Commands {
         /// Oracle keypair file
         #[clap(long)]
         keypair: String,
-        /// VRF keypair file
-        #[clap(long)]
-        vrf_keypair: String,
+        /// VRF keystore or keypair file
+        #[clap(long, required_unless_present = "vrf_signer_socket")]
+        vrf_keypair: Option<String>,
+        /// File containing the VRF keystore password (defaults to $VRF_KEYSTORE_PASSWORD)
+        #[clap(long, conflicts_with = "vrf_signer_socket")]
+        vrf_password_file: Option<String>,
+        /// Unix socket of a `vrf-signer` process holding the VRF key
+        #[clap(long, conflicts_with = "vrf_keypair")]
+        vrf_signer_socket: Option<String>,
         /// RPC URL
         #[clap(long, default_value = "https://api.devnet.solana.com")]
         rpc_url: String,
         /// WS URL
         #[clap(long)]
         ws_url: Option<String>,
+        /// Maximum number of fulfillment transactions in flight
+        #[clap(long, default_value_t = 8)]
+        max_in_flight: usize,
+        /// Maximum number of requests fulfilled per transaction
+        #[clap(long, default_value_t = 8)]
+        max_batch_size: usize,
+        /// Percentile of recent prioritization fees to pay
+        #[clap(long, default_value_t = 75)]
+        priority_fee_percentile: u8,
+        /// Maximum compute unit price in micro-lamports
+        #[clap(long, default_value_t = 100_000)]
+        max_compute_unit_price: u64,
     },
 }
 
@@ -95,48 +123,6 @@ fn load_or_create_oracle_keypair() -> Result<Keypair, Box<dyn Error>> {
     }
 }
 
-fn load_or_create_vrf_keypair(keypair_path: &str) -> Result<ECVRFKeyPair, Box<dyn Error>> {
-    let path = Path::new(keypair_path);
-    if path.exists() {
-        println!("Loading VRF keypair from {}", path.display());
-        let mut file = File::open(path)?;
-        let mut keypair_bytes = Vec::new();
-        file.read_to_end(&mut keypair_bytes)?;
-        match ECVRFKeyPair::from_bytes(&keypair_bytes) {
-            Ok(keypair) => {
-                println!("Successfully loaded VRF keypair");
-                Ok(keypair)
-            }
-            Err(e) => {
-                println!("Error loading VRF keypair: {}", e);
-                println!("Generating new VRF keypair...");
-                let keypair = ECVRFKeyPair::generate(&mut thread_rng());
-                
-                // Save the keypair
-                let mut keypair_bytes = keypair.sk.as_ref().to_vec();
-                keypair_bytes.extend_from_slice(keypair.pk.as_ref());
-                let mut file = File::create(path)?;
-                file.write_all(&keypair_bytes)?;
-                
-                println!("New VRF keypair generated and saved to {}", path.display());
-                Ok(keypair)
-            }
-        }
-    } else {
-        println!("VRF keypair not found at {}, generating new one", path.display());
-        let keypair = ECVRFKeyPair::generate(&mut thread_rng());
-        
-        // Save the keypair
-        let mut keypair_bytes = keypair.sk.as_ref().to_vec();
-        keypair_bytes.extend_from_slice(keypair.pk.as_ref());
-        let mut file = File::create(path)?;
-        file.write_all(&keypair_bytes)?;
-        
-        println!("VRF keypair generated and saved to {}", path.display());
-        Ok(keypair)
-    }
-}
-
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
     // Initialize the logger
@@ -146,38 +132,45 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Generate { output } => {
+        Commands::Generate { output, password_file } => {
+            let password = read_keystore_password(password_file.as_deref().map(Path::new))?;
+
             // Generate a new VRF keypair
             let mut rng = thread_rng();
             let keypair = ECVRFKeyPair::generate(&mut rng);
             
-            // Convert the keypair to bytes
-            let mut keypair_bytes = Vec::new();
-            keypair_bytes.extend_from_slice(keypair.pk.as_ref());
-            keypair_bytes.extend_from_slice(keypair.sk.as_ref());
-            
-            // Write the keypair to the output file
-            let output_path = output.clone(); // Clone to avoid move
-            let mut file = File::create(output_path)?;
-            file.write_all(&keypair_bytes)?;
+            // Write the keypair to an encrypted keystore
+            save_keystore(Path::new(&output), &keypair, &password)?;
             println!("Generated VRF keypair and saved to {}", output);
+            println!("VRF public key: {}", hex::encode(keypair.pk.as_ref()));
             Ok(())
         }
-        Commands::Test { keypair, alpha } => {
+        Commands::Test { keypair, alpha, password_file } => {
             // Load the keypair
-            let keypair_bytes = fs::read(&keypair)?;
-            let keypair = ECVRFKeyPair::from_bytes(&keypair_bytes)?;
+            let signer = LocalVrfSigner::load(Path::new(&keypair), password_file.as_deref().map(Path::new))?;
             
             // Generate a proof
             let alpha_bytes = alpha.as_bytes();
-            let proof = keypair.prove(alpha_bytes);
+            let proof = signer.prove(alpha_bytes)?;
             let hash = proof.to_hash();
             
             println!("Alpha: {}", alpha);
             println!("Generated proof hash: {:?}", hex::encode(&hash));
             Ok(())
         }
-        Commands::Run { program_id, keypair, vrf_keypair, rpc_url, ws_url } => {
+        Commands::Run {
+            program_id,
+            keypair,
+            vrf_keypair,
+            vrf_password_file,
+            vrf_signer_socket,
+            rpc_url,
+            ws_url,
+            max_in_flight,
+            max_batch_size,
+            priority_fee_percentile,
+            max_compute_unit_price,
+        } => {
             // Add panic catching mechanism
             std::panic::set_hook(Box::new(|panic_info| {
                 eprintln!("VRF server panic: {:?}", panic_info);
@@ -187,11 +180,16 @@ async fn main() -> Result<(), Box<dyn Error>> {
             let oracle_keypair = read_keypair_file(keypair)
                 .map_err(|e| format!("Failed to read keypair file: {}", e))?;
             
-            // Load the VRF keypair
-            let vrf_keypair_bytes = fs::read(&vrf_keypair)
-                .map_err(|e| format!("Failed to read VRF keypair file: {}", e))?;
-            let vrf_keypair = ECVRFKeyPair::from_bytes(&vrf_keypair_bytes)
-                .map_err(|e| format!("Failed to parse VRF keypair: {}", e))?;
+            // Use the remote VRF signer if one is configured, otherwise load the keypair
+            let vrf_signer: Arc<dyn VrfSigner> = match (vrf_signer_socket, vrf_keypair) {
+                (Some(socket), _) => Arc::new(RemoteVrfSigner::connect(Path::new(&socket))?),
+                (None, Some(vrf_keypair)) => Arc::new(LocalVrfSigner::load(
+                    Path::new(&vrf_keypair),
+                    vrf_password_file.as_deref().map(Path::new),
+                )?),
+                (None, None) => return Err("Either --vrf-keypair or --vrf-signer-socket is required".into()),
+            };
+            println!("VRF public key: {}", hex::encode(vrf_signer.public_key()));
             
             // Determine the WebSocket URL
             let ws_url = ws_url.unwrap_or_else(|| {
@@ -201,13 +199,20 @@ async fn main() -> Result<(), Box<dyn Error>> {
             });
             
             // Create and run the VRF server
-            let server = VRFServer::new(
+            let server = VRFServer::with_signers(
                 &rpc_url,
                 &ws_url,
                 &program_id,
-                oracle_keypair,
-                vrf_keypair,
-            )?;
+                Arc::new(oracle_keypair),
+                vrf_signer,
+            )?
+            .with_submitter_config(SubmitterConfig {
+                max_in_flight,
+                max_batch_size,
+                priority_fee_percentile,
+                max_compute_unit_price,
+                ..SubmitterConfig::default()
+            });
             
             println!("Starting VRF server...");
             println!("Program ID: {}", program_id);
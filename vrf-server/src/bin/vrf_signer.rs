@@ -0,0 +1,40 @@
+use {
+    vrf_server::signer::{serve_unix_socket, AlphaFormat, LocalVrfSigner, VrfSigner},
+    std::{
+        error::Error,
+        path::PathBuf,
+        sync::Arc,
+    },
+    clap::Parser,
+};
+
+/// Holds the VRF secret key and returns proofs over a Unix socket, so the
+/// networked vrf-server process never sees the key
+#[derive(Parser, Debug)]
+#[clap(name = "vrf-signer", about = "VRF signing service for the Kamui VRF server")]
+struct Cli {
+    /// VRF keystore or keypair file
+    #[clap(long)]
+    vrf_keypair: PathBuf,
+    /// File containing the VRF keystore password (defaults to $VRF_KEYSTORE_PASSWORD)
+    #[clap(long)]
+    vrf_password_file: Option<PathBuf>,
+    /// Unix socket to listen on
+    #[clap(long, default_value = "vrf-signer.sock")]
+    socket: PathBuf,
+    /// Alpha formats the signer will prove: seed32, len:<bytes> or prefix:<hex>
+    #[clap(long = "allow-alpha", default_value = "seed32")]
+    allow_alpha: Vec<AlphaFormat>,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    env_logger::init();
+
+    let cli = Cli::parse();
+
+    let signer = LocalVrfSigner::load(&cli.vrf_keypair, cli.vrf_password_file.as_deref())?;
+    println!("VRF public key: {}", hex::encode(signer.public_key()));
+    println!("Allowed alpha formats: {:?}", cli.allow_alpha);
+
+    serve_unix_socket(&cli.socket, Arc::new(signer), cli.allow_alpha)
+}
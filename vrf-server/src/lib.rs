@@ -3,6 +3,9 @@ pub mod instruction;
 pub mod state;
 pub mod event;
 pub mod vrf_server;
+pub mod submitter;
+pub mod signer;
+pub mod native;
 
 // Re-export the modules
 pub use crate::error::*;
@@ -10,3 +13,5 @@ pub use crate::instruction::*;
 pub use crate::state::*;
 pub use crate::event::*;
 pub use crate::vrf_server::*;
+pub use crate::submitter::*;
+pub use crate::signer::*;
@@ -16,10 +16,14 @@ struct Args {
     #[arg(short, long)]
     keypair: String,
 
-    /// Path to the VRF keypair file
+    /// Path to the VRF keystore
     #[arg(short, long, default_value = "vrf-keypair.json")]
     vrf_keypair: String,
 
+    /// File containing the VRF keystore password (defaults to $VRF_KEYSTORE_PASSWORD)
+    #[arg(long)]
+    vrf_password_file: Option<String>,
+
     /// Program ID of the VRF coordinator
     #[arg(short, long)]
     program_id: String,
@@ -54,7 +58,10 @@ async fn main() -> Result<(), Box<dyn Error>> {
     
     // Load VRF keypair
     println!("Loading VRF keypair from: {:?}", args.vrf_keypair);
-    let vrf_keypair = crate::vrf_server::load_or_create_keypair(args.vrf_keypair.as_ref().into())?;
+    let vrf_keypair = crate::vrf_server::load_or_create_keypair(
+        args.vrf_keypair.as_ref(),
+        args.vrf_password_file.as_ref().map(std::path::Path::new),
+    )?;
     
     let program_id = Pubkey::from_str(&args.program_id)?;
 
@@ -0,0 +1,157 @@
+//! Account and instruction layouts of the native coordinator (`kamui-program`).
+//!
+//! The server fulfills requests of both coordinators: requests of the legacy
+//! coordinator in [crate::state] one per transaction, and requests of the native
+//! coordinator in batches through [VrfCoordinatorInstruction::ProcessRequestBatch].
+//! The layouts below must match `kamui-program` byte for byte.
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::pubkey::Pubkey,
+};
+
+/// Maximum number of requests in a `ProcessRequestBatch` instruction
+pub const MAX_BATCH_SIZE: usize = 16;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Clone)]
+pub enum RequestStatus {
+    Pending,
+    Fulfilled,
+    Cancelled,
+    Expired,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct RandomnessRequest {
+    /// The subscription this request belongs to
+    pub subscription: Pubkey,
+    /// The seed used for randomness
+    pub seed: [u8; 32],
+    /// The requester's program ID that will receive the callback
+    pub requester: Pubkey,
+    /// The callback function data
+    pub callback_data: Vec<u8>,
+    /// Block number when request was made
+    pub request_slot: u64,
+    /// Status of the request
+    pub status: RequestStatus,
+    /// Number of random words requested
+    pub num_words: u32,
+    /// Maximum compute units for callback
+    pub callback_gas_limit: u64,
+    /// Request pool ID
+    pub pool_id: u8,
+    /// Request index in pool
+    pub request_index: u32,
+    /// Unique request identifier
+    pub request_id: [u8; 32],
+}
+
+/// Instructions of the native coordinator. Only the fulfillment instructions are
+/// built by the server; the others keep the variant indices aligned.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub enum VrfCoordinatorInstruction {
+    CreateEnhancedSubscription {
+        min_balance: u64,
+        confirmations: u8,
+        max_requests: u16,
+    },
+    FundSubscription {
+        amount: u64,
+    },
+    InitializeRequestPool {
+        pool_id: u8,
+        max_size: u32,
+    },
+    RequestRandomness {
+        seed: [u8; 32],
+        callback_data: Vec<u8>,
+        num_words: u32,
+        minimum_confirmations: u8,
+        callback_gas_limit: u64,
+        pool_id: u8,
+    },
+
+    /// Fulfill randomness request
+    /// Accounts expected:
+    /// 0. `[signer]` Oracle
+    /// 1. `[writable]` Request account
+    /// 2. `[writable]` VRF result account (PDA)
+    /// 3. `[writable]` Request pool account
+    /// 4. `[writable]` Subscription account
+    /// 5. `[]` Callback program
+    /// 6. `[]` System program
+    /// 7. `[]` Oracle config account
+    FulfillRandomness {
+        proof: Vec<u8>,
+        public_key: Vec<u8>,
+        request_id: [u8; 32],
+        pool_id: u8,
+        request_index: u32,
+    },
+    CancelRequest {
+        request_id: [u8; 32],
+        pool_id: u8,
+        request_index: u32,
+    },
+    CleanExpiredRequests {
+        pool_id: u8,
+    },
+    InitializeOracleRegistry {
+        min_stake: u64,
+        rotation_frequency: u64,
+    },
+    RegisterOracle {
+        vrf_key: [u8; 32],
+        stake_amount: u64,
+        proof_of_possession: [u8; 64],
+    },
+    DeactivateOracle,
+
+    /// Process a batch of randomness requests
+    /// Accounts expected:
+    /// 0. `[signer]` Oracle
+    /// 1. `[]` Oracle config account
+    /// 2. `[writable]` Request pool account
+    /// 3. `[]` System program
+    ///
+    /// Followed by four accounts per request, in batch order:
+    /// - `[writable]` Request account
+    /// - `[writable]` VRF result account (PDA)
+    /// - `[writable]` Subscription account
+    /// - `[]` Callback program
+    ///
+    /// Requests that fail verification are skipped on-chain and stay pending.
+    ProcessRequestBatch {
+        request_ids: Vec<[u8; 32]>,
+        proofs: Vec<Vec<u8>>,
+        public_keys: Vec<Vec<u8>>,
+        pool_id: u8,
+        request_indices: Vec<u32>,
+    },
+    RotateOracles,
+    UpdateOracleReputation {
+        oracle_authority: Pubkey,
+        successful_fulfillments: u16,
+        failed_fulfillments: u16,
+    },
+}
+
+/// Oracle config account of `oracle`
+pub fn oracle_config_address(oracle: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"oracle", oracle.as_ref()], program_id).0
+}
+
+/// Request pool `pool_id` of `subscription`
+pub fn request_pool_address(subscription: &Pubkey, pool_id: u8, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"request_pool", subscription.as_ref(), &[pool_id]],
+        program_id,
+    )
+    .0
+}
+
+/// VRF result account of the request with `request_id`
+pub fn vrf_result_address(request_id: &[u8; 32], program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"vrf_result", request_id.as_ref()], program_id).0
+}
@@ -0,0 +1,532 @@
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    mangekyou::{
+        kamui_vrf::{
+            ecvrf::{ECVRFKeyPair, ECVRFProof, ECVRFProver, ECVRFPublicKey},
+            VRFProof,
+        },
+        keystore::{KdfParams, Keystore},
+    },
+    rand::thread_rng,
+    std::{
+        error::Error,
+        fs,
+        io::{Read, Write},
+        os::unix::{
+            fs::{DirBuilderExt, OpenOptionsExt, PermissionsExt},
+            net::{UnixListener, UnixStream},
+        },
+        path::{Path, PathBuf},
+        str::FromStr,
+        sync::{Arc, Mutex},
+        thread,
+    },
+    log::{error, info, trace, warn},
+};
+
+/// Largest frame accepted on the signer socket
+pub const MAX_FRAME_SIZE: usize = 4096;
+
+/// Largest alpha accepted by a prefix rule
+pub const MAX_ALPHA_LENGTH: usize = 256;
+
+/// Environment variable read for the VRF keystore password when no password file is given
+pub const KEYSTORE_PASSWORD_ENV: &str = "VRF_KEYSTORE_PASSWORD";
+
+/// Produces VRF proofs without exposing the secret key to the caller
+pub trait VrfSigner: Send + Sync {
+    /// The VRF public key proofs verify against
+    fn public_key(&self) -> [u8; 32];
+
+    /// Prove `alpha` under the signer's VRF key
+    fn prove(&self, alpha: &[u8]) -> Result<ECVRFProof, Box<dyn Error>>;
+}
+
+/// Signer backed by a VRF keypair held in this process, proving with a precomputed basepoint table
+pub struct LocalVrfSigner {
+    prover: ECVRFProver,
+}
+
+impl LocalVrfSigner {
+    pub fn new(keypair: ECVRFKeyPair) -> Self {
+        Self { prover: ECVRFProver::new(keypair) }
+    }
+
+    /// Load a keypair stored as raw bytes, a JSON byte array or a hex string
+    pub fn from_file(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let contents = fs::read(path)
+            .map_err(|e| format!("Failed to read VRF keypair file {:?}: {}", path, e))?;
+
+        let keypair = if let Ok(json_bytes) = serde_json::from_slice::<Vec<u8>>(&contents) {
+            ECVRFKeyPair::from_bytes(&json_bytes)?
+        } else if let Ok(key_bytes) = hex::decode(String::from_utf8_lossy(&contents).trim()) {
+            ECVRFKeyPair::from_bytes(&key_bytes)?
+        } else {
+            ECVRFKeyPair::from_bytes(&contents)?
+        };
+
+        Ok(Self::new(keypair))
+    }
+
+    /// Decrypt a keypair from an encrypted keystore
+    pub fn from_keystore(path: &Path, password: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(Self::new(load_keystore(path, password)?))
+    }
+
+    /// Load a keystore, reading its password from `password_file` or the environment.
+    /// Unencrypted keypair files are still accepted, with a warning.
+    pub fn load(path: &Path, password_file: Option<&Path>) -> Result<Self, Box<dyn Error>> {
+        if is_keystore(path) {
+            let password = read_keystore_password(password_file)?;
+            return Self::from_keystore(path, &password);
+        }
+
+        warn!(
+            "VRF keypair {:?} is stored unencrypted; convert it with `ecvrf-cli keystore import`",
+            path
+        );
+        Self::from_file(path)
+    }
+}
+
+/// Whether the file at `path` holds an encrypted keystore
+pub fn is_keystore(path: &Path) -> bool {
+    fs::read_to_string(path)
+        .map(|contents| Keystore::from_json(&contents).is_ok())
+        .unwrap_or(false)
+}
+
+/// Read the keystore password from `password_file`, falling back to [KEYSTORE_PASSWORD_ENV]
+pub fn read_keystore_password(password_file: Option<&Path>) -> Result<String, Box<dyn Error>> {
+    let password = match password_file {
+        Some(path) => fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read password file {:?}: {}", path, e))?
+            .trim_end_matches(['\r', '\n'])
+            .to_string(),
+        None => std::env::var(KEYSTORE_PASSWORD_ENV).map_err(|_| {
+            format!("No keystore password given; use a password file or set {}", KEYSTORE_PASSWORD_ENV)
+        })?,
+    };
+    if password.is_empty() {
+        return Err("Keystore password must not be empty".into());
+    }
+    Ok(password)
+}
+
+/// Decrypt the VRF keypair stored in the keystore at `path`
+pub fn load_keystore(path: &Path, password: &str) -> Result<ECVRFKeyPair, Box<dyn Error>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read VRF keystore {:?}: {}", path, e))?;
+    let keystore = Keystore::from_json(&contents)?;
+    keystore
+        .to_ecvrf_keypair(password.as_bytes())
+        .map_err(|_| format!("Failed to decrypt VRF keystore {:?}: wrong password or corrupt file", path).into())
+}
+
+/// Encrypt `keypair` into a new keystore at `path`, readable only by the owner
+pub fn save_keystore(path: &Path, keypair: &ECVRFKeyPair, password: &str) -> Result<(), Box<dyn Error>> {
+    let keystore = Keystore::from_ecvrf_keypair(keypair, password.as_bytes(), KdfParams::default(), &mut thread_rng())?;
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)
+        .map_err(|e| format!("Failed to create VRF keystore {:?}: {}", path, e))?;
+    file.write_all(keystore.to_json()?.as_bytes())?;
+    Ok(())
+}
+
+impl VrfSigner for LocalVrfSigner {
+    fn public_key(&self) -> [u8; 32] {
+        let mut public_key = [0u8; 32];
+        public_key.copy_from_slice(self.prover.keypair().pk.as_ref());
+        public_key
+    }
+
+    fn prove(&self, alpha: &[u8]) -> Result<ECVRFProof, Box<dyn Error>> {
+        safely_generate_vrf_proof(&self.prover, alpha)
+            .ok_or_else(|| "Failed to generate VRF proof".into())
+    }
+}
+
+/// Signer that asks a separate `vrf-signer` process for proofs over a Unix socket.
+/// Proofs are verified against the signer's public key before they are returned.
+pub struct RemoteVrfSigner {
+    /// Path of the signer socket
+    socket_path: PathBuf,
+    /// Public key reported by the signer when the connection was set up
+    public_key: [u8; 32],
+    /// Connection reused across requests
+    stream: Mutex<UnixStream>,
+}
+
+impl RemoteVrfSigner {
+    pub fn connect(socket_path: &Path) -> Result<Self, Box<dyn Error>> {
+        let mut stream = UnixStream::connect(socket_path)
+            .map_err(|e| format!("Failed to connect to VRF signer at {:?}: {}", socket_path, e))?;
+
+        let public_key = match call(&mut stream, &SignerRequest::PublicKey)? {
+            SignerResponse::PublicKey(public_key) => public_key,
+            SignerResponse::Rejected(reason) => return Err(format!("VRF signer rejected request: {}", reason).into()),
+            response => return Err(format!("Unexpected VRF signer response: {:?}", response).into()),
+        };
+
+        Ok(Self {
+            socket_path: socket_path.to_path_buf(),
+            public_key,
+            stream: Mutex::new(stream),
+        })
+    }
+}
+
+impl VrfSigner for RemoteVrfSigner {
+    fn public_key(&self) -> [u8; 32] {
+        self.public_key
+    }
+
+    fn prove(&self, alpha: &[u8]) -> Result<ECVRFProof, Box<dyn Error>> {
+        let request = SignerRequest::Prove { alpha: alpha.to_vec() };
+
+        let response = {
+            let mut stream = self.stream.lock().unwrap();
+            match call(&mut stream, &request) {
+                Ok(response) => response,
+                Err(e) => {
+                    // The signer may have restarted; retry once on a fresh connection
+                    warn!("VRF signer connection failed ({}), reconnecting", e);
+                    *stream = UnixStream::connect(&self.socket_path)?;
+                    call(&mut stream, &request)?
+                }
+            }
+        };
+
+        let proof_bytes = match response {
+            SignerResponse::Proof(proof_bytes) => proof_bytes,
+            SignerResponse::Rejected(reason) => return Err(format!("VRF signer rejected alpha: {}", reason).into()),
+            response => return Err(format!("Unexpected VRF signer response: {:?}", response).into()),
+        };
+
+        if proof_bytes.len() != 80 {
+            return Err(format!("VRF signer returned a {}-byte proof", proof_bytes.len()).into());
+        }
+
+        let proof = ECVRFProof::from_bytes(&proof_bytes)?;
+        let public_key = ECVRFPublicKey::from_bytes(&self.public_key)?;
+        proof
+            .verify(alpha, &public_key)
+            .map_err(|e| format!("VRF signer returned an invalid proof: {:?}", e))?;
+
+        Ok(proof)
+    }
+}
+
+/// Request sent to the `vrf-signer` process
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub enum SignerRequest {
+    /// Return the VRF public key
+    PublicKey,
+    /// Prove the given alpha string
+    Prove { alpha: Vec<u8> },
+}
+
+/// Response from the `vrf-signer` process
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub enum SignerResponse {
+    PublicKey([u8; 32]),
+    Proof(Vec<u8>),
+    Rejected(String),
+}
+
+/// An alpha string shape the signer is willing to prove
+#[derive(Clone, Debug, PartialEq)]
+pub enum AlphaFormat {
+    /// Exactly this many bytes, e.g. a 32-byte request seed
+    Length(usize),
+    /// Starts with a fixed domain prefix, up to `MAX_ALPHA_LENGTH` bytes in total
+    Prefix(Vec<u8>),
+}
+
+impl AlphaFormat {
+    pub fn matches(&self, alpha: &[u8]) -> bool {
+        match self {
+            AlphaFormat::Length(len) => alpha.len() == *len,
+            AlphaFormat::Prefix(prefix) => alpha.len() <= MAX_ALPHA_LENGTH && alpha.starts_with(prefix),
+        }
+    }
+}
+
+impl FromStr for AlphaFormat {
+    type Err = String;
+
+    /// Parse `seed32`, `len:<bytes>` or `prefix:<hex>`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "seed32" {
+            return Ok(AlphaFormat::Length(32));
+        }
+        if let Some(len) = s.strip_prefix("len:") {
+            return len
+                .parse()
+                .map(AlphaFormat::Length)
+                .map_err(|e| format!("Invalid alpha length {}: {}", len, e));
+        }
+        if let Some(prefix) = s.strip_prefix("prefix:") {
+            return hex::decode(prefix)
+                .map(AlphaFormat::Prefix)
+                .map_err(|e| format!("Invalid alpha prefix {}: {}", prefix, e));
+        }
+        Err(format!("Unknown alpha format: {} (expected seed32, len:<bytes> or prefix:<hex>)", s))
+    }
+}
+
+/// Serve proofs from `signer` on a Unix socket, only for alphas matching one of `allowed`.
+/// The socket is only reachable at `socket_path` once it has owner-only permissions.
+pub fn serve_unix_socket(
+    socket_path: &Path,
+    signer: Arc<dyn VrfSigner>,
+    allowed: Vec<AlphaFormat>,
+) -> Result<(), Box<dyn Error>> {
+    if socket_path.exists() {
+        fs::remove_file(socket_path)?;
+    }
+
+    let listener = bind_owner_only(socket_path)?;
+    info!("VRF signer listening on {:?}", socket_path);
+
+    let allowed = Arc::new(allowed);
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let signer = signer.clone();
+                let allowed = allowed.clone();
+                thread::spawn(move || handle_connection(stream, signer.as_ref(), &allowed));
+            }
+            Err(e) => error!("Failed to accept VRF signer connection: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Bind a Unix socket inside a private directory and move it to `socket_path` once it is
+/// owner-only, so it is never reachable with the permissions of the process umask
+fn bind_owner_only(socket_path: &Path) -> Result<UnixListener, Box<dyn Error>> {
+    let mut staging = socket_path.as_os_str().to_owned();
+    staging.push(format!(".{}.tmp", std::process::id()));
+    let staging = PathBuf::from(staging);
+    fs::DirBuilder::new().mode(0o700).create(&staging)?;
+
+    let staged_socket = staging.join("signer.sock");
+    let bound = UnixListener::bind(&staged_socket).and_then(|listener| {
+        fs::set_permissions(&staged_socket, fs::Permissions::from_mode(0o600))?;
+        fs::rename(&staged_socket, socket_path)?;
+        Ok(listener)
+    });
+
+    let _ = fs::remove_file(&staged_socket);
+    let _ = fs::remove_dir(&staging);
+    Ok(bound?)
+}
+
+fn handle_connection(mut stream: UnixStream, signer: &dyn VrfSigner, allowed: &[AlphaFormat]) {
+    loop {
+        let request = match read_frame(&mut stream).and_then(|frame| Ok(SignerRequest::try_from_slice(&frame)?)) {
+            Ok(request) => request,
+            // The client hung up or sent something we cannot parse
+            Err(_) => return,
+        };
+
+        let response = match request {
+            SignerRequest::PublicKey => SignerResponse::PublicKey(signer.public_key()),
+            SignerRequest::Prove { alpha } => {
+                if !allowed.iter().any(|format| format.matches(&alpha)) {
+                    warn!("Rejected {}-byte alpha that matches no allowed format", alpha.len());
+                    SignerResponse::Rejected("alpha format not allowed".to_string())
+                } else {
+                    match signer.prove(&alpha) {
+                        Ok(proof) => SignerResponse::Proof(proof.to_bytes()),
+                        Err(e) => SignerResponse::Rejected(e.to_string()),
+                    }
+                }
+            }
+        };
+
+        let sent = borsh::to_vec(&response)
+            .map_err(|e| e.into())
+            .and_then(|frame| write_frame(&mut stream, &frame));
+        if sent.is_err() {
+            return;
+        }
+    }
+}
+
+fn call(stream: &mut UnixStream, request: &SignerRequest) -> Result<SignerResponse, Box<dyn Error>> {
+    write_frame(stream, &borsh::to_vec(request)?)?;
+    Ok(SignerResponse::try_from_slice(&read_frame(stream)?)?)
+}
+
+/// Frames are a little-endian u32 length followed by a borsh-encoded body
+fn write_frame(stream: &mut UnixStream, frame: &[u8]) -> Result<(), Box<dyn Error>> {
+    if frame.len() > MAX_FRAME_SIZE {
+        return Err(format!("Frame of {} bytes exceeds limit", frame.len()).into());
+    }
+    stream.write_all(&(frame.len() as u32).to_le_bytes())?;
+    stream.write_all(frame)?;
+    Ok(())
+}
+
+fn read_frame(stream: &mut UnixStream) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut len = [0u8; 4];
+    stream.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_FRAME_SIZE {
+        return Err(format!("Frame of {} bytes exceeds limit", len).into());
+    }
+    let mut frame = vec![0u8; len];
+    stream.read_exact(&mut frame)?;
+    Ok(frame)
+}
+
+/// Safely generate a VRF proof for the given request
+fn safely_generate_vrf_proof(prover: &ECVRFProver, seed: &[u8]) -> Option<ECVRFProof> {
+    // Add defensive code to handle potential key issues
+    trace!("Attempting to generate VRF proof with seed: {:?}", seed);
+
+    // Try to generate the proof in a way that catches potential panics
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        prover.prove(seed)
+    }));
+
+    match result {
+        Ok(proof) => {
+            trace!("Successfully generated VRF proof");
+            Some(proof)
+        },
+        Err(e) => {
+            error!("Failed to generate VRF proof: {:?}", e);
+
+            // Try to recover what went wrong for better diagnostics
+            if let Some(error_str) = e.downcast_ref::<String>() {
+                error!("Error message: {}", error_str);
+            } else if let Some(error_str) = e.downcast_ref::<&str>() {
+                error!("Error message: {}", error_str);
+            }
+
+            warn!("Checking if VRF keypair is valid...");
+            // Check if the public key is valid
+            if !is_valid_vrf_keypair(prover.keypair()) {
+                error!("VRF keypair appears to be invalid. Please generate a new VRF keypair");
+            }
+
+            None
+        }
+    }
+}
+
+/// Check if the VRF keypair appears to be valid
+fn is_valid_vrf_keypair(keypair: &ECVRFKeyPair) -> bool {
+    // Basic check - are the keys non-zero?
+    let pk_bytes = keypair.pk.as_ref();
+    let sk_bytes = keypair.sk.as_ref();
+
+    if pk_bytes.iter().all(|&b| b == 0) || sk_bytes.iter().all(|&b| b == 0) {
+        return false;
+    }
+
+    // Try to derive a public key from the private key and check if it matches
+    // This is a basic test - it doesn't fully validate the keypair
+    let derived_keypair = ECVRFKeyPair::from(keypair.sk.clone());
+    let derived_pk_bytes = derived_keypair.pk.as_ref();
+
+    // Compare public keys
+    pk_bytes == derived_pk_bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mangekyou::kamui_vrf::VRFKeyPair;
+
+    #[test]
+    fn test_alpha_format_parsing() {
+        assert_eq!("seed32".parse::<AlphaFormat>(), Ok(AlphaFormat::Length(32)));
+        assert_eq!("len:64".parse::<AlphaFormat>(), Ok(AlphaFormat::Length(64)));
+        assert_eq!("prefix:6b616d7569".parse::<AlphaFormat>(), Ok(AlphaFormat::Prefix(b"kamui".to_vec())));
+        assert!("len:x".parse::<AlphaFormat>().is_err());
+        assert!("anything".parse::<AlphaFormat>().is_err());
+    }
+
+    #[test]
+    fn test_alpha_format_matches() {
+        assert!(AlphaFormat::Length(32).matches(&[0u8; 32]));
+        assert!(!AlphaFormat::Length(32).matches(&[0u8; 33]));
+
+        let prefix = AlphaFormat::Prefix(b"kamui".to_vec());
+        assert!(prefix.matches(b"kamui:request:1"));
+        assert!(!prefix.matches(b"other:request:1"));
+        assert!(!prefix.matches(&[b"kamui".as_slice(), &[0u8; MAX_ALPHA_LENGTH]].concat()));
+    }
+
+    #[test]
+    fn test_remote_signer_round_trip() {
+        let keypair = ECVRFKeyPair::generate(&mut thread_rng());
+        let local = Arc::new(LocalVrfSigner::new(keypair));
+        let public_key = local.public_key();
+
+        let socket_path = std::env::temp_dir().join(format!("vrf-signer-test-{}.sock", std::process::id()));
+        let server_path = socket_path.clone();
+        let server_signer: Arc<dyn VrfSigner> = local.clone();
+        thread::spawn(move || serve_unix_socket(&server_path, server_signer, vec![AlphaFormat::Length(32)]).unwrap());
+
+        // Wait for the listener to come up
+        let mut remote = None;
+        for _ in 0..50 {
+            if let Ok(signer) = RemoteVrfSigner::connect(&socket_path) {
+                remote = Some(signer);
+                break;
+            }
+            thread::sleep(std::time::Duration::from_millis(20));
+        }
+        let remote = remote.expect("signer did not start");
+
+        assert_eq!(remote.public_key(), public_key);
+        assert_eq!(fs::metadata(&socket_path).unwrap().permissions().mode() & 0o777, 0o600);
+
+        let seed = [7u8; 32];
+        let proof = remote.prove(&seed).unwrap();
+        assert_eq!(proof.to_bytes(), local.prove(&seed).unwrap().to_bytes());
+
+        // Alphas outside the allowlist are refused
+        assert!(remote.prove(b"not a seed").is_err());
+
+        let _ = fs::remove_file(&socket_path);
+    }
+
+    #[test]
+    fn test_keystore_round_trip() {
+        let keypair = ECVRFKeyPair::generate(&mut thread_rng());
+        let public_key = keypair.pk.as_ref().to_vec();
+
+        let dir = std::env::temp_dir().join(format!("vrf-keystore-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("vrf-keystore.json");
+        let _ = fs::remove_file(&path);
+
+        save_keystore(&path, &keypair, "hunter2").unwrap();
+        assert!(is_keystore(&path));
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+
+        // Existing keystores are never overwritten
+        assert!(save_keystore(&path, &keypair, "hunter2").is_err());
+
+        let signer = LocalVrfSigner::from_keystore(&path, "hunter2").unwrap();
+        assert_eq!(signer.public_key().to_vec(), public_key);
+        assert!(LocalVrfSigner::from_keystore(&path, "hunter3").is_err());
+
+        let password_file = dir.join("password");
+        fs::write(&password_file, "hunter2\n").unwrap();
+        let signer = LocalVrfSigner::load(&path, Some(&password_file)).unwrap();
+        assert_eq!(signer.public_key().to_vec(), public_key);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
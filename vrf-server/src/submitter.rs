@@ -0,0 +1,317 @@
+use {
+    solana_program::pubkey::Pubkey,
+    solana_sdk::{
+        commitment_config::CommitmentConfig,
+        compute_budget::ComputeBudgetInstruction,
+        hash::Hash,
+        instruction::Instruction,
+        packet::PACKET_DATA_SIZE,
+        signature::{Signature, Signer},
+        transaction::Transaction,
+    },
+    solana_client::{
+        nonblocking::rpc_client::RpcClient,
+        rpc_config::{RpcSendTransactionConfig, RpcSimulateTransactionConfig},
+    },
+    std::{error::Error, time::Duration},
+    log::{debug, info, warn},
+};
+
+/// Highest compute unit limit a single transaction may request
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Tuning knobs for the fulfillment submission pipeline
+#[derive(Clone, Debug)]
+pub struct SubmitterConfig {
+    /// Maximum number of fulfillment transactions in flight at once
+    pub max_in_flight: usize,
+    /// Maximum number of requests packed into one transaction
+    pub max_batch_size: usize,
+    /// Extra headroom added on top of the simulated compute units, in percent
+    pub compute_unit_margin_percent: u32,
+    /// Compute unit limit used when simulation does not report consumption
+    pub fallback_compute_unit_limit: u32,
+    /// Percentile of recent prioritization fees to pay (0-100)
+    pub priority_fee_percentile: u8,
+    /// Upper bound on the compute unit price, in micro-lamports
+    pub max_compute_unit_price: u64,
+    /// How often to poll signature status and rebroadcast
+    pub poll_interval: Duration,
+    /// Number of times a transaction is re-signed after its blockhash expires
+    pub max_resign_attempts: usize,
+}
+
+impl Default for SubmitterConfig {
+    fn default() -> Self {
+        Self {
+            max_in_flight: 8,
+            max_batch_size: 8,
+            compute_unit_margin_percent: 20,
+            fallback_compute_unit_limit: 400_000,
+            priority_fee_percentile: 75,
+            max_compute_unit_price: 100_000,
+            poll_interval: Duration::from_millis(500),
+            max_resign_attempts: 3,
+        }
+    }
+}
+
+/// Submits transactions with compute budget instructions derived from simulation and
+/// recent prioritization fees, confirming without blocking other submissions
+pub struct TransactionSubmitter {
+    /// Non-blocking RPC client used for simulation, sending and confirmation
+    rpc_client: RpcClient,
+    /// Pipeline configuration
+    config: SubmitterConfig,
+    /// Commitment level a transaction must reach to count as landed
+    commitment: CommitmentConfig,
+}
+
+impl TransactionSubmitter {
+    pub fn new(rpc_url: &str, commitment: CommitmentConfig, config: SubmitterConfig) -> Self {
+        Self {
+            rpc_client: RpcClient::new_with_commitment(rpc_url.to_string(), commitment),
+            config,
+            commitment,
+        }
+    }
+
+    pub fn config(&self) -> &SubmitterConfig {
+        &self.config
+    }
+
+    /// Price and submit `instructions`, returning once the transaction is confirmed.
+    /// The transaction is re-signed with a fresh blockhash if the previous one expires.
+    pub async fn submit(
+        &self,
+        instructions: &[Instruction],
+        payer: &(dyn Signer + Send + Sync),
+    ) -> Result<Signature, Box<dyn Error>> {
+        let compute_unit_limit = self.estimate_compute_unit_limit(instructions, payer).await?;
+        let compute_unit_price = self.estimate_compute_unit_price(instructions).await;
+        debug!(
+            "Using compute unit limit {} and price {} micro-lamports",
+            compute_unit_limit, compute_unit_price
+        );
+
+        let budgeted = with_compute_budget(instructions, compute_unit_limit, compute_unit_price);
+
+        for attempt in 0..=self.config.max_resign_attempts {
+            let (blockhash, last_valid_block_height) = self
+                .rpc_client
+                .get_latest_blockhash_with_commitment(self.commitment)
+                .await?;
+            let transaction = Transaction::new_signed_with_payer(
+                &budgeted,
+                Some(&payer.pubkey()),
+                &[payer as &dyn Signer],
+                blockhash,
+            );
+
+            if let Some(signature) = self.send_until_expired(&transaction, last_valid_block_height).await? {
+                return Ok(signature);
+            }
+
+            warn!(
+                "Blockhash expired before transaction {} landed, re-signing (attempt {}/{})",
+                transaction.signatures[0],
+                attempt + 1,
+                self.config.max_resign_attempts
+            );
+        }
+
+        Err("Transaction was not confirmed before its blockhash expired".into())
+    }
+
+    /// Broadcast `transaction` until it is confirmed or its blockhash is no longer valid.
+    /// Returns `None` on expiry so the caller can re-sign.
+    async fn send_until_expired(
+        &self,
+        transaction: &Transaction,
+        last_valid_block_height: u64,
+    ) -> Result<Option<Signature>, Box<dyn Error>> {
+        let send_config = RpcSendTransactionConfig {
+            skip_preflight: true,
+            max_retries: Some(0),
+            ..RpcSendTransactionConfig::default()
+        };
+        let signature = transaction.signatures[0];
+
+        loop {
+            if let Err(e) = self.rpc_client.send_transaction_with_config(transaction, send_config).await {
+                debug!("Failed to broadcast {}: {}", signature, e);
+            }
+
+            tokio::time::sleep(self.config.poll_interval).await;
+
+            let statuses = self.rpc_client.get_signature_statuses(&[signature]).await?.value;
+            if let Some(Some(status)) = statuses.first() {
+                if let Some(err) = &status.err {
+                    return Err(format!("Transaction {} failed: {}", signature, err).into());
+                }
+                if status.satisfies_commitment(self.commitment) {
+                    info!("Transaction {} confirmed", signature);
+                    return Ok(Some(signature));
+                }
+            }
+
+            let block_height = self
+                .rpc_client
+                .get_block_height_with_commitment(self.commitment)
+                .await?;
+            if block_height > last_valid_block_height {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Simulate the transaction at the maximum limit and derive a limit from the units consumed
+    async fn estimate_compute_unit_limit(
+        &self,
+        instructions: &[Instruction],
+        payer: &(dyn Signer + Send + Sync),
+    ) -> Result<u32, Box<dyn Error>> {
+        let simulated = with_compute_budget(instructions, MAX_COMPUTE_UNIT_LIMIT, 0);
+        let transaction = Transaction::new_signed_with_payer(
+            &simulated,
+            Some(&payer.pubkey()),
+            &[payer as &dyn Signer],
+            Hash::default(),
+        );
+
+        let result = self
+            .rpc_client
+            .simulate_transaction_with_config(
+                &transaction,
+                RpcSimulateTransactionConfig {
+                    sig_verify: false,
+                    replace_recent_blockhash: true,
+                    commitment: Some(self.commitment),
+                    ..RpcSimulateTransactionConfig::default()
+                },
+            )
+            .await?
+            .value;
+
+        if let Some(err) = result.err {
+            return Err(format!("Simulation failed: {} (logs: {:?})", err, result.logs).into());
+        }
+
+        Ok(compute_unit_limit_with_margin(
+            result.units_consumed.unwrap_or(0),
+            self.config.compute_unit_margin_percent,
+            self.config.fallback_compute_unit_limit,
+        ))
+    }
+
+    /// Price the transaction from recent prioritization fees on its writable accounts
+    async fn estimate_compute_unit_price(&self, instructions: &[Instruction]) -> u64 {
+        let mut writable: Vec<Pubkey> = instructions
+            .iter()
+            .flat_map(|ix| ix.accounts.iter())
+            .filter(|meta| meta.is_writable)
+            .map(|meta| meta.pubkey)
+            .collect();
+        writable.sort();
+        writable.dedup();
+
+        match self.rpc_client.get_recent_prioritization_fees(&writable).await {
+            Ok(fees) => {
+                let fees: Vec<u64> = fees.iter().map(|fee| fee.prioritization_fee).collect();
+                select_compute_unit_price(
+                    &fees,
+                    self.config.priority_fee_percentile,
+                    self.config.max_compute_unit_price,
+                )
+            }
+            Err(e) => {
+                warn!("Failed to fetch recent prioritization fees: {}", e);
+                0
+            }
+        }
+    }
+}
+
+/// Prepend compute budget instructions to `instructions`
+pub fn with_compute_budget(
+    instructions: &[Instruction],
+    compute_unit_limit: u32,
+    compute_unit_price: u64,
+) -> Vec<Instruction> {
+    let mut budgeted = Vec::with_capacity(instructions.len() + 2);
+    budgeted.push(ComputeBudgetInstruction::set_compute_unit_limit(compute_unit_limit));
+    if compute_unit_price > 0 {
+        budgeted.push(ComputeBudgetInstruction::set_compute_unit_price(compute_unit_price));
+    }
+    budgeted.extend_from_slice(instructions);
+    budgeted
+}
+
+/// Whether `instructions`, with compute budget instructions added, fit in one transaction packet
+pub fn fits_in_packet(instructions: &[Instruction], payer: &Pubkey) -> bool {
+    let budgeted = with_compute_budget(instructions, MAX_COMPUTE_UNIT_LIMIT, 1);
+    let transaction = Transaction::new_with_payer(&budgeted, Some(payer));
+    bincode::serialized_size(&transaction)
+        .map(|size| size as usize <= PACKET_DATA_SIZE)
+        .unwrap_or(false)
+}
+
+/// Add `margin_percent` headroom to the simulated units, clamped to the runtime maximum.
+/// A transaction limited to 0 units cannot execute, so `fallback_limit` is used when
+/// simulation reports no consumption.
+pub fn compute_unit_limit_with_margin(units_consumed: u64, margin_percent: u32, fallback_limit: u32) -> u32 {
+    if units_consumed == 0 {
+        return fallback_limit.min(MAX_COMPUTE_UNIT_LIMIT);
+    }
+    let limit = units_consumed.saturating_mul(100 + margin_percent as u64) / 100;
+    limit.min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32
+}
+
+/// Pick the given percentile of recent fees, capped at `max_price`
+pub fn select_compute_unit_price(recent_fees: &[u64], percentile: u8, max_price: u64) -> u64 {
+    if recent_fees.is_empty() {
+        return 0;
+    }
+
+    let mut fees = recent_fees.to_vec();
+    fees.sort_unstable();
+    let index = (fees.len() - 1) * percentile.min(100) as usize / 100;
+    fees[index].min(max_price)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_unit_limit_with_margin() {
+        assert_eq!(compute_unit_limit_with_margin(100_000, 20, 400_000), 120_000);
+        assert_eq!(compute_unit_limit_with_margin(0, 20, 400_000), 400_000);
+        assert_eq!(compute_unit_limit_with_margin(1_300_000, 20, 400_000), MAX_COMPUTE_UNIT_LIMIT);
+    }
+
+    #[test]
+    fn test_select_compute_unit_price() {
+        assert_eq!(select_compute_unit_price(&[], 75, 1_000), 0);
+        assert_eq!(select_compute_unit_price(&[40, 10, 30, 20, 50], 50, 1_000), 30);
+        assert_eq!(select_compute_unit_price(&[40, 10, 30, 20, 50], 100, 1_000), 50);
+        assert_eq!(select_compute_unit_price(&[40, 10, 30, 20, 50], 100, 35), 35);
+    }
+
+    #[test]
+    fn test_fits_in_packet() {
+        use solana_sdk::instruction::AccountMeta;
+
+        let payer = Pubkey::new_unique();
+        let small = Instruction::new_with_bytes(Pubkey::new_unique(), &[0; 100], vec![AccountMeta::new(payer, true)]);
+        let large = Instruction::new_with_bytes(Pubkey::new_unique(), &[0; PACKET_DATA_SIZE], vec![AccountMeta::new(payer, true)]);
+        assert!(fits_in_packet(&[small], &payer));
+        assert!(!fits_in_packet(&[large], &payer));
+    }
+
+    #[test]
+    fn test_with_compute_budget_skips_zero_price() {
+        assert_eq!(with_compute_budget(&[], 200_000, 0).len(), 1);
+        assert_eq!(with_compute_budget(&[], 200_000, 10).len(), 2);
+    }
+}
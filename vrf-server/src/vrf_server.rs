@@ -5,7 +5,6 @@ use {
     solana_sdk::{
         commitment_config::CommitmentConfig,
         signature::{Keypair, Signer},
-        transaction::Transaction,
         instruction::{AccountMeta, Instruction},
         system_program,
     },
@@ -23,19 +22,24 @@ use {
     },
     crate::{
         instruction::VrfCoordinatorInstruction,
+        native,
+        signer::{is_keystore, load_keystore, read_keystore_password, save_keystore, LocalVrfSigner, VrfSigner},
         state::{RandomnessRequest, RequestStatus, Subscription},
+        submitter::{fits_in_packet, SubmitterConfig, TransactionSubmitter},
     },
     std::{
+        collections::{HashMap, HashSet},
         str::FromStr,
         error::Error,
         fs::File,
-        io::{Write, Read},
+        io::Read,
         path::Path,
+        sync::{Arc, Mutex},
     },
+    futures_util::stream::{self, StreamExt},
     rand,
     serde_json,
     log::{debug, error, info, trace, warn},
-    mangekyou::kamui_vrf::ecvrf::ECVRFProof,
 };
 
 pub struct VRFServer {
@@ -43,37 +47,88 @@ pub struct VRFServer {
     rpc_client: RpcClient,
     /// VRF coordinator program ID
     program_id: Pubkey,
-    /// Oracle keypair for signing transactions
-    oracle_keypair: Keypair,
-    /// VRF keypair for generating proofs
-    vrf_keypair: ECVRFKeyPair,
+    /// Oracle signer for signing transactions
+    oracle_signer: Arc<dyn Signer + Send + Sync>,
+    /// VRF signer for generating proofs
+    vrf_signer: Arc<dyn VrfSigner>,
     /// Commitment level for transactions
     commitment: CommitmentConfig,
+    /// Pipeline used to price, send and confirm fulfillment transactions
+    submitter: Arc<TransactionSubmitter>,
+    /// Requests with a fulfillment currently being submitted
+    in_flight: Arc<Mutex<HashSet<Pubkey>>>,
+}
+
+/// A pending request, in the layout of the coordinator that created it
+enum PendingRequest {
+    /// Request of the legacy coordinator, fulfilled one per transaction
+    Legacy(RandomnessRequest),
+    /// Request of the native coordinator, fulfilled in batches
+    Native(native::RandomnessRequest),
+}
+
+impl PendingRequest {
+    /// Parse request account data following the discriminator
+    fn parse(data: &[u8]) -> std::io::Result<Self> {
+        // The two layouts differ in length, so at most one of them parses
+        native::RandomnessRequest::try_from_slice(data)
+            .map(PendingRequest::Native)
+            .or_else(|_| RandomnessRequest::try_from_slice(data).map(PendingRequest::Legacy))
+    }
+
+    fn is_pending(&self) -> bool {
+        match self {
+            PendingRequest::Legacy(request) => request.status == RequestStatus::Pending,
+            PendingRequest::Native(request) => request.status == native::RequestStatus::Pending,
+        }
+    }
+
+    fn status(&self) -> String {
+        match self {
+            PendingRequest::Legacy(request) => format!("{:?}", request.status),
+            PendingRequest::Native(request) => format!("{:?}", request.status),
+        }
+    }
+}
+
+/// A generated proof and the accounts needed to deliver it on-chain
+struct PreparedFulfillment {
+    /// Request account being fulfilled
+    request: Pubkey,
+    /// VRF result account (PDA)
+    vrf_result: Pubkey,
+    /// Program that receives the callback
+    requester: Pubkey,
+    /// Subscription the request belongs to
+    subscription: Pubkey,
+    /// Serialized VRF proof
+    proof: Vec<u8>,
+    /// Position of the request in its pool, for requests of the native coordinator
+    native: Option<NativeRequestKey>,
+}
+
+/// Identifies a request of the native coordinator within its request pool
+#[derive(Clone, Copy)]
+struct NativeRequestKey {
+    request_id: [u8; 32],
+    pool_id: u8,
+    request_index: u32,
 }
 
 // Implement Clone for VRFServer outside the impl block
 impl Clone for VRFServer {
     fn clone(&self) -> Self {
-        // Use correct keypair cloning to maintain the same VRF keypair
-        let vrf_keypair = match self.clone_vrf_keypair() {
-            Ok(kp) => kp,
-            Err(e) => {
-                eprintln!("Failed to clone VRF keypair: {}", e);
-                // Create a fallback keypair
-                let mut rng = rand::thread_rng();
-                ECVRFKeyPair::generate(&mut rng)
-            }
-        };
-        
         Self {
             rpc_client: RpcClient::new_with_commitment(
                 self.rpc_client.url().to_string(),
                 self.commitment,
             ),
             program_id: self.program_id,
-            oracle_keypair: Keypair::from_bytes(&self.oracle_keypair.to_bytes()).unwrap(),
-            vrf_keypair,
+            oracle_signer: self.oracle_signer.clone(),
+            vrf_signer: self.vrf_signer.clone(),
             commitment: self.commitment,
+            submitter: self.submitter.clone(),
+            in_flight: self.in_flight.clone(),
         }
     }
 }
@@ -85,6 +140,24 @@ impl VRFServer {
         program_id: &str,
         oracle_keypair: Keypair,
         vrf_keypair: ECVRFKeyPair,
+    ) -> Result<Self, Box<dyn Error>> {
+        Self::with_signers(
+            rpc_url,
+            ws_url,
+            program_id,
+            Arc::new(oracle_keypair),
+            Arc::new(LocalVrfSigner::new(vrf_keypair)),
+        )
+    }
+
+    /// Create a server whose oracle and VRF keys are held by the given signers,
+    /// e.g. a `RemoteVrfSigner` so the VRF secret key never enters this process
+    pub fn with_signers(
+        rpc_url: &str,
+        _ws_url: &str,
+        program_id: &str,
+        oracle_signer: Arc<dyn Signer + Send + Sync>,
+        vrf_signer: Arc<dyn VrfSigner>,
     ) -> Result<Self, Box<dyn Error>> {
         Ok(Self {
             rpc_client: RpcClient::new_with_commitment(
@@ -92,12 +165,28 @@ impl VRFServer {
                 CommitmentConfig::confirmed(),
             ),
             program_id: Pubkey::from_str(program_id)?,
-            oracle_keypair,
-            vrf_keypair,
+            oracle_signer,
+            vrf_signer,
             commitment: CommitmentConfig::confirmed(),
+            submitter: Arc::new(TransactionSubmitter::new(
+                rpc_url,
+                CommitmentConfig::confirmed(),
+                SubmitterConfig::default(),
+            )),
+            in_flight: Arc::new(Mutex::new(HashSet::new())),
         })
     }
 
+    /// Replace the default submission pipeline settings
+    pub fn with_submitter_config(mut self, config: SubmitterConfig) -> Self {
+        self.submitter = Arc::new(TransactionSubmitter::new(
+            &self.rpc_client.url(),
+            self.commitment,
+            config,
+        ));
+        self
+    }
+
     /// Start both polling and WebSocket monitoring
     pub async fn run(&self) -> Result<(), Box<dyn Error>> {
         println!("Starting VRF server...");
@@ -221,6 +310,8 @@ impl VRFServer {
             }
         }
         
+        let mut pending: Vec<(Pubkey, PendingRequest)> = Vec::new();
+
         // If no accounts found with the standard discriminator, or if we have subscriptions, 
         // try checking for derived request accounts from subscriptions
         if request_accounts.len() == 0 || subscription_accounts.len() > 0 {
@@ -273,11 +364,7 @@ impl VRFServer {
                                             Ok(request) => {
                                                 if request.status == RequestStatus::Pending {
                                                     println!("Found new pending VRF request from derived path: {}", request_pda);
-                                                    if let Err(e) = self.fulfill_request(&request_pda, request).await {
-                                                        eprintln!("Failed to fulfill derived VRF request {}: {}", request_pda, e);
-                                                    } else {
-                                                        println!("Successfully fulfilled derived VRF request {}", request_pda);
-                                                    }
+                                                    pending.push((request_pda, PendingRequest::Legacy(request)));
                                                 } else {
                                                     println!("Derived request not pending, status: {:?}", request.status);
                                                 }
@@ -322,16 +409,13 @@ impl VRFServer {
             }
             
             // Try to deserialize the request
-            match RandomnessRequest::try_from_slice(&account.data[8..]) {
+            match PendingRequest::parse(&account.data[8..]) {
                 Ok(request) => {
-                    if request.status == RequestStatus::Pending {
+                    if request.is_pending() {
                         println!("Found new pending VRF request: {}", pubkey);
-                        match self.fulfill_request(&pubkey, request).await {
-                            Ok(_) => println!("Successfully fulfilled VRF request {}", pubkey),
-                            Err(e) => eprintln!("Failed to fulfill VRF request {}: {}", pubkey, e),
-                        }
+                        pending.push((pubkey, request));
                     } else {
-                        println!("Request not pending, status: {:?}", request.status);
+                        println!("Request not pending, status: {}", request.status());
                     }
                 }
                 Err(e) => {
@@ -340,104 +424,305 @@ impl VRFServer {
             }
         }
 
+        // The same request can be found through both lookups
+        let mut seen = HashSet::new();
+        pending.retain(|(pubkey, _)| seen.insert(*pubkey));
+
+        // Submit in the background so slow confirmations don't hold up polling.
+        // Claimed requests are skipped by later polls until their submission ends.
+        let pending = self.claim_requests(pending);
+        if !pending.is_empty() {
+            let server = self.clone();
+            tokio::spawn(async move { server.fulfill_requests(pending).await });
+        }
+
         Ok(())
     }
 
-    /// Fulfill a single VRF request
-    async fn fulfill_request(
+    /// Mark requests as in flight, dropping those whose fulfillment is already being submitted
+    fn claim_requests(&self, pending: Vec<(Pubkey, PendingRequest)>) -> Vec<(Pubkey, PendingRequest)> {
+        let mut in_flight = self.in_flight.lock().unwrap();
+        pending.into_iter().filter(|(pubkey, _)| in_flight.insert(*pubkey)).collect()
+    }
+
+    /// Fulfill claimed requests, packing as many as fit into each transaction and
+    /// submitting transactions concurrently up to the submitter's in-flight limit.
+    /// The requests are released once their submissions finish.
+    async fn fulfill_requests(&self, pending: Vec<(Pubkey, PendingRequest)>) {
+        let claimed: Vec<Pubkey> = pending.iter().map(|(pubkey, _)| *pubkey).collect();
+
+        // Proving is CPU-bound and a remote signer blocks on its socket, so keep it
+        // off the async runtime
+        let server = self.clone();
+        let prepared = tokio::task::spawn_blocking(move || {
+            pending
+                .iter()
+                .filter_map(|(pubkey, request)| server.prepare_fulfillment(pubkey, request))
+                .collect::<Vec<_>>()
+        })
+        .await
+        .unwrap_or_else(|e| {
+            eprintln!("VRF proving task failed: {}", e);
+            Vec::new()
+        });
+        let batches = self.pack_fulfillments(prepared);
+
+        let max_in_flight = self.submitter.config().max_in_flight.max(1);
+        println!(
+            "Fulfilling {} pending requests in {} transactions ({} in flight max)",
+            claimed.len(),
+            batches.len(),
+            max_in_flight
+        );
+
+        stream::iter(batches)
+            .for_each_concurrent(max_in_flight, |batch| async move {
+                let instruction = match self.fulfillment_instruction(&batch) {
+                    Ok(ix) => ix,
+                    Err(e) => {
+                        eprintln!("Failed to build fulfillment instruction: {}", e);
+                        return;
+                    }
+                };
+
+                println!("Sending transaction to fulfill {} VRF requests...", batch.len());
+
+                // Compute budget and priority fee are set by the submitter, which also
+                // re-signs with a fresh blockhash if the transaction expires
+                match self.submitter.submit(&[instruction], self.oracle_signer.as_ref()).await {
+                    Ok(signature) => {
+                        for fulfillment in &batch {
+                            println!("Successfully fulfilled VRF request {}", fulfillment.request);
+                        }
+                        println!("Transaction signature: {}", signature);
+                        if batch.len() > 1 {
+                            // The coordinator skips requests that fail verification,
+                            // which stay pending and are picked up by a later poll
+                            println!("Check BatchItemFailed events for requests skipped by the coordinator");
+                        }
+                    }
+                    Err(e) => {
+                        for fulfillment in &batch {
+                            eprintln!("Failed to fulfill VRF request {}: {}", fulfillment.request, e);
+                        }
+                    }
+                }
+            })
+            .await;
+
+        let mut in_flight = self.in_flight.lock().unwrap();
+        for pubkey in &claimed {
+            in_flight.remove(pubkey);
+        }
+    }
+
+    /// Generate the VRF proof for a request and collect the accounts needed to deliver it
+    fn prepare_fulfillment(
         &self,
         request_pubkey: &Pubkey,
-        request: RandomnessRequest,
-    ) -> Result<(), Box<dyn Error>> {
+        request: &PendingRequest,
+    ) -> Option<PreparedFulfillment> {
         println!("Generating VRF proof for request: {}", request_pubkey);
         
+        let (seed, requester, subscription) = match request {
+            PendingRequest::Legacy(request) => (&request.seed, request.requester, request.subscription),
+            PendingRequest::Native(request) => (&request.seed, request.requester, request.subscription),
+        };
+
         // Generate VRF proof
-        let proof = match safely_generate_vrf_proof(&self.vrf_keypair, &request.seed) {
-            Some(p) => p,
-            None => {
-                error!("Failed to generate VRF proof for request {}", request_pubkey);
-                return Ok(());
+        let proof = match self.vrf_signer.prove(seed) {
+            Ok(p) => p,
+            Err(e) => {
+                error!("Failed to generate VRF proof for request {}: {}", request_pubkey, e);
+                return None;
             }
         };
         let proof_bytes = proof.to_bytes();
         let output = proof.to_hash();
-        let public_key_bytes = self.vrf_keypair.pk.as_ref().to_vec();
         
         println!("Generated VRF output: {}", hex::encode(&output));
         println!("VRF proof: {}", hex::encode(&proof_bytes));
         
         // Derive VRF result PDA
-        let (vrf_result, _bump) = Pubkey::find_program_address(
-            &[b"vrf_result", request_pubkey.as_ref()],
-            &self.program_id,
-        );
+        let (vrf_result, native) = match request {
+            PendingRequest::Legacy(_) => {
+                let (vrf_result, _bump) = Pubkey::find_program_address(
+                    &[b"vrf_result", request_pubkey.as_ref()],
+                    &self.program_id,
+                );
+                (vrf_result, None)
+            }
+            PendingRequest::Native(request) => (
+                native::vrf_result_address(&request.request_id, &self.program_id),
+                Some(NativeRequestKey {
+                    request_id: request.request_id,
+                    pool_id: request.pool_id,
+                    request_index: request.request_index,
+                }),
+            ),
+        };
         
         println!("VRF result account: {}", vrf_result);
-        
-        // Create fulfill randomness instruction
-        let fulfill_ix = VrfCoordinatorInstruction::FulfillRandomness {
+
+        Some(PreparedFulfillment {
+            request: *request_pubkey,
+            vrf_result,
+            requester,
+            subscription,
             proof: proof_bytes.to_vec(),
-            public_key: public_key_bytes.clone(),
+            native,
+        })
+    }
+
+    /// Group prepared fulfillments into batches that each fit in a single transaction.
+    /// Requests of the legacy coordinator are fulfilled one per transaction; requests
+    /// of the native coordinator are batched per subscription and request pool.
+    fn pack_fulfillments(&self, prepared: Vec<PreparedFulfillment>) -> Vec<Vec<PreparedFulfillment>> {
+        let max_batch_size = self
+            .submitter
+            .config()
+            .max_batch_size
+            .clamp(1, native::MAX_BATCH_SIZE);
+        let payer = self.oracle_signer.pubkey();
+
+        let mut batches = Vec::new();
+        let mut pools: Vec<Vec<PreparedFulfillment>> = Vec::new();
+        let mut pool_positions: HashMap<(Pubkey, u8), usize> = HashMap::new();
+        for fulfillment in prepared {
+            match fulfillment.native {
+                None => batches.push(vec![fulfillment]),
+                Some(key) => {
+                    let position = *pool_positions
+                        .entry((fulfillment.subscription, key.pool_id))
+                        .or_insert_with(|| {
+                            pools.push(Vec::new());
+                            pools.len() - 1
+                        });
+                    pools[position].push(fulfillment);
+                }
+            }
+        }
+
+        for pool in pools {
+            let mut current: Vec<PreparedFulfillment> = Vec::new();
+            for fulfillment in pool {
+                current.push(fulfillment);
+
+                let fits = current.len() <= max_batch_size
+                    && self
+                        .fulfillment_instruction(&current)
+                        .map(|ix| fits_in_packet(&[ix], &payer))
+                        .unwrap_or(false);
+
+                // Close the batch without the request that overflowed it
+                if !fits && current.len() > 1 {
+                    let overflow = current.pop().unwrap();
+                    batches.push(std::mem::replace(&mut current, vec![overflow]));
+                }
+            }
+            if !current.is_empty() {
+                batches.push(current);
+            }
+        }
+
+        batches
+    }
+
+    /// Build a `FulfillRandomness` instruction for a request of the legacy coordinator,
+    /// or a `ProcessRequestBatch` instruction for requests of the native coordinator
+    /// that share a subscription and request pool
+    fn fulfillment_instruction(&self, batch: &[PreparedFulfillment]) -> Result<Instruction, Box<dyn Error>> {
+        match batch {
+            [] => Err("Empty fulfillment batch".into()),
+            [fulfillment] if fulfillment.native.is_none() => self.legacy_fulfillment_instruction(fulfillment),
+            _ => self.native_batch_instruction(batch),
+        }
+    }
+
+    fn legacy_fulfillment_instruction(&self, fulfillment: &PreparedFulfillment) -> Result<Instruction, Box<dyn Error>> {
+        let fulfill_ix = VrfCoordinatorInstruction::FulfillRandomness {
+            proof: fulfillment.proof.clone(),
+            public_key: self.vrf_signer.public_key().to_vec(),
         };
-        let fulfill_ix_data = borsh::to_vec(&fulfill_ix)?;
-        
-        let instruction = Instruction {
+
+        Ok(Instruction {
             program_id: self.program_id,
             accounts: vec![
-                AccountMeta::new(self.oracle_keypair.pubkey(), true),
-                AccountMeta::new(request_pubkey.clone(), false),
-                AccountMeta::new(vrf_result, false),
-                AccountMeta::new_readonly(request.requester, false),
-                AccountMeta::new(request.subscription, false),
+                AccountMeta::new(self.oracle_signer.pubkey(), true),
+                AccountMeta::new(fulfillment.request, false),
+                AccountMeta::new(fulfillment.vrf_result, false),
+                AccountMeta::new_readonly(fulfillment.requester, false),
+                AccountMeta::new(fulfillment.subscription, false),
                 AccountMeta::new_readonly(system_program::id(), false),
-                AccountMeta::new_readonly(request.requester, false),
+                AccountMeta::new_readonly(fulfillment.requester, false),
             ],
-            data: fulfill_ix_data,
-        };
-        
-        // Create and send transaction with base64 encoding
-        let recent_blockhash = self.rpc_client.get_latest_blockhash()?;
-        
-        let transaction = Transaction::new_signed_with_payer(
-            &[instruction],
-            Some(&self.oracle_keypair.pubkey()),
-            &[&self.oracle_keypair],
-            recent_blockhash,
-        );
-        
-        println!("Sending transaction to fulfill VRF request...");
-        
-        // Send transaction with preflight checks and commitment
-        let signature = self.rpc_client.send_and_confirm_transaction_with_spinner(&transaction)?;
-        
-        println!("VRF request fulfilled successfully!");
-        println!("Transaction signature: {}", signature);
-        
-        Ok(())
+            data: borsh::to_vec(&fulfill_ix)?,
+        })
     }
 
-    // Helper method to recreate the VRF keypair since it doesn't implement Clone
-    fn clone_vrf_keypair(&self) -> Result<ECVRFKeyPair, Box<dyn Error>> {
-        // Extract the private and public key bytes from the existing keypair
-        let sk_bytes = self.vrf_keypair.sk.as_ref();
-        let pk_bytes = self.vrf_keypair.pk.as_ref();
-        
-        // Combine them into a single buffer
-        let mut combined = Vec::with_capacity(sk_bytes.len() + pk_bytes.len());
-        combined.extend_from_slice(sk_bytes);
-        combined.extend_from_slice(pk_bytes);
-        
-        // Create a new keypair from these bytes and handle the Result properly
-        Ok(ECVRFKeyPair::from_bytes(&combined)?)
+    fn native_batch_instruction(&self, batch: &[PreparedFulfillment]) -> Result<Instruction, Box<dyn Error>> {
+        let subscription = batch[0].subscription;
+        let keys = batch
+            .iter()
+            .map(|fulfillment| fulfillment.native.ok_or("Legacy request in a native batch"))
+            .collect::<Result<Vec<_>, _>>()?;
+        let pool_id = keys[0].pool_id;
+        if batch.iter().zip(&keys).any(|(fulfillment, key)| {
+            fulfillment.subscription != subscription || key.pool_id != pool_id
+        }) {
+            return Err("Batched requests must share a subscription and request pool".into());
+        }
+
+        let oracle = self.oracle_signer.pubkey();
+        let batch_ix = native::VrfCoordinatorInstruction::ProcessRequestBatch {
+            request_ids: keys.iter().map(|key| key.request_id).collect(),
+            proofs: batch.iter().map(|fulfillment| fulfillment.proof.clone()).collect(),
+            public_keys: vec![self.vrf_signer.public_key().to_vec(); batch.len()],
+            pool_id,
+            request_indices: keys.iter().map(|key| key.request_index).collect(),
+        };
+
+        let mut accounts = Vec::with_capacity(4 + 4 * batch.len());
+        accounts.push(AccountMeta::new(oracle, true));
+        accounts.push(AccountMeta::new_readonly(
+            native::oracle_config_address(&oracle, &self.program_id),
+            false,
+        ));
+        accounts.push(AccountMeta::new(
+            native::request_pool_address(&subscription, pool_id, &self.program_id),
+            false,
+        ));
+        accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+        for fulfillment in batch {
+            accounts.push(AccountMeta::new(fulfillment.request, false));
+            accounts.push(AccountMeta::new(fulfillment.vrf_result, false));
+            accounts.push(AccountMeta::new(fulfillment.subscription, false));
+            accounts.push(AccountMeta::new_readonly(fulfillment.requester, false));
+        }
+
+        Ok(Instruction {
+            program_id: self.program_id,
+            accounts,
+            data: borsh::to_vec(&batch_ix)?,
+        })
     }
 }
 
-/// Load or create a new VRF keypair
-pub fn load_or_create_keypair(keypair_path: &Path) -> Result<ECVRFKeyPair, Box<dyn Error>> {
+/// Load a VRF keypair, or generate one and store it in a new encrypted keystore.
+/// The keystore password is read from `password_file` or the environment.
+pub fn load_or_create_keypair(keypair_path: &Path, password_file: Option<&Path>) -> Result<ECVRFKeyPair, Box<dyn Error>> {
     println!("Loading VRF keypair from: {:?}", keypair_path);
     
     // If the file exists, try to load it
     if keypair_path.exists() {
+        if is_keystore(keypair_path) {
+            let password = read_keystore_password(password_file)?;
+            let keypair = load_keystore(keypair_path, &password)?;
+            println!("Loaded VRF keypair from encrypted keystore");
+            return Ok(keypair);
+        }
+
+        warn!("VRF keypair {:?} is stored unencrypted; convert it with `ecvrf-cli keystore import`", keypair_path);
         let mut file = File::open(keypair_path)?;
         let mut contents = String::new();
         file.read_to_string(&mut contents)?;
@@ -459,84 +744,223 @@ pub fn load_or_create_keypair(keypair_path: &Path) -> Result<ECVRFKeyPair, Box<d
     }
     
     println!("VRF keypair not found, generating a new one");
+    let password = read_keystore_password(password_file)?;
     
     // Generate a new keypair
     let mut rng = rand::thread_rng();
     let keypair = ECVRFKeyPair::generate(&mut rng);
     
-    // Save the keypair - combine public and private key bytes
-    let mut file = File::create(keypair_path)?;
-    
-    // Save pk and sk as a combined array
-    let mut combined = Vec::with_capacity(64);
-    
-    // Add private key bytes - for mangekyou library, we'll need to manually extract bytes
-    let sk_ref = keypair.sk.as_ref();
-    combined.extend_from_slice(sk_ref);
-    
-    // Add public key bytes
-    let pk_ref = keypair.pk.as_ref();
-    combined.extend_from_slice(pk_ref);
-    
-    // Save as a JSON array
-    let json_bytes = serde_json::to_string(&combined)?;
-    file.write_all(json_bytes.as_bytes())?;
+    save_keystore(keypair_path, &keypair, &password)?;
     
-    println!("Generated and saved new VRF keypair");
+    println!("Generated and saved new VRF keypair to encrypted keystore");
     Ok(keypair)
 }
 
-/// Safely generate a VRF proof for the given request
-fn safely_generate_vrf_proof(vrf_keypair: &ECVRFKeyPair, seed: &[u8]) -> Option<ECVRFProof> {
-    // Add defensive code to handle potential key issues
-    trace!("Attempting to generate VRF proof with seed: {:?}", seed);
-    
-    // Try to generate the proof in a way that catches potential panics
-    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-        vrf_keypair.prove(seed)
-    }));
-    
-    match result {
-        Ok(proof) => {
-            trace!("Successfully generated VRF proof");
-            Some(proof)
-        },
-        Err(e) => {
-            error!("Failed to generate VRF proof: {:?}", e);
-            
-            // Try to recover what went wrong for better diagnostics
-            if let Some(error_str) = e.downcast_ref::<String>() {
-                error!("Error message: {}", error_str);
-            } else if let Some(error_str) = e.downcast_ref::<&str>() {
-                error!("Error message: {}", error_str);
-            }
-            
-            warn!("Checking if VRF keypair is valid...");
-            // Check if the public key is valid
-            if !is_valid_vrf_keypair(vrf_keypair) {
-                error!("VRF keypair appears to be invalid. Please generate a new VRF keypair");
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        mangekyou::kamui_vrf::ecvrf::ECVRFProof,
+    };
+
+    fn server(vrf_keypair: ECVRFKeyPair, max_batch_size: usize) -> VRFServer {
+        VRFServer::new(
+            "http://127.0.0.1:8899",
+            "ws://127.0.0.1:8900",
+            &Pubkey::new_unique().to_string(),
+            Keypair::new(),
+            vrf_keypair,
+        )
+        .unwrap()
+        .with_submitter_config(SubmitterConfig {
+            max_batch_size,
+            ..SubmitterConfig::default()
+        })
+    }
+
+    fn native_request(subscription: Pubkey, pool_id: u8, request_index: u32) -> native::RandomnessRequest {
+        native::RandomnessRequest {
+            subscription,
+            seed: [request_index as u8; 32],
+            requester: Pubkey::new_unique(),
+            callback_data: Vec::new(),
+            request_slot: 1,
+            status: native::RequestStatus::Pending,
+            num_words: 1,
+            callback_gas_limit: 100_000,
+            pool_id,
+            request_index,
+            request_id: [request_index as u8 + 100; 32],
+        }
+    }
+
+    fn legacy_request(subscription: Pubkey) -> RandomnessRequest {
+        RandomnessRequest {
+            subscription,
+            seed: [7; 32],
+            requester: Pubkey::new_unique(),
+            callback_data: Vec::new(),
+            request_block: 1,
+            status: RequestStatus::Pending,
+            num_words: 1,
+            callback_gas_limit: 100_000,
+            nonce: 0,
+            commitment: [0; 32],
+        }
+    }
+
+    fn prepare(server: &VRFServer, requests: Vec<PendingRequest>) -> Vec<PreparedFulfillment> {
+        requests
+            .iter()
+            .map(|request| server.prepare_fulfillment(&Pubkey::new_unique(), request).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_request_layouts() {
+        let native_data = borsh::to_vec(&native_request(Pubkey::new_unique(), 0, 3)).unwrap();
+        assert!(matches!(
+            PendingRequest::parse(&native_data),
+            Ok(PendingRequest::Native(request)) if request.request_index == 3
+        ));
+
+        let legacy_data = borsh::to_vec(&legacy_request(Pubkey::new_unique())).unwrap();
+        assert!(matches!(PendingRequest::parse(&legacy_data), Ok(PendingRequest::Legacy(_))));
+
+        assert!(PendingRequest::parse(&native_data[..native_data.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_native_batch_instruction() {
+        let vrf_keypair = ECVRFKeyPair::generate(&mut rand::thread_rng());
+        let vrf_public_key = vrf_keypair.pk.clone();
+        let server = server(vrf_keypair, 8);
+        let subscription = Pubkey::new_unique();
+        let requests: Vec<_> = (0..3).map(|index| native_request(subscription, 2, index)).collect();
+        let batch = prepare(
+            &server,
+            requests.iter().cloned().map(PendingRequest::Native).collect(),
+        );
+
+        let instruction = server.fulfillment_instruction(&batch).unwrap();
+        assert_eq!(instruction.program_id, server.program_id);
+
+        let oracle = server.oracle_signer.pubkey();
+        assert_eq!(instruction.accounts.len(), 4 + 4 * batch.len());
+        assert_eq!(instruction.accounts[0], AccountMeta::new(oracle, true));
+        assert_eq!(
+            instruction.accounts[1].pubkey,
+            native::oracle_config_address(&oracle, &server.program_id)
+        );
+        assert_eq!(
+            instruction.accounts[2],
+            AccountMeta::new(native::request_pool_address(&subscription, 2, &server.program_id), false)
+        );
+        assert_eq!(instruction.accounts[3].pubkey, system_program::id());
+        for (i, (fulfillment, request)) in batch.iter().zip(&requests).enumerate() {
+            let accounts = &instruction.accounts[4 + 4 * i..8 + 4 * i];
+            assert_eq!(accounts[0], AccountMeta::new(fulfillment.request, false));
+            assert_eq!(
+                accounts[1],
+                AccountMeta::new(native::vrf_result_address(&request.request_id, &server.program_id), false)
+            );
+            assert_eq!(accounts[2], AccountMeta::new(subscription, false));
+            assert_eq!(accounts[3], AccountMeta::new_readonly(request.requester, false));
+        }
+
+        match native::VrfCoordinatorInstruction::try_from_slice(&instruction.data).unwrap() {
+            native::VrfCoordinatorInstruction::ProcessRequestBatch {
+                request_ids,
+                proofs,
+                public_keys,
+                pool_id,
+                request_indices,
+            } => {
+                assert_eq!(pool_id, 2);
+                assert_eq!(request_indices, vec![0, 1, 2]);
+                assert_eq!(request_ids, requests.iter().map(|r| r.request_id).collect::<Vec<_>>());
+                assert_eq!(public_keys, vec![vrf_public_key.as_ref().to_vec(); 3]);
+                // Each proof is over the seed of its own request
+                for (proof, request) in proofs.iter().zip(&requests) {
+                    let proof = ECVRFProof::from_bytes(proof).unwrap();
+                    assert!(proof.verify(&request.seed, &vrf_public_key).is_ok());
+                }
             }
-            
-            None
+            other => panic!("unexpected instruction {:?}", other),
         }
     }
-}
 
-/// Check if the VRF keypair appears to be valid
-fn is_valid_vrf_keypair(keypair: &ECVRFKeyPair) -> bool {
-    // Basic check - are the keys non-zero?
-    let pk_bytes = keypair.pk.as_ref();
-    let sk_bytes = keypair.sk.as_ref();
-    
-    if pk_bytes.iter().all(|&b| b == 0) || sk_bytes.iter().all(|&b| b == 0) {
-        return false;
+    #[test]
+    fn test_native_batch_rejects_mixed_pools() {
+        let server = server(ECVRFKeyPair::generate(&mut rand::thread_rng()), 8);
+        let subscription = Pubkey::new_unique();
+        let mixed = prepare(
+            &server,
+            vec![
+                PendingRequest::Native(native_request(subscription, 0, 0)),
+                PendingRequest::Native(native_request(subscription, 1, 1)),
+            ],
+        );
+        assert!(server.fulfillment_instruction(&mixed).is_err());
+
+        let with_legacy = prepare(
+            &server,
+            vec![
+                PendingRequest::Native(native_request(subscription, 0, 0)),
+                PendingRequest::Legacy(legacy_request(subscription)),
+            ],
+        );
+        assert!(server.fulfillment_instruction(&with_legacy).is_err());
+        assert!(server.fulfillment_instruction(&[]).is_err());
     }
-    
-    // Try to derive a public key from the private key and check if it matches
-    // This is a basic test - it doesn't fully validate the keypair
-    let derived_keypair = ECVRFKeyPair::from(keypair.sk.clone());
-    let derived_pk_bytes = derived_keypair.pk.as_ref();
-    
-    // Compare public keys
-    pk_bytes == derived_pk_bytes
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_pack_fulfillments() {
+        let server = server(ECVRFKeyPair::generate(&mut rand::thread_rng()), 3);
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut requests = Vec::new();
+        for index in 0..5 {
+            requests.push(PendingRequest::Native(native_request(first, 0, index)));
+        }
+        requests.push(PendingRequest::Native(native_request(first, 1, 5)));
+        requests.push(PendingRequest::Native(native_request(second, 0, 6)));
+        requests.push(PendingRequest::Legacy(legacy_request(first)));
+        requests.push(PendingRequest::Legacy(legacy_request(second)));
+
+        let batches = server.pack_fulfillments(prepare(&server, requests));
+        let sizes: Vec<_> = batches.iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![1, 1, 3, 2, 1, 1]);
+
+        let payer = server.oracle_signer.pubkey();
+        for batch in &batches {
+            let instruction = server.fulfillment_instruction(batch).unwrap();
+            assert!(fits_in_packet(&[instruction], &payer));
+        }
+
+        // Legacy requests keep the single-request FulfillRandomness layout
+        let legacy = server.fulfillment_instruction(&batches[0]).unwrap();
+        assert_eq!(legacy.accounts.len(), 7);
+        assert!(matches!(
+            VrfCoordinatorInstruction::try_from_slice(&legacy.data),
+            Ok(VrfCoordinatorInstruction::FulfillRandomness { .. })
+        ));
+    }
+
+    #[test]
+    fn test_pack_fulfillments_limits_batch_to_packet() {
+        let server = server(ECVRFKeyPair::generate(&mut rand::thread_rng()), native::MAX_BATCH_SIZE);
+        let subscription = Pubkey::new_unique();
+        let requests = (0..native::MAX_BATCH_SIZE as u32)
+            .map(|index| PendingRequest::Native(native_request(subscription, 0, index)))
+            .collect();
+
+        let batches = server.pack_fulfillments(prepare(&server, requests));
+        assert!(batches.len() > 1);
+        assert_eq!(batches.iter().map(Vec::len).sum::<usize>(), native::MAX_BATCH_SIZE);
+        let payer = server.oracle_signer.pubkey();
+        for batch in &batches {
+            let instruction = server.fulfillment_instruction(batch).unwrap();
+            assert!(fits_in_packet(&[instruction], &payer));
+        }
+    }
+}